@@ -0,0 +1,298 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::apps::{Apps, CacheStats, CrawlerMetrics, HostMetaCache};
+use crate::config::Config;
+use crate::http_result::HttpResult;
+use crate::infrastructure::NetworkInfo;
+use http_api_problem::{HttpApiProblem, StatusCode};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::Json;
+use rocket::State;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+
+/// The token of the `Authorization: Bearer {token}` header, if any.
+pub struct BearerToken(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for BearerToken {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(BearerToken(
+            request
+                .headers()
+                .get_one("Authorization")
+                .and_then(|authorization| authorization.strip_prefix("Bearer "))
+                .map(|token| token.trim().to_string()),
+        ))
+    }
+}
+
+impl BearerToken {
+    /// Accepts the token only if it is the configured admin API key. Without an admin API key,
+    /// the endpoints that require it are disabled.
+    pub fn authorize(&self, config: &Config) -> HttpResult<()> {
+        let Some(api_key) = config.admin_api_key() else {
+            return Err(HttpApiProblem::with_title_and_type(StatusCode::FORBIDDEN)
+                .detail("The endpoint is disabled because no admin API key has been configured.")
+                .into());
+        };
+
+        // Compare in constant time so that the response time does not reveal the API key
+        let is_authorized = self
+            .0
+            .as_deref()
+            .is_some_and(|token| bool::from(token.as_bytes().ct_eq(api_key.unsecure().as_bytes())));
+        if !is_authorized {
+            return Err(
+                HttpApiProblem::with_title_and_type(StatusCode::UNAUTHORIZED)
+                    .detail("The request must provide the admin API key as bearer token.")
+                    .into(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(FromForm)]
+pub struct NetworkCleanupOptions {
+    #[field(name = "dryRun", default = false)]
+    dry_run: bool,
+}
+
+/// Deletes the networks of apps that do not have containers anymore and returns them. Requires
+/// the configured admin API key as bearer token, even for dry runs.
+#[post("/admin/network-cleanup?<options..>")]
+pub async fn network_cleanup(
+    apps: &State<Arc<Apps>>,
+    config: &State<Config>,
+    bearer_token: BearerToken,
+    options: NetworkCleanupOptions,
+) -> HttpResult<Json<Vec<NetworkInfo>>> {
+    bearer_token.authorize(config)?;
+    Ok(Json(apps.delete_orphaned_networks(options.dry_run).await?))
+}
+
+/// Describes how the crawler of the web host meta data performs. Requires the configured admin
+/// API key as bearer token.
+#[get("/admin/host-meta-metrics", format = "application/json")]
+pub fn host_meta_metrics(
+    host_meta_cache: &State<HostMetaCache>,
+    config: &State<Config>,
+    bearer_token: BearerToken,
+) -> HttpResult<Json<CrawlerMetrics>> {
+    bearer_token.authorize(config)?;
+    Ok(Json(host_meta_cache.metrics()))
+}
+
+/// Returns the configuration values with redacted credentials. Requires the configured admin API
+/// key as bearer token.
+#[get("/admin/config", format = "application/json")]
+pub fn config(
+    config: &State<Config>,
+    bearer_token: BearerToken,
+) -> HttpResult<Json<serde_json::Value>> {
+    bearer_token.authorize(config)?;
+    Ok(Json(config.redacted_values()))
+}
+
+/// Describes the current content of the web host meta cache. Requires the configured admin API
+/// key as bearer token.
+#[get("/admin/cache-stats", format = "application/json")]
+pub fn cache_stats(
+    host_meta_cache: &State<HostMetaCache>,
+    config: &State<Config>,
+    bearer_token: BearerToken,
+) -> HttpResult<Json<CacheStats>> {
+    bearer_token.authorize(config)?;
+    Ok(Json(host_meta_cache.get_cache_stats()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::AppsService;
+    use crate::config_from_str;
+    use crate::infrastructure::Dummy;
+    use rocket::http::{Header, Status};
+    use rocket::local::asynchronous::Client;
+
+    async fn client_with_config(prevant_config: Config) -> Client {
+        let rocket = rocket::build()
+            .manage(prevant_config)
+            .mount("/api", routes![config]);
+        Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance")
+    }
+
+    #[tokio::test]
+    async fn should_return_redacted_config() {
+        let client = client_with_config(config_from_str!(
+            r#"
+            [admin]
+            api_key = 'admin-key'
+            "#
+        ))
+        .await;
+
+        let response = client
+            .get("/api/admin/config")
+            .header(Header::new("Accept", "application/json"))
+            .header(Header::new("Authorization", "Bearer admin-key"))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.into_json::<serde_json::Value>().await,
+            Some(serde_json::json!({ "admin": { "api_key": "<redacted>" } }))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_requests_without_admin_api_key() {
+        let client = client_with_config(config_from_str!(
+            r#"
+            [admin]
+            api_key = 'admin-key'
+            "#
+        ))
+        .await;
+
+        for authorization in ["Bearer other-key", "Basic YWRtaW46YWRtaW4="] {
+            let response = client
+                .get("/api/admin/config")
+                .header(Header::new("Accept", "application/json"))
+                .header(Header::new("Authorization", authorization))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Unauthorized);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_disable_config_without_admin_api_key() {
+        let client = client_with_config(config_from_str!("")).await;
+
+        let response = client
+            .get("/api/admin/config")
+            .header(Header::new("Accept", "application/json"))
+            .header(Header::new("Authorization", "Bearer admin-key"))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    async fn client_with_network_cleanup() -> Client {
+        let infrastructure = Box::new(Dummy::new());
+        let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+        let rocket = rocket::build()
+            .manage(apps)
+            .manage(config_from_str!(
+                r#"
+                [admin]
+                api_key = 'admin-key'
+                "#
+            ))
+            .mount("/api", routes![network_cleanup]);
+        Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance")
+    }
+
+    #[tokio::test]
+    async fn should_require_admin_api_key_for_host_meta_endpoints() {
+        let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+        let rocket = rocket::build()
+            .manage(host_meta_cache)
+            .manage(config_from_str!(
+                r#"
+                [admin]
+                api_key = 'admin-key'
+                "#
+            ))
+            .mount("/api", routes![host_meta_metrics, cache_stats]);
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        for uri in ["/api/admin/host-meta-metrics", "/api/admin/cache-stats"] {
+            let response = client
+                .get(uri)
+                .header(Header::new("Accept", "application/json"))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Unauthorized);
+
+            let response = client
+                .get(uri)
+                .header(Header::new("Accept", "application/json"))
+                .header(Header::new("Authorization", "Bearer admin-key"))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_clean_up_networks() {
+        let client = client_with_network_cleanup().await;
+
+        let response = client
+            .post("/api/admin/network-cleanup?dryRun=true")
+            .header(Header::new("Authorization", "Bearer admin-key"))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().await, Some(String::from("[]")));
+    }
+
+    #[tokio::test]
+    async fn should_reject_network_cleanup_without_admin_api_key() {
+        let client = client_with_network_cleanup().await;
+
+        for dry_run in [true, false] {
+            let response = client
+                .post(format!("/api/admin/network-cleanup?dryRun={dry_run}"))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Unauthorized);
+
+            let response = client
+                .post(format!("/api/admin/network-cleanup?dryRun={dry_run}"))
+                .header(Header::new("Authorization", "Bearer other-key"))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Unauthorized);
+        }
+    }
+}
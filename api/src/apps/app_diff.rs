@@ -0,0 +1,122 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2024 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::models::ServiceConfig;
+use serde::Serialize;
+
+/// Describes how the services of an app would change if the given service configurations were
+/// deployed. Services of the app that are not part of the deployment keep running and, therefore,
+/// are listed as unchanged.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct AppDiff {
+    to_add: Vec<String>,
+    to_update: Vec<ServiceUpdate>,
+    unchanged: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ServiceUpdate {
+    service: String,
+    old_image: String,
+    new_image: String,
+}
+
+/// Compares the `current` service configurations of an app with the `new` ones by service name.
+/// A service will be updated if the image, including its tag, differs.
+pub(super) fn diff(current: &[ServiceConfig], new: &[ServiceConfig]) -> AppDiff {
+    let mut diff = AppDiff::default();
+
+    for config in new {
+        match current
+            .iter()
+            .find(|current| current.service_name() == config.service_name())
+        {
+            None => diff.to_add.push(config.service_name().clone()),
+            Some(current) if current.image() != config.image() => {
+                diff.to_update.push(ServiceUpdate {
+                    service: config.service_name().clone(),
+                    old_image: current.image().to_string(),
+                    new_image: config.image().to_string(),
+                })
+            }
+            Some(_) => diff.unchanged.push(config.service_name().clone()),
+        }
+    }
+
+    diff.unchanged.extend(
+        current
+            .iter()
+            .filter(|current| {
+                !new.iter()
+                    .any(|config| config.service_name() == current.service_name())
+            })
+            .map(|current| current.service_name().clone()),
+    );
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sc;
+
+    #[test]
+    fn should_add_unknown_services() {
+        let diff = diff(&[], &[sc!("db", "mariadb:10.3")]);
+
+        assert_eq!(diff.to_add, vec![String::from("db")]);
+        assert!(diff.to_update.is_empty());
+        assert!(diff.unchanged.is_empty());
+    }
+
+    #[test]
+    fn should_update_services_with_different_image_tag() {
+        let diff = diff(
+            &[sc!("db", "mariadb:10.3"), sc!("api", "nginx:1.27")],
+            &[sc!("db", "mariadb:10.4"), sc!("api", "nginx:1.27")],
+        );
+
+        assert!(diff.to_add.is_empty());
+        assert_eq!(
+            diff.to_update,
+            vec![ServiceUpdate {
+                service: String::from("db"),
+                old_image: String::from("docker.io/library/mariadb:10.3"),
+                new_image: String::from("docker.io/library/mariadb:10.4"),
+            }]
+        );
+        assert_eq!(diff.unchanged, vec![String::from("api")]);
+    }
+
+    #[test]
+    fn should_keep_services_that_are_not_deployed() {
+        let diff = diff(&[sc!("db", "mariadb:10.3")], &[sc!("api", "nginx:1.27")]);
+
+        assert_eq!(diff.to_add, vec![String::from("api")]);
+        assert_eq!(diff.unchanged, vec![String::from("db")]);
+    }
+}
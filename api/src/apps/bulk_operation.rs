@@ -0,0 +1,120 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2024 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::apps::AppsServiceError;
+use crate::models::service::Service;
+use crate::models::AppName;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::future::Future;
+use tokio::sync::Semaphore;
+
+/// Summarizes an operation on multiple apps, e.g. deleting them. The operation continues with the
+/// remaining apps if it fails for one of them.
+#[derive(Debug, Default, Serialize)]
+pub struct BulkOperationResult {
+    succeeded: Vec<AppName>,
+    failed: Vec<BulkOperationFailure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkOperationFailure {
+    name: AppName,
+    error: String,
+}
+
+impl BulkOperationResult {
+    #[cfg(test)]
+    pub fn succeeded(&self) -> &[AppName] {
+        &self.succeeded
+    }
+
+    #[cfg(test)]
+    pub fn failed(&self) -> &[BulkOperationFailure] {
+        &self.failed
+    }
+
+    pub fn has_failures(&self) -> bool {
+        !self.failed.is_empty()
+    }
+}
+
+#[cfg(test)]
+impl BulkOperationFailure {
+    pub fn name(&self) -> &AppName {
+        &self.name
+    }
+}
+
+/// Applies `operation` to each app while at most `parallelism` operations run at the same time.
+///
+/// Duplicate app names are only processed once because concurrent operations on the same app wait
+/// for each other.
+pub(super) async fn run<'a, F, Fut>(
+    app_names: &'a [AppName],
+    parallelism: usize,
+    operation: F,
+) -> BulkOperationResult
+where
+    F: Fn(&'a AppName) -> Fut,
+    Fut: Future<Output = Result<Vec<Service>, AppsServiceError>>,
+{
+    let mut seen = HashSet::new();
+    let semaphore = Semaphore::new(parallelism.max(1));
+    let operation = &operation;
+
+    let results = futures::future::join_all(
+        app_names
+            .iter()
+            .filter(|app_name| seen.insert(*app_name))
+            .map(|app_name| {
+                let semaphore = &semaphore;
+                async move {
+                    let _permit = semaphore
+                        .acquire()
+                        .await
+                        .expect("The semaphore is never closed");
+                    (app_name, operation(app_name).await)
+                }
+            }),
+    )
+    .await;
+
+    let mut result = BulkOperationResult::default();
+    for (app_name, operation_result) in results {
+        match operation_result {
+            Ok(_) => result.succeeded.push(app_name.clone()),
+            Err(err) => {
+                debug!("Bulk operation failed for {app_name}: {err}");
+                result.failed.push(BulkOperationFailure {
+                    name: app_name.clone(),
+                    error: err.to_string(),
+                });
+            }
+        }
+    }
+    result
+}
@@ -0,0 +1,219 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2024 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// The longest time in minutes that a circuit stays open, no matter how often the half-open
+/// attempts failed.
+const MAX_OPEN_MINUTES: i64 = 30;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CircuitState {
+    /// Attempts are allowed.
+    Closed,
+    /// Attempts are skipped until `reopen_at`.
+    Open { reopen_at: DateTime<Utc> },
+    /// A single attempt is allowed that decides whether the circuit closes or opens again.
+    HalfOpen,
+}
+
+struct Circuit {
+    failures: u32,
+    state: CircuitState,
+    open_duration: Duration,
+}
+
+/// Tracks the failed attempts per key and stops the attempts for a key after `threshold`
+/// consecutive failures. Each time the attempt after the open period fails, the open period is
+/// doubled.
+pub struct CircuitBreaker<K> {
+    threshold: Option<u32>,
+    open_duration: Duration,
+    circuits: HashMap<K, Circuit>,
+}
+
+impl<K> CircuitBreaker<K>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a circuit breaker that never opens without `threshold`.
+    pub fn new(threshold: Option<u32>, open_duration: Duration) -> Self {
+        Self {
+            threshold,
+            open_duration,
+            circuits: HashMap::new(),
+        }
+    }
+
+    /// Returns `true` if an attempt for the key may be made at `now`. An open circuit becomes
+    /// half-open when its open period has passed.
+    pub fn allows_attempt(&mut self, key: &K, now: DateTime<Utc>) -> bool {
+        let Some(circuit) = self.circuits.get_mut(key) else {
+            return true;
+        };
+
+        match circuit.state {
+            CircuitState::Closed | CircuitState::HalfOpen => true,
+            CircuitState::Open { reopen_at } if now >= reopen_at => {
+                info!("Circuit of {key:?} is half-open, trying once");
+                circuit.state = CircuitState::HalfOpen;
+                true
+            }
+            CircuitState::Open { .. } => false,
+        }
+    }
+
+    pub fn record_success(&mut self, key: &K) {
+        if let Some(circuit) = self.circuits.remove(key) {
+            if circuit.state != CircuitState::Closed {
+                info!("Circuit of {key:?} is closed again");
+            }
+        }
+    }
+
+    pub fn record_failure(&mut self, key: &K, now: DateTime<Utc>) {
+        let Some(threshold) = self.threshold else {
+            return;
+        };
+
+        let open_duration = self.open_duration;
+        let circuit = self.circuits.entry(key.clone()).or_insert_with(|| Circuit {
+            failures: 0,
+            state: CircuitState::Closed,
+            open_duration,
+        });
+        circuit.failures = circuit.failures.saturating_add(1);
+
+        match circuit.state {
+            CircuitState::HalfOpen => {
+                circuit.open_duration =
+                    (circuit.open_duration * 2).min(Duration::minutes(MAX_OPEN_MINUTES));
+                circuit.state = CircuitState::Open {
+                    reopen_at: now + circuit.open_duration,
+                };
+                info!(
+                    "Circuit of {key:?} is open again for {} seconds",
+                    circuit.open_duration.num_seconds()
+                );
+            }
+            CircuitState::Closed if circuit.failures >= threshold => {
+                circuit.state = CircuitState::Open {
+                    reopen_at: now + circuit.open_duration,
+                };
+                info!(
+                    "Circuit of {key:?} is open for {} seconds after {} failures",
+                    circuit.open_duration.num_seconds(),
+                    circuit.failures
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Forgets the failures of the key, e.g. because the service is gone.
+    pub fn remove(&mut self, key: &K) {
+        self.circuits.remove(key);
+    }
+
+    #[cfg(test)]
+    pub fn state(&self, key: &K) -> CircuitState {
+        self.circuits
+            .get(key)
+            .map(|circuit| circuit.state.clone())
+            .unwrap_or(CircuitState::Closed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_open_circuit_after_threshold() {
+        let now = Utc::now();
+        let mut circuit_breaker = CircuitBreaker::new(Some(2), Duration::seconds(60));
+
+        circuit_breaker.record_failure(&"db", now);
+        assert!(circuit_breaker.allows_attempt(&"db", now));
+
+        circuit_breaker.record_failure(&"db", now);
+        assert_eq!(
+            circuit_breaker.state(&"db"),
+            CircuitState::Open {
+                reopen_at: now + Duration::seconds(60)
+            }
+        );
+        assert!(!circuit_breaker.allows_attempt(&"db", now + Duration::seconds(59)));
+        assert!(circuit_breaker.allows_attempt(&"other", now));
+    }
+
+    #[test]
+    fn should_close_circuit_after_successful_half_open_attempt() {
+        let now = Utc::now();
+        let mut circuit_breaker = CircuitBreaker::new(Some(1), Duration::seconds(60));
+        circuit_breaker.record_failure(&"db", now);
+
+        let later = now + Duration::seconds(60);
+        assert!(circuit_breaker.allows_attempt(&"db", later));
+        assert_eq!(circuit_breaker.state(&"db"), CircuitState::HalfOpen);
+
+        circuit_breaker.record_success(&"db");
+        assert_eq!(circuit_breaker.state(&"db"), CircuitState::Closed);
+    }
+
+    #[test]
+    fn should_double_open_duration_after_failed_half_open_attempt() {
+        let now = Utc::now();
+        let mut circuit_breaker = CircuitBreaker::new(Some(1), Duration::seconds(60));
+        circuit_breaker.record_failure(&"db", now);
+
+        let later = now + Duration::seconds(60);
+        assert!(circuit_breaker.allows_attempt(&"db", later));
+        circuit_breaker.record_failure(&"db", later);
+
+        assert_eq!(
+            circuit_breaker.state(&"db"),
+            CircuitState::Open {
+                reopen_at: later + Duration::seconds(120)
+            }
+        );
+    }
+
+    #[test]
+    fn should_never_open_circuit_without_threshold() {
+        let now = Utc::now();
+        let mut circuit_breaker = CircuitBreaker::new(None, Duration::seconds(60));
+
+        for _ in 0..100 {
+            circuit_breaker.record_failure(&"db", now);
+        }
+
+        assert!(circuit_breaker.allows_attempt(&"db", now));
+    }
+}
@@ -0,0 +1,170 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::apps::Apps;
+use crate::models::{AppName, AppStatusChangeId, ServiceConfig};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::time::sleep;
+
+/// Watches a directory that contains a TOML file per app, e.g. `master.toml`, and deploys the
+/// services of a file whenever it changes.
+pub struct ConfigDirWatcher {
+    dir: PathBuf,
+    modification_times: HashMap<PathBuf, SystemTime>,
+}
+
+#[derive(Deserialize)]
+struct ServiceConfigs {
+    #[serde(default)]
+    services: Vec<ServiceConfig>,
+}
+
+impl ConfigDirWatcher {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            modification_times: HashMap::new(),
+        }
+    }
+
+    pub fn spawn(mut self, apps: Arc<Apps>) {
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = self.deploy_changed_files(&apps).await {
+                    error!("Cannot read config directory {}: {err}", self.dir.display());
+                }
+                sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    async fn deploy_changed_files(&mut self, apps: &Apps) -> std::io::Result<()> {
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("toml") {
+                continue;
+            }
+
+            let modified = entry.metadata().await?.modified()?;
+            if self.modification_times.get(&path) == Some(&modified) {
+                continue;
+            }
+            self.modification_times.insert(path.clone(), modified);
+
+            let (app_name, service_configs) = match Self::read_file(&path).await {
+                Ok(result) => result,
+                Err(err) => {
+                    error!("Cannot deploy services of {}: {err}", path.display());
+                    continue;
+                }
+            };
+
+            debug!(
+                "Deploying {} services of {app_name} from {}",
+                service_configs.len(),
+                path.display()
+            );
+            if let Err(err) = apps
+                .create_or_update(&app_name, &AppStatusChangeId::new(), None, &service_configs)
+                .await
+            {
+                error!("Cannot deploy services of {}: {err}", path.display());
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn read_file(path: &Path) -> anyhow::Result<(AppName, Vec<ServiceConfig>)> {
+        let app_name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow::anyhow!("The file name is not valid UTF-8"))?;
+        let app_name = AppName::from_str(app_name)?;
+
+        let content = tokio::fs::read_to_string(path).await?;
+        Ok((app_name, parse_service_configs(&content)?))
+    }
+}
+
+/// Parses the services of a file that lists them as array of tables, e.g.
+///
+/// ```toml
+/// [[services]]
+/// serviceName = 'db'
+/// image = 'mariadb:10.3'
+/// ```
+fn parse_service_configs(content: &str) -> Result<Vec<ServiceConfig>, toml::de::Error> {
+    Ok(toml::de::from_str::<ServiceConfigs>(content)?.services)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Image;
+
+    #[test]
+    fn should_parse_service_configs() {
+        let service_configs = parse_service_configs(
+            r#"
+            [[services]]
+            serviceName = 'db'
+            image = 'mariadb:10.3'
+            env = { MYSQL_USER = 'admin' }
+
+            [[services]]
+            serviceName = 'nginx'
+            image = 'nginx'
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(service_configs.len(), 2);
+        assert_eq!(service_configs[0].service_name(), "db");
+        assert_eq!(
+            service_configs[0].image(),
+            &Image::from_str("mariadb:10.3").unwrap()
+        );
+        assert_eq!(
+            service_configs[0]
+                .env()
+                .and_then(|env| env.variable("MYSQL_USER"))
+                .map(|variable| variable.value().unsecure()),
+            Some("admin")
+        );
+        assert_eq!(service_configs[1].service_name(), "nginx");
+    }
+
+    #[test]
+    fn should_reject_invalid_toml() {
+        assert!(parse_service_configs("[[services]]\nimage = ").is_err());
+    }
+}
@@ -24,9 +24,11 @@
  * =========================LICENSE_END==================================
  */
 
+use crate::apps::circuit_breaker::CircuitBreaker;
 use crate::apps::{Apps, AppsError};
+use crate::config::SubdomainRoutingConfig;
 use crate::infrastructure::HttpForwarder;
-use crate::models::service::{Service, ServiceBuilder, ServiceStatus};
+use crate::models::service::{diff_services, Service, ServiceBuilder, ServiceStatus};
 use crate::models::{AppName, RequestInfo, WebHostMeta};
 use chrono::{DateTime, Utc};
 use evmap::{ReadHandleFactory, WriteHandle};
@@ -36,16 +38,94 @@ use http::header::{HOST, USER_AGENT};
 use multimap::MultiMap;
 use std::collections::{HashMap, HashSet};
 use std::convert::From;
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::time::sleep;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::Mutex;
+use tokio::time::{interval, sleep, Interval, MissedTickBehavior};
+use url::Url;
 use yansi::Paint;
 
 pub struct HostMetaCache {
     reader_factory: ReadHandleFactory<Key, Arc<Value>>,
+    metrics: Arc<StdMutex<CrawlerMetrics>>,
+    forced_crawls: Sender<AppName>,
 }
 pub struct HostMetaCrawler {
     writer: WriteHandle<Key, Arc<Value>>,
+    metrics: Arc<StdMutex<CrawlerMetrics>>,
+    /// The apps that must be crawled immediately, see [`HostMetaCache::force_crawl_app`].
+    forced_crawls: Receiver<AppName>,
+    /// The minimal time between two requests to the services.
+    request_interval: Option<Duration>,
+    /// The services of the previous crawl. Only services that have been added since then will be
+    /// crawled.
+    services: MultiMap<AppName, Service>,
+    /// The services whose web host meta data could not be resolved yet and, therefore, will be
+    /// crawled again.
+    keys_to_retry: HashSet<Key>,
+    /// Skips the retries of services that failed repeatedly.
+    circuit_breaker: CircuitBreaker<Key>,
+    /// The services of apps with higher priority are crawled first. Apps without priority have
+    /// the priority `0`.
+    crawl_priority: HashMap<AppName, u8>,
+}
+
+/// Describes how the crawler performed since PREvant has been started.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrawlerMetrics {
+    total_resolutions_attempted: u64,
+    successful_resolutions: u64,
+    failed_resolutions: u64,
+    empty_resolutions: u64,
+    average_resolution_ms: f64,
+    last_crawl_duration_ms: u64,
+    services_in_cache: usize,
+}
+
+/// Describes the current content of the web host meta cache.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    total_entries: usize,
+    apps_with_entries: usize,
+    oldest_entry: Option<DateTime<Utc>>,
+    newest_entry: Option<DateTime<Utc>>,
+}
+
+impl CrawlerMetrics {
+    fn record_crawl<'a, I>(
+        &mut self,
+        resolutions: I,
+        crawl_duration: Duration,
+        services_in_cache: usize,
+    ) where
+        I: IntoIterator<Item = (&'a WebHostMeta, Duration)>,
+    {
+        let mut total_resolution_ms =
+            self.average_resolution_ms * self.total_resolutions_attempted as f64;
+
+        for (web_host_meta, duration) in resolutions {
+            self.total_resolutions_attempted += 1;
+            if !web_host_meta.is_valid() {
+                self.failed_resolutions += 1;
+            } else if web_host_meta.is_empty() {
+                self.empty_resolutions += 1;
+            } else {
+                self.successful_resolutions += 1;
+            }
+            total_resolution_ms += duration.as_micros() as f64 / 1000.0;
+        }
+
+        if self.total_resolutions_attempted > 0 {
+            self.average_resolution_ms =
+                total_resolution_ms / self.total_resolutions_attempted as f64;
+        }
+        self.last_crawl_duration_ms = crawl_duration.as_millis() as u64;
+        self.services_in_cache = services_in_cache;
+    }
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -54,28 +134,96 @@ struct Key {
     service_id: String,
 }
 
+impl Key {
+    fn new(app_name: AppName, service_id: String) -> Self {
+        Self {
+            app_name,
+            service_id,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 struct Value {
     timestamp: DateTime<Utc>,
+    service_name: String,
     web_host_meta: WebHostMeta,
 }
 
 pub fn new() -> (HostMetaCache, HostMetaCrawler) {
     let (reader, writer) = evmap::new();
+    let metrics = Arc::new(StdMutex::new(CrawlerMetrics::default()));
+    let (forced_crawls_sender, forced_crawls_receiver) = channel(64);
 
     (
         HostMetaCache {
             reader_factory: reader.factory(),
+            metrics: metrics.clone(),
+            forced_crawls: forced_crawls_sender,
+        },
+        HostMetaCrawler {
+            writer,
+            metrics,
+            forced_crawls: forced_crawls_receiver,
+            request_interval: None,
+            services: MultiMap::new(),
+            keys_to_retry: HashSet::new(),
+            circuit_breaker: CircuitBreaker::new(None, chrono::Duration::zero()),
+            crawl_priority: HashMap::new(),
         },
-        HostMetaCrawler { writer },
     )
 }
 
 impl HostMetaCache {
+    pub fn metrics(&self) -> CrawlerMetrics {
+        self.metrics.lock().unwrap().clone()
+    }
+
+    /// Requests the crawler to resolve the web host meta data of all services of the app right
+    /// away instead of waiting for the next crawl, e.g. after the app has been deployed.
+    pub fn force_crawl_app(&self, app_name: &AppName) {
+        match self.forced_crawls.try_send(app_name.clone()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(app_name)) => {
+                debug!("Skipping forced crawl of {app_name} because too many crawls are pending")
+            }
+            Err(TrySendError::Closed(app_name)) => {
+                warn!("Cannot force crawl of {app_name} because the crawler is not running")
+            }
+        }
+    }
+
+    pub fn get_cache_stats(&self) -> CacheStats {
+        let reader = self.reader_factory.handle();
+
+        let entries = reader
+            .map_into::<_, Vec<_>, _>(|key, values| {
+                values
+                    .iter()
+                    .map(|value| (key.app_name.clone(), value.timestamp))
+                    .collect::<Vec<_>>()
+            })
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        CacheStats {
+            total_entries: entries.len(),
+            apps_with_entries: entries
+                .iter()
+                .map(|(app_name, _)| app_name)
+                .collect::<HashSet<_>>()
+                .len(),
+            oldest_entry: entries.iter().map(|(_, timestamp)| *timestamp).min(),
+            newest_entry: entries.iter().map(|(_, timestamp)| *timestamp).max(),
+        }
+    }
+
     pub fn update_meta_data(
         &self,
         services: MultiMap<AppName, Service>,
         request_info: &RequestInfo,
+        subdomain_routing: Option<&SubdomainRoutingConfig>,
     ) -> MultiMap<AppName, Service> {
         let mut assigned_apps = MultiMap::new();
 
@@ -88,14 +236,25 @@ impl HostMetaCache {
                     service_id: service.id().to_string(),
                 };
 
+                let service_url = subdomain_routing.and_then(|subdomain_routing| {
+                    subdomain_routing.service_url(
+                        request_info.get_base_url(),
+                        app_name,
+                        service.service_name(),
+                    )
+                });
+
                 let mut b =
                     ServiceBuilder::from(service).base_url(request_info.get_base_url().clone());
                 if let Some(value) = reader.get_one(&key) {
-                    b = b.web_host_meta(
-                        value
-                            .web_host_meta
-                            .with_base_url(request_info.get_base_url()),
-                    );
+                    b = b.web_host_meta(public_web_host_meta(
+                        &value.web_host_meta,
+                        service_url.as_ref(),
+                        request_info,
+                    ));
+                }
+                if let Some(service_url) = service_url {
+                    b = b.service_url(service_url);
                 }
 
                 assigned_apps.insert(key.app_name, b.build().unwrap());
@@ -104,53 +263,360 @@ impl HostMetaCache {
 
         assigned_apps
     }
+
+    /// Returns the cached web host meta data of the service whose links point to the public URL
+    /// of the service, or `None` if the service has not been crawled yet.
+    pub fn get_one(
+        &self,
+        app_name: &AppName,
+        service_id: &str,
+        request_info: &RequestInfo,
+        subdomain_routing: Option<&SubdomainRoutingConfig>,
+    ) -> Option<WebHostMeta> {
+        let reader = self.reader_factory.handle();
+        let value = reader.get_one(&Key::new(app_name.clone(), service_id.to_string()))?;
+
+        let service_url = subdomain_routing.and_then(|subdomain_routing| {
+            subdomain_routing.service_url(
+                request_info.get_base_url(),
+                app_name,
+                &value.service_name,
+            )
+        });
+        Some(public_web_host_meta(
+            &value.web_host_meta,
+            service_url.as_ref(),
+            request_info,
+        ))
+    }
+
+    /// Returns the names of the app's services together with the targets of their links that have
+    /// the given relation type. Like in `update_meta_data`, the targets point to the public URL of
+    /// the services.
+    pub fn find_by_relation(
+        &self,
+        app_name: &AppName,
+        rel: &str,
+        request_info: &RequestInfo,
+        subdomain_routing: Option<&SubdomainRoutingConfig>,
+    ) -> Vec<(String, Url)> {
+        self.web_host_meta_of_app(app_name, request_info, subdomain_routing)
+            .into_iter()
+            .flat_map(|(service_name, web_host_meta)| {
+                web_host_meta
+                    .links_with_relation(rel)
+                    .into_iter()
+                    .map(move |href| (service_name.clone(), href))
+            })
+            .collect()
+    }
+
+    /// Merges the web host meta data of the app's services into a single document. Returns `None`
+    /// if none of the services provides web host meta data.
+    pub fn merged_web_host_meta(
+        &self,
+        app_name: &AppName,
+        request_info: &RequestInfo,
+        subdomain_routing: Option<&SubdomainRoutingConfig>,
+    ) -> Option<WebHostMeta> {
+        let metas = self
+            .web_host_meta_of_app(app_name, request_info, subdomain_routing)
+            .into_iter()
+            .map(|(_, web_host_meta)| web_host_meta)
+            .filter(|web_host_meta| !web_host_meta.is_empty())
+            .collect::<Vec<_>>();
+
+        if metas.is_empty() {
+            None
+        } else {
+            Some(WebHostMeta::merge(metas))
+        }
+    }
+
+    /// Returns the cached web host meta data of the app's services, sorted by service name, whose
+    /// links point to the public URL of the services.
+    fn web_host_meta_of_app(
+        &self,
+        app_name: &AppName,
+        request_info: &RequestInfo,
+        subdomain_routing: Option<&SubdomainRoutingConfig>,
+    ) -> Vec<(String, WebHostMeta)> {
+        let reader = self.reader_factory.handle();
+
+        let mut web_host_metas = reader
+            .map_into::<_, Vec<_>, _>(|key, values| {
+                if &key.app_name != app_name {
+                    return Vec::new();
+                }
+
+                values
+                    .iter()
+                    .map(|value| {
+                        let service_url = subdomain_routing.and_then(|subdomain_routing| {
+                            subdomain_routing.service_url(
+                                request_info.get_base_url(),
+                                app_name,
+                                &value.service_name,
+                            )
+                        });
+
+                        let web_host_meta = public_web_host_meta(
+                            &value.web_host_meta,
+                            service_url.as_ref(),
+                            request_info,
+                        );
+                        (value.service_name.clone(), web_host_meta)
+                    })
+                    .collect()
+            })
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        web_host_metas.sort_by(|(service_name, _), (other_service_name, _)| {
+            service_name.cmp(other_service_name)
+        });
+        web_host_metas
+    }
+}
+
+/// Points the links of the web host meta data to the public URL of the service. Services that are
+/// routed by their subdomain are served from the root path, thus, the path prefix of the request
+/// only applies to the other services.
+fn public_web_host_meta(
+    web_host_meta: &WebHostMeta,
+    service_url: Option<&Url>,
+    request_info: &RequestInfo,
+) -> WebHostMeta {
+    match service_url {
+        Some(service_url) => web_host_meta.with_base_url(service_url, None),
+        None => web_host_meta.with_base_url(
+            request_info.get_base_url(),
+            request_info.get_forwarded_prefix(),
+        ),
+    }
 }
 
 impl HostMetaCrawler {
+    /// Limits the number of requests per second that the crawler sends to the services.
+    pub fn with_rate_limit(mut self, requests_per_second: Option<u32>) -> Self {
+        self.request_interval = requests_per_second
+            .filter(|requests_per_second| *requests_per_second > 0)
+            .map(|requests_per_second| Duration::from_secs(1) / requests_per_second);
+        self
+    }
+
+    /// Stops retrying a service for `open_duration` after it failed `threshold` times in a row.
+    /// Without threshold, failed services are retried in each crawl.
+    pub fn with_circuit_breaker(mut self, threshold: Option<u32>, open_duration: Duration) -> Self {
+        self.circuit_breaker = CircuitBreaker::new(
+            threshold,
+            chrono::Duration::from_std(open_duration).unwrap_or_else(|_| chrono::Duration::zero()),
+        );
+        self
+    }
+
+    pub fn with_crawl_priorities(mut self, crawl_priorities: HashMap<AppName, u8>) -> Self {
+        for (app_name, priority) in crawl_priorities {
+            self.set_crawl_priority(app_name, priority);
+        }
+        self
+    }
+
+    /// Makes the crawler resolve the web host meta data of the app's services before the ones of
+    /// apps with lower priority.
+    pub fn set_crawl_priority(&mut self, app_name: AppName, priority: u8) {
+        self.crawl_priority.insert(app_name, priority);
+    }
+
+    /// Orders the services by the priority of their apps. Within the same priority, services that
+    /// have been started within the last minute come first so that users of new deployments do not
+    /// have to wait for the services that are still starting elsewhere.
+    fn sort_by_crawl_priority(&self, services: &mut [(Key, Service)], now: DateTime<Utc>) {
+        services.sort_by_key(|(key, service)| {
+            let priority = self
+                .crawl_priority
+                .get(&key.app_name)
+                .copied()
+                .unwrap_or_default();
+            let is_new =
+                now.signed_duration_since(*service.started_at()) < chrono::Duration::minutes(1);
+            std::cmp::Reverse((priority, is_new))
+        });
+    }
+
     pub fn spawn(mut self, apps: Arc<Apps>) {
         let timestamp_prevant_startup = Utc::now();
 
         tokio::spawn(async move {
+            let rate_limiter = self.request_interval.map(|request_interval| {
+                let mut rate_limiter = interval(request_interval);
+                rate_limiter.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                Mutex::new(rate_limiter)
+            });
+
             loop {
-                sleep(Duration::from_secs(5)).await;
-                if let Err(err) = self.crawl(apps.clone(), timestamp_prevant_startup).await {
-                    error!("Cannot load apps: {}", err);
+                let forced_app_name = tokio::select! {
+                    _ = sleep(Duration::from_secs(5)) => None,
+                    Some(app_name) = self.forced_crawls.recv() => Some(app_name),
+                };
+
+                let Some(app_name) = forced_app_name else {
+                    if let Err(err) = self
+                        .crawl(
+                            apps.clone(),
+                            timestamp_prevant_startup,
+                            rate_limiter.as_ref(),
+                        )
+                        .await
+                    {
+                        error!("Cannot load apps: {}", err);
+                    }
+                    continue;
+                };
+
+                let mut app_names = HashSet::from([app_name]);
+                while let Ok(app_name) = self.forced_crawls.try_recv() {
+                    app_names.insert(app_name);
+                }
+                for app_name in app_names {
+                    if let Err(err) = self
+                        .crawl_single_app(
+                            apps.clone(),
+                            &app_name,
+                            timestamp_prevant_startup,
+                            rate_limiter.as_ref(),
+                        )
+                        .await
+                    {
+                        error!("Cannot load apps: {}", err);
+                    }
                 }
             }
         });
     }
 
+    /// Resolves the web host meta data of all services of the app, including the services that
+    /// have already been crawled.
+    async fn crawl_single_app(
+        &mut self,
+        all_apps: Arc<Apps>,
+        app_name: &AppName,
+        since_timestamp: DateTime<Utc>,
+        rate_limiter: Option<&Mutex<Interval>>,
+    ) -> Result<(), AppsError> {
+        debug!("Resolving web host meta data of {app_name} on request.");
+        let Some(services) = all_apps.get_apps().await?.remove(app_name) else {
+            return Ok(());
+        };
+
+        let services_to_crawl = services
+            .iter()
+            .map(|service| {
+                (
+                    Key::new(app_name.clone(), service.id().to_string()),
+                    service.clone(),
+                )
+            })
+            .collect::<Vec<(Key, Service)>>();
+
+        // The next crawl must not crawl these services again.
+        self.services.remove(app_name);
+        self.services.insert_many(app_name.clone(), services);
+
+        self.resolve_and_store_host_meta(
+            all_apps,
+            services_to_crawl,
+            since_timestamp,
+            rate_limiter,
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Resolves the web host meta data of the new services. All changes to the cache, i.e. the
+    /// cleared stale entries and the resolved entries, are published to the readers with a single
+    /// `refresh()` at the end of the crawl because each refresh has to wait until all readers have
+    /// left the previous map.
     async fn crawl(
         &mut self,
         all_apps: Arc<Apps>,
         since_timestamp: DateTime<Utc>,
+        rate_limiter: Option<&Mutex<Interval>>,
     ) -> Result<(), AppsError> {
         debug!("Resolving list of apps for web host meta cache.");
         let apps = all_apps.get_apps().await?;
 
-        self.clear_stale_web_host_meta(&apps);
+        let has_cleared_stale_entries = self.clear_stale_web_host_meta(&apps);
+
+        let diff = diff_services(&self.services, &apps);
+        for (app_name, services) in diff.removed().iter_all() {
+            for service in services {
+                let key = Key::new(app_name.clone(), service.id().to_string());
+                self.circuit_breaker.remove(&key);
+                self.keys_to_retry.remove(&key);
+            }
+        }
 
-        let services_without_host_meta = apps
+        let added_services = diff.added().iter_all().flat_map(|(app_name, services)| {
+            services.iter().map(move |service| {
+                (
+                    Key::new(app_name.clone(), service.id().to_string()),
+                    service,
+                )
+            })
+        });
+        let now = Utc::now();
+        let keys_to_retry = &self.keys_to_retry;
+        let circuit_breaker = &mut self.circuit_breaker;
+        let services_to_retry = diff
+            .unchanged()
             .iter_all()
             .flat_map(|(app_name, services)| {
-                services
-                    .iter()
-                    // avoid cloning when https://github.com/havarnov/multimap/issues/24 has been implemented
-                    .map(move |service| {
-                        let key = Key {
-                            app_name: app_name.clone(),
-                            service_id: service.id().to_string(),
-                        };
-                        (key, service.clone())
-                    })
+                services.iter().map(move |service| {
+                    (
+                        Key::new(app_name.clone(), service.id().to_string()),
+                        service,
+                    )
+                })
             })
-            .filter(|(key, _service)| !self.writer.contains_key(key))
+            .filter(|(key, _service)| {
+                keys_to_retry.contains(key) && circuit_breaker.allows_attempt(key, now)
+            });
+        // avoid cloning when https://github.com/havarnov/multimap/issues/24 has been implemented
+        let mut services_without_host_meta = added_services
+            .chain(services_to_retry)
+            .map(|(key, service)| (key, service.clone()))
             .collect::<Vec<(Key, Service)>>();
+        self.services = apps;
+        self.sort_by_crawl_priority(&mut services_without_host_meta, now);
 
         if services_without_host_meta.is_empty() {
+            if has_cleared_stale_entries {
+                self.writer.refresh();
+            }
             return Ok(());
         }
 
+        self.resolve_and_store_host_meta(
+            all_apps,
+            services_without_host_meta,
+            since_timestamp,
+            rate_limiter,
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Resolves the web host meta data of the services and publishes the valid ones with a
+    /// single `refresh()`. The invalid ones will be crawled again.
+    async fn resolve_and_store_host_meta(
+        &mut self,
+        all_apps: Arc<Apps>,
+        services_without_host_meta: Vec<(Key, Service)>,
+        since_timestamp: DateTime<Utc>,
+        rate_limiter: Option<&Mutex<Interval>>,
+    ) {
         debug!(
             "Resolving web host meta data for {:?}.",
             services_without_host_meta
@@ -159,32 +625,52 @@ impl HostMetaCrawler {
                 .fold(String::new(), |a, b| a + &b + ", ")
         );
         let now = Utc::now();
+        let crawl_started_at = Instant::now();
         let duration_prevant_startup = Utc::now().signed_duration_since(since_timestamp);
         let resolved_host_meta_infos = Self::resolve_host_meta(
             all_apps,
             services_without_host_meta,
             duration_prevant_startup,
+            rate_limiter,
         )
         .await;
-        for (key, _service, web_host_meta) in resolved_host_meta_infos {
+
+        let mut resolutions = Vec::with_capacity(resolved_host_meta_infos.len());
+        for (key, service, web_host_meta, duration) in resolved_host_meta_infos {
+            resolutions.push((web_host_meta.clone(), duration));
             if !web_host_meta.is_valid() {
+                self.circuit_breaker.record_failure(&key, now);
+                self.keys_to_retry.insert(key);
                 continue;
             }
+            self.circuit_breaker.record_success(&key);
+            self.keys_to_retry.remove(&key);
 
-            self.writer.insert(
+            self.writer.update(
                 key,
                 Arc::new(Value {
                     timestamp: now,
+                    service_name: service.service_name().clone(),
                     web_host_meta,
                 }),
             );
         }
 
         self.writer.refresh();
-        Ok(())
+
+        self.metrics.lock().unwrap().record_crawl(
+            resolutions
+                .iter()
+                .map(|(web_host_meta, duration)| (web_host_meta, *duration)),
+            crawl_started_at.elapsed(),
+            self.writer.len(),
+        );
     }
 
-    fn clear_stale_web_host_meta(&mut self, apps: &MultiMap<AppName, Service>) {
+    /// Removes the entries of services that are gone, paused, or restarted since they have been
+    /// crawled. The removal is not visible to the readers until the caller refreshes the writer.
+    /// Returns `true` if any entry has been removed.
+    fn clear_stale_web_host_meta(&mut self, apps: &MultiMap<AppName, Service>) -> bool {
         let copy: HashMap<Key, Vec<_>> = self
             .writer
             .map_into(|k, vs| (k.clone(), vs.iter().cloned().collect()));
@@ -212,7 +698,7 @@ impl HostMetaCrawler {
             .collect::<HashSet<Key>>();
 
         if keys_to_clear.is_empty() {
-            return;
+            return false;
         }
 
         debug!("Clearing stale apps: {:?}", keys_to_clear);
@@ -220,24 +706,31 @@ impl HostMetaCrawler {
         for key in keys_to_clear {
             self.writer.empty(key);
         }
-        self.writer.refresh();
+        true
     }
 
     async fn resolve_host_meta(
         apps: Arc<Apps>,
         services_without_host_meta: Vec<(Key, Service)>,
         duration_prevant_startup: chrono::Duration,
-    ) -> Vec<(Key, Service, WebHostMeta)> {
+        rate_limiter: Option<&Mutex<Interval>>,
+    ) -> Vec<(Key, Service, WebHostMeta, Duration)> {
         let number_of_services = services_without_host_meta.len();
         if number_of_services == 0 {
             return Vec::with_capacity(0);
         }
 
         let infrastructure = apps.infrastructure();
+        let subdomain_routing = apps.subdomain_routing().is_some();
 
         let mut futures = services_without_host_meta
             .into_iter()
             .map(|(key, service)| async {
+                if let Some(rate_limiter) = rate_limiter {
+                    rate_limiter.lock().await.tick().await;
+                }
+                let started_at = Instant::now();
+
                 let http_forwarder = match infrastructure.http_forwarder().await {
                     Ok(portforwarder) => portforwarder,
                     Err(err) => {
@@ -246,11 +739,18 @@ impl HostMetaCrawler {
                             key.app_name,
                             service.service_name()
                         );
-                        return (key, service, WebHostMeta::empty());
+                        return (key, service, WebHostMeta::empty(), started_at.elapsed());
                     }
                 };
-                Self::resolve_web_host_meta(http_forwarder, key, service, duration_prevant_startup)
-                    .await
+                let (key, service, web_host_meta) = Self::resolve_web_host_meta(
+                    http_forwarder,
+                    key,
+                    service,
+                    duration_prevant_startup,
+                    subdomain_routing,
+                )
+                .await;
+                (key, service, web_host_meta, started_at.elapsed())
             })
             .collect::<FuturesUnordered<_>>();
 
@@ -267,7 +767,14 @@ impl HostMetaCrawler {
         key: Key,
         service: Service,
         duration_prevant_startup: chrono::Duration,
+        subdomain_routing: bool,
     ) -> (Key, Service, WebHostMeta) {
+        // Services routed by their subdomain are served from the root path.
+        let forwarded_prefix = if subdomain_routing {
+            String::new()
+        } else {
+            format!("/{}/{}", service.app_name(), service.service_name())
+        };
         let response = http_forwarder
             .request_web_host_meta(
                 &key.app_name,
@@ -283,10 +790,7 @@ impl HostMetaCrawler {
                     .header(HOST, "127.0.0.1")
                     .header("Connection", "Close")
                     .header("Forwarded", "host=www.prevant.example.com;proto=http")
-                    .header(
-                        "X-Forwarded-Prefix",
-                        format!("/{}/{}", service.app_name(), service.service_name()),
-                    )
+                    .header("X-Forwarded-Prefix", forwarded_prefix)
                     .header("Accept", "application/json")
                     .body(http_body_util::Empty::<bytes::Bytes>::new())
                     .unwrap(),
@@ -336,9 +840,23 @@ impl HostMetaCrawler {
     }
     #[cfg(test)]
     pub fn fake_empty_host_meta_info(&mut self, app_name: AppName, service_id: String) {
-        let web_host_meta = WebHostMeta::empty();
+        self.fake_host_meta_info(app_name, service_id, WebHostMeta::empty());
+    }
+
+    /// Caches the web host meta data for the service whose ID must equal its name.
+    ///
+    /// A single `refresh()` makes the value visible to the readers. `flush()` only refreshes if
+    /// there are unpublished changes, thus, it is not needed after `refresh()`.
+    #[cfg(test)]
+    pub fn fake_host_meta_info(
+        &mut self,
+        app_name: AppName,
+        service_id: String,
+        web_host_meta: WebHostMeta,
+    ) {
         let value = Arc::new(Value {
             timestamp: chrono::Utc::now(),
+            service_name: service_id.clone(),
             web_host_meta,
         });
 
@@ -351,6 +869,110 @@ impl HostMetaCrawler {
         );
 
         self.writer.refresh();
-        self.writer.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn should_provide_cache_stats() {
+        let (cache, mut crawler) = new();
+        assert_eq!(cache.get_cache_stats(), CacheStats::default());
+
+        crawler.fake_empty_host_meta_info(AppName::master(), String::from("service-a"));
+        crawler.fake_empty_host_meta_info(AppName::master(), String::from("service-b"));
+        crawler.fake_empty_host_meta_info(
+            AppName::from_str("other").unwrap(),
+            String::from("service-a"),
+        );
+
+        let stats = cache.get_cache_stats();
+        assert_eq!(stats.total_entries, 3);
+        assert_eq!(stats.apps_with_entries, 2);
+        assert!(stats.oldest_entry.is_some());
+        assert!(stats.oldest_entry <= stats.newest_entry);
+    }
+
+    #[tokio::test]
+    async fn should_queue_forced_crawls() {
+        let (cache, mut crawler) = new();
+
+        cache.force_crawl_app(&AppName::master());
+
+        assert_eq!(crawler.forced_crawls.try_recv(), Ok(AppName::master()));
+    }
+
+    #[test]
+    fn should_sort_services_by_crawl_priority() {
+        let (_cache, crawler) = new();
+        let mut crawler = crawler.with_crawl_priorities(HashMap::from([(
+            AppName::from_str("important").unwrap(),
+            5,
+        )]));
+        crawler.set_crawl_priority(AppName::from_str("unimportant").unwrap(), 0);
+
+        let now = Utc::now();
+        let service = |app_name: &str, started_at: DateTime<Utc>| {
+            (
+                Key::new(AppName::from_str(app_name).unwrap(), String::from(app_name)),
+                ServiceBuilder::new()
+                    .id(String::from(app_name))
+                    .app_name(String::from(app_name))
+                    .config(crate::sc!("service-a"))
+                    .started_at(started_at)
+                    .build()
+                    .unwrap(),
+            )
+        };
+        let mut services = vec![
+            service("unimportant", now - chrono::Duration::hours(1)),
+            service("new", now - chrono::Duration::seconds(10)),
+            service("important", now - chrono::Duration::hours(1)),
+        ];
+
+        crawler.sort_by_crawl_priority(&mut services, now);
+
+        assert_eq!(
+            services
+                .iter()
+                .map(|(key, _)| key.app_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["important", "new", "unimportant"]
+        );
+    }
+
+    #[test]
+    fn should_record_crawl_metrics() {
+        let mut metrics = CrawlerMetrics::default();
+
+        metrics.record_crawl(
+            [
+                (&WebHostMeta::empty(), Duration::from_millis(10)),
+                (&WebHostMeta::invalid(), Duration::from_millis(30)),
+            ],
+            Duration::from_millis(42),
+            1,
+        );
+        metrics.record_crawl(
+            [(&WebHostMeta::invalid(), Duration::from_millis(50))],
+            Duration::from_millis(50),
+            1,
+        );
+
+        assert_eq!(
+            metrics,
+            CrawlerMetrics {
+                total_resolutions_attempted: 3,
+                successful_resolutions: 0,
+                failed_resolutions: 2,
+                empty_resolutions: 1,
+                average_resolution_ms: 30.0,
+                last_crawl_duration_ms: 50,
+                services_in_cache: 1,
+            }
+        );
     }
 }
@@ -23,30 +23,54 @@
  * THE SOFTWARE.
  * =========================LICENSE_END==================================
  */
+mod app_diff;
+mod bulk_operation;
+mod circuit_breaker;
+mod config_dir_watcher;
 mod host_meta_cache;
+mod network_cleaner;
 mod routes;
 
 pub use crate::apps::AppsService as Apps;
 pub use crate::apps::AppsServiceError as AppsError;
-use crate::config::{Config, ConfigError};
+use crate::config::{Config, ConfigError, PullPolicy, SubdomainRoutingConfig};
 use crate::deployment::deployment_unit::DeploymentUnitBuilder;
-use crate::infrastructure::Infrastructure;
+use crate::deployment::RolloutStrategy;
+use crate::infrastructure::{
+    AppDeployment, DockerInfrastructureError, Infrastructure, NetworkInfo, StoppedService,
+};
 use crate::models::service::{ContainerType, Service, ServiceStatus};
-use crate::models::{AppName, AppStatusChangeId, LogChunk, ServiceConfig};
+use crate::models::{
+    AppName, AppNameError, AppStateArchive, AppStatusChangeId, ComposeWriter, Environment,
+    LogChunk, LogLine, ServiceConfig, ServiceQuery, ServiceSearchResult,
+};
 use crate::registry::Registry;
 use crate::registry::RegistryError;
-use chrono::{DateTime, FixedOffset};
+pub use app_diff::AppDiff;
+pub use bulk_operation::BulkOperationResult;
+use chrono::{DateTime, FixedOffset, Utc};
+pub use config_dir_watcher::ConfigDirWatcher;
 use futures::stream::BoxStream;
 use futures::StreamExt;
 use handlebars::RenderError;
 pub use host_meta_cache::new as host_meta_crawling;
-pub use host_meta_cache::HostMetaCache;
+pub use host_meta_cache::{CacheStats, CrawlerMetrics, HostMetaCache};
 use multimap::MultiMap;
-pub use routes::{apps_routes, delete_app_sync};
+pub use network_cleaner::NetworkCleaner;
+pub use routes::{apps_routes, delete_app_sync, services_routes};
 use std::collections::{HashMap, HashSet};
 use std::convert::From;
 use std::sync::{Arc, Condvar, Mutex};
 use std::time::Duration;
+use tokio::net::TcpStream;
+
+/// Services without health check are considered to be ready after they have been running for this
+/// period.
+static READY_GRACE_PERIOD: Duration = Duration::from_secs(5);
+static READY_MAX_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Bounds how long a request may wait for an app to become ready so that clients cannot tie up
+/// requests for arbitrary durations.
+static READY_MAX_TIMEOUT: Duration = Duration::from_secs(3600);
 
 pub struct AppsService {
     config: Config,
@@ -143,10 +167,92 @@ impl AppsService {
         self.infrastructure.as_ref()
     }
 
+    /// Deletes the networks of apps without containers, see
+    /// [`Infrastructure::delete_orphaned_networks`].
+    pub async fn delete_orphaned_networks(
+        &self,
+        dry_run: bool,
+    ) -> Result<Vec<NetworkInfo>, AppsServiceError> {
+        Ok(self
+            .infrastructure
+            .delete_orphaned_networks(dry_run)
+            .await?)
+    }
+
+    pub fn subdomain_routing(&self) -> Option<&SubdomainRoutingConfig> {
+        self.config.subdomain_routing()
+    }
+
+    pub fn host_meta_ttl(&self) -> Duration {
+        self.config.host_meta_ttl()
+    }
+
     /// Analyzes running containers and returns a map of `app-name` with the
     /// corresponding list of `Service`s.
     pub async fn get_apps(&self) -> Result<MultiMap<AppName, Service>, AppsServiceError> {
-        Ok(self.infrastructure.get_services().await?)
+        let mut apps = self.infrastructure.get_services().await?;
+        apps.retain(|app_name, _| self.config.is_app_in_shard(app_name));
+        Ok(apps)
+    }
+
+    /// Returns the services of all apps that match the query, ordered by app and service name.
+    pub async fn search_services(
+        &self,
+        query: &ServiceQuery,
+    ) -> Result<Vec<ServiceSearchResult>, AppsServiceError> {
+        let mut results = self.infrastructure.search_services(query).await?;
+        results.retain(|result| self.config.is_app_in_shard(result.app_name()));
+        results.sort_by(|a, b| {
+            a.app_name()
+                .as_str()
+                .cmp(b.app_name().as_str())
+                .then_with(|| a.service_name().cmp(b.service_name()))
+        });
+        Ok(results)
+    }
+
+    /// Returns the names of the deployed apps without resolving the details of their services.
+    pub async fn get_app_names(&self) -> Result<HashSet<AppName>, AppsServiceError> {
+        let mut app_names = self.infrastructure.get_app_names().await?;
+        app_names.retain(|app_name| self.config.is_app_in_shard(app_name));
+        Ok(app_names)
+    }
+
+    fn ensure_app_in_shard(&self, app_name: &AppName) -> Result<(), AppsServiceError> {
+        if self.config.is_app_in_shard(app_name) {
+            Ok(())
+        } else {
+            Err(AppsServiceError::AppNotInShard {
+                app_name: app_name.clone(),
+            })
+        }
+    }
+
+    pub async fn get_stopped_services(
+        &self,
+        app_name: &AppName,
+    ) -> Result<Vec<StoppedService>, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
+        Ok(self.infrastructure.get_stopped_services(app_name).await?)
+    }
+
+    pub async fn prune_stopped_containers(
+        &self,
+        app_name: &AppName,
+    ) -> Result<usize, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
+        Ok(self
+            .infrastructure
+            .prune_stopped_containers(app_name)
+            .await?)
+    }
+
+    pub async fn get_deployments(
+        &self,
+        app_name: &AppName,
+    ) -> Result<Vec<AppDeployment>, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
+        Ok(self.infrastructure.get_deployments(app_name).await?)
     }
 
     fn create_or_get_app_guard(
@@ -197,6 +303,8 @@ impl AppsService {
             .map(|config| {
                 let mut replicated_config = config;
                 replicated_config.set_container_type(ContainerType::Replica);
+                let replicated_env = replicated_config.env().and_then(Environment::replicated);
+                replicated_config.set_env(replicated_env);
                 replicated_config
             })
             .collect::<Vec<ServiceConfig>>())
@@ -234,6 +342,33 @@ impl AppsService {
         replicate_from: Option<AppName>,
         service_configs: &[ServiceConfig],
     ) -> Result<Vec<Service>, AppsServiceError> {
+        self.create_or_update_with_rollout_strategy(
+            app_name,
+            status_id,
+            replicate_from,
+            service_configs,
+            RolloutStrategy::default(),
+            false,
+        )
+        .await
+    }
+
+    /// Like [`create_or_update`](Self::create_or_update) but replaces the running services of the
+    /// application according to the given `rollout_strategy`. If `skip_companions` is set, the
+    /// companions of the configuration won't be added to the application.
+    pub async fn create_or_update_with_rollout_strategy(
+        &self,
+        app_name: &AppName,
+        status_id: &AppStatusChangeId,
+        replicate_from: Option<AppName>,
+        service_configs: &[ServiceConfig],
+        rollout_strategy: RolloutStrategy,
+        skip_companions: bool,
+    ) -> Result<Vec<Service>, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
+        app_name
+            .check_deployable(self.config.additional_reserved_app_names())
+            .map_err(|error| AppsServiceError::AppNameNotDeployable { error })?;
         let guard = self.create_or_get_app_guard(app_name.clone(), AppGuardKind::Deployment)?;
 
         if !guard.is_first() {
@@ -244,8 +379,15 @@ impl AppsService {
 
         guard.notify_with_result(
             self,
-            self.create_or_update_impl(app_name, status_id, replicate_from, service_configs)
-                .await,
+            self.create_or_update_impl(
+                app_name,
+                status_id,
+                replicate_from,
+                service_configs,
+                rollout_strategy,
+                skip_companions,
+            )
+            .await,
         )
     }
 
@@ -255,12 +397,14 @@ impl AppsService {
         status_id: &AppStatusChangeId,
         replicate_from: Option<AppName>,
         service_configs: &[ServiceConfig],
+        rollout_strategy: RolloutStrategy,
+        skip_companions: bool,
     ) -> Result<Vec<Service>, AppsServiceError> {
         if let Some(app_limit) = self.config.app_limit() {
-            let apps = self.get_apps().await?;
+            let app_names = self.get_app_names().await?;
 
-            if apps
-                .keys()
+            if app_names
+                .iter()
                 // filtering the app_name that is send because otherwise clients wouldn't be able
                 // to update an existing application.
                 .filter(|existing_app_name| *existing_app_name != app_name)
@@ -295,8 +439,12 @@ impl AppsService {
             })
             .collect::<Vec<_>>();
 
-        let deployment_unit_builder = DeploymentUnitBuilder::init(app_name.clone(), configs)
-            .extend_with_config(&self.config)
+        let mut deployment_unit_builder =
+            DeploymentUnitBuilder::init(app_name.clone(), configs).extend_with_config(&self.config);
+        if skip_companions {
+            deployment_unit_builder = deployment_unit_builder.without_companions();
+        }
+        let deployment_unit_builder = deployment_unit_builder
             .extend_with_templating_only_service_configs(configs_for_templating);
 
         let images = deployment_unit_builder.images();
@@ -317,19 +465,21 @@ impl AppsService {
             .apply_hooks(&self.config)
             .await?;
 
-        let deployment_unit = if let Some(base_traefik_ingress_route) = base_traefik_ingress_route {
-            trace!(
-                "The base URL for {app_name} is: {:?}",
-                base_traefik_ingress_route
-                    .to_url()
-                    .map(|url| url.to_string())
-            );
-            deployment_unit_builder
-                .apply_base_traefik_ingress_route(base_traefik_ingress_route)
-                .build()
-        } else {
-            deployment_unit_builder.build()
-        };
+        let mut deployment_unit =
+            if let Some(base_traefik_ingress_route) = base_traefik_ingress_route {
+                trace!(
+                    "The base URL for {app_name} is: {:?}",
+                    base_traefik_ingress_route
+                        .to_url()
+                        .map(|url| url.to_string())
+                );
+                deployment_unit_builder
+                    .apply_base_traefik_ingress_route(base_traefik_ingress_route)
+                    .build()
+            } else {
+                deployment_unit_builder.build()
+            };
+        deployment_unit.set_rollout_strategy(rollout_strategy);
 
         let services = self
             .infrastructure
@@ -349,23 +499,97 @@ impl AppsService {
         app_name: &AppName,
         status_id: &AppStatusChangeId,
     ) -> Result<Vec<Service>, AppsServiceError> {
+        self.delete_app_with_volumes(app_name, status_id, false)
+            .await
+    }
+
+    /// Like [`delete_app`](Self::delete_app) but keeps the named volumes of the services if
+    /// `preserve_volumes` is set.
+    pub async fn delete_app_with_volumes(
+        &self,
+        app_name: &AppName,
+        status_id: &AppStatusChangeId,
+        preserve_volumes: bool,
+    ) -> Result<Vec<Service>, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
         let guard = self.create_or_get_app_guard(app_name.clone(), AppGuardKind::Deletion)?;
 
         if !guard.is_first() {
             guard.wait_for_result()
         } else {
-            guard.notify_with_result(self, self.delete_app_impl(app_name, status_id).await)
+            guard.notify_with_result(
+                self,
+                self.delete_app_impl(app_name, status_id, preserve_volumes)
+                    .await,
+            )
+        }
+    }
+
+    /// Deletes the given apps like [`delete_app_with_volumes`](Self::delete_app_with_volumes) while
+    /// at most the configured number of apps are deleted at the same time.
+    pub async fn delete_apps(
+        &self,
+        app_names: &[AppName],
+        preserve_volumes: bool,
+    ) -> BulkOperationResult {
+        bulk_operation::run(
+            app_names,
+            self.config.bulk_operation_parallelism(),
+            |app_name| async move {
+                self.delete_app_with_volumes(app_name, &AppStatusChangeId::new(), preserve_volumes)
+                    .await
+            },
+        )
+        .await
+    }
+
+    /// Starts the services of the given apps that are not running, e.g. because they have been
+    /// stopped via [`change_status`](Self::change_status), while at most the configured number of
+    /// apps are started at the same time.
+    pub async fn start_apps(&self, app_names: &[AppName]) -> BulkOperationResult {
+        bulk_operation::run(
+            app_names,
+            self.config.bulk_operation_parallelism(),
+            |app_name| self.start_app(app_name),
+        )
+        .await
+    }
+
+    async fn start_app(&self, app_name: &AppName) -> Result<Vec<Service>, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
+
+        let Some(services) = self.infrastructure.get_services().await?.remove(app_name) else {
+            return Err(AppsServiceError::AppNotFound {
+                app_name: app_name.clone(),
+            });
+        };
+
+        let mut started_services = Vec::new();
+        for service in services
+            .into_iter()
+            .filter(|service| *service.status() != ServiceStatus::Running)
+        {
+            if let Some(service) = self
+                .infrastructure
+                .change_status(app_name, service.service_name(), ServiceStatus::Running)
+                .await?
+            {
+                started_services.push(service);
+            }
         }
+
+        Ok(started_services)
     }
 
     async fn delete_app_impl(
         &self,
         app_name: &AppName,
         status_id: &AppStatusChangeId,
+        preserve_volumes: bool,
     ) -> Result<Vec<Service>, AppsServiceError> {
         let services = self
             .infrastructure
-            .stop_services(&status_id.to_string(), app_name)
+            .stop_services(&status_id.to_string(), app_name, preserve_volumes)
             .await?;
         if services.is_empty() {
             Err(AppsServiceError::AppNotFound {
@@ -376,6 +600,308 @@ impl AppsService {
         }
     }
 
+    /// Adds the environment variables of the services running in `base_app_name` to the service
+    /// configurations with the same service name. Variables defined by the given service
+    /// configurations take precedence over the inherited ones.
+    pub async fn inherit_environment(
+        &self,
+        base_app_name: &AppName,
+        mut service_configs: Vec<ServiceConfig>,
+    ) -> Result<Vec<ServiceConfig>, AppsServiceError> {
+        let base_configs = self
+            .infrastructure
+            .get_configs_of_app(base_app_name)
+            .await?;
+
+        for config in service_configs.iter_mut() {
+            if let Some(base_env) = base_configs
+                .iter()
+                .find(|base_config| base_config.service_name() == config.service_name())
+                .and_then(|base_config| base_config.env())
+            {
+                config.inherit_env(base_env);
+            }
+        }
+
+        Ok(service_configs)
+    }
+
+    /// Partially updates the app: the services of `patches` will be merged with the configuration
+    /// of the running services (see [`ServiceConfig::merge`]) and redeployed while all other
+    /// services of the app stay untouched. Returns all services of the app.
+    pub async fn update_services(
+        &self,
+        app_name: &AppName,
+        patches: &[ServiceConfig],
+    ) -> Result<Vec<Service>, AppsServiceError> {
+        let base_configs = self.infrastructure.get_configs_of_app(app_name).await?;
+        if base_configs.is_empty() {
+            return Err(AppsServiceError::AppNotFound {
+                app_name: app_name.clone(),
+            });
+        }
+
+        let configs = patches
+            .iter()
+            .map(|patch| {
+                match base_configs
+                    .iter()
+                    .find(|base_config| base_config.service_name() == patch.service_name())
+                {
+                    Some(base_config) => ServiceConfig::merge(base_config, patch),
+                    None => patch.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Replicating from the app itself avoids that missing services will be added from master.
+        self.create_or_update(
+            app_name,
+            &AppStatusChangeId::new(),
+            Some(app_name.clone()),
+            &configs,
+        )
+        .await?;
+
+        Ok(self.get_apps().await?.remove(app_name).unwrap_or_default())
+    }
+
+    /// Updates the environment variables of a single service and redeploys it while all other
+    /// services of the app stay untouched. Variables without value will be removed. Returns `None`
+    /// if the service does not exist.
+    pub async fn update_service_env(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+        env_updates: &HashMap<String, Option<String>>,
+    ) -> Result<Option<Service>, AppsServiceError> {
+        let Some(mut config) = self
+            .infrastructure
+            .get_configs_of_app(app_name)
+            .await?
+            .into_iter()
+            .find(|config| config.service_name() == service_name)
+        else {
+            return Ok(None);
+        };
+
+        let env = config
+            .env()
+            .cloned()
+            .unwrap_or_else(|| Environment::new(Vec::new()));
+        config.set_env(Some(env.update(env_updates)));
+
+        // Replicating from the app itself avoids that missing services will be added from master.
+        let services = self
+            .create_or_update(
+                app_name,
+                &AppStatusChangeId::new(),
+                Some(app_name.clone()),
+                &[config],
+            )
+            .await?;
+
+        Ok(services
+            .into_iter()
+            .find(|service| service.service_name() == service_name))
+    }
+
+    /// Returns the tags of the app, e.g. the team or project that the app belongs to.
+    pub async fn get_app_tags(&self, app_name: &AppName) -> Result<Vec<String>, AppsServiceError> {
+        Ok(self.infrastructure.get_app_tags(app_name).await?)
+    }
+
+    /// Replaces the tags of the app. The tags are stored apart from the services, thus, the
+    /// services keep running untouched.
+    pub async fn tag_app(
+        &self,
+        app_name: &AppName,
+        mut tags: Vec<String>,
+    ) -> Result<Vec<String>, AppsServiceError> {
+        if let Some(tag) = tags
+            .iter()
+            .find(|tag| tag.is_empty() || tag.contains(',') || tag.trim() != tag.as_str())
+        {
+            return Err(AppsServiceError::InvalidTag { tag: tag.clone() });
+        }
+        tags.sort();
+        tags.dedup();
+
+        if !self
+            .infrastructure
+            .get_app_names()
+            .await?
+            .contains(app_name)
+        {
+            return Err(AppsServiceError::AppNotFound {
+                app_name: app_name.clone(),
+            });
+        }
+
+        self.infrastructure.set_app_tags(app_name, &tags).await?;
+
+        Ok(tags)
+    }
+
+    /// Returns the services of the apps that are tagged with `tag`.
+    pub async fn get_apps_with_tag(
+        &self,
+        tag: &str,
+    ) -> Result<MultiMap<AppName, Service>, AppsServiceError> {
+        let mut apps = self.get_apps().await?;
+        let tags_of_apps = self.infrastructure.get_tags_of_apps().await?;
+
+        let untagged_apps = apps
+            .keys()
+            .filter(|app_name| {
+                !tags_of_apps
+                    .get(*app_name)
+                    .map(|tags| tags.iter().any(|t| t == tag))
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for app_name in untagged_apps {
+            apps.remove(&app_name);
+        }
+
+        Ok(apps)
+    }
+
+    /// Compares the running services of the app with the given service configurations without
+    /// deploying them, e.g. to preview which services a deployment would add or update.
+    pub async fn diff_app(
+        &self,
+        app_name: &AppName,
+        service_configs: &[ServiceConfig],
+    ) -> Result<AppDiff, AppsServiceError> {
+        let configs = self.infrastructure.get_configs_of_app(app_name).await?;
+        Ok(app_diff::diff(&configs, service_configs))
+    }
+
+    /// Describes the running services of the app as a Compose file, e.g. to run the app locally.
+    /// Returns `None` if the app does not exist.
+    pub async fn compose_file(
+        &self,
+        app_name: &AppName,
+    ) -> Result<Option<String>, AppsServiceError> {
+        let configs = self.infrastructure.get_configs_of_app(app_name).await?;
+        if configs.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(ComposeWriter::write(&configs)))
+    }
+
+    pub async fn export_app_state(
+        &self,
+        app_name: &AppName,
+    ) -> Result<Option<AppStateArchive>, AppsServiceError> {
+        Ok(self.infrastructure.export_app_state(app_name).await?)
+    }
+
+    /// Moves all services of `app_name` to `new_app_name`.
+    ///
+    /// Neither Docker nor Kubernetes support changing the labels of running containers or renaming
+    /// networks and namespaces. Therefore, the services will be deployed under the new name, based
+    /// on the configurations of the running services, before the old app will be deleted.
+    pub async fn rename_app(
+        &self,
+        app_name: &AppName,
+        new_app_name: &AppName,
+    ) -> Result<Vec<Service>, AppsServiceError> {
+        // Deployments of the app in the meantime would get lost and deployments of the new app
+        // would be overridden.
+        let mut result = None;
+        self.infrastructure
+            .with_deployment_locks(
+                &[app_name.clone(), new_app_name.clone()],
+                Box::pin(async {
+                    result = Some(self.move_app(app_name, new_app_name).await);
+                }),
+            )
+            .await?;
+
+        result.expect("The operation runs while the deployment locks are held")
+    }
+
+    async fn move_app(
+        &self,
+        app_name: &AppName,
+        new_app_name: &AppName,
+    ) -> Result<Vec<Service>, AppsServiceError> {
+        let apps = self.get_apps().await?;
+        if !apps.contains_key(app_name) {
+            return Err(AppsServiceError::AppNotFound {
+                app_name: app_name.clone(),
+            });
+        }
+        if apps.contains_key(new_app_name) {
+            return Err(AppsServiceError::AppAlreadyExists {
+                app_name: new_app_name.clone(),
+            });
+        }
+
+        let configs = self.infrastructure.get_configs_of_app(app_name).await?;
+        let services = self
+            .create_or_update(new_app_name, &AppStatusChangeId::new(), None, &configs)
+            .await?;
+
+        // The tags are stored apart from the services, thus, they have to be moved explicitly.
+        let tags = self.infrastructure.get_app_tags(app_name).await?;
+        if !tags.is_empty() {
+            self.infrastructure
+                .set_app_tags(new_app_name, &tags)
+                .await?;
+        }
+
+        self.delete_app(app_name, &AppStatusChangeId::new()).await?;
+
+        Ok(services)
+    }
+
+    /// Deploys the services of `source_app_name` under `target_app_name` while the source app
+    /// keeps running. The images of the source app are already available locally, thus, they are
+    /// only pulled if they are missing, unless the service configuration requires otherwise.
+    pub async fn copy_app(
+        &self,
+        source_app_name: &AppName,
+        target_app_name: &AppName,
+    ) -> Result<Vec<Service>, AppsServiceError> {
+        let apps = self.get_apps().await?;
+        if !apps.contains_key(source_app_name) {
+            return Err(AppsServiceError::AppNotFound {
+                app_name: source_app_name.clone(),
+            });
+        }
+        if apps.contains_key(target_app_name) {
+            return Err(AppsServiceError::AppAlreadyExists {
+                app_name: target_app_name.clone(),
+            });
+        }
+
+        let configs = self
+            .infrastructure
+            .get_configs_of_app(source_app_name)
+            .await?
+            .into_iter()
+            .map(|mut config| {
+                if config.pull_policy().is_none() {
+                    config.set_pull_policy(Some(PullPolicy::IfNotPresent));
+                }
+                config
+            })
+            .collect::<Vec<_>>();
+
+        self.create_or_update(
+            target_app_name,
+            &AppStatusChangeId::new(),
+            Some(source_app_name.clone()),
+            &configs,
+        )
+        .await
+    }
+
     pub async fn stream_logs<'a>(
         &'a self,
         app_name: &'a AppName,
@@ -410,34 +936,234 @@ impl AppsService {
         Ok(Some(LogChunk::from(log_lines)))
     }
 
+    /// Returns the log lines that the service has produced since the given time or `None` if
+    /// there is no such service.
+    pub async fn get_logs_since(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Option<Vec<LogLine>>, AppsServiceError> {
+        let configs = self.infrastructure.get_configs_of_app(app_name).await?;
+        if !configs
+            .iter()
+            .any(|config| config.service_name() == service_name)
+        {
+            return Ok(None);
+        }
+
+        let mut log_lines = Vec::new();
+        let mut log_stream = self
+            .infrastructure
+            .get_service_logs_since(app_name, service_name, since)
+            .await;
+
+        while let Some(result) = log_stream.next().await {
+            log_lines.push(result?);
+        }
+
+        Ok(Some(log_lines))
+    }
+
     pub async fn change_status(
         &self,
         app_name: &AppName,
         service_name: &str,
         status: ServiceStatus,
     ) -> Result<Option<Service>, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
         Ok(self
             .infrastructure
             .change_status(app_name, service_name, status)
             .await?)
     }
-}
 
-/// Defines error cases for the `AppService`
-#[derive(Debug, Clone, thiserror::Error)]
-pub enum AppsServiceError {
-    #[error("Cannot find app {app_name}.")]
-    AppNotFound { app_name: AppName },
-    #[error("Cannot create more than {limit} apps")]
-    AppLimitExceeded { limit: usize },
-    #[error("The app {app_name} is currently within deployment by another request.")]
-    AppIsInDeployment { app_name: AppName },
-    #[error("The app {app_name} is currently within deletion in by another request.")]
-    AppIsInDeletion { app_name: AppName },
-    /// Will be used when the service cannot interact correctly with the infrastructure.
-    #[error("Cannot interact with infrastructure: {error}")]
-    InfrastructureError { error: Arc<anyhow::Error> },
-    /// Will be used if the service configuration cannot be loaded.
+    /// Waits until all instances of the app are ready to handle traffic: they must be running and
+    /// their health check must pass. Instances without health check must have been running for
+    /// [`READY_GRACE_PERIOD`]. The services are polled with an exponential backoff starting at
+    /// two seconds.
+    pub async fn wait_for_app_ready(
+        &self,
+        app_name: &AppName,
+        timeout: Duration,
+    ) -> Result<(), AppsServiceError> {
+        if timeout > READY_MAX_TIMEOUT {
+            return Err(AppsServiceError::InvalidTimeout {
+                timeout_secs: timeout.as_secs(),
+                max_timeout_secs: READY_MAX_TIMEOUT.as_secs(),
+            });
+        }
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut poll_interval = Duration::from_secs(2);
+
+        loop {
+            let not_ready_services = self.not_ready_services(app_name).await?;
+            if not_ready_services.is_empty() {
+                return Ok(());
+            }
+
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                return Err(AppsServiceError::Timeout {
+                    app_name: app_name.clone(),
+                    not_ready_services,
+                });
+            }
+
+            tokio::time::sleep(poll_interval.min(deadline - now)).await;
+            poll_interval = (poll_interval * 2).min(READY_MAX_POLL_INTERVAL);
+        }
+    }
+
+    async fn not_ready_services(
+        &self,
+        app_name: &AppName,
+    ) -> Result<Vec<String>, AppsServiceError> {
+        let services = self.get_apps().await?.remove(app_name).ok_or_else(|| {
+            AppsServiceError::AppNotFound {
+                app_name: app_name.clone(),
+            }
+        })?;
+
+        let mut not_ready_services = Vec::new();
+        for service in services
+            .iter()
+            .filter(|service| service.container_type() == &ContainerType::Instance)
+        {
+            let ready = service.status() == &ServiceStatus::Running
+                && match self
+                    .infrastructure
+                    .is_service_healthy(app_name, service.service_name())
+                    .await?
+                {
+                    Some(healthy) => healthy,
+                    None => (Utc::now() - *service.started_at())
+                        .to_std()
+                        .is_ok_and(|running| running >= READY_GRACE_PERIOD),
+                };
+
+            if !ready {
+                not_ready_services.push(service.service_name().clone());
+            }
+        }
+
+        Ok(not_ready_services)
+    }
+
+    pub async fn is_service_healthy(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Result<Option<bool>, AppsServiceError> {
+        Ok(self
+            .infrastructure
+            .is_service_healthy(app_name, service_name)
+            .await?)
+    }
+
+    pub async fn force_stop_service(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Result<Option<Service>, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
+        Ok(self
+            .infrastructure
+            .force_stop_service(app_name, service_name)
+            .await?)
+    }
+
+    /// Replaces the running version of the service by its canary. Returns `false` if the service
+    /// has no canary.
+    pub async fn promote_canary(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Result<bool, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
+        Ok(self
+            .infrastructure
+            .promote_canary(app_name, service_name)
+            .await?)
+    }
+
+    /// Opens a TCP connection to the port of the service's container. Returns `None` if the
+    /// service does not exist.
+    pub async fn open_port_forward(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+        port: u16,
+    ) -> Result<Option<TcpStream>, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
+        Ok(self
+            .infrastructure
+            .open_port_forward(app_name, service_name, port)
+            .await?)
+    }
+
+    /// Removes the canary of the service. Returns `false` if the service has no canary.
+    pub async fn rollback_canary(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Result<bool, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
+        Ok(self
+            .infrastructure
+            .rollback_canary(app_name, service_name)
+            .await?)
+    }
+
+    pub async fn inspect_service(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Result<Option<serde_json::Value>, AppsServiceError> {
+        self.ensure_app_in_shard(app_name)?;
+        Ok(self
+            .infrastructure
+            .inspect_service(app_name, service_name)
+            .await?)
+    }
+}
+
+/// Defines error cases for the `AppService`
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum AppsServiceError {
+    #[error("Cannot find app {app_name}.")]
+    AppNotFound { app_name: AppName },
+    #[error("Cannot create more than {limit} apps")]
+    AppLimitExceeded { limit: usize },
+    #[error("The app {app_name} is currently within deployment by another request.")]
+    AppIsInDeployment { app_name: AppName },
+    #[error("The app {app_name} is currently within deletion in by another request.")]
+    AppIsInDeletion { app_name: AppName },
+    #[error("The app {app_name} already exists.")]
+    AppAlreadyExists { app_name: AppName },
+    #[error("The app {app_name} is managed by another PREvant instance.")]
+    AppNotInShard { app_name: AppName },
+    #[error("{error}")]
+    AppNameNotDeployable { error: AppNameError },
+    #[error("Invalid tag “{tag}”: tags must neither be empty nor contain commas or surrounding whitespaces.")]
+    InvalidTag { tag: String },
+    #[error("The services {} of app {app_name} did not become ready in time.", not_ready_services.join(", "))]
+    Timeout {
+        app_name: AppName,
+        not_ready_services: Vec<String>,
+    },
+    #[error(
+        "The timeout of {timeout_secs} seconds exceeds the maximum of {max_timeout_secs} seconds."
+    )]
+    InvalidTimeout {
+        timeout_secs: u64,
+        max_timeout_secs: u64,
+    },
+    /// Will be used when the service cannot interact correctly with the infrastructure.
+    #[error("Cannot interact with infrastructure: {error}")]
+    InfrastructureError { error: Arc<anyhow::Error> },
+    /// Will be used if the service configuration cannot be loaded.
     #[error("Invalid configuration: {error}")]
     InvalidServerConfiguration { error: Arc<ConfigError> },
     #[error("Invalid configuration (invalid template): {error}")]
@@ -466,6 +1192,14 @@ impl From<anyhow::Error> for AppsServiceError {
     }
 }
 
+impl From<DockerInfrastructureError> for AppsServiceError {
+    fn from(error: DockerInfrastructureError) -> Self {
+        AppsServiceError::InfrastructureError {
+            error: Arc::new(anyhow::Error::from(error)),
+        }
+    }
+}
+
 impl From<RenderError> for AppsServiceError {
     fn from(error: RenderError) -> Self {
         AppsServiceError::InvalidTemplateFormat {
@@ -486,7 +1220,9 @@ impl From<RegistryError> for AppsServiceError {
 mod tests {
 
     use super::*;
-    use crate::infrastructure::{Dummy, TraefikIngressRoute, TraefikRouterRule};
+    use crate::infrastructure::{
+        Dummy, InfrastructureCall, TraefikIngressRoute, TraefikRouterRule,
+    };
     use crate::models::{EnvironmentVariable, ServiceBuilder};
     use crate::sc;
     use chrono::Utc;
@@ -505,6 +1241,21 @@ mod tests {
         };
     }
 
+    #[test]
+    fn should_keep_docker_infrastructure_error() {
+        let error = AppsServiceError::from(DockerInfrastructureError::ImageNotFound {
+            internal_message: String::from("no such image"),
+        });
+
+        match error {
+            AppsServiceError::InfrastructureError { error } => assert!(matches!(
+                error.downcast_ref::<DockerInfrastructureError>(),
+                Some(DockerInfrastructureError::ImageNotFound { .. })
+            )),
+            error => panic!("Unexpected error {error}"),
+        }
+    }
+
     macro_rules! assert_contains_service {
         ( $services:expr, $service_name:expr, $container_type:expr ) => {
             assert!(
@@ -593,6 +1344,195 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn should_tag_app_and_keep_tags_on_redeployment() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        apps.create_or_update(
+            &AppName::master(),
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-a")],
+        )
+        .await?;
+
+        let tags = apps
+            .tag_app(
+                &AppName::master(),
+                vec![String::from("team-a"), String::from("frontend")],
+            )
+            .await?;
+        assert_eq!(tags, vec![String::from("frontend"), String::from("team-a")]);
+
+        let deployments = apps
+            .infrastructure
+            .as_any()
+            .downcast_ref::<Dummy>()
+            .unwrap()
+            .calls()
+            .into_iter()
+            .filter(|call| matches!(call, InfrastructureCall::DeployServices { .. }))
+            .count();
+        assert_eq!(deployments, 1, "tagging must not redeploy the services");
+
+        apps.create_or_update(
+            &AppName::master(),
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-b")],
+        )
+        .await?;
+        assert_eq!(apps.get_app_tags(&AppName::master()).await?, tags);
+        assert_eq!(apps.get_apps_with_tag("frontend").await?.keys().count(), 1);
+        assert!(apps.get_apps_with_tag("backend").await?.is_empty());
+
+        apps.tag_app(&AppName::master(), Vec::new()).await?;
+        assert!(apps.get_app_tags(&AppName::master()).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_not_wait_for_unknown_app_to_be_ready() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let result = apps
+            .wait_for_app_ready(&AppName::master(), Duration::ZERO)
+            .await;
+
+        assert!(matches!(result, Err(AppsServiceError::AppNotFound { .. })));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_not_wait_longer_than_the_maximum_timeout() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let result = apps
+            .wait_for_app_ready(&AppName::master(), Duration::from_secs(u64::MAX))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppsServiceError::InvalidTimeout {
+                timeout_secs: u64::MAX,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_wait_for_running_app_to_be_ready() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        apps.create_or_update(
+            &AppName::master(),
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-a")],
+        )
+        .await?;
+
+        apps.wait_for_app_ready(&AppName::master(), Duration::ZERO)
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_time_out_waiting_for_paused_app() -> Result<(), AppsServiceError> {
+        let mut services = MultiMap::new();
+        services.insert(
+            AppName::master(),
+            ServiceBuilder::new()
+                .id("service-a".to_string())
+                .app_name("master".to_string())
+                .config(sc!("service-a"))
+                .service_status(ServiceStatus::Paused)
+                .started_at(Utc::now())
+                .build()
+                .unwrap(),
+        );
+        let infrastructure = Box::new(Dummy::with_services(services));
+        let apps = AppsService::new(Config::default(), infrastructure)?;
+
+        let result = apps
+            .wait_for_app_ready(&AppName::master(), Duration::ZERO)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppsServiceError::Timeout { not_ready_services, .. })
+                if not_ready_services == vec![String::from("service-a")]
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_not_tag_app_with_invalid_tags() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        apps.create_or_update(
+            &AppName::master(),
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-a")],
+        )
+        .await?;
+
+        let result = apps
+            .tag_app(&AppName::master(), vec![String::from("a,b")])
+            .await;
+        assert!(matches!(result, Err(AppsServiceError::InvalidTag { .. })));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_return_app_names() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        apps.create_or_update(
+            &AppName::master(),
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-a"), sc!("service-b")],
+        )
+        .await?;
+        apps.create_or_update(
+            &AppName::from_str("branch").unwrap(),
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-a")],
+        )
+        .await?;
+
+        let app_names = apps.get_app_names().await?;
+
+        assert_eq!(
+            app_names,
+            HashSet::from([AppName::master(), AppName::from_str("branch").unwrap()])
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn should_override_replicas_from_master() -> Result<(), AppsServiceError> {
         let config = Config::default();
@@ -842,8 +1782,7 @@ Log msg 3 of service-a of app master
     }
 
     #[tokio::test]
-    async fn should_filter_companions_if_services_to_deploy_contain_same_service_name(
-    ) -> Result<(), AppsServiceError> {
+    async fn should_not_deploy_companions_if_skipped() -> Result<(), AppsServiceError> {
         let config = config_from_str!(
             r#"
             [companions.openid]
@@ -861,13 +1800,51 @@ Log msg 3 of service-a of app master
         let apps = AppsService::new(config, infrastructure)?;
 
         let app_name = AppName::master();
-        let configs = vec![sc!("openid"), sc!("db")];
-        apps.create_or_update(&app_name, &AppStatusChangeId::new(), None, &configs)
-            .await?;
-        let deployed_apps = apps.get_apps().await?;
-
-        let services = deployed_apps.get_vec(&app_name).unwrap();
-        assert_eq!(services.len(), 2);
+        apps.create_or_update_with_rollout_strategy(
+            &app_name,
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-a")],
+            RolloutStrategy::default(),
+            true,
+        )
+        .await?;
+        let deployed_apps = apps.get_apps().await?;
+
+        let services = deployed_apps.get_vec(&app_name).unwrap();
+        assert_eq!(services.len(), 1);
+        assert_contains_service!(services, "service-a", ContainerType::Instance);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_filter_companions_if_services_to_deploy_contain_same_service_name(
+    ) -> Result<(), AppsServiceError> {
+        let config = config_from_str!(
+            r#"
+            [companions.openid]
+            serviceName = 'openid'
+            type = 'application'
+            image = 'keycloak/keycloak:23.0'
+
+            [companions.db]
+            serviceName = 'db'
+            type = 'service'
+            image = 'postgres:16.1'
+        "#
+        );
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let app_name = AppName::master();
+        let configs = vec![sc!("openid"), sc!("db")];
+        apps.create_or_update(&app_name, &AppStatusChangeId::new(), None, &configs)
+            .await?;
+        let deployed_apps = apps.get_apps().await?;
+
+        let services = deployed_apps.get_vec(&app_name).unwrap();
+        assert_eq!(services.len(), 2);
         assert_contains_service!(services, "openid", ContainerType::Instance);
         assert_contains_service!(services, "db", ContainerType::Instance);
 
@@ -1042,6 +2019,435 @@ Log msg 3 of service-a of app master
         Ok(())
     }
 
+    #[tokio::test]
+    async fn should_delete_multiple_apps_and_collect_failures() -> Result<(), AppsServiceError> {
+        let config = config_from_str!(
+            r#"
+            [applications]
+            bulk_operation_parallelism = 2
+        "#
+        );
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        for app_name in ["master", "feature-1", "feature-2"] {
+            apps.create_or_update(
+                &AppName::from_str(app_name).unwrap(),
+                &AppStatusChangeId::new(),
+                None,
+                &vec![sc!("service-a")],
+            )
+            .await?;
+        }
+
+        let result = apps
+            .delete_apps(
+                &[
+                    AppName::master(),
+                    AppName::from_str("feature-1").unwrap(),
+                    AppName::from_str("unknown").unwrap(),
+                    AppName::from_str("feature-2").unwrap(),
+                    AppName::master(),
+                ],
+                false,
+            )
+            .await;
+
+        assert_eq!(
+            result.succeeded(),
+            &[
+                AppName::master(),
+                AppName::from_str("feature-1").unwrap(),
+                AppName::from_str("feature-2").unwrap(),
+            ]
+        );
+        assert_eq!(
+            result
+                .failed()
+                .iter()
+                .map(|failure| failure.name().clone())
+                .collect::<Vec<_>>(),
+            vec![AppName::from_str("unknown").unwrap()]
+        );
+        assert_eq!(apps.get_app_names().await?, HashSet::new());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_inherit_environment_from_base_app() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let base_app_name = AppName::from_str("base").unwrap();
+        apps.create_or_update(
+            &base_app_name,
+            &AppStatusChangeId::new(),
+            None,
+            &vec![crate::sc!(
+                "service-a",
+                "service-a:latest",
+                env = ("API_KEY" => "shared-key", "LOG_LEVEL" => "info")
+            )],
+        )
+        .await?;
+
+        let configs = apps
+            .inherit_environment(
+                &base_app_name,
+                vec![
+                    crate::sc!("service-a", "service-a:latest", env = ("LOG_LEVEL" => "debug")),
+                    sc!("service-b"),
+                ],
+            )
+            .await?;
+
+        let env = configs[0].env().unwrap();
+        assert_eq!(
+            env.variable("API_KEY").map(|v| v.value().unsecure()),
+            Some("shared-key")
+        );
+        assert_eq!(
+            env.variable("LOG_LEVEL").map(|v| v.value().unsecure()),
+            Some("debug")
+        );
+        assert_eq!(configs[1].env(), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_replicate_only_replicated_environment_variables() -> Result<(), AppsServiceError>
+    {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let mut service_a = sc!("service-a");
+        service_a.set_env(Some(Environment::new(vec![
+            EnvironmentVariable::with_replicated(
+                String::from("API_KEY"),
+                SecUtf8::from("shared-key"),
+            ),
+            EnvironmentVariable::new(String::from("LOG_LEVEL"), SecUtf8::from("debug")),
+        ])));
+        apps.create_or_update(
+            &AppName::master(),
+            &AppStatusChangeId::new(),
+            None,
+            &vec![service_a],
+        )
+        .await?;
+
+        let app_name = AppName::from_str("feature-x").unwrap();
+        apps.create_or_update(
+            &app_name,
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-b")],
+        )
+        .await?;
+
+        let configs = apps.infrastructure.get_configs_of_app(&app_name).await?;
+        let replica = configs
+            .iter()
+            .find(|config| config.service_name() == "service-a")
+            .unwrap();
+        assert_eq!(
+            replica.env(),
+            Some(&Environment::new(vec![EnvironmentVariable::new(
+                String::from("API_KEY"),
+                SecUtf8::from("shared-key")
+            )]))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_rename_app() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let app_name = AppName::from_str("feature-x").unwrap();
+        let new_app_name = AppName::from_str("bugfix-x").unwrap();
+        apps.create_or_update(
+            &app_name,
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-a")],
+        )
+        .await?;
+
+        apps.rename_app(&app_name, &new_app_name).await?;
+
+        let deployed_apps = apps.get_apps().await?;
+        assert_eq!(deployed_apps.get(&app_name), None);
+        assert_contains_service!(
+            deployed_apps.get_vec(&new_app_name).unwrap(),
+            "service-a",
+            ContainerType::Instance
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_update_service_env() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let app_name = AppName::from_str("feature-x").unwrap();
+        apps.create_or_update(
+            &app_name,
+            &AppStatusChangeId::new(),
+            None,
+            &vec![
+                sc!("service-a", "service-a:latest", env = ("FEATURE" => "off", "DEBUG" => "true")),
+                sc!("service-b"),
+            ],
+        )
+        .await?;
+
+        let service = apps
+            .update_service_env(
+                &app_name,
+                "service-a",
+                &HashMap::from([
+                    (String::from("FEATURE"), Some(String::from("on"))),
+                    (String::from("DEBUG"), None),
+                ]),
+            )
+            .await?;
+        assert!(service.is_some());
+
+        let configs = apps.infrastructure.get_configs_of_app(&app_name).await?;
+        assert_eq!(configs.len(), 2);
+        let env = configs
+            .iter()
+            .find(|config| config.service_name() == "service-a")
+            .and_then(|config| config.env())
+            .unwrap();
+        assert_eq!(
+            env.variable("FEATURE"),
+            Some(&EnvironmentVariable::new(
+                String::from("FEATURE"),
+                secstr::SecUtf8::from("on")
+            ))
+        );
+        assert_eq!(env.variable("DEBUG"), None);
+
+        let service = apps
+            .update_service_env(&app_name, "service-c", &HashMap::new())
+            .await?;
+        assert!(service.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_keep_environment_variables_when_updating_service_env(
+    ) -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let mut service_a = sc!("service-a");
+        service_a.set_env(Some(Environment::new(vec![
+            EnvironmentVariable::with_replicated(
+                String::from("API_KEY"),
+                SecUtf8::from("shared-key"),
+            ),
+            EnvironmentVariable::new(String::from("LOG_LEVEL"), SecUtf8::from("debug")),
+        ])));
+        let service_b = sc!("service-b", "service-b:latest", env = ("DB_HOST" => "db"));
+        let app_name = AppName::from_str("feature-x").unwrap();
+        apps.create_or_update(
+            &app_name,
+            &AppStatusChangeId::new(),
+            None,
+            &vec![service_a, service_b],
+        )
+        .await?;
+
+        apps.update_service_env(
+            &app_name,
+            "service-a",
+            &HashMap::from([(String::from("FEATURE"), Some(String::from("on")))]),
+        )
+        .await?;
+
+        let configs = apps.infrastructure.get_configs_of_app(&app_name).await?;
+        let env_of = |service_name: &str| {
+            configs
+                .iter()
+                .find(|config| config.service_name() == service_name)
+                .and_then(|config| config.env())
+                .unwrap()
+        };
+        assert_eq!(
+            env_of("service-a").variable("LOG_LEVEL").map(|v| v.value()),
+            Some(&SecUtf8::from("debug"))
+        );
+        assert!(env_of("service-a")
+            .variable("API_KEY")
+            .map_or(false, |v| v.replicate()));
+        assert_eq!(
+            env_of("service-a").variable("FEATURE").map(|v| v.value()),
+            Some(&SecUtf8::from("on"))
+        );
+        assert_eq!(
+            env_of("service-b").variable("DB_HOST").map(|v| v.value()),
+            Some(&SecUtf8::from("db"))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_copy_app() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let app_name = AppName::from_str("feature-x").unwrap();
+        let new_app_name = AppName::from_str("feature-x-copy").unwrap();
+        apps.create_or_update(
+            &app_name,
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-a"), sc!("service-b")],
+        )
+        .await?;
+
+        apps.copy_app(&app_name, &new_app_name).await?;
+
+        let deployed_apps = apps.get_apps().await?;
+        for app_name in [&app_name, &new_app_name] {
+            let services = deployed_apps.get_vec(app_name).unwrap();
+            assert_eq!(services.len(), 2);
+            assert_contains_service!(services, "service-a", ContainerType::Instance);
+            assert_contains_service!(services, "service-b", ContainerType::Instance);
+        }
+
+        let configs = apps
+            .infrastructure
+            .get_configs_of_app(&new_app_name)
+            .await?;
+        assert!(configs
+            .iter()
+            .all(|config| config.pull_policy() == Some(PullPolicy::IfNotPresent)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_not_copy_app_to_existing_app() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        for app_name in ["feature-x", "feature-y"] {
+            apps.create_or_update(
+                &AppName::from_str(app_name).unwrap(),
+                &AppStatusChangeId::new(),
+                None,
+                &vec![sc!("service-a")],
+            )
+            .await?;
+        }
+
+        let result = apps
+            .copy_app(
+                &AppName::from_str("feature-x").unwrap(),
+                &AppName::from_str("feature-y").unwrap(),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(AppsServiceError::AppAlreadyExists { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_update_services_partially() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let app_name = AppName::from_str("feature-x").unwrap();
+        apps.create_or_update(
+            &app_name,
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-a"), sc!("service-b")],
+        )
+        .await?;
+
+        let services = apps
+            .update_services(&app_name, &[sc!("service-a", "nginx:1.25")])
+            .await?;
+
+        assert_eq!(services.len(), 2);
+        assert_contains_service!(services, "service-b", ContainerType::Instance);
+        assert_eq!(
+            services
+                .iter()
+                .find(|service| service.service_name() == "service-a")
+                .map(|service| service.image().to_string()),
+            Some(String::from("docker.io/library/nginx:1.25"))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_not_update_services_of_unknown_app() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let result = apps
+            .update_services(&AppName::master(), &[sc!("service-a")])
+            .await;
+
+        assert!(matches!(result, Err(AppsServiceError::AppNotFound { .. })));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_not_rename_app_to_existing_app() -> Result<(), AppsServiceError> {
+        let config = Config::default();
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        let app_name = AppName::from_str("feature-x").unwrap();
+        for app_name in [&app_name, &AppName::master()] {
+            apps.create_or_update(
+                app_name,
+                &AppStatusChangeId::new(),
+                None,
+                &vec![sc!("service-a")],
+            )
+            .await?;
+        }
+
+        let result = apps.rename_app(&app_name, &AppName::master()).await;
+
+        assert!(matches!(
+            result,
+            Err(AppsServiceError::AppAlreadyExists { .. })
+        ));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn should_delete_apps_from_parallel_threads_returning_the_same_result(
     ) -> Result<(), AppsServiceError> {
@@ -1312,6 +2718,74 @@ Log msg 3 of service-a of app master
         Ok(())
     }
 
+    #[tokio::test]
+    async fn should_only_manage_apps_of_shard() -> Result<(), AppsServiceError> {
+        let config = config_from_str!(
+            r#"
+            [applications]
+            shard_index = 3
+            shard_total = 4
+        "#
+        );
+        let infrastructure = Box::new(Dummy::new());
+        let apps = AppsService::new(config, infrastructure)?;
+
+        apps.create_or_update(
+            &AppName::master(),
+            &AppStatusChangeId::new(),
+            None,
+            &vec![sc!("service-a")],
+        )
+        .await?;
+
+        let result = apps
+            .create_or_update(
+                &AppName::from_str("feature-2").unwrap(),
+                &AppStatusChangeId::new(),
+                None,
+                &vec![sc!("service-a")],
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppsServiceError::AppNotInShard { .. })
+        ));
+        assert_eq!(
+            apps.get_app_names().await?,
+            HashSet::from([AppName::master()])
+        );
+
+        let feature = AppName::from_str("feature-2").unwrap();
+        assert!(matches!(
+            apps.get_stopped_services(&feature).await,
+            Err(AppsServiceError::AppNotInShard { .. })
+        ));
+        assert!(matches!(
+            apps.prune_stopped_containers(&feature).await,
+            Err(AppsServiceError::AppNotInShard { .. })
+        ));
+        assert!(matches!(
+            apps.get_deployments(&feature).await,
+            Err(AppsServiceError::AppNotInShard { .. })
+        ));
+        assert!(matches!(
+            apps.change_status(&feature, "service-a", ServiceStatus::Paused)
+                .await,
+            Err(AppsServiceError::AppNotInShard { .. })
+        ));
+        assert!(matches!(
+            apps.inspect_service(&feature, "service-a").await,
+            Err(AppsServiceError::AppNotInShard { .. })
+        ));
+        assert!(matches!(
+            apps.force_stop_service(&feature, "service-a").await,
+            Err(AppsServiceError::AppNotInShard { .. })
+        ));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn do_update_app_when_exceeding_application_number_limit() -> Result<(), AppsServiceError>
     {
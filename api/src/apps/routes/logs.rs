@@ -1,9 +1,9 @@
 use crate::{
     apps::Apps,
     http_result::HttpResult,
-    models::{AppName, AppNameError, LogChunk},
+    models::{AppName, AppNameError, LogChunk, LogLine},
 };
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
 use futures::stream::StreamExt;
 use http_api_problem::HttpApiProblem;
 use rocket::http::hyper::header::{ACCEPT, CONTENT_DISPOSITION, LINK};
@@ -12,6 +12,7 @@ use rocket::{
     request::FromRequest,
     response::stream::{Event, EventStream},
     response::{Responder, Response},
+    serde::json::Json,
     Request, State,
 };
 use std::{str::FromStr, sync::Arc};
@@ -95,6 +96,40 @@ pub(super) async fn stream_logs<'r>(
     })
 }
 
+/// Returns the log lines that the service has produced since the given time, including the output
+/// stream of each line, e.g. to forward new log lines to a log aggregation.
+#[get(
+    "/<app_name>/logs/<service_name>?<since>",
+    format = "application/json",
+    rank = 3
+)]
+pub(super) async fn logs_since(
+    app_name: Result<AppName, AppNameError>,
+    service_name: &str,
+    since: &str,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<Json<Vec<LogLine>>> {
+    let app_name = app_name?;
+    let since = DateTime::parse_from_rfc3339(since).map_err(|err| {
+        HttpApiProblem::with_title(http_api_problem::StatusCode::BAD_REQUEST)
+            .detail(format!("{}", err))
+    })?;
+
+    match apps
+        .get_logs_since(&app_name, service_name, since.with_timezone(&Utc))
+        .await?
+    {
+        Some(log_lines) => Ok(Json(log_lines)),
+        None => Err(
+            HttpApiProblem::with_title(http_api_problem::StatusCode::NOT_FOUND)
+                .detail(format!(
+                    "Cannot find service {service_name} of app {app_name}."
+                ))
+                .into(),
+        ),
+    }
+}
+
 pub struct LogsResponse<'a> {
     log_chunk: Option<LogChunk>,
     app_name: AppName,
@@ -219,10 +254,60 @@ mod test {
         let rocket = rocket::build()
             .manage(host_meta_cache)
             .manage(apps)
-            .mount("/api/apps", routes![logs, stream_logs]);
+            .mount("/api/apps", routes![logs, stream_logs, logs_since]);
         Ok(Client::tracked(rocket).await.expect("valid rocket"))
     }
 
+    #[tokio::test]
+    async fn logs_since_as_json() -> Result<(), crate::apps::AppsServiceError> {
+        let (host_meta_cache, mut _host_meta_crawler) = crate::host_meta_crawling();
+
+        let client =
+            set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+        let response = client
+            .get("/api/apps/master/logs/service-a?since=2019-07-18T07:30:00%2B00:00")
+            .header(Accept::JSON)
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_json::<serde_json::Value>().await.unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!([
+                {
+                    "timestamp": "2019-07-18T07:30:00Z",
+                    "stream": "stdout",
+                    "content": "Log msg 2 of service-a of app master\n"
+                },
+                {
+                    "timestamp": "2019-07-18T07:35:00Z",
+                    "stream": "stdout",
+                    "content": "Log msg 3 of service-a of app master\n"
+                }
+            ])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn logs_since_of_unknown_service() -> Result<(), crate::apps::AppsServiceError> {
+        let (host_meta_cache, mut _host_meta_crawler) = crate::host_meta_crawling();
+
+        let client =
+            set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+        let response = client
+            .get("/api/apps/master/logs/service-z?since=2019-07-18T07:30:00%2B00:00")
+            .header(Accept::JSON)
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::NotFound);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn log_weblink_with_no_limit() -> Result<(), crate::apps::AppsServiceError> {
         let (host_meta_cache, mut _host_meta_crawler) = crate::host_meta_crawling();
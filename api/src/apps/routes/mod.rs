@@ -24,54 +24,160 @@
  * =========================LICENSE_END==================================
  */
 
+use crate::admin::BearerToken;
 use crate::apps::HostMetaCache;
-use crate::apps::{Apps, AppsError};
+use crate::apps::{AppDiff, Apps, AppsError, BulkOperationResult};
+use crate::client_certificate::ClientCertificate;
+use crate::config::Config;
+use crate::deployment::RolloutStrategy;
 use crate::http_result::{HttpApiError, HttpResult};
+use crate::infrastructure::{
+    AppDeployment, DnsHealth, DockerInfrastructureError, StoppedService, UnsupportedOperation,
+};
 use crate::models::request_info::RequestInfo;
-use crate::models::service::{Service, ServiceStatus};
-use crate::models::ServiceConfig;
-use crate::models::{AppName, AppNameError};
+use crate::models::service::{ContainerType, Service, ServiceStatus};
+use crate::models::{AppName, AppNameError, AppSummary, WebHostMeta};
 use crate::models::{AppStatusChangeId, AppStatusChangeIdError};
+use crate::models::{ComposeParser, ServiceConfig, ServiceQuery, ServiceSearchResult};
+use crate::request_id::RequestId;
+use crate::secrets::SecretError;
 use http_api_problem::{HttpApiProblem, StatusCode};
-use multimap::MultiMap;
 use regex::Regex;
-use rocket::http::Status;
+use rocket::http::{ContentType, Status};
 use rocket::request::{FromRequest, Outcome, Request};
 use rocket::response::{Responder, Response};
 use rocket::serde::json::Json;
 use rocket::State;
+use std::collections::HashMap;
 use std::future::Future;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::task::Poll;
 use std::time::Duration;
 use tokio::time::timeout;
 
 mod logs;
+mod port_forward;
 
 pub fn apps_routes() -> Vec<rocket::Route> {
     rocket::routes![
         apps,
         delete_app,
+        delete_apps,
+        start_apps,
         create_app,
+        patch_app,
+        create_app_from_compose,
+        create_app_from_k8s_deployment,
+        diff_app,
+        compose_file,
+        export_app_state,
+        copy_app,
+        app_tags,
+        tag_app,
+        wait_for_app_ready,
         logs::logs,
         logs::stream_logs,
+        logs::logs_since,
         change_status,
         status_change,
+        inspect_service,
+        force_stop_service,
+        update_service_env,
+        version_drift,
+        services_by_relation,
+        host_meta,
+        service_host_meta,
+        service_readiness,
+        refresh_host_meta,
+        stopped_services,
+        promote_canary,
+        rollback_canary,
+        prune_stopped_containers,
+        deployments,
+        port_forward::port_forward,
     ]
 }
 
-#[get("/", format = "application/json")]
+pub fn services_routes() -> Vec<rocket::Route> {
+    rocket::routes![search_services]
+}
+
+#[get("/?<tag>", format = "application/json")]
 async fn apps(
     apps: &State<Arc<Apps>>,
+    tag: Option<&str>,
     request_info: RequestInfo,
     host_meta_cache: &State<HostMetaCache>,
-) -> HttpResult<Json<MultiMap<AppName, Service>>> {
-    let services = apps.get_apps().await?;
+    dns_health: Option<&State<DnsHealth>>,
+) -> HttpResult<Json<Vec<AppSummary>>> {
+    let services = match tag {
+        Some(tag) => apps.get_apps_with_tag(tag).await?,
+        None => apps.get_apps().await?,
+    };
+
+    let mut apps = host_meta_cache
+        .update_meta_data(services, &request_info, apps.subdomain_routing())
+        .into_iter()
+        .collect::<Vec<_>>();
+    apps.sort_by(|(app_name, _), (other_app_name, _)| {
+        app_name.as_str().cmp(other_app_name.as_str())
+    });
+
     Ok(Json(
-        host_meta_cache.update_meta_data(services, &request_info),
+        apps.into_iter()
+            .map(|(app_name, services)| {
+                let dns_degraded =
+                    dns_health.map_or(false, |dns_health| dns_health.is_degraded(&app_name));
+                AppSummary::new(app_name, services).with_dns_degraded(dns_degraded)
+            })
+            .collect(),
     ))
 }
 
+/// Searches the services of all apps, e.g. to find the services that run an image that needs to
+/// be patched. The results can be paged with `offset` and `limit`.
+#[get(
+    "/services?<image_prefix>&<app_name_prefix>&<service_name>&<container_type>&<tag>&<offset>&<limit>",
+    format = "application/json"
+)]
+#[allow(clippy::too_many_arguments)]
+async fn search_services(
+    apps: &State<Arc<Apps>>,
+    image_prefix: Option<String>,
+    app_name_prefix: Option<String>,
+    service_name: Option<String>,
+    container_type: Option<&str>,
+    tag: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> HttpResult<Paginated<ServiceSearchResult>> {
+    let container_type = container_type
+        .map(ContainerType::from_str)
+        .transpose()
+        .map_err(|e| {
+            HttpApiProblem::with_title_and_type(StatusCode::BAD_REQUEST).detail(e.to_string())
+        })?;
+
+    let query = ServiceQuery {
+        image_prefix,
+        app_name_prefix,
+        service_name,
+        container_type,
+        tag,
+    };
+    let results = apps.search_services(&query).await?;
+
+    let total_count = results.len();
+    let items = results
+        .into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+
+    Ok(Paginated { items, total_count })
+}
+
 #[get("/<app_name>/status-changes/<status_id>", format = "application/json")]
 async fn status_change(
     app_name: Result<AppName, AppNameError>,
@@ -92,18 +198,25 @@ async fn status_change(
     }
 }
 
-#[delete("/<app_name>")]
+#[delete("/<app_name>?<delete_app_form..>")]
 pub async fn delete_app(
     app_name: Result<AppName, AppNameError>,
     apps: &State<Arc<Apps>>,
+    delete_app_form: DeleteAppOptions,
     options: RunOptions,
+    request_id: RequestId,
+    client_certificate: Option<ClientCertificate>,
 ) -> HttpResult<AsyncCompletion<Json<Vec<Service>>>> {
     let app_name = app_name?;
     let app_name_cloned = app_name.clone();
     let status_id = AppStatusChangeId::new();
+    let preserve_volumes = delete_app_form.preserve_volumes;
 
     let apps = (**apps).clone();
-    let future = async move { apps.delete_app(&app_name, &status_id).await };
+    let future = request_id.scope(async move {
+        apps.delete_app_with_volumes(&app_name, &status_id, preserve_volumes)
+            .await
+    });
 
     match spawn_with_options(options, future).await? {
         Poll::Pending => Ok(AsyncCompletion::Pending(app_name_cloned, status_id)),
@@ -115,8 +228,17 @@ pub async fn delete_app(
 pub async fn delete_app_sync(
     app_name: Result<AppName, AppNameError>,
     apps: &State<Arc<Apps>>,
+    request_id: RequestId,
 ) -> HttpResult<Json<Vec<Service>>> {
-    match delete_app(app_name, apps, RunOptions::Sync).await? {
+    match delete_app(
+        app_name,
+        apps,
+        DeleteAppOptions::default(),
+        RunOptions::Sync,
+        request_id,
+    )
+    .await?
+    {
         AsyncCompletion::Pending(_, _) => {
             Err(HttpApiProblem::with_title(StatusCode::INTERNAL_SERVER_ERROR).into())
         }
@@ -135,7 +257,108 @@ pub async fn create_app(
     create_app_form: CreateAppOptions,
     service_configs: Result<Json<Vec<ServiceConfig>>, rocket::serde::json::Error<'_>>,
     options: RunOptions,
+    request_id: RequestId,
+    client_certificate: Option<ClientCertificate>,
+) -> HttpResult<AsyncCompletion<Json<Vec<Service>>>> {
+    let service_configs = service_configs.map_err(|e| {
+        let detail = match e {
+            rocket::serde::json::Error::Parse(_, e) => e.to_string(),
+            e => e.to_string(),
+        };
+
+        HttpApiProblem::with_title_and_type(StatusCode::BAD_REQUEST).detail(detail)
+    })?;
+
+    create_or_update_app(
+        app_name?,
+        apps,
+        create_app_form,
+        service_configs.into_inner(),
+        options,
+        request_id,
+        client_certificate,
+    )
+    .await
+}
+
+#[post(
+    "/<app_name>/deploy/compose?<create_app_form..>",
+    data = "<compose_file>"
+)]
+pub async fn create_app_from_compose(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+    create_app_form: CreateAppOptions,
+    compose_file: String,
+    options: RunOptions,
+    request_id: RequestId,
+    client_certificate: Option<ClientCertificate>,
 ) -> HttpResult<AsyncCompletion<Json<Vec<Service>>>> {
+    let service_configs = ComposeParser::parse(&compose_file).map_err(|e| {
+        HttpApiProblem::with_title_and_type(StatusCode::BAD_REQUEST).detail(e.to_string())
+    })?;
+
+    create_or_update_app(
+        app_name?,
+        apps,
+        create_app_form,
+        service_configs,
+        options,
+        request_id,
+        client_certificate,
+    )
+    .await
+}
+
+/// Deploys the first container of a Kubernetes `Deployment` manifest. Parts of the manifest that
+/// cannot be mapped to a service configuration are reported as `Warning` headers.
+#[post(
+    "/<app_name>/deploy/k8s-deployment?<create_app_form..>",
+    data = "<deployment>"
+)]
+pub async fn create_app_from_k8s_deployment(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+    create_app_form: CreateAppOptions,
+    deployment: String,
+    options: RunOptions,
+    request_id: RequestId,
+    client_certificate: Option<ClientCertificate>,
+) -> HttpResult<WithWarnings<AsyncCompletion<Json<Vec<Service>>>>> {
+    let (service_config, warnings) =
+        ServiceConfig::from_k8s_deployment(&deployment).map_err(|e| {
+            HttpApiProblem::with_title_and_type(StatusCode::BAD_REQUEST).detail(e.to_string())
+        })?;
+
+    let response = create_or_update_app(
+        app_name?,
+        apps,
+        create_app_form,
+        vec![service_config],
+        options,
+        request_id,
+        client_certificate,
+    )
+    .await?;
+
+    Ok(WithWarnings {
+        response,
+        warnings: warnings.iter().map(ToString::to_string).collect(),
+    })
+}
+
+/// Shows which services would be added or updated if the given service configurations were
+/// deployed. Neither containers nor pods are created or modified.
+#[post(
+    "/<app_name>/diff",
+    format = "application/json",
+    data = "<service_configs>"
+)]
+async fn diff_app(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+    service_configs: Result<Json<Vec<ServiceConfig>>, rocket::serde::json::Error<'_>>,
+) -> HttpResult<Json<AppDiff>> {
     let service_configs = service_configs.map_err(|e| {
         let detail = match e {
             rocket::serde::json::Error::Parse(_, e) => e.to_string(),
@@ -145,21 +368,42 @@ pub async fn create_app(
         HttpApiProblem::with_title_and_type(StatusCode::BAD_REQUEST).detail(detail)
     })?;
 
+    Ok(Json(apps.diff_app(&app_name?, &service_configs).await?))
+}
+
+async fn create_or_update_app(
+    app_name: AppName,
+    apps: &State<Arc<Apps>>,
+    create_app_form: CreateAppOptions,
+    service_configs: Vec<ServiceConfig>,
+    options: RunOptions,
+    request_id: RequestId,
+    client_certificate: Option<ClientCertificate>,
+) -> HttpResult<AsyncCompletion<Json<Vec<Service>>>> {
     let status_id = AppStatusChangeId::new();
-    let app_name = app_name?;
     let app_name_cloned = app_name.clone();
     let replicate_from = create_app_form.replicate_from().clone();
+    let base_app = create_app_form.base_app().clone();
+    let rollout_strategy = create_app_form.rollout_strategy();
+    let skip_companions = create_app_form.skip_companions();
 
     let apps = (**apps).clone();
-    let future = async move {
-        apps.create_or_update(
+    let future = request_id.scope(ClientCertificate::scope(client_certificate, async move {
+        let service_configs = match base_app {
+            Some(base_app) => apps.inherit_environment(&base_app, service_configs).await?,
+            None => service_configs,
+        };
+
+        apps.create_or_update_with_rollout_strategy(
             &app_name.clone(),
             &status_id,
             replicate_from,
             &service_configs,
+            rollout_strategy,
+            skip_companions,
         )
         .await
-    };
+    }));
 
     match spawn_with_options(options, future).await? {
         Poll::Pending => Ok(AsyncCompletion::Pending(app_name_cloned, status_id)),
@@ -168,6 +412,175 @@ pub async fn create_app(
     }
 }
 
+/// Either renames the app or partially updates its services, depending on the payload.
+#[patch("/<app_name>", format = "application/json", data = "<payload>")]
+async fn patch_app(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+    payload: Json<PatchAppPayload>,
+    request_id: RequestId,
+) -> HttpResult<Json<Vec<Service>>> {
+    let app_name = app_name?;
+
+    let services = match payload.into_inner() {
+        PatchAppPayload::Rename(payload) => {
+            let new_app_name = AppName::from_str(&payload.new_name)?;
+            request_id
+                .scope(apps.rename_app(&app_name, &new_app_name))
+                .await?
+        }
+        PatchAppPayload::Services(patches) => {
+            request_id
+                .scope(apps.update_services(&app_name, &patches))
+                .await?
+        }
+    };
+
+    Ok(Json(services))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkAppsPayload {
+    app_names: Vec<String>,
+}
+
+impl BulkAppsPayload {
+    fn app_names(&self) -> Result<Vec<AppName>, AppNameError> {
+        self.app_names
+            .iter()
+            .map(|app_name| AppName::from_str(app_name))
+            .collect()
+    }
+}
+
+/// Responds with `207 Multi-Status` if the operation failed for some of the apps.
+fn bulk_operation_response(result: BulkOperationResult) -> (Status, Json<BulkOperationResult>) {
+    let status = if result.has_failures() {
+        Status::MultiStatus
+    } else {
+        Status::Ok
+    };
+    (status, Json(result))
+}
+
+#[post(
+    "/bulk/delete?<delete_app_form..>",
+    format = "application/json",
+    data = "<payload>"
+)]
+async fn delete_apps(
+    apps: &State<Arc<Apps>>,
+    delete_app_form: DeleteAppOptions,
+    payload: Json<BulkAppsPayload>,
+    request_id: RequestId,
+) -> HttpResult<(Status, Json<BulkOperationResult>)> {
+    let app_names = payload.app_names()?;
+
+    let result = request_id
+        .scope(apps.delete_apps(&app_names, delete_app_form.preserve_volumes))
+        .await;
+
+    Ok(bulk_operation_response(result))
+}
+
+#[post("/bulk/start", format = "application/json", data = "<payload>")]
+async fn start_apps(
+    apps: &State<Arc<Apps>>,
+    payload: Json<BulkAppsPayload>,
+    request_id: RequestId,
+) -> HttpResult<(Status, Json<BulkOperationResult>)> {
+    let app_names = payload.app_names()?;
+
+    let result = request_id.scope(apps.start_apps(&app_names)).await;
+
+    Ok(bulk_operation_response(result))
+}
+
+/// Returns the tags of the app.
+#[get("/<app_name>/tags", format = "application/json")]
+async fn app_tags(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<Json<Vec<String>>> {
+    Ok(Json(apps.get_app_tags(&app_name?).await?))
+}
+
+/// Replaces the tags of the app without redeploying its services.
+#[put("/<app_name>/tags", format = "application/json", data = "<tags>")]
+async fn tag_app(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+    tags: Json<Vec<String>>,
+) -> HttpResult<Json<Vec<String>>> {
+    Ok(Json(apps.tag_app(&app_name?, tags.into_inner()).await?))
+}
+
+/// Waits until all services of the app are ready to handle traffic, e.g. before CI pipelines run
+/// integration tests against the app.
+#[get("/<app_name>/wait-for-ready?<timeout_secs>")]
+async fn wait_for_app_ready(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+    timeout_secs: Option<u64>,
+) -> HttpResult<Status> {
+    apps.wait_for_app_ready(&app_name?, Duration::from_secs(timeout_secs.unwrap_or(120)))
+        .await?;
+    Ok(Status::NoContent)
+}
+
+/// Describes the running services of the app as a Compose file.
+#[get("/<app_name>/compose")]
+async fn compose_file(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<(ContentType, String)> {
+    let app_name = app_name?;
+
+    match apps.compose_file(&app_name).await? {
+        Some(compose_file) => Ok((ContentType::new("application", "x-yaml"), compose_file)),
+        None => Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!("Cannot find app {app_name}."))
+            .into()),
+    }
+}
+
+/// Exports the configurations of the app's services as ZIP archive so that the app can be
+/// reproduced later.
+#[get("/<app_name>/export")]
+async fn export_app_state(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<(ContentType, Vec<u8>)> {
+    let app_name = app_name?;
+
+    match apps.export_app_state(&app_name).await? {
+        Some(archive) => Ok((ContentType::ZIP, archive.into_bytes())),
+        None => Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!("Cannot find app {app_name}."))
+            .into()),
+    }
+}
+
+/// Deploys the services of `source_app_name` under `app_name`. Other than renaming, the source app
+/// keeps running.
+#[post("/<app_name>/copy-from/<source_app_name>")]
+async fn copy_app(
+    app_name: Result<AppName, AppNameError>,
+    source_app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+    request_id: RequestId,
+) -> HttpResult<Json<Vec<Service>>> {
+    let app_name = app_name?;
+    let source_app_name = source_app_name?;
+
+    let services = request_id
+        .scope(apps.copy_app(&source_app_name, &app_name))
+        .await?;
+
+    Ok(Json(services))
+}
+
 #[put(
     "/<app_name>/states/<service_name>",
     format = "application/json",
@@ -178,112 +591,624 @@ async fn change_status(
     service_name: String,
     apps: &State<Arc<Apps>>,
     status_data: Json<ServiceStatusData>,
+    request_id: RequestId,
 ) -> HttpResult<ServiceStatusResponse> {
     let app_name = app_name?;
     let status = status_data.status.clone();
 
-    let service = apps.change_status(&app_name, &service_name, status).await?;
+    let service = request_id
+        .scope(apps.change_status(&app_name, &service_name, status))
+        .await?;
 
     Ok(ServiceStatusResponse { service })
 }
 
-#[derive(Debug, PartialEq)]
-pub enum RunOptions {
-    Sync,
-    Async { wait: Option<Duration> },
+#[get(
+    "/<app_name>/services/<service_name>/inspect",
+    format = "application/json"
+)]
+async fn inspect_service(
+    app_name: Result<AppName, AppNameError>,
+    service_name: String,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<Json<serde_json::Value>> {
+    let app_name = app_name?;
+
+    match apps.inspect_service(&app_name, &service_name).await? {
+        Some(details) => Ok(Json(details)),
+        None => Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!(
+                "Cannot find service {service_name} of app {app_name}."
+            ))
+            .into()),
+    }
 }
 
-pub async fn spawn_with_options<F>(options: RunOptions, future: F) -> HttpResult<Poll<F::Output>>
-where
-    F: Future + Send + 'static,
-    F::Output: Send + 'static,
-{
-    let join_handle = tokio::spawn(future);
+/// Removes the service's container without a graceful shutdown, e.g. when stopping it hangs
+/// because the process ignores `SIGTERM`. Requires the configured admin API key as bearer token.
+#[post("/<app_name>/services/<service_name>/force-stop")]
+async fn force_stop_service(
+    app_name: Result<AppName, AppNameError>,
+    service_name: String,
+    apps: &State<Arc<Apps>>,
+    config: &State<Config>,
+    bearer_token: BearerToken,
+) -> HttpResult<Json<Service>> {
+    bearer_token.authorize(config)?;
+    let app_name = app_name?;
 
-    match options {
-        RunOptions::Sync => Ok(Poll::Ready(join_handle.await.map_err(map_join_error)?)),
-        RunOptions::Async { wait: None } => Ok(Poll::Pending),
-        RunOptions::Async {
-            wait: Some(duration),
-        } => {
-            match tokio::spawn(timeout(duration, join_handle))
-                .await
-                .map_err(map_join_error)?
-            {
-                // Execution completed before timeout
-                Ok(Ok(result)) => Ok(Poll::Ready(result)),
-                // JoinError occurred before timeout
-                Ok(Err(err)) => Err(map_join_error(err)),
-                // Timeout elapsed while waiting for future
-                Err(_) => Ok(Poll::Pending),
-            }
-        }
+    match apps.force_stop_service(&app_name, &service_name).await? {
+        Some(service) => Ok(Json(service)),
+        None => Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!(
+                "Cannot find service {service_name} of app {app_name}."
+            ))
+            .into()),
     }
 }
 
-fn map_join_error(err: tokio::task::JoinError) -> HttpApiError {
-    HttpApiProblem::with_title(StatusCode::INTERNAL_SERVER_ERROR)
-        .detail(format!("{}", err))
-        .into()
-}
+/// Replaces the running version of the service by its canary, see `strategy=canary`.
+#[post("/<app_name>/services/<service_name>/promote-canary")]
+async fn promote_canary(
+    app_name: Result<AppName, AppNameError>,
+    service_name: String,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<Status> {
+    let app_name = app_name?;
 
-#[derive(FromForm)]
-pub struct CreateAppOptions {
-    #[field(name = "replicateFrom")]
-    replicate_from: Option<AppName>,
+    if !apps.promote_canary(&app_name, &service_name).await? {
+        return Err(canary_not_found(&app_name, &service_name));
+    }
+    Ok(Status::NoContent)
 }
 
-impl CreateAppOptions {
-    fn replicate_from(&self) -> &Option<AppName> {
-        &self.replicate_from
+/// Removes the canary of the service and keeps its running version.
+#[post("/<app_name>/services/<service_name>/rollback-canary")]
+async fn rollback_canary(
+    app_name: Result<AppName, AppNameError>,
+    service_name: String,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<Status> {
+    let app_name = app_name?;
+
+    if !apps.rollback_canary(&app_name, &service_name).await? {
+        return Err(canary_not_found(&app_name, &service_name));
     }
+    Ok(Status::NoContent)
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct ServiceStatusData {
-    status: ServiceStatus,
+fn canary_not_found(app_name: &AppName, service_name: &str) -> HttpApiError {
+    HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+        .detail(format!(
+            "Cannot find a canary of service {service_name} of app {app_name}."
+        ))
+        .into()
 }
 
-pub struct ServiceStatusResponse {
-    service: Option<Service>,
+/// Updates the environment variables of the service and recreates its container. Variables with
+/// `null` as value will be removed.
+#[patch(
+    "/<app_name>/services/<service_name>/env",
+    format = "application/json",
+    data = "<env_updates>"
+)]
+async fn update_service_env(
+    app_name: Result<AppName, AppNameError>,
+    service_name: String,
+    apps: &State<Arc<Apps>>,
+    env_updates: Json<HashMap<String, Option<String>>>,
+    request_id: RequestId,
+) -> HttpResult<Json<Service>> {
+    let app_name = app_name?;
+
+    match request_id
+        .scope(apps.update_service_env(&app_name, &service_name, &env_updates))
+        .await?
+    {
+        Some(service) => Ok(Json(service)),
+        None => Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!(
+                "Cannot find service {service_name} of app {app_name}."
+            ))
+            .into()),
+    }
 }
 
-pub enum AsyncCompletion<T> {
-    Pending(AppName, AppStatusChangeId),
-    Ready(T),
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionDrift {
+    service_name: String,
+    deployed_by_version: Option<String>,
+    current_version: &'static str,
 }
 
-impl<'r, T> Responder<'r, 'static> for AsyncCompletion<T>
-where
-    T: Responder<'r, 'static>,
-{
-    fn respond_to(self, request: &'r Request) -> Result<Response<'static>, Status> {
-        match self {
-            AsyncCompletion::Pending(app_name, status_id) => {
-                let url = format!("/api/apps/{}/status-changes/{}", app_name, status_id);
-                Response::build()
-                    .status(Status::Accepted)
-                    .raw_header("Location", url)
-                    .ok()
-            }
-            AsyncCompletion::Ready(result) => result.respond_to(request),
+/// Lists the services of the app that have been deployed by an older version of PREvant.
+#[get("/<app_name>/version-drift", format = "application/json")]
+async fn version_drift(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<Json<Vec<VersionDrift>>> {
+    let app_name = app_name?;
+    let current_version = clap::crate_version!();
+
+    let services = match apps.get_apps().await?.remove(&app_name) {
+        Some(services) => services,
+        None => {
+            return Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+                .detail(format!("Cannot find app {app_name}."))
+                .into())
         }
-    }
+    };
+
+    Ok(Json(
+        services
+            .into_iter()
+            .filter(|service| service.is_deployed_by_older_version(current_version))
+            .map(|service| VersionDrift {
+                service_name: service.service_name().clone(),
+                deployed_by_version: service.deployed_by_version().map(String::from),
+                current_version,
+            })
+            .collect(),
+    ))
 }
 
-impl<'r> Responder<'r, 'static> for ServiceStatusResponse {
-    fn respond_to(self, _request: &'r Request) -> Result<Response<'static>, Status> {
-        match self.service {
-            Some(_service) => Response::build().status(Status::Accepted).ok(),
-            None => Response::build().status(Status::NotFound).ok(),
-        }
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServiceRelation {
+    service_name: String,
+    href: url::Url,
+}
+
+/// Lists the services of the app whose web host meta data provide links with the given relation
+/// type, e.g. `describedby`.
+#[get("/<app_name>/services-by-relation?<rel>", format = "application/json")]
+async fn services_by_relation(
+    app_name: Result<AppName, AppNameError>,
+    rel: &str,
+    apps: &State<Arc<Apps>>,
+    request_info: RequestInfo,
+    host_meta_cache: &State<HostMetaCache>,
+) -> HttpResult<Json<Vec<ServiceRelation>>> {
+    let app_name = app_name?;
+
+    if !apps.get_app_names().await?.contains(&app_name) {
+        return Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!("Cannot find app {app_name}."))
+            .into());
     }
+
+    Ok(Json(
+        host_meta_cache
+            .find_by_relation(&app_name, rel, &request_info, apps.subdomain_routing())
+            .into_iter()
+            .map(|(service_name, href)| ServiceRelation { service_name, href })
+            .collect(),
+    ))
 }
 
-impl From<AppsError> for HttpApiError {
-    fn from(error: AppsError) -> Self {
-        let status = match &error {
-            AppsError::AppLimitExceeded { .. } => StatusCode::PRECONDITION_FAILED,
+/// Aggregates the web host meta data of the app's services.
+#[get("/<app_name>/.well-known/host-meta.json", format = "application/json")]
+async fn host_meta(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+    request_info: RequestInfo,
+    host_meta_cache: &State<HostMetaCache>,
+) -> HttpResult<Json<WebHostMeta>> {
+    let app_name = app_name?;
+
+    match host_meta_cache.merged_web_host_meta(&app_name, &request_info, apps.subdomain_routing()) {
+        Some(web_host_meta) => Ok(Json(web_host_meta)),
+        None => Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!(
+                "None of the services of app {app_name} provides web host meta data."
+            ))
+            .into()),
+    }
+}
+
+pub struct ServiceHostMetaResponse {
+    web_host_meta: Option<WebHostMeta>,
+    ttl: Duration,
+}
+
+/// Returns the cached web host meta data of a single service. Clients may cache the response for
+/// the configured TTL of the web host meta data.
+#[get(
+    "/<app_name>/services/<service_name>/host-meta",
+    format = "application/json"
+)]
+async fn service_host_meta(
+    app_name: Result<AppName, AppNameError>,
+    service_name: &str,
+    apps: &State<Arc<Apps>>,
+    request_info: RequestInfo,
+    host_meta_cache: &State<HostMetaCache>,
+) -> HttpResult<ServiceHostMetaResponse> {
+    let app_name = app_name?;
+
+    let services = apps.get_apps().await?;
+    let service = services
+        .get_vec(&app_name)
+        .and_then(|services| {
+            services
+                .iter()
+                .find(|service| service.service_name() == service_name)
+        })
+        .ok_or_else(|| {
+            HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND).detail(format!(
+                "Cannot find service {service_name} of app {app_name}."
+            ))
+        })?;
+
+    Ok(ServiceHostMetaResponse {
+        web_host_meta: host_meta_cache.get_one(
+            &app_name,
+            service.id(),
+            &request_info,
+            apps.subdomain_routing(),
+        ),
+        ttl: apps.host_meta_ttl(),
+    })
+}
+
+#[derive(Serialize)]
+struct ServiceReadiness {
+    ready: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'static str>,
+}
+
+/// Checks whether the service is ready to accept traffic: the service must be running, its health
+/// check must pass (if configured), and its web host meta data must have been crawled
+/// successfully. CI/CD pipelines can poll this endpoint before running integration tests.
+#[get(
+    "/<app_name>/services/<service_name>/ready",
+    format = "application/json"
+)]
+async fn service_readiness(
+    app_name: Result<AppName, AppNameError>,
+    service_name: &str,
+    apps: &State<Arc<Apps>>,
+    request_info: RequestInfo,
+    host_meta_cache: &State<HostMetaCache>,
+) -> HttpResult<(Status, Json<ServiceReadiness>)> {
+    let app_name = app_name?;
+
+    let services = apps.get_apps().await?;
+    let service = services
+        .get_vec(&app_name)
+        .and_then(|services| {
+            services
+                .iter()
+                .find(|service| service.service_name() == service_name)
+        })
+        .ok_or_else(|| {
+            HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND).detail(format!(
+                "Cannot find service {service_name} of app {app_name}."
+            ))
+        })?;
+
+    let reason = if service.status() != &ServiceStatus::Running {
+        Some("not_running")
+    } else if apps.is_service_healthy(&app_name, service_name).await? == Some(false) {
+        Some("health_check_failing")
+    } else if !host_meta_cache
+        .get_one(
+            &app_name,
+            service.id(),
+            &request_info,
+            apps.subdomain_routing(),
+        )
+        .is_some_and(|web_host_meta| web_host_meta.is_valid())
+    {
+        Some("host_meta_unavailable")
+    } else {
+        None
+    };
+
+    let status = if reason.is_none() {
+        Status::Ok
+    } else {
+        Status::ServiceUnavailable
+    };
+    Ok((
+        status,
+        Json(ServiceReadiness {
+            ready: reason.is_none(),
+            reason,
+        }),
+    ))
+}
+
+/// Crawls the web host meta data of the app's services right away instead of waiting for the
+/// next periodic crawl.
+#[post("/<app_name>/refresh-host-meta")]
+async fn refresh_host_meta(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+    host_meta_cache: &State<HostMetaCache>,
+) -> HttpResult<Status> {
+    let app_name = app_name?;
+
+    if !apps.get_app_names().await?.contains(&app_name) {
+        return Err(AppsError::AppNotFound { app_name }.into());
+    }
+
+    host_meta_cache.force_crawl_app(&app_name);
+
+    Ok(Status::Accepted)
+}
+
+/// Lists the services of the app whose containers are not running anymore, e.g. because they
+/// crashed.
+#[get("/<app_name>/stopped-services", format = "application/json")]
+async fn stopped_services(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<Json<Vec<StoppedService>>> {
+    let app_name = app_name?;
+
+    if !apps.get_app_names().await?.contains(&app_name) {
+        return Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!("Cannot find app {app_name}."))
+            .into());
+    }
+
+    Ok(Json(apps.get_stopped_services(&app_name).await?))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PrunedContainers {
+    removed_containers: usize,
+}
+
+/// Removes the exited containers of the app that have been superseded by other containers of their
+/// service, e.g. the ones that a crashed deployment left behind.
+#[delete("/<app_name>/stopped-containers")]
+async fn prune_stopped_containers(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<Json<PrunedContainers>> {
+    let app_name = app_name?;
+
+    if !apps.get_app_names().await?.contains(&app_name) {
+        return Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!("Cannot find app {app_name}."))
+            .into());
+    }
+
+    let removed_containers = apps.prune_stopped_containers(&app_name).await?;
+    Ok(Json(PrunedContainers { removed_containers }))
+}
+
+/// Lists the groups of running services that have been deployed together.
+#[get("/<app_name>/deployments", format = "application/json")]
+async fn deployments(
+    app_name: Result<AppName, AppNameError>,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<Json<Vec<AppDeployment>>> {
+    let app_name = app_name?;
+
+    if !apps.get_app_names().await?.contains(&app_name) {
+        return Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!("Cannot find app {app_name}."))
+            .into());
+    }
+
+    Ok(Json(apps.get_deployments(&app_name).await?))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RunOptions {
+    Sync,
+    Async { wait: Option<Duration> },
+}
+
+pub async fn spawn_with_options<F>(options: RunOptions, future: F) -> HttpResult<Poll<F::Output>>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let join_handle = tokio::spawn(future);
+
+    match options {
+        RunOptions::Sync => Ok(Poll::Ready(join_handle.await.map_err(map_join_error)?)),
+        RunOptions::Async { wait: None } => Ok(Poll::Pending),
+        RunOptions::Async {
+            wait: Some(duration),
+        } => {
+            match tokio::spawn(timeout(duration, join_handle))
+                .await
+                .map_err(map_join_error)?
+            {
+                // Execution completed before timeout
+                Ok(Ok(result)) => Ok(Poll::Ready(result)),
+                // JoinError occurred before timeout
+                Ok(Err(err)) => Err(map_join_error(err)),
+                // Timeout elapsed while waiting for future
+                Err(_) => Ok(Poll::Pending),
+            }
+        }
+    }
+}
+
+fn map_join_error(err: tokio::task::JoinError) -> HttpApiError {
+    HttpApiProblem::with_title(StatusCode::INTERNAL_SERVER_ERROR)
+        .detail(format!("{}", err))
+        .into()
+}
+
+#[derive(FromForm)]
+pub struct CreateAppOptions {
+    #[field(name = "replicateFrom")]
+    replicate_from: Option<AppName>,
+    #[field(name = "baseApp")]
+    base_app: Option<AppName>,
+    strategy: Option<Strategy>,
+    #[field(name = "healthCheckTimeoutSecs", default = 300)]
+    health_check_timeout_secs: u64,
+    #[field(name = "canaryWeightPercent", validate = range(1..100), default = 10)]
+    canary_weight_percent: u8,
+    #[field(name = "promoteAfterSecs")]
+    promote_after_secs: Option<u64>,
+    #[field(name = "skipCompanions", default = false)]
+    skip_companions: bool,
+}
+
+#[derive(Default, FromForm)]
+pub struct DeleteAppOptions {
+    #[field(name = "preserveVolumes", default = false)]
+    preserve_volumes: bool,
+}
+
+#[derive(Clone, Copy, Debug, FromFormField, PartialEq)]
+pub enum Strategy {
+    #[field(value = "recreate")]
+    Recreate,
+    #[field(value = "blue-green")]
+    BlueGreen,
+    #[field(value = "canary")]
+    Canary,
+}
+
+impl CreateAppOptions {
+    fn replicate_from(&self) -> &Option<AppName> {
+        &self.replicate_from
+    }
+
+    fn rollout_strategy(&self) -> RolloutStrategy {
+        match self.strategy {
+            None | Some(Strategy::Recreate) => RolloutStrategy::Recreate,
+            Some(Strategy::BlueGreen) => RolloutStrategy::BlueGreen {
+                health_check_timeout: Duration::from_secs(self.health_check_timeout_secs),
+            },
+            Some(Strategy::Canary) => RolloutStrategy::Canary {
+                weight_percent: self.canary_weight_percent,
+                promote_after: self.promote_after_secs.map(Duration::from_secs),
+            },
+        }
+    }
+
+    fn base_app(&self) -> &Option<AppName> {
+        &self.base_app
+    }
+
+    fn skip_companions(&self) -> bool {
+        self.skip_companions
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum PatchAppPayload {
+    Rename(RenameAppPayload),
+    Services(Vec<ServiceConfig>),
+}
+
+#[derive(Deserialize)]
+pub struct RenameAppPayload {
+    new_name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ServiceStatusData {
+    status: ServiceStatus,
+}
+
+pub struct ServiceStatusResponse {
+    service: Option<Service>,
+}
+
+pub enum AsyncCompletion<T> {
+    Pending(AppName, AppStatusChangeId),
+    Ready(T),
+}
+
+impl<'r, T> Responder<'r, 'static> for AsyncCompletion<T>
+where
+    T: Responder<'r, 'static>,
+{
+    fn respond_to(self, request: &'r Request) -> Result<Response<'static>, Status> {
+        match self {
+            AsyncCompletion::Pending(app_name, status_id) => {
+                let url = format!("/api/apps/{}/status-changes/{}", app_name, status_id);
+                Response::build()
+                    .status(Status::Accepted)
+                    .raw_header("Location", url)
+                    .ok()
+            }
+            AsyncCompletion::Ready(result) => result.respond_to(request),
+        }
+    }
+}
+
+/// A page of items whose `X-Total-Count` header contains the number of all items.
+pub struct Paginated<T> {
+    items: Vec<T>,
+    total_count: usize,
+}
+
+impl<'r, T> Responder<'r, 'static> for Paginated<T>
+where
+    T: serde::Serialize,
+{
+    fn respond_to(self, request: &'r Request) -> Result<Response<'static>, Status> {
+        Response::build_from(Json(self.items).respond_to(request)?)
+            .raw_header("X-Total-Count", self.total_count.to_string())
+            .ok()
+    }
+}
+
+/// Adds a [`Warning`](https://www.rfc-editor.org/rfc/rfc7234#section-5.5) header with the
+/// miscellaneous persistent warning code 299 for each warning to the response.
+pub struct WithWarnings<T> {
+    response: T,
+    warnings: Vec<String>,
+}
+
+impl<'r, T> Responder<'r, 'static> for WithWarnings<T>
+where
+    T: Responder<'r, 'static>,
+{
+    fn respond_to(self, request: &'r Request) -> Result<Response<'static>, Status> {
+        let mut response = Response::build_from(self.response.respond_to(request)?);
+        for warning in self.warnings {
+            response.raw_header_adjoin("Warning", format!("299 - {:?}", warning));
+        }
+        response.ok()
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ServiceHostMetaResponse {
+    fn respond_to(self, request: &'r Request) -> Result<Response<'static>, Status> {
+        let cache_control = format!("max-age={}", self.ttl.as_secs());
+        match self.web_host_meta {
+            Some(web_host_meta) => Response::build_from(Json(web_host_meta).respond_to(request)?)
+                .raw_header("Cache-Control", cache_control)
+                .ok(),
+            None => Response::build()
+                .status(Status::NoContent)
+                .raw_header("Cache-Control", cache_control)
+                .ok(),
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ServiceStatusResponse {
+    fn respond_to(self, _request: &'r Request) -> Result<Response<'static>, Status> {
+        match self.service {
+            Some(_service) => Response::build().status(Status::Accepted).ok(),
+            None => Response::build().status(Status::NotFound).ok(),
+        }
+    }
+}
+
+impl From<AppsError> for HttpApiError {
+    fn from(error: AppsError) -> Self {
+        let status = match &error {
+            AppsError::AppLimitExceeded { .. } => StatusCode::PRECONDITION_FAILED,
             AppsError::UnableToResolveImage { error } => match **error {
                 crate::registry::RegistryError::ImageNotFound { .. } => StatusCode::NOT_FOUND,
                 _ => StatusCode::INTERNAL_SERVER_ERROR,
@@ -291,8 +1216,60 @@ impl From<AppsError> for HttpApiError {
             AppsError::AppNotFound { .. } => StatusCode::NOT_FOUND,
             AppsError::AppIsInDeployment { .. } => StatusCode::CONFLICT,
             AppsError::AppIsInDeletion { .. } => StatusCode::CONFLICT,
+            AppsError::AppAlreadyExists { .. } => StatusCode::CONFLICT,
+            AppsError::AppNotInShard { .. } => StatusCode::MISDIRECTED_REQUEST,
+            AppsError::AppNameNotDeployable { .. } => StatusCode::CONFLICT,
+            AppsError::InvalidTag { .. } => StatusCode::BAD_REQUEST,
+            AppsError::Timeout { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            AppsError::InvalidTimeout { .. } => StatusCode::BAD_REQUEST,
+            AppsError::InfrastructureError { error: err } if err.is::<UnsupportedOperation>() => {
+                StatusCode::NOT_IMPLEMENTED
+            }
+            AppsError::InfrastructureError { error: err } => {
+                match err.downcast_ref::<DockerInfrastructureError>() {
+                    Some(DockerInfrastructureError::ImageNotFound { .. }) => StatusCode::NOT_FOUND,
+                    Some(DockerInfrastructureError::UnauthorizedRegistry { .. })
+                    | Some(DockerInfrastructureError::DeviceNotPermitted { .. })
+                    | Some(DockerInfrastructureError::EnvNotInheritable { .. })
+                    | Some(DockerInfrastructureError::SecretResolution {
+                        err: SecretError::NotPermitted { .. },
+                        ..
+                    }) => StatusCode::FORBIDDEN,
+                    Some(DockerInfrastructureError::InvalidMemorySwap { .. })
+                    | Some(DockerInfrastructureError::InvalidMemorySwappiness { .. })
+                    | Some(DockerInfrastructureError::InvalidPidLimit { .. })
+                    | Some(DockerInfrastructureError::InvalidWorkingDir { .. })
+                    | Some(DockerInfrastructureError::InvalidStopSignal { .. })
+                    | Some(DockerInfrastructureError::InvalidDnsServer { .. })
+                    | Some(DockerInfrastructureError::InvalidTimezone { .. })
+                    | Some(DockerInfrastructureError::InvalidNamedVolume { .. })
+                    | Some(DockerInfrastructureError::InvalidReplicas { .. })
+                    | Some(DockerInfrastructureError::PortNotExposed { .. })
+                    | Some(DockerInfrastructureError::InvalidIpcMode { .. })
+                    | Some(DockerInfrastructureError::CyclicDependencies { .. })
+                    | Some(DockerInfrastructureError::IpcContainerNotRunning { .. })
+                    | Some(DockerInfrastructureError::IpcContainerNotShareable { .. })
+                    | Some(DockerInfrastructureError::InheritedEnvNotFound { .. })
+                    | Some(DockerInfrastructureError::SecretResolution {
+                        err: SecretError::NotFound { .. } | SecretError::NoProvider { .. },
+                        ..
+                    }) => StatusCode::BAD_REQUEST,
+                    Some(DockerInfrastructureError::DeploymentLockTimeout { .. })
+                    | Some(DockerInfrastructureError::ContainerNameConflict { .. })
+                    | Some(DockerInfrastructureError::NamedVolumeConflict { .. }) => {
+                        StatusCode::CONFLICT
+                    }
+                    Some(DockerInfrastructureError::UnexpectedError { .. }) => {
+                        error!("Cannot interact with Docker: {}", error);
+                        StatusCode::SERVICE_UNAVAILABLE
+                    }
+                    _ => {
+                        error!("Internal server error: {}", error);
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    }
+                }
+            }
             AppsError::FailedToParseTraefikRule { .. }
-            | AppsError::InfrastructureError { .. }
             | AppsError::InvalidServerConfiguration { .. }
             | AppsError::InvalidTemplateFormat { .. }
             | AppsError::InvalidDeploymentHook => {
@@ -450,6 +1427,7 @@ mod tests {
 
     mod url_rendering {
         use crate::apps::{AppsService, HostMetaCache};
+        use crate::config_from_str;
         use crate::infrastructure::Dummy;
         use crate::models::{AppName, AppStatusChangeId};
         use crate::sc;
@@ -480,6 +1458,12 @@ mod tests {
             let rocket = rocket::build()
                 .manage(host_meta_cache)
                 .manage(apps)
+                .manage(config_from_str!(
+                    r#"
+                    [admin]
+                    api_key = 'admin-key'
+                    "#
+                ))
                 .mount("/", routes![crate::apps::routes::apps])
                 .mount("/api/apps", crate::apps::apps_routes());
             Ok(Client::tracked(rocket).await.expect("valid rocket"))
@@ -506,72 +1490,531 @@ mod tests {
             let body_str = response.into_string().await.expect("valid response body");
             let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
 
-            assert_json_include!(actual: value_in_json, expected: json!({
-             "master": [
+            assert_json_include!(actual: value_in_json, expected: json!([{
+                "appName": "master",
+                "services": [
                     {
                      "url":"http://prevant.com:8433/master/service-a/"
                     }
                 ]
-            }));
+            }]));
 
             Ok(())
         }
 
         #[tokio::test]
-        async fn host_header_response_with_xforwardedproto_and_other_default_values(
-        ) -> Result<(), crate::apps::AppsServiceError> {
+        async fn services_by_relation() -> Result<(), crate::apps::AppsServiceError> {
             let (host_meta_cache, mut host_meta_crawler) = crate::host_meta_crawling();
             let client =
                 set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
-            host_meta_crawler.fake_empty_host_meta_info(AppName::master(), "service-a".to_string());
+            host_meta_crawler.fake_host_meta_info(
+                AppName::master(),
+                "service-a".to_string(),
+                serde_json::from_value(json!({
+                    "links": [
+                        { "rel": "describedby", "href": "http://service-a/master/service-a/docs" },
+                        { "rel": "item", "href": "http://service-a/master/service-a/items" }
+                    ]
+                }))
+                .unwrap(),
+            );
 
-            let get = client
-                .get("/")
-                .header(rocket::http::Header::new("x-forwarded-proto", "https"))
-                .header(rocket::http::Header::new("host", "localhost"))
+            let response = client
+                .get("/api/apps/master/services-by-relation?rel=describedby")
+                .header(rocket::http::Header::new("host", "prevant.com"))
                 .header(ContentType::JSON)
-                .dispatch();
-
-            let response = get.await;
+                .dispatch()
+                .await;
 
+            assert_eq!(response.status(), Status::Ok);
             let body_str = response.into_string().await.expect("valid response body");
             let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
-            assert_json_include!(actual: value_in_json, expected: json!({
-             "master": [
-                    {
-                     "url":"https://localhost/master/service-a/"
-                    }
-                ]
-            }));
+            assert_eq!(
+                value_in_json,
+                json!([{
+                    "serviceName": "service-a",
+                    "href": "http://prevant.com/master/service-a/docs"
+                }])
+            );
+
+            let response = client
+                .get("/api/apps/other/services-by-relation?rel=describedby")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotFound);
 
             Ok(())
         }
 
         #[tokio::test]
-        async fn host_header_response_with_xforwardedhost_and_other_default_values(
+        async fn services_by_relation_with_forwarded_prefix(
         ) -> Result<(), crate::apps::AppsServiceError> {
             let (host_meta_cache, mut host_meta_crawler) = crate::host_meta_crawling();
             let client =
                 set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
-            host_meta_crawler.fake_empty_host_meta_info(AppName::master(), "service-a".to_string());
+            host_meta_crawler.fake_host_meta_info(
+                AppName::master(),
+                "service-a".to_string(),
+                serde_json::from_value(json!({
+                    "links": [
+                        { "rel": "describedby", "href": "http://service-a/master/service-a/docs" }
+                    ]
+                }))
+                .unwrap(),
+            );
 
-            let get = client
-                .get("/")
-                .header(rocket::http::Header::new("x-forwarded-host", "prevant.com"))
+            let response = client
+                .get("/api/apps/master/services-by-relation?rel=describedby")
+                .header(rocket::http::Header::new("host", "prevant.com"))
+                .header(rocket::http::Header::new("x-forwarded-prefix", "/prevant/"))
                 .header(ContentType::JSON)
-                .dispatch();
+                .dispatch()
+                .await;
 
-            let response = get.await;
+            assert_eq!(response.status(), Status::Ok);
+            let body_str = response.into_string().await.expect("valid response body");
+            let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
+            assert_eq!(
+                value_in_json,
+                json!([{
+                    "serviceName": "service-a",
+                    "href": "http://prevant.com/prevant/master/service-a/docs"
+                }])
+            );
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn force_stop_with_unsupported_runtime() -> Result<(), crate::apps::AppsServiceError>
+        {
+            let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .post("/api/apps/master/services/service-a/force-stop")
+                .header(ContentType::JSON)
+                .header(Header::new("Authorization", "Bearer admin-key"))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotImplemented);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn force_stop_without_admin_api_key() -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .post("/api/apps/master/services/service-a/force-stop")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Unauthorized);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn force_stop_with_wrong_admin_api_key() -> Result<(), crate::apps::AppsServiceError>
+        {
+            let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .post("/api/apps/master/services/service-a/force-stop")
+                .header(ContentType::JSON)
+                .header(Header::new("Authorization", "Bearer other-key"))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Unauthorized);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn force_stop_without_configured_admin_api_key(
+        ) -> Result<(), crate::apps::AppsServiceError> {
+            let infrastructure = Box::new(Dummy::new());
+            let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+            let rocket = rocket::build()
+                .manage(apps)
+                .manage(config_from_str!(""))
+                .mount("/api/apps", crate::apps::apps_routes());
+            let client = Client::tracked(rocket).await.expect("valid rocket");
+
+            let response = client
+                .post("/api/apps/master/services/service-a/force-stop")
+                .header(ContentType::JSON)
+                .header(Header::new("Authorization", "Bearer admin-key"))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Forbidden);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn stopped_services() -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .get("/api/apps/master/stopped-services")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
+            let body_str = response.into_string().await.expect("valid response body");
+            let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
+            assert_eq!(value_in_json, json!([]));
+
+            let response = client
+                .get("/api/apps/other/stopped-services")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotFound);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn promote_and_rollback_without_canary() -> Result<(), crate::apps::AppsServiceError>
+        {
+            let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            for action in ["promote-canary", "rollback-canary"] {
+                let response = client
+                    .post(format!("/api/apps/master/services/service-a/{action}"))
+                    .dispatch()
+                    .await;
+                assert_eq!(response.status(), Status::NotFound);
+            }
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn port_forward() -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .get("/api/apps/master/services/service-a/port-forward?port=5432")
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::BadRequest);
+
+            // The dummy infrastructure cannot open TCP connections to its services
+            let response = client
+                .get("/api/apps/master/services/service-a/port-forward?port=5432")
+                .header(Header::new("Upgrade", "websocket"))
+                .header(Header::new("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotFound);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn prune_stopped_containers() -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
 
+            let response = client
+                .delete("/api/apps/master/stopped-containers")
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
             let body_str = response.into_string().await.expect("valid response body");
             let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
-            assert_json_include!(actual: value_in_json, expected: json!({
-             "master": [
+            assert_eq!(value_in_json, json!({ "removedContainers": 0 }));
+
+            let response = client
+                .delete("/api/apps/other/stopped-containers")
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotFound);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn deployments() -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .get("/api/apps/master/deployments")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
+            let body_str = response.into_string().await.expect("valid response body");
+            let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
+            assert_eq!(value_in_json, json!([]));
+
+            let response = client
+                .get("/api/apps/other/deployments")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotFound);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn merged_host_meta() -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, mut host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .get("/api/apps/master/.well-known/host-meta.json")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotFound);
+
+            host_meta_crawler.fake_host_meta_info(
+                AppName::master(),
+                "service-a".to_string(),
+                serde_json::from_value(json!({
+                    "links": [
+                        { "rel": "describedby", "href": "http://service-a/master/service-a/docs" }
+                    ]
+                }))
+                .unwrap(),
+            );
+
+            let response = client
+                .get("/api/apps/master/.well-known/host-meta.json")
+                .header(rocket::http::Header::new("host", "prevant.com"))
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::Ok);
+            let body_str = response.into_string().await.expect("valid response body");
+            let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
+            assert_eq!(
+                value_in_json,
+                json!({
+                    "links": [
+                        { "rel": "describedby", "href": "http://prevant.com/master/service-a/docs" }
+                    ]
+                })
+            );
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn service_host_meta() -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, mut host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .get("/api/apps/master/services/service-a/host-meta")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NoContent);
+            assert_eq!(
+                response.headers().get_one("Cache-Control"),
+                Some("max-age=5")
+            );
+
+            host_meta_crawler.fake_host_meta_info(
+                AppName::master(),
+                "service-a".to_string(),
+                serde_json::from_value(json!({
+                    "links": [
+                        { "rel": "describedby", "href": "http://service-a/master/service-a/docs" }
+                    ]
+                }))
+                .unwrap(),
+            );
+
+            let response = client
+                .get("/api/apps/master/services/service-a/host-meta")
+                .header(rocket::http::Header::new("host", "prevant.com"))
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(
+                response.headers().get_one("Cache-Control"),
+                Some("max-age=5")
+            );
+            let body_str = response.into_string().await.expect("valid response body");
+            let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
+            assert_eq!(
+                value_in_json,
+                json!({
+                    "links": [
+                        { "rel": "describedby", "href": "http://prevant.com/master/service-a/docs" }
+                    ]
+                })
+            );
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn refresh_host_meta() -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .post("/api/apps/master/refresh-host-meta")
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Accepted);
+
+            let response = client
+                .post("/api/apps/unknown/refresh-host-meta")
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotFound);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn service_readiness() -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, mut host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .get("/api/apps/master/services/service-a/ready")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::ServiceUnavailable);
+            let body_str = response.into_string().await.expect("valid response body");
+            assert_eq!(
+                serde_json::from_str::<Value>(&body_str).unwrap(),
+                json!({ "ready": false, "reason": "host_meta_unavailable" })
+            );
+
+            host_meta_crawler.fake_empty_host_meta_info(AppName::master(), "service-a".to_string());
+
+            let response = client
+                .get("/api/apps/master/services/service-a/ready")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
+            let body_str = response.into_string().await.expect("valid response body");
+            assert_eq!(
+                serde_json::from_str::<Value>(&body_str).unwrap(),
+                json!({ "ready": true })
+            );
+
+            let response = client
+                .get("/api/apps/master/services/service-b/ready")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotFound);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn service_host_meta_of_unknown_service() -> Result<(), crate::apps::AppsServiceError>
+        {
+            let (host_meta_cache, _host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+
+            let response = client
+                .get("/api/apps/master/services/service-b/host-meta")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotFound);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn host_header_response_with_xforwardedproto_and_other_default_values(
+        ) -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, mut host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+            host_meta_crawler.fake_empty_host_meta_info(AppName::master(), "service-a".to_string());
+
+            let get = client
+                .get("/")
+                .header(rocket::http::Header::new("x-forwarded-proto", "https"))
+                .header(rocket::http::Header::new("host", "localhost"))
+                .header(ContentType::JSON)
+                .dispatch();
+
+            let response = get.await;
+
+            let body_str = response.into_string().await.expect("valid response body");
+            let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
+            assert_json_include!(actual: value_in_json, expected: json!([{
+                "appName": "master",
+                "services": [
+                    {
+                     "url":"https://localhost/master/service-a/"
+                    }
+                ]
+            }]));
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn host_header_response_with_xforwardedhost_and_other_default_values(
+        ) -> Result<(), crate::apps::AppsServiceError> {
+            let (host_meta_cache, mut host_meta_crawler) = crate::host_meta_crawling();
+            let client =
+                set_up_rocket_with_dummy_infrastructure_and_a_running_app(host_meta_cache).await?;
+            host_meta_crawler.fake_empty_host_meta_info(AppName::master(), "service-a".to_string());
+
+            let get = client
+                .get("/")
+                .header(rocket::http::Header::new("x-forwarded-host", "prevant.com"))
+                .header(ContentType::JSON)
+                .dispatch();
+
+            let response = get.await;
+
+            let body_str = response.into_string().await.expect("valid response body");
+            let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
+            assert_json_include!(actual: value_in_json, expected: json!([{
+                "appName": "master",
+                "services": [
                     {
                      "url":"http://prevant.com/master/service-a/"
                     }
                 ]
-            }));
+            }]));
 
             Ok(())
         }
@@ -595,13 +2038,14 @@ mod tests {
 
             let body_str = response.into_string().await.expect("valid response body");
             let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
-            assert_json_include!(actual: value_in_json, expected: json!({
-             "master": [
+            assert_json_include!(actual: value_in_json, expected: json!([{
+                "appName": "master",
+                "services": [
                     {
                      "url":"http://localhost:8433/master/service-a/"
                     }
                 ]
-            }));
+            }]));
 
             Ok(())
         }
@@ -623,13 +2067,14 @@ mod tests {
 
             let body_str = response.into_string().await.expect("valid response body");
             let value_in_json: Value = serde_json::from_str(&body_str).unwrap();
-            assert_json_include!(actual: value_in_json, expected: json!({
-             "master": [
+            assert_json_include!(actual: value_in_json, expected: json!([{
+                "appName": "master",
+                "services": [
                     {
                      "url":"http://localhost/master/service-a/"
                     }
                 ]
-            }));
+            }]));
 
             Ok(())
         }
@@ -688,38 +2133,76 @@ mod tests {
             let response = get.dispatch().await;
             assert_eq!(response.status(), Status::BadRequest);
         }
-    }
-
-    mod http_api_error {
-        use super::super::*;
-        use crate::{
-            apps::{AppsError, AppsService},
-            infrastructure::Dummy,
-            registry::RegistryError,
-        };
-        use assert_json_diff::assert_json_eq;
-        use rocket::{http::ContentType, local::asynchronous::Client};
+    }
+
+    mod http_api_error {
+        use super::super::*;
+        use crate::{
+            apps::{AppsError, AppsService},
+            infrastructure::Dummy,
+            registry::RegistryError,
+        };
+        use assert_json_diff::assert_json_eq;
+        use rocket::{http::ContentType, local::asynchronous::Client};
+
+        #[tokio::test]
+        async fn invalid_service_payload() {
+            let infrastructure = Box::new(Dummy::new());
+            let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+
+            let rocket = rocket::build()
+                .manage(apps)
+                .mount("/", routes![crate::apps::routes::create_app]);
+
+            let client = Client::tracked(rocket).await.expect("valid rocket");
+            let response = client
+                .post("/master")
+                .body(
+                    serde_json::json!([{
+                        "serviceName": "db",
+                        "image": "private-registry.example.com/_/postgres"
+                    }])
+                    .to_string(),
+                )
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::BadRequest);
+
+            let body = response.into_string().await.unwrap();
+            assert_json_eq!(
+                serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+                serde_json::json!({
+                    "type": "https://httpstatuses.com/400",
+                    "status": 400,
+                    "title": "Bad Request",
+                    "detail": "Invalid image: private-registry.example.com/_/postgres at line 1 column 51"
+                })
+            );
+        }
 
         #[tokio::test]
-        async fn invalid_service_payload() {
+        async fn unsupported_compose_fields() {
             let infrastructure = Box::new(Dummy::new());
             let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
 
             let rocket = rocket::build()
                 .manage(apps)
-                .mount("/", routes![crate::apps::routes::create_app]);
+                .mount("/", routes![crate::apps::routes::create_app_from_compose]);
 
             let client = Client::tracked(rocket).await.expect("valid rocket");
             let response = client
-                .post("/master")
+                .post("/master/deploy/compose")
                 .body(
-                    serde_json::json!([{
-                        "serviceName": "db",
-                        "image": "private-registry.example.com/_/postgres"
-                    }])
-                    .to_string(),
+                    r#"
+                    services:
+                      db:
+                        image: postgres
+                        healthcheck:
+                          test: pg_isready
+                    "#,
                 )
-                .header(ContentType::JSON)
                 .dispatch()
                 .await;
 
@@ -732,11 +2215,53 @@ mod tests {
                     "type": "https://httpstatuses.com/400",
                     "status": 400,
                     "title": "Bad Request",
-                    "detail": "Invalid image: private-registry.example.com/_/postgres at line 1 column 51"
+                    "detail": "The Compose file contains unsupported fields: services.db.healthcheck"
                 })
             );
         }
 
+        #[tokio::test]
+        async fn k8s_deployment_with_warnings() {
+            let infrastructure = Box::new(Dummy::new());
+            let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+
+            let rocket = rocket::build().manage(apps).mount(
+                "/",
+                routes![crate::apps::routes::create_app_from_k8s_deployment],
+            );
+
+            let client = Client::tracked(rocket).await.expect("valid rocket");
+            let response = client
+                .post("/master/deploy/k8s-deployment")
+                .body(
+                    r#"
+                    apiVersion: apps/v1
+                    kind: Deployment
+                    metadata:
+                      name: db
+                    spec:
+                      selector: {}
+                      template:
+                        spec:
+                          containers:
+                            - name: db
+                              image: sha256:7bdc25d1694ef984782a16f6f1710c1c6bc83ba7a131b515baf532bea021d011
+                              ports:
+                                - containerPort: 5432
+                    "#,
+                )
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(
+                response.headers().get("Warning").collect::<Vec<_>>(),
+                vec![
+                    r#"299 - "The field spec.template.spec.containers[0].ports is not supported and has been ignored.""#
+                ]
+            );
+        }
+
         #[tokio::test]
         async fn image_registry_authentication_error() {
             #[get("/")]
@@ -828,5 +2353,389 @@ mod tests {
                 })
             );
         }
+
+        #[tokio::test]
+        async fn docker_unauthorized_registry_error() {
+            #[get("/")]
+            fn unauthorized_registry() -> HttpResult<&'static str> {
+                Err(AppsError::from(
+                    crate::infrastructure::DockerInfrastructureError::UnauthorizedRegistry {
+                        image: String::from("docker.io/library/postgres:latest"),
+                        allowed: vec![String::from("registry.example.com")],
+                    },
+                )
+                .into())
+            }
+            let rocket = rocket::build().mount("/", routes![unauthorized_registry]);
+
+            let client = Client::tracked(rocket).await.expect("valid rocket");
+            let response = client.get("/").dispatch().await;
+
+            assert_eq!(response.status(), Status::Forbidden);
+        }
+    }
+    mod parse_create_app_options {
+        use crate::apps::routes::*;
+        use rocket::form::Form;
+
+        #[test]
+        fn without_strategy() {
+            let options = Form::<CreateAppOptions>::parse("replicateFrom=master").unwrap();
+
+            assert_eq!(options.rollout_strategy(), RolloutStrategy::Recreate);
+            assert!(!options.skip_companions());
+        }
+
+        #[test]
+        fn with_skipped_companions() {
+            let options = Form::<CreateAppOptions>::parse("skipCompanions=true").unwrap();
+
+            assert!(options.skip_companions());
+        }
+
+        #[test]
+        fn with_blue_green_strategy() {
+            let options =
+                Form::<CreateAppOptions>::parse("strategy=blue-green&healthCheckTimeoutSecs=60")
+                    .unwrap();
+
+            assert_eq!(
+                options.rollout_strategy(),
+                RolloutStrategy::BlueGreen {
+                    health_check_timeout: Duration::from_secs(60)
+                }
+            );
+        }
+
+        #[test]
+        fn with_canary_strategy() {
+            let options = Form::<CreateAppOptions>::parse(
+                "strategy=canary&canaryWeightPercent=20&promoteAfterSecs=600",
+            )
+            .unwrap();
+
+            assert_eq!(
+                options.rollout_strategy(),
+                RolloutStrategy::Canary {
+                    weight_percent: 20,
+                    promote_after: Some(Duration::from_secs(600))
+                }
+            );
+        }
+
+        #[test]
+        fn with_invalid_canary_weight() {
+            let options =
+                Form::<CreateAppOptions>::parse("strategy=canary&canaryWeightPercent=100");
+
+            assert!(options.is_err());
+        }
+    }
+    mod bulk_operations {
+        use super::super::*;
+        use crate::{
+            apps::AppsService,
+            infrastructure::{Dummy, InfrastructureCall},
+        };
+        use rocket::local::asynchronous::Client;
+        use std::collections::HashSet;
+
+        #[tokio::test]
+        async fn delete_apps_with_partial_failure() -> Result<(), crate::apps::AppsServiceError> {
+            let infrastructure = Box::new(Dummy::new());
+            let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+            apps.create_or_update(
+                &AppName::master(),
+                &AppStatusChangeId::new(),
+                None,
+                &vec![crate::sc!("service-a")],
+            )
+            .await?;
+
+            let rocket = rocket::build()
+                .manage(apps)
+                .mount("/", routes![crate::apps::routes::delete_apps]);
+
+            let client = Client::tracked(rocket).await.expect("valid rocket");
+            let response = client
+                .post("/bulk/delete")
+                .header(ContentType::JSON)
+                .body(r#"{ "appNames": [ "master", "unknown" ] }"#)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::MultiStatus);
+            let body = response.into_json::<serde_json::Value>().await.unwrap();
+            assert_eq!(body["succeeded"], serde_json::json!(["master"]));
+            assert_eq!(body["failed"][0]["name"], serde_json::json!("unknown"));
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn delete_apps_stops_services_of_each_app(
+        ) -> Result<(), crate::apps::AppsServiceError> {
+            let infrastructure = Box::new(Dummy::new());
+            let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+            for app_name in ["master", "other"] {
+                apps.create_or_update(
+                    &AppName::from_str(app_name).unwrap(),
+                    &AppStatusChangeId::new(),
+                    None,
+                    &vec![crate::sc!("service-a")],
+                )
+                .await?;
+            }
+
+            let rocket = rocket::build()
+                .manage(apps.clone())
+                .mount("/", routes![crate::apps::routes::delete_apps]);
+
+            let client = Client::tracked(rocket).await.expect("valid rocket");
+            let response = client
+                .post("/bulk/delete")
+                .header(ContentType::JSON)
+                .body(r#"{ "appNames": [ "master", "other" ] }"#)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
+
+            let stopped_apps = apps
+                .infrastructure
+                .as_any()
+                .downcast_ref::<Dummy>()
+                .unwrap()
+                .calls()
+                .into_iter()
+                .filter_map(|call| match call {
+                    InfrastructureCall::StopServices { app_name } => Some(app_name.to_string()),
+                    _ => None,
+                })
+                .collect::<HashSet<_>>();
+            assert_eq!(
+                stopped_apps,
+                HashSet::from([String::from("master"), String::from("other")])
+            );
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn delete_apps_with_invalid_app_name() {
+            let infrastructure = Box::new(Dummy::new());
+            let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+
+            let rocket = rocket::build()
+                .manage(apps)
+                .mount("/", routes![crate::apps::routes::delete_apps]);
+
+            let client = Client::tracked(rocket).await.expect("valid rocket");
+            let response = client
+                .post("/bulk/delete")
+                .header(ContentType::JSON)
+                .body(r#"{ "appNames": [ "master", "in valid" ] }"#)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::BadRequest);
+        }
+    }
+
+    mod search_services {
+        use super::super::*;
+        use crate::{apps::AppsService, infrastructure::Dummy};
+        use rocket::local::asynchronous::Client;
+
+        async fn client() -> Result<Client, crate::apps::AppsServiceError> {
+            let infrastructure = Box::new(Dummy::new());
+            let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+            for app_name in ["master", "feature-login", "feature-search"] {
+                apps.create_or_update(
+                    &AppName::from_str(app_name).unwrap(),
+                    &AppStatusChangeId::new(),
+                    None,
+                    &vec![crate::sc!("service-a"), crate::sc!("service-b")],
+                )
+                .await?;
+            }
+
+            let rocket = rocket::build()
+                .manage(apps)
+                .mount("/", routes![crate::apps::routes::search_services]);
+
+            Ok(Client::tracked(rocket).await.expect("valid rocket"))
+        }
+
+        #[tokio::test]
+        async fn search_by_app_name_prefix_and_service_name(
+        ) -> Result<(), crate::apps::AppsServiceError> {
+            let client = client().await?;
+
+            let response = client
+                .get("/services?app_name_prefix=feature-&service_name=service-a")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(response.headers().get_one("X-Total-Count"), Some("2"));
+            let body = response.into_json::<serde_json::Value>().await.unwrap();
+            let found = body
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|result| (result["appName"].clone(), result["serviceName"].clone()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                found,
+                vec![
+                    (
+                        serde_json::json!("feature-login"),
+                        serde_json::json!("service-a")
+                    ),
+                    (
+                        serde_json::json!("feature-search"),
+                        serde_json::json!("service-a")
+                    ),
+                ]
+            );
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn search_with_limit() -> Result<(), crate::apps::AppsServiceError> {
+            let client = client().await?;
+
+            let response = client
+                .get("/services?offset=1&limit=2")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(response.headers().get_one("X-Total-Count"), Some("6"));
+            let body = response.into_json::<serde_json::Value>().await.unwrap();
+            assert_eq!(body.as_array().unwrap().len(), 2);
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn search_with_invalid_container_type() -> Result<(), crate::apps::AppsServiceError> {
+            let client = client().await?;
+
+            let response = client
+                .get("/services?container_type=unknown")
+                .header(ContentType::JSON)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::BadRequest);
+
+            Ok(())
+        }
+    }
+
+    mod diff_app {
+        use super::super::*;
+        use crate::{apps::AppsService, infrastructure::Dummy};
+        use rocket::local::asynchronous::Client;
+
+        #[tokio::test]
+        async fn diff_against_running_services() -> Result<(), crate::apps::AppsServiceError> {
+            let infrastructure = Box::new(Dummy::new());
+            let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+            apps.create_or_update(
+                &AppName::master(),
+                &AppStatusChangeId::new(),
+                None,
+                &vec![
+                    crate::sc!("db", "mariadb:10.3"),
+                    crate::sc!("api", "nginx:1.27"),
+                ],
+            )
+            .await?;
+
+            let rocket = rocket::build()
+                .manage(apps)
+                .mount("/", routes![crate::apps::routes::diff_app]);
+
+            let client = Client::tracked(rocket).await.expect("valid rocket");
+            let response = client
+                .post("/master/diff")
+                .header(ContentType::JSON)
+                .body(
+                    r#"[
+                        { "serviceName": "db", "image": "mariadb:10.4" },
+                        { "serviceName": "api", "image": "nginx:1.27" },
+                        { "serviceName": "cache", "image": "redis" }
+                    ]"#,
+                )
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::Ok);
+            let body = response.into_json::<serde_json::Value>().await.unwrap();
+            assert_eq!(
+                body,
+                serde_json::json!({
+                    "to_add": ["cache"],
+                    "to_update": [{
+                        "service": "db",
+                        "old_image": "docker.io/library/mariadb:10.3",
+                        "new_image": "docker.io/library/mariadb:10.4"
+                    }],
+                    "unchanged": ["api"]
+                })
+            );
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn diff_with_invalid_payload() {
+            let infrastructure = Box::new(Dummy::new());
+            let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+
+            let rocket = rocket::build()
+                .manage(apps)
+                .mount("/", routes![crate::apps::routes::diff_app]);
+
+            let client = Client::tracked(rocket).await.expect("valid rocket");
+            let response = client
+                .post("/master/diff")
+                .header(ContentType::JSON)
+                .body(r#"{ "serviceName": "db" }"#)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::BadRequest);
+        }
+    }
+
+    mod inspect_service {
+        use super::super::*;
+        use crate::{apps::AppsService, infrastructure::Dummy};
+        use rocket::{http::Accept, local::asynchronous::Client};
+
+        #[tokio::test]
+        async fn not_found_for_unknown_service() {
+            let infrastructure = Box::new(Dummy::new());
+            let apps = Arc::new(AppsService::new(Default::default(), infrastructure).unwrap());
+
+            let rocket = rocket::build()
+                .manage(apps)
+                .mount("/", routes![crate::apps::routes::inspect_service]);
+
+            let client = Client::tracked(rocket).await.expect("valid rocket");
+            let response = client
+                .get("/master/services/db/inspect")
+                .header(Accept::JSON)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::NotFound);
+        }
     }
 }
@@ -0,0 +1,153 @@
+use crate::{
+    apps::Apps,
+    http_result::HttpResult,
+    models::{AppName, AppNameError},
+};
+use futures::{SinkExt, StreamExt};
+use http_api_problem::{HttpApiProblem, StatusCode};
+use rocket::{
+    data::{IoHandler, IoStream},
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::{Responder, Response},
+    Request, State,
+};
+use std::{io, pin::Pin, sync::Arc};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{
+    handshake::derive_accept_key, protocol::Role, Error as WebSocketError, Message,
+};
+use tokio_tungstenite::WebSocketStream;
+
+/// Tunnels a WebSocket connection to a TCP port of the service's container, bypassing the reverse
+/// proxy, e.g. to connect a database client similar to `kubectl port-forward`. The payload of the
+/// WebSocket messages is written to the TCP connection and the data received from the TCP
+/// connection is sent as binary messages. The infrastructure only opens the ports that the service
+/// exposes.
+#[get("/<app_name>/services/<service_name>/port-forward?<port>")]
+pub(super) async fn port_forward(
+    app_name: Result<AppName, AppNameError>,
+    service_name: &str,
+    port: u16,
+    websocket_key: Option<WebSocketKey>,
+    apps: &State<Arc<Apps>>,
+) -> HttpResult<TcpTunnel> {
+    let app_name = app_name?;
+
+    let Some(websocket_key) = websocket_key else {
+        return Err(HttpApiProblem::with_title_and_type(StatusCode::BAD_REQUEST)
+            .detail("The port forwarding requires a WebSocket upgrade request.")
+            .into());
+    };
+
+    let Some(stream) = apps
+        .open_port_forward(&app_name, service_name, port)
+        .await?
+    else {
+        return Err(HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+            .detail(format!(
+                "Cannot find service {service_name} of app {app_name}."
+            ))
+            .into());
+    };
+
+    Ok(TcpTunnel {
+        accept_key: derive_accept_key(websocket_key.0.as_bytes()),
+        stream,
+    })
+}
+
+/// The `Sec-WebSocket-Key` of a request that asks to upgrade the connection to a WebSocket.
+pub(super) struct WebSocketKey(String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for WebSocketKey {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        let is_websocket_upgrade = headers
+            .get("Upgrade")
+            .any(|protocol| protocol.eq_ignore_ascii_case("websocket"));
+
+        match headers.get_one("Sec-WebSocket-Key") {
+            Some(key) if is_websocket_upgrade => Outcome::Success(WebSocketKey(key.to_string())),
+            _ => Outcome::Forward(Status::BadRequest),
+        }
+    }
+}
+
+pub(super) struct TcpTunnel {
+    accept_key: String,
+    stream: TcpStream,
+}
+
+impl<'r> Responder<'r, 'static> for TcpTunnel {
+    fn respond_to(self, _request: &'r Request<'_>) -> rocket::response::Result<'static> {
+        // Rocket sets the status 101 and the headers `Connection` and `Upgrade` itself
+        Response::build()
+            .raw_header("Sec-WebSocket-Accept", self.accept_key)
+            .upgrade(
+                "websocket",
+                TcpTunnelHandler {
+                    stream: self.stream,
+                },
+            )
+            .ok()
+    }
+}
+
+struct TcpTunnelHandler {
+    stream: TcpStream,
+}
+
+#[rocket::async_trait]
+impl IoHandler for TcpTunnelHandler {
+    async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()> {
+        let TcpTunnelHandler { stream } = *Pin::into_inner(self);
+        let (mut tcp_reader, mut tcp_writer) = stream.into_split();
+        let (mut websocket_sink, mut websocket_stream) =
+            WebSocketStream::from_raw_socket(io, Role::Server, None)
+                .await
+                .split();
+
+        let to_container = async {
+            while let Some(message) = websocket_stream.next().await {
+                match message.map_err(to_io_error)? {
+                    Message::Binary(data) => tcp_writer.write_all(&data).await?,
+                    Message::Text(text) => tcp_writer.write_all(text.as_bytes()).await?,
+                    Message::Close(_) => break,
+                    // Pings are answered by tungstenite
+                    Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => {}
+                }
+            }
+            tcp_writer.shutdown().await
+        };
+
+        let to_client = async {
+            let mut buffer = vec![0; 8 * 1024];
+            loop {
+                let read = tcp_reader.read(&mut buffer).await?;
+                if read == 0 {
+                    break;
+                }
+                websocket_sink
+                    .send(Message::Binary(buffer[..read].to_vec()))
+                    .await
+                    .map_err(to_io_error)?;
+            }
+            websocket_sink.close().await.map_err(to_io_error)
+        };
+
+        // The tunnel is closed as soon as one side closes its connection
+        tokio::select! {
+            result = to_container => result,
+            result = to_client => result,
+        }
+    }
+}
+
+fn to_io_error(err: WebSocketError) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err)
+}
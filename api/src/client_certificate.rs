@@ -0,0 +1,202 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use rocket::http::Status;
+use rocket::mtls::Certificate;
+use rocket::request::{FromRequest, Outcome, Request};
+use std::fmt::Display;
+use std::future::Future;
+
+tokio::task_local! {
+    static CURRENT_CLIENT_CERTIFICATE: Option<ClientCertificate>;
+}
+
+/// The certificate that a client presented to authenticate itself, see `tls.ca_cert_path` of
+/// [`TlsConfig`](crate::config::TlsConfig). The certificate has already been verified against the
+/// CA when the TLS connection has been established, thus, the request guard only extracts the
+/// common name of the certificate's subject. Requests without certificate are forwarded and
+/// certificates without common name are rejected with `401 Unauthorized`, use
+/// `Option<ClientCertificate>` if the certificate is optional.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientCertificate {
+    common_name: String,
+}
+
+impl ClientCertificate {
+    pub fn common_name(&self) -> &str {
+        &self.common_name
+    }
+
+    /// Returns the client certificate of the request that is currently processed by this task.
+    pub fn current() -> Option<ClientCertificate> {
+        CURRENT_CLIENT_CERTIFICATE
+            .try_with(Option::clone)
+            .ok()
+            .flatten()
+    }
+
+    /// Runs the future with the client certificate as the current one, e.g. so that the
+    /// deployments of the future can be attributed to the client.
+    pub fn scope<F>(client_certificate: Option<Self>, future: F) -> impl Future<Output = F::Output>
+    where
+        F: Future,
+    {
+        CURRENT_CLIENT_CERTIFICATE.scope(client_certificate, future)
+    }
+}
+
+impl Display for ClientCertificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.common_name)
+    }
+}
+
+#[derive(Debug)]
+pub enum ClientCertificateError {
+    MissingCommonName,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientCertificate {
+    type Error = ClientCertificateError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let certificate = match request.guard::<Certificate<'r>>().await {
+            Outcome::Success(certificate) => certificate,
+            Outcome::Forward(status) => return Outcome::Forward(status),
+            Outcome::Error((status, _)) => return Outcome::Forward(status),
+        };
+
+        match certificate.subject().common_name() {
+            Some(common_name) => Outcome::Success(ClientCertificate {
+                common_name: common_name.to_string(),
+            }),
+            None => Outcome::Error((
+                Status::Unauthorized,
+                ClientCertificateError::MissingCommonName,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::local::asynchronous::Client;
+
+    /// Self-signed with the subject `O=aixigo, CN=jane.doe`.
+    static CERTIFICATE_WITH_COMMON_NAME: &str = r#"-----BEGIN CERTIFICATE-----
+MIIBnzCCAUWgAwIBAgIUXcOLntuSsZwepRdV00oeZxG4WEkwCgYIKoZIzj0EAwIw
+JDEPMA0GA1UECgwGYWl4aWdvMREwDwYDVQQDDAhqYW5lLmRvZTAgFw0yNjEwMTYx
+MTQxMTBaGA8yMTI2MDkyMjExNDExMFowJDEPMA0GA1UECgwGYWl4aWdvMREwDwYD
+VQQDDAhqYW5lLmRvZTBZMBMGByqGSM49AgEGCCqGSM49AwEHA0IABA0y0vNfdFjk
+Mg8mRN2HbxcbQKKcCAmc1ImiY30W+icRc00yOE+lS74wZfa+kxcOsJrvuiOlZKzm
+K6dBnB+mk36jUzBRMB0GA1UdDgQWBBQxUmJ3bZ587bDiJHKGCA6s3wd3TjAfBgNV
+HSMEGDAWgBQxUmJ3bZ587bDiJHKGCA6s3wd3TjAPBgNVHRMBAf8EBTADAQH/MAoG
+CCqGSM49BAMCA0gAMEUCIEtBooydAc9+1CkA2jsJVVx1L2WYq+HwKhIuCjLQjawH
+AiEAzqEcpCC47z4YayxNv0rTsokoFgYpvvw+KcnCr4YJ3ZY=
+-----END CERTIFICATE-----
+"#;
+
+    /// Self-signed with the subject `O=aixigo`.
+    static CERTIFICATE_WITHOUT_COMMON_NAME: &str = r#"-----BEGIN CERTIFICATE-----
+MIIBeTCCAR+gAwIBAgIULxbDzlmMf+8i6h4tO/i+7dEFroMwCgYIKoZIzj0EAwIw
+ETEPMA0GA1UECgwGYWl4aWdvMCAXDTI2MTAxNjExNDExMFoYDzIxMjYwOTIyMTE0
+MTEwWjARMQ8wDQYDVQQKDAZhaXhpZ28wWTATBgcqhkjOPQIBBggqhkjOPQMBBwNC
+AAQnpp5M+uALLFq46Mtu9bI0ZmkONvURFQSZWvgaEmLAGJDHWKujZBvo+RhCqR8q
+yP0cMgzm9uArYHHa7KDq8NL2o1MwUTAdBgNVHQ4EFgQUzwgw/KeITkhMNyd6Atjd
+9vAsM6wwHwYDVR0jBBgwFoAUzwgw/KeITkhMNyd6Atjd9vAsM6wwDwYDVR0TAQH/
+BAUwAwEB/zAKBggqhkjOPQQDAgNIADBFAiBCTiaqQ2n018zdI3ixJKvYIXH7ccqO
+tGrKi9cerVTKrwIhAMsoq/doWXwGsGYXtaWKDiYZ6/RHjlxTXKE7KUe5Ddjs
+-----END CERTIFICATE-----
+"#;
+
+    #[get("/")]
+    async fn common_name(client_certificate: ClientCertificate) -> String {
+        ClientCertificate::scope(Some(client_certificate), async {
+            ClientCertificate::current().unwrap().to_string()
+        })
+        .await
+    }
+
+    #[get("/optional")]
+    async fn optional_common_name(client_certificate: Option<ClientCertificate>) -> String {
+        client_certificate
+            .map(|client_certificate| client_certificate.common_name().to_string())
+            .unwrap_or_else(|| String::from("anonymous"))
+    }
+
+    async fn client() -> Client {
+        let rocket = rocket::build().mount("/", routes![common_name, optional_common_name]);
+        Client::tracked(rocket).await.expect("valid rocket")
+    }
+
+    #[tokio::test]
+    async fn should_extract_common_name_of_client_certificate() {
+        let client = client().await;
+
+        let response = client
+            .get("/")
+            .identity(CERTIFICATE_WITH_COMMON_NAME.as_bytes())
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().await, Some(String::from("jane.doe")));
+    }
+
+    #[tokio::test]
+    async fn should_reject_client_certificate_without_common_name() {
+        let client = client().await;
+
+        let response = client
+            .get("/")
+            .identity(CERTIFICATE_WITHOUT_COMMON_NAME.as_bytes())
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[tokio::test]
+    async fn should_forward_requests_without_client_certificate() {
+        let client = client().await;
+
+        let response = client.get("/").dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = client.get("/optional").dispatch().await;
+        assert_eq!(
+            response.into_string().await,
+            Some(String::from("anonymous"))
+        );
+    }
+
+    #[test]
+    fn should_not_have_current_client_certificate_outside_of_scope() {
+        assert_eq!(ClientCertificate::current(), None);
+    }
+}
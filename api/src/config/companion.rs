@@ -25,7 +25,7 @@
  */
 use crate::config::AppSelector;
 use crate::models::service::ContainerType;
-use crate::models::{AppName, Environment, Image, ServiceConfig};
+use crate::models::{AppName, Environment, Image, ServiceConfig, ServiceConfigBuilder};
 use handlebars::{Handlebars, RenderError, RenderErrorReason};
 use secstr::SecUtf8;
 use serde_value::Value;
@@ -219,16 +219,21 @@ impl Companion {
 // config and ServiceConfig
 impl From<Companion> for ServiceConfig {
     fn from(companion: Companion) -> ServiceConfig {
-        let mut config =
-            ServiceConfig::new(companion.service_name.clone(), companion.image.clone());
-
-        config.set_env(companion.env.clone().map(|env| {
-            Environment::new(
+        let mut config_builder = ServiceConfigBuilder::new()
+            .service_name(companion.service_name.clone())
+            .image(companion.image.clone())
+            .container_type(companion.companion_type.into());
+        if let Some(env) = &companion.env {
+            config_builder = config_builder.env(Environment::new(
                 env.iter()
                     .map(|variable| variable.clone().with_templated(true))
                     .collect(),
-            )
-        }));
+            ));
+        }
+        let mut config = config_builder
+            .build()
+            .expect("service name and image are mandatory for a companion");
+
         config.set_labels(companion.labels.clone());
 
         if let Some(files) = &companion.files {
@@ -239,8 +244,6 @@ impl From<Companion> for ServiceConfig {
             config.set_routing(routing.clone());
         }
 
-        config.set_container_type(companion.companion_type.into());
-
         config
     }
 }
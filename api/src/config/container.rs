@@ -23,16 +23,189 @@
  * THE SOFTWARE.
  * =========================LICENSE_END==================================
  */
+use crate::models::{Image, ServiceConfig};
 use bytesize::ByteSize;
 use serde::{de, Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::net::Ipv6Addr;
+use std::path::{Component, Path};
+use std::str::FromStr;
+use std::time::Duration;
 
-#[derive(Clone, Default, Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct ContainerConfig {
-    #[serde(deserialize_with = "ContainerConfig::parse_from_memory_string")]
+    #[serde(
+        default,
+        deserialize_with = "ContainerConfig::parse_from_memory_string"
+    )]
     memory_limit: Option<ByteSize>,
+    #[serde(default = "ContainerConfig::default_pull_timeout_secs")]
+    pull_timeout_secs: u64,
+    #[serde(default)]
+    default_pull_policy: PullPolicy,
+    #[serde(default)]
+    default_restart_policy: RestartPolicy,
+    #[serde(default)]
+    allowed_devices: Vec<String>,
+    #[serde(default)]
+    allowed_image_registries: Option<Vec<String>>,
+    #[serde(default)]
+    oci_pull_registries: Vec<String>,
+    #[serde(default)]
+    inheritable_env: Vec<String>,
+    #[serde(default)]
+    global_container_labels: BTreeMap<String, String>,
+    #[serde(default)]
+    max_deployment_duration_secs: Option<u64>,
+    #[serde(default)]
+    default_pid_limit: Option<u64>,
+    #[serde(default)]
+    readiness_timeout_secs: Option<u64>,
+    #[serde(default, deserialize_with = "ContainerConfig::parse_network_mtu")]
+    network_mtu: Option<u16>,
+    #[serde(default)]
+    enable_ipv6: bool,
+    #[serde(default, deserialize_with = "ContainerConfig::parse_ipv6_subnet")]
+    ipv6_subnet: Option<Ipv6Subnet>,
+    #[serde(default)]
+    dns_servers: Vec<String>,
+    #[serde(default)]
+    dns_search_domains: Vec<String>,
+}
+
+impl Default for ContainerConfig {
+    fn default() -> Self {
+        Self {
+            memory_limit: None,
+            pull_timeout_secs: Self::default_pull_timeout_secs(),
+            default_pull_policy: PullPolicy::default(),
+            default_restart_policy: RestartPolicy::default(),
+            allowed_devices: Vec::new(),
+            allowed_image_registries: None,
+            oci_pull_registries: Vec::new(),
+            inheritable_env: Vec::new(),
+            global_container_labels: BTreeMap::new(),
+            max_deployment_duration_secs: None,
+            default_pid_limit: None,
+            readiness_timeout_secs: None,
+            network_mtu: None,
+            enable_ipv6: false,
+            ipv6_subnet: None,
+            dns_servers: Vec::new(),
+            dns_search_domains: Vec::new(),
+        }
+    }
+}
+
+/// Determines when the image of a service will be pulled before its container is started.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+pub enum PullPolicy {
+    Always,
+    IfNotPresent,
+    Never,
+}
+
+impl Default for PullPolicy {
+    fn default() -> Self {
+        Self::Always
+    }
+}
+
+/// Determines when the container runtime restarts the container of a service.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+pub enum RestartPolicy {
+    Always,
+    UnlessStopped,
+    OnFailure,
+    No,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self::Always
+    }
+}
+
+/// An IPv6 subnet in CIDR notation, e.g. `fd00:7072:6576::/48`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Ipv6Subnet {
+    address: Ipv6Addr,
+    prefix_len: u8,
+}
+
+impl Ipv6Subnet {
+    /// Returns the `/64` subnets of this subnet. Docker networks use `/64` subnets because SLAAC
+    /// and many IPv6 tools expect this prefix length.
+    pub fn subnets_64(&self) -> impl Iterator<Item = Ipv6Subnet> {
+        let base = u128::from(self.address);
+        let count = 1_u128 << (64 - self.prefix_len);
+        (0..count).map(move |index| Ipv6Subnet {
+            address: Ipv6Addr::from(base + (index << 64)),
+            prefix_len: 64,
+        })
+    }
+
+    /// Returns `true` if this subnet and the subnet of `address` with `prefix_len` share any
+    /// address, i.e. one of them contains the other. The prefix length of the other subnet is not
+    /// limited to 64 because networks that PREvant did not create may use any subnet.
+    pub fn overlaps(&self, address: Ipv6Addr, prefix_len: u8) -> bool {
+        let prefix_len = self.prefix_len.min(prefix_len);
+        let mask = u128::MAX
+            .checked_shl(128 - u32::from(prefix_len))
+            .unwrap_or(0);
+        u128::from(self.address) & mask == u128::from(address) & mask
+    }
+}
+
+impl Default for Ipv6Subnet {
+    fn default() -> Self {
+        Self {
+            address: Ipv6Addr::new(0xfd00, 0x7072, 0x6576, 0, 0, 0, 0, 0),
+            prefix_len: 48,
+        }
+    }
+}
+
+impl FromStr for Ipv6Subnet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix_len) = s
+            .split_once('/')
+            .ok_or_else(|| format!("The IPv6 subnet {s} must be in CIDR notation"))?;
+        let address = address
+            .parse::<Ipv6Addr>()
+            .map_err(|err| format!("The IPv6 subnet {s} is invalid: {err}"))?;
+        let prefix_len = prefix_len
+            .parse::<u8>()
+            .ok()
+            .filter(|prefix_len| *prefix_len <= 64)
+            .ok_or_else(|| {
+                format!("The prefix length of the IPv6 subnet {s} must be at most 64")
+            })?;
+
+        let mask = u128::MAX
+            .checked_shl(128 - u32::from(prefix_len))
+            .unwrap_or(0);
+        Ok(Self {
+            address: Ipv6Addr::from(u128::from(address) & mask),
+            prefix_len,
+        })
+    }
+}
+
+impl Display for Ipv6Subnet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
 }
 
 impl ContainerConfig {
+    fn default_pull_timeout_secs() -> u64 {
+        300
+    }
+
     fn parse_from_memory_string<'de, D>(deserializer: D) -> Result<Option<ByteSize>, D::Error>
     where
         D: Deserializer<'de>,
@@ -55,7 +228,498 @@ impl ContainerConfig {
         }
     }
 
+    fn parse_network_mtu<'de, D>(deserializer: D) -> Result<Option<u16>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mtu = u16::deserialize(deserializer)?;
+        if !(576..=9000).contains(&mtu) {
+            return Err(de::Error::custom(format!(
+                "The network MTU {mtu} must be between 576 and 9000"
+            )));
+        }
+        Ok(Some(mtu))
+    }
+
+    fn parse_ipv6_subnet<'de, D>(deserializer: D) -> Result<Option<Ipv6Subnet>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let subnet = String::deserialize(deserializer)?;
+        Ipv6Subnet::from_str(&subnet)
+            .map(Some)
+            .map_err(de::Error::custom)
+    }
+
     pub fn memory_limit(&self) -> Option<ByteSize> {
         self.memory_limit
     }
+
+    /// Returns the memory limit that applies to the given service. The service might request a
+    /// lower limit than the configured one but it cannot exceed it.
+    pub fn memory_limit_of(&self, service_config: &ServiceConfig) -> Option<ByteSize> {
+        match (self.memory_limit, service_config.memory_limit()) {
+            (Some(limit), Some(requested)) => Some(std::cmp::min(limit, requested)),
+            (limit, requested) => limit.or(requested),
+        }
+    }
+
+    /// Returns the number of seconds after which pulling the image of the given service will be
+    /// aborted.
+    pub fn pull_timeout_secs_of(&self, service_config: &ServiceConfig) -> u64 {
+        service_config
+            .pull_timeout_secs()
+            .unwrap_or(self.pull_timeout_secs)
+    }
+
+    /// Returns the pull policy of the given service which falls back to the configured default.
+    pub fn pull_policy_of(&self, service_config: &ServiceConfig) -> PullPolicy {
+        service_config
+            .pull_policy()
+            .unwrap_or(self.default_pull_policy)
+    }
+
+    /// Returns the maximum number of processes of the given service's container which falls back
+    /// to the configured default.
+    pub fn pid_limit_of(&self, service_config: &ServiceConfig) -> Option<u64> {
+        service_config.pid_limit().or(self.default_pid_limit)
+    }
+
+    pub fn default_pid_limit(&self) -> Option<u64> {
+        self.default_pid_limit
+    }
+
+    /// Returns how long a deployment may take before it is aborted. Without limit, deployments
+    /// might hang forever, e.g. if pulling an image stalls.
+    pub fn max_deployment_duration(&self) -> Option<Duration> {
+        self.max_deployment_duration_secs.map(Duration::from_secs)
+    }
+
+    /// Returns the MTU of the networks that will be created for the apps.
+    pub fn network_mtu(&self) -> Option<u16> {
+        self.network_mtu
+    }
+
+    /// Returns the subnet from which the networks of the apps get their IPv6 subnets or `None` if
+    /// the networks are IPv4 only.
+    pub fn ipv6_subnet(&self) -> Option<Ipv6Subnet> {
+        if !self.enable_ipv6 {
+            return None;
+        }
+        Some(self.ipv6_subnet.unwrap_or_default())
+    }
+
+    /// Returns the DNS servers that the container of the service uses instead of Docker's
+    /// embedded DNS server. The service can override the configured servers.
+    pub fn dns_servers_of<'a>(&'a self, service_config: &'a ServiceConfig) -> &'a [String] {
+        service_config.dns_override().unwrap_or(&self.dns_servers)
+    }
+
+    pub fn dns_search_domains(&self) -> &[String] {
+        &self.dns_search_domains
+    }
+
+    /// Returns how long a deployment waits for the `readinessUrl` of a service to respond with
+    /// `200 OK`.
+    pub fn readiness_timeout(&self) -> Duration {
+        Duration::from_secs(self.readiness_timeout_secs.unwrap_or(120))
+    }
+
+    pub fn restart_policy(&self) -> RestartPolicy {
+        self.default_restart_policy
+    }
+
+    /// Returns the labels that are added to every container. Labels of the services and the labels
+    /// that PREvant uses to manage the containers take precedence.
+    pub fn global_container_labels(&self) -> &BTreeMap<String, String> {
+        &self.global_container_labels
+    }
+
+    pub fn allowed_image_registries(&self) -> &[String] {
+        self.allowed_image_registries.as_deref().unwrap_or_default()
+    }
+
+    /// Checks if the image may be deployed. An image is permitted if it starts with one of the
+    /// configured registry prefixes, e.g. `ghcr.io/myorg` permits `ghcr.io/myorg/app:latest`.
+    /// Without configured registries all images are permitted. Images that are only referenced by
+    /// their digest are rejected in that case because their registry cannot be determined.
+    pub fn is_image_allowed(&self, image: &Image) -> bool {
+        let allowed_registries = self.allowed_image_registries();
+        if allowed_registries.is_empty() {
+            return true;
+        }
+
+        match image {
+            Image::Digest { .. } => false,
+            Image::Named { .. } => Self::matches_any_registry(image, allowed_registries),
+        }
+    }
+
+    /// Checks if the image must be pulled through the OCI Distribution API instead of the Docker
+    /// daemon, e.g. because the registry only serves OCI image manifests. The registries are
+    /// matched by prefix like [`ContainerConfig::is_image_allowed`] does.
+    pub fn is_pulled_via_oci(&self, image: &Image) -> bool {
+        match image {
+            Image::Digest { .. } => false,
+            Image::Named { .. } => Self::matches_any_registry(image, &self.oci_pull_registries),
+        }
+    }
+
+    fn matches_any_registry(image: &Image, registries: &[String]) -> bool {
+        let image = image.to_string();
+        registries.iter().any(|registry| {
+            let registry = registry.trim_end_matches('/');
+            image
+                .strip_prefix(registry)
+                .map_or(false, |image_path| image_path.starts_with('/'))
+        })
+    }
+
+    /// Returns `true` if services may copy the environment variable `name` from the environment of
+    /// PREvant via `envInherit`.
+    pub fn is_env_inheritable(&self, name: &str) -> bool {
+        self.inheritable_env
+            .iter()
+            .any(|inheritable| inheritable == name)
+    }
+
+    /// Checks if the host device may be exposed to containers. A device is permitted if its path
+    /// or one of its parent directories is part of the configured allowlist. The paths are compared
+    /// by their components and paths that refer to a parent directory via `..` are rejected because
+    /// they could escape the allowed directories.
+    pub fn is_device_allowed(&self, host_path: &str) -> bool {
+        let host_path = Path::new(host_path);
+        if !host_path.is_absolute()
+            || host_path
+                .components()
+                .any(|component| component == Component::ParentDir)
+        {
+            return false;
+        }
+
+        self.allowed_devices
+            .iter()
+            .any(|allowed_device| host_path.starts_with(allowed_device))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sc;
+    use std::str::FromStr;
+
+    #[test]
+    fn should_use_configured_memory_limit() {
+        let container_config = ContainerConfig {
+            memory_limit: Some(ByteSize::gb(1)),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            container_config.memory_limit_of(&sc!("db")),
+            Some(ByteSize::gb(1))
+        );
+    }
+
+    #[test]
+    fn should_use_lower_memory_limit_of_service() {
+        let container_config = ContainerConfig {
+            memory_limit: Some(ByteSize::gb(1)),
+            ..Default::default()
+        };
+        let mut service_config = sc!("db");
+        service_config.set_memory_limit(Some(ByteSize::mb(512)));
+
+        assert_eq!(
+            container_config.memory_limit_of(&service_config),
+            Some(ByteSize::mb(512))
+        );
+    }
+
+    #[test]
+    fn should_not_exceed_configured_memory_limit() {
+        let container_config = ContainerConfig {
+            memory_limit: Some(ByteSize::gb(1)),
+            ..Default::default()
+        };
+        let mut service_config = sc!("db");
+        service_config.set_memory_limit(Some(ByteSize::gb(4)));
+
+        assert_eq!(
+            container_config.memory_limit_of(&service_config),
+            Some(ByteSize::gb(1))
+        );
+    }
+
+    #[test]
+    fn should_use_default_pull_timeout() {
+        let container_config = ContainerConfig::default();
+
+        assert_eq!(container_config.pull_timeout_secs_of(&sc!("db")), 300);
+    }
+
+    #[test]
+    fn should_use_pull_timeout_of_service() {
+        let container_config = ContainerConfig::default();
+        let mut service_config = sc!("db");
+        service_config.set_pull_timeout_secs(Some(30));
+
+        assert_eq!(container_config.pull_timeout_secs_of(&service_config), 30);
+    }
+
+    #[test]
+    fn should_parse_pull_and_restart_policies() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            default_pull_policy = 'IfNotPresent'
+            default_restart_policy = 'UnlessStopped'
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            container_config.pull_policy_of(&sc!("db")),
+            PullPolicy::IfNotPresent
+        );
+        assert_eq!(
+            container_config.restart_policy(),
+            RestartPolicy::UnlessStopped
+        );
+    }
+
+    #[test]
+    fn should_parse_readiness_timeout() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            readiness_timeout_secs = 30
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            container_config.readiness_timeout(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            ContainerConfig::default().readiness_timeout(),
+            Duration::from_secs(120)
+        );
+    }
+
+    #[test]
+    fn should_parse_network_mtu() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            network_mtu = 1450
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(container_config.network_mtu(), Some(1450));
+    }
+
+    #[test]
+    fn should_reject_network_mtu_out_of_range() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            network_mtu = 9001
+            "#,
+        );
+
+        assert!(container_config.is_err());
+    }
+
+    #[test]
+    fn should_parse_ipv6_subnet() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            enable_ipv6 = true
+            ipv6_subnet = 'fd12:3456:789a:1::/56'
+            "#,
+        )
+        .unwrap();
+
+        let ipv6_subnet = container_config.ipv6_subnet().unwrap();
+        assert_eq!(ipv6_subnet.to_string(), "fd12:3456:789a::/56");
+        assert_eq!(
+            ipv6_subnet
+                .subnets_64()
+                .take(2)
+                .map(|subnet| subnet.to_string())
+                .collect::<Vec<_>>(),
+            vec!["fd12:3456:789a::/64", "fd12:3456:789a:1::/64"]
+        );
+    }
+
+    #[test]
+    fn should_use_default_ipv6_subnet() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            enable_ipv6 = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(container_config.ipv6_subnet(), Some(Ipv6Subnet::default()));
+        assert_eq!(ContainerConfig::default().ipv6_subnet(), None);
+    }
+
+    #[test]
+    fn should_reject_ipv6_subnet_with_too_long_prefix() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            ipv6_subnet = 'fd00::/80'
+            "#,
+        );
+
+        assert!(container_config.is_err());
+    }
+
+    #[test]
+    fn should_prefer_pull_policy_of_service() {
+        let container_config = ContainerConfig {
+            default_pull_policy: PullPolicy::IfNotPresent,
+            ..Default::default()
+        };
+        let mut service_config = sc!("db");
+        service_config.set_pull_policy(Some(PullPolicy::Always));
+
+        assert_eq!(
+            container_config.pull_policy_of(&service_config),
+            PullPolicy::Always
+        );
+    }
+
+    #[test]
+    fn should_fall_back_to_default_pid_limit() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            default_pid_limit = 200
+            "#,
+        )
+        .unwrap();
+        let mut service_config = sc!("db");
+
+        assert_eq!(container_config.pid_limit_of(&service_config), Some(200));
+
+        service_config.set_pid_limit(Some(500));
+        assert_eq!(container_config.pid_limit_of(&service_config), Some(500));
+
+        assert_eq!(ContainerConfig::default().pid_limit_of(&sc!("db")), None);
+    }
+
+    #[test]
+    fn should_parse_max_deployment_duration() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            max_deployment_duration_secs = 600
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            container_config.max_deployment_duration(),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(ContainerConfig::default().max_deployment_duration(), None);
+    }
+
+    #[test]
+    fn should_not_allow_devices_by_default() {
+        let container_config = ContainerConfig::default();
+
+        assert!(!container_config.is_device_allowed("/dev/ttyUSB0"));
+    }
+
+    #[test]
+    fn should_only_inherit_configured_env() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            inheritable_env = [ 'NPM_TOKEN' ]
+            "#,
+        )
+        .unwrap();
+
+        assert!(container_config.is_env_inheritable("NPM_TOKEN"));
+        assert!(!container_config.is_env_inheritable("PREVANT_ADMIN_API_KEY"));
+        assert!(!ContainerConfig::default().is_env_inheritable("NPM_TOKEN"));
+    }
+
+    #[test]
+    fn should_allow_configured_devices() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            allowed_devices = [ '/dev/ttyUSB0', '/dev/bus/usb/' ]
+            "#,
+        )
+        .unwrap();
+
+        assert!(container_config.is_device_allowed("/dev/ttyUSB0"));
+        assert!(container_config.is_device_allowed("/dev/bus/usb/001/002"));
+        assert!(!container_config.is_device_allowed("/dev/ttyUSB1"));
+        assert!(!container_config.is_device_allowed("/dev/bus/usbmon"));
+        assert!(container_config.is_device_allowed("/dev/bus//usb/./001"));
+    }
+
+    #[test]
+    fn should_not_allow_devices_outside_of_allowed_directories() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            allowed_devices = [ '/dev/bus/usb' ]
+            "#,
+        )
+        .unwrap();
+
+        assert!(!container_config.is_device_allowed("/dev/bus/usb/../../sda"));
+        assert!(!container_config.is_device_allowed("/dev/bus/usb/.."));
+        assert!(!container_config.is_device_allowed("dev/bus/usb/001"));
+    }
+
+    #[test]
+    fn should_allow_all_images_by_default() {
+        let container_config = ContainerConfig::default();
+
+        assert!(container_config.is_image_allowed(&Image::from_str("mariadb").unwrap()));
+    }
+
+    #[test]
+    fn should_allow_images_of_configured_registries() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            allowed_image_registries = [ 'registry.company.com', 'ghcr.io/myorg' ]
+            "#,
+        )
+        .unwrap();
+
+        assert!(container_config
+            .is_image_allowed(&Image::from_str("registry.company.com/team/app:1.0").unwrap()));
+        assert!(container_config.is_image_allowed(&Image::from_str("ghcr.io/myorg/app").unwrap()));
+        assert!(!container_config
+            .is_image_allowed(&Image::from_str("ghcr.io/myorganization/app").unwrap()));
+        assert!(!container_config.is_image_allowed(&Image::from_str("mariadb").unwrap()));
+        assert!(!container_config.is_image_allowed(
+            &Image::from_str(
+                "sha256:9895c9b90b58c9490471b877f6bb6a90e6bdc154da7fbb526a0322ea242fc913"
+            )
+            .unwrap()
+        ));
+    }
+
+    #[test]
+    fn should_pull_images_of_configured_registries_via_oci() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            oci_pull_registries = [ '123456789.dkr.ecr.eu-central-1.amazonaws.com' ]
+            "#,
+        )
+        .unwrap();
+
+        assert!(container_config.is_pulled_via_oci(
+            &Image::from_str("123456789.dkr.ecr.eu-central-1.amazonaws.com/team/app:1.0").unwrap()
+        ));
+        assert!(!container_config.is_pulled_via_oci(&Image::from_str("mariadb").unwrap()));
+        assert!(!ContainerConfig::default()
+            .is_pulled_via_oci(&Image::from_str("ghcr.io/myorg/app").unwrap()));
+    }
 }
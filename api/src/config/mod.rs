@@ -29,24 +29,30 @@ pub use self::companion::DeploymentStrategy;
 pub use self::companion::Routing;
 pub use self::companion::StorageStrategy;
 use self::companion::{Companion, CompanionType, Companions};
-pub use self::container::ContainerConfig;
-pub use self::runtime::Runtime;
+pub use self::container::{ContainerConfig, Ipv6Subnet, PullPolicy, RestartPolicy};
+pub use self::runtime::{KubernetesIngressConfig, Runtime};
+use crate::infrastructure::{is_sensitive_environment_variable, REDACTED_VALUE};
 use crate::models::AppName;
 use crate::models::ServiceConfig;
+use crate::secrets::{EnvFileSecretProvider, SecretProvider};
 use app_selector::AppSelector;
 use clap::Parser;
 use figment::providers::{Env, Format, Toml};
 use figment::value::{Dict, Map, Tag, Value};
 use figment::{Metadata, Profile};
+use log::LevelFilter;
 use secstr::SecUtf8;
-use std::collections::BTreeMap;
+use serde::{de, Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashMap};
 use std::convert::From;
 use std::fmt::Display;
 use std::io::Error as IOError;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 use std::usize;
 use toml::de::Error as TomlError;
+use url::Url;
 
 mod app_selector;
 mod companion;
@@ -157,6 +163,26 @@ pub struct Config {
     hooks: Option<BTreeMap<String, PathBuf>>,
     #[serde(default)]
     registries: BTreeMap<String, Registry>,
+    subdomain_routing: Option<SubdomainRoutingConfig>,
+    #[serde(default)]
+    host_meta: HostMeta,
+    #[serde(default)]
+    network_cleanup: NetworkCleanup,
+    #[serde(default)]
+    network: NetworkDriver,
+    tls: Option<TlsConfig>,
+    consul_integration: Option<ConsulConfig>,
+    #[serde(default)]
+    secret_provider: SecretProviderConfig,
+    #[serde(default)]
+    monitoring: Monitoring,
+    #[serde(default)]
+    admin: Admin,
+    #[serde(default, deserialize_with = "Config::parse_log_filters")]
+    log_filters: BTreeMap<String, LevelFilter>,
+    /// The raw values of all configuration sources, see [`Config::redacted_values`].
+    #[serde(skip)]
+    values: serde_json::Value,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
@@ -169,19 +195,257 @@ struct Registry {
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
 struct Applications {
     max: Option<usize>,
+    #[serde(default)]
+    peer_apps: Vec<String>,
+    deployment_lock_timeout_secs: Option<u64>,
+    redis_url: Option<Url>,
+    watch_config_dir: Option<PathBuf>,
+    shard_index: Option<usize>,
+    shard_total: Option<usize>,
+    bulk_operation_parallelism: Option<usize>,
+    #[serde(default)]
+    additional_reserved_names: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+struct NetworkCleanup {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+struct Admin {
+    api_key: Option<SecUtf8>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+struct Monitoring {
+    #[serde(default)]
+    enable_dns_health_check: bool,
+}
+
+#[derive(Clone, Default, Deserialize)]
+struct SecretProviderConfig {
+    env_file: Option<PathBuf>,
+    /// Maps the names of the secrets to the apps that may reference them. Secrets without app
+    /// selector cannot be referenced at all.
+    #[serde(default)]
+    app_selectors: BTreeMap<String, AppSelector>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+struct HostMeta {
+    requests_per_second: Option<u32>,
+    ttl_secs: Option<u64>,
+    circuit_break_threshold: Option<u32>,
+    circuit_open_secs: Option<u64>,
+    #[serde(default)]
+    crawl_priority: BTreeMap<String, u8>,
+}
+
+/// Makes the services reachable at `{service_name}.{app_name}.{base_domain}` instead of
+/// `{host}/{app_name}/{service_name}/`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct SubdomainRoutingConfig {
+    base_domain: String,
+}
+
+impl SubdomainRoutingConfig {
+    /// Returns the host name under which the service of the app is reachable, e.g.
+    /// `db.master.example.com`.
+    pub fn host_of(&self, app_name: &AppName, service_name: &str) -> String {
+        format!(
+            "{service_name}.{}.{}",
+            app_name.as_str().to_lowercase(),
+            self.base_domain.trim_matches('.')
+        )
+    }
+
+    /// Returns the URL of the service of the app. Scheme and port are taken from `base_url`.
+    pub fn service_url(
+        &self,
+        base_url: &url::Url,
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Option<url::Url> {
+        let mut url = base_url.clone();
+        url.set_host(Some(&self.host_of(app_name, service_name)))
+            .ok()?;
+        url.set_path("/");
+        Some(url)
+    }
+}
+
+/// Serves the REST API via HTTPS. With `ca_cert_path`, clients may authenticate themselves with
+/// certificates that have been signed by the CA and with `require_client_cert` they must do so.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct TlsConfig {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    ca_cert_path: Option<PathBuf>,
+    #[serde(default)]
+    require_client_cert: bool,
+}
+
+impl TlsConfig {
+    pub fn to_rocket_tls_config(&self) -> Result<rocket::config::TlsConfig, String> {
+        let tls_config = rocket::config::TlsConfig::from_paths(&self.cert_path, &self.key_path);
+
+        match &self.ca_cert_path {
+            Some(ca_cert_path) => Ok(tls_config.with_mutual(
+                rocket::mtls::MutualTls::from_path(ca_cert_path)
+                    .mandatory(self.require_client_cert),
+            )),
+            None if self.require_client_cert => Err(String::from(
+                "tls.require_client_cert requires tls.ca_cert_path to verify client certificates",
+            )),
+            None => Ok(tls_config),
+        }
+    }
+}
+
+/// Registers the services that the Docker runtime deploys in the catalog of a Consul agent, e.g.
+/// `http://localhost:8500`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct ConsulConfig {
+    address: url::Url,
+    token: Option<SecUtf8>,
+    check_interval_secs: Option<u64>,
+    timeout_secs: Option<u64>,
+}
+
+impl ConsulConfig {
+    pub fn address(&self) -> &url::Url {
+        &self.address
+    }
+
+    pub fn token(&self) -> Option<&SecUtf8> {
+        self.token.as_ref()
+    }
+
+    /// Returns how often Consul checks the health of the registered services.
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_secs(self.check_interval_secs.unwrap_or(10))
+    }
+
+    /// Returns how long PREvant waits for the Consul agent and for the web host meta data of a
+    /// service that is about to be registered.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or(5))
+    }
+}
+
+/// Determines the driver of the networks that PREvant creates for the apps.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(tag = "driver")]
+pub enum NetworkDriver {
+    Bridge,
+    Overlay,
+    /// Lets the containers appear as physical devices on the network of the host interface
+    /// `parent`, e.g. `eth0`.
+    Macvlan {
+        parent: String,
+    },
+    /// Isolates the containers completely, i.e. they are neither connected to each other nor to
+    /// Traefik.
+    None,
+}
+
+impl Default for NetworkDriver {
+    fn default() -> Self {
+        Self::Bridge
+    }
 }
 
 impl Config {
     pub fn from_figment(cli: &CliArgs) -> Result<Self, figment::Error> {
-        figment::Figment::new()
-            .merge(Toml::file(
-                cli.config
-                    .as_ref()
-                    .unwrap_or(&PathBuf::from_str("config.toml").unwrap()),
-            ))
-            .merge(Env::prefixed("PREVANT_").split("_"))
-            .merge(cli)
-            .extract::<Config>()
+        Self::extract(
+            figment::Figment::new()
+                .merge(Toml::file(
+                    cli.config
+                        .as_ref()
+                        .unwrap_or(&PathBuf::from_str("config.toml").unwrap()),
+                ))
+                .merge(Env::prefixed("PREVANT_").split("_"))
+                .merge(cli),
+        )
+    }
+
+    fn parse_log_filters<'de, D>(deserializer: D) -> Result<BTreeMap<String, LevelFilter>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        BTreeMap::<String, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(module, level)| {
+                let level = LevelFilter::from_str(&level).map_err(|_| {
+                    de::Error::custom(format!(
+                        "The log level {level} of {module} must be off, error, warn, info, debug, or trace"
+                    ))
+                })?;
+                Ok((module, level))
+            })
+            .collect()
+    }
+
+    pub(crate) fn extract(figment: figment::Figment) -> Result<Self, figment::Error> {
+        let mut config = figment.extract::<Config>()?;
+        config.values = figment.extract::<serde_json::Value>()?;
+
+        match (
+            config.applications.shard_index,
+            config.applications.shard_total,
+        ) {
+            (Some(shard_index), Some(shard_total)) if shard_index >= shard_total => {
+                return Err(figment::Error::from(format!(
+                    "The shard_index {shard_index} of the applications must be less than shard_total {shard_total}"
+                )));
+            }
+            (Some(_), None) | (None, Some(_)) => {
+                return Err(figment::Error::from(String::from(
+                    "The shard_index and shard_total of the applications must be configured together"
+                )));
+            }
+            _ => {}
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the values of the configuration file, the environment variables, and the command
+    /// line arguments. Values that might contain credentials are redacted, including `KEY=VALUE`
+    /// strings such as the environment variables of companions.
+    pub fn redacted_values(&self) -> serde_json::Value {
+        fn redact(value: serde_json::Value) -> serde_json::Value {
+            match value {
+                serde_json::Value::Object(values) => values
+                    .into_iter()
+                    .map(|(key, value)| {
+                        if is_sensitive_environment_variable(&key) {
+                            (key, serde_json::Value::from(REDACTED_VALUE))
+                        } else {
+                            (key, redact(value))
+                        }
+                    })
+                    .collect(),
+                serde_json::Value::Array(values) => values.into_iter().map(redact).collect(),
+                serde_json::Value::String(value) => match value.split_once('=') {
+                    Some((key, _)) if is_sensitive_environment_variable(key) => {
+                        serde_json::Value::from(format!("{key}={REDACTED_VALUE}"))
+                    }
+                    _ => serde_json::Value::String(value),
+                },
+                value => value,
+            }
+        }
+
+        redact(self.values.clone())
+    }
+
+    /// Returns the key that requests to the admin API, e.g. `GET /api/admin/config`, must provide
+    /// as bearer token. Without key, these endpoints are disabled.
+    pub fn admin_api_key(&self) -> Option<&SecUtf8> {
+        self.admin.api_key.as_ref()
     }
 
     pub fn runtime_config(&self) -> &Runtime {
@@ -195,6 +459,10 @@ impl Config {
         }
     }
 
+    pub fn subdomain_routing(&self) -> Option<&SubdomainRoutingConfig> {
+        self.subdomain_routing.as_ref()
+    }
+
     pub fn jira_config(&self) -> Option<JiraConfig> {
         self.jira.as_ref().cloned()
     }
@@ -275,6 +543,145 @@ impl Config {
     pub fn app_limit(&self) -> Option<usize> {
         return self.applications.max;
     }
+
+    /// Returns `true` if the services of the app must be able to communicate with the services
+    /// of the other peer apps.
+    pub fn is_peer_app(&self, app_name: &AppName) -> bool {
+        self.applications
+            .peer_apps
+            .iter()
+            .any(|peer_app| peer_app == app_name.as_str())
+    }
+
+    /// Returns how many requests per second PREvant sends to the services in order to crawl their
+    /// web host meta data. Without limit, all services are requested at once.
+    pub fn host_meta_requests_per_second(&self) -> Option<u32> {
+        self.host_meta.requests_per_second
+    }
+
+    /// Returns how long clients may cache the web host meta data of a service. Defaults to the
+    /// interval in which the crawler runs.
+    pub fn host_meta_ttl(&self) -> Duration {
+        Duration::from_secs(self.host_meta.ttl_secs.unwrap_or(5))
+    }
+
+    /// Returns after how many consecutive failures the crawler stops requesting the web host meta
+    /// data of a service for a while. Without threshold, the crawler retries in each crawl.
+    pub fn host_meta_circuit_break_threshold(&self) -> Option<u32> {
+        self.host_meta
+            .circuit_break_threshold
+            .filter(|threshold| *threshold > 0)
+    }
+
+    /// Returns how long the crawler stops requesting a service after the threshold has been
+    /// reached. The duration doubles each time the next request fails as well.
+    pub fn host_meta_circuit_open_duration(&self) -> Duration {
+        Duration::from_secs(self.host_meta.circuit_open_secs.unwrap_or(60))
+    }
+
+    /// Returns the priorities of the apps whose web host meta data the crawler resolves first.
+    /// Names that are not valid app names are ignored.
+    pub fn host_meta_crawl_priorities(&self) -> HashMap<AppName, u8> {
+        self.host_meta
+            .crawl_priority
+            .iter()
+            .filter_map(|(app_name, priority)| Some((AppName::from_str(app_name).ok()?, *priority)))
+            .collect()
+    }
+
+    /// Returns the log levels of modules, e.g. `prevant::infrastructure::docker`, that override
+    /// the global log level. `RUST_LOG` takes precedence over these levels.
+    pub fn log_filters(&self) -> &BTreeMap<String, LevelFilter> {
+        &self.log_filters
+    }
+
+    /// Returns `true` if the periodic clean-up of orphaned networks must only log the networks
+    /// instead of deleting them.
+    pub fn network_cleanup_dry_run(&self) -> bool {
+        self.network_cleanup.dry_run
+    }
+
+    /// Returns the directory that contains TOML files with the services of the apps that PREvant
+    /// deploys when the files change.
+    pub fn watch_config_dir(&self) -> Option<&PathBuf> {
+        self.applications.watch_config_dir.as_ref()
+    }
+
+    /// Returns `true` if PREvant checks periodically that the services of each app can resolve
+    /// each other's names.
+    pub fn is_dns_health_check_enabled(&self) -> bool {
+        self.monitoring.enable_dns_health_check
+    }
+
+    /// Returns the provider of the secrets that services reference via `envFromSecrets`.
+    pub fn secret_provider(&self) -> Option<Box<dyn SecretProvider>> {
+        self.secret_provider
+            .env_file
+            .clone()
+            .map(|path| Box::new(EnvFileSecretProvider::new(path)) as Box<dyn SecretProvider>)
+    }
+
+    /// Checks if the services of the app may reference the secret via `envFromSecrets`.
+    pub fn is_secret_accessible(&self, secret_name: &str, app_name: &AppName) -> bool {
+        self.secret_provider
+            .app_selectors
+            .get(secret_name)
+            .map_or(false, |app_selector| app_selector.matches(app_name))
+    }
+
+    pub fn tls(&self) -> Option<&TlsConfig> {
+        self.tls.as_ref()
+    }
+
+    pub fn consul_integration(&self) -> Option<&ConsulConfig> {
+        self.consul_integration.as_ref()
+    }
+
+    pub fn network_driver(&self) -> &NetworkDriver {
+        &self.network
+    }
+
+    /// Returns `true` if this PREvant instance manages the app. If multiple instances share the
+    /// apps, each instance only manages the apps whose [shard](AppName::shard) matches the
+    /// configured `shard_index`.
+    pub fn is_app_in_shard(&self, app_name: &AppName) -> bool {
+        match (self.applications.shard_index, self.applications.shard_total) {
+            (Some(shard_index), Some(shard_total)) if shard_total > 0 => {
+                app_name.shard(shard_total) == shard_index
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns the app names that cannot be deployed in addition to the names reserved by PREvant,
+    /// see [`AppName::is_reserved`].
+    pub fn additional_reserved_app_names(&self) -> &[String] {
+        &self.applications.additional_reserved_names
+    }
+
+    /// Returns how many apps the bulk operations, e.g. deleting multiple apps, process at the same
+    /// time.
+    pub fn bulk_operation_parallelism(&self) -> usize {
+        self.applications
+            .bulk_operation_parallelism
+            .unwrap_or(5)
+            .max(1)
+    }
+
+    /// Returns how long a deployment waits for another deployment of the same app to finish.
+    pub fn deployment_lock_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.applications
+                .deployment_lock_timeout_secs
+                .unwrap_or(300),
+        )
+    }
+
+    /// Returns the URL of the Redis instance that holds the deployment locks if multiple PREvant
+    /// instances share the same Docker host.
+    pub fn redis_url(&self) -> Option<&Url> {
+        self.applications.redis_url.as_ref()
+    }
 }
 
 impl JiraConfig {
@@ -324,16 +731,14 @@ macro_rules! config_from_str {
     ( $config_str:expr ) => {{
         use figment::providers::Format;
         let provider = figment::providers::Toml::string($config_str);
-        figment::Figment::from(provider)
-            .extract::<crate::config::Config>()
-            .unwrap()
+        crate::config::Config::extract(figment::Figment::from(provider)).unwrap()
     }};
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{service::ContainerType, Image};
+    use crate::models::{service::ContainerType, Image, ServiceConfigBuilder};
     use std::str::FromStr;
 
     macro_rules! service_config {
@@ -343,7 +748,11 @@ mod tests {
             hasher.update($name);
             let img_hash = &format!("sha256:{:x}", hasher.finalize());
 
-            ServiceConfig::new(String::from($name), Image::from_str(&img_hash).unwrap())
+            ServiceConfigBuilder::new()
+                .service_name(String::from($name))
+                .image(Image::from_str(&img_hash).unwrap())
+                .build()
+                .unwrap()
         }};
     }
 
@@ -531,6 +940,26 @@ mod tests {
         assert_eq!(companion_configs.len(), 0);
     }
 
+    #[test]
+    fn should_restrict_secrets_of_secret_provider_to_selected_apps() {
+        let config = config_from_str!(
+            r#"
+            [secret_provider]
+            env_file = '/run/secrets/prevant.env'
+
+            [secret_provider.app_selectors]
+            db-password = 'master|feature-.+'
+            "#
+        );
+
+        assert!(config.is_secret_accessible("db-password", &AppName::master()));
+        assert!(
+            config.is_secret_accessible("db-password", &AppName::from_str("feature-1").unwrap())
+        );
+        assert!(!config.is_secret_accessible("db-password", &AppName::from_str("other").unwrap()));
+        assert!(!config.is_secret_accessible("api-token", &AppName::master()));
+    }
+
     #[test]
     fn should_set_service_secrets_with_default_app_selector() {
         let config = config_from_str!(
@@ -643,6 +1072,366 @@ mod tests {
         assert_eq!(service_config.files(), None);
     }
 
+    #[test]
+    fn should_parse_peer_apps() {
+        let config = config_from_str!(
+            r#"
+            [applications]
+            peer_apps = [ 'master', 'shared-services' ]
+            "#
+        );
+
+        assert!(config.is_peer_app(&AppName::from_str("shared-services").unwrap()));
+        assert!(!config.is_peer_app(&AppName::from_str("feature-x").unwrap()));
+    }
+
+    #[test]
+    fn should_parse_shard() {
+        let config = config_from_str!(
+            r#"
+            [applications]
+            shard_index = 3
+            shard_total = 4
+            "#
+        );
+
+        assert!(config.is_app_in_shard(&AppName::master()));
+        assert!(!config.is_app_in_shard(&AppName::from_str("feature-2").unwrap()));
+        assert!(config_from_str!("").is_app_in_shard(&AppName::from_str("feature-2").unwrap()));
+    }
+
+    #[test]
+    fn should_not_parse_shard_index_out_of_range() {
+        let provider = Toml::string(
+            r#"
+            [applications]
+            shard_index = 4
+            shard_total = 4
+            "#,
+        );
+
+        assert!(Config::extract(figment::Figment::from(provider)).is_err());
+    }
+
+    #[test]
+    fn should_not_parse_incomplete_shard() {
+        for shard in ["shard_index = 1", "shard_total = 4"] {
+            let provider = Toml::string(&format!("[applications]\n{shard}"));
+
+            assert!(Config::extract(figment::Figment::from(provider)).is_err());
+        }
+    }
+
+    #[test]
+    fn should_parse_log_filters() {
+        let config = config_from_str!(
+            r#"
+            [log_filters]
+            'prevant::infrastructure::docker' = 'info'
+            'prevant::apps::host_meta_cache' = 'WARN'
+            "#
+        );
+
+        assert_eq!(
+            config.log_filters(),
+            &BTreeMap::from([
+                (
+                    String::from("prevant::apps::host_meta_cache"),
+                    LevelFilter::Warn
+                ),
+                (
+                    String::from("prevant::infrastructure::docker"),
+                    LevelFilter::Info
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn should_reject_unknown_log_levels() {
+        let config = Config::extract(figment::Figment::new().merge(Toml::string(
+            r#"
+            [log_filters]
+            'prevant::infrastructure::docker' = 'verbose'
+            "#,
+        )));
+
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn should_parse_host_meta_crawl_priorities() {
+        let config = config_from_str!(
+            r#"
+            [host_meta.crawl_priority]
+            master = 10
+            "#
+        );
+
+        assert_eq!(
+            config.host_meta_crawl_priorities(),
+            HashMap::from([(AppName::master(), 10)])
+        );
+    }
+
+    #[test]
+    fn should_parse_host_meta_circuit_breaker() {
+        let config = config_from_str!(
+            r#"
+            [host_meta]
+            circuit_break_threshold = 5
+            circuit_open_secs = 30
+            "#
+        );
+        assert_eq!(config.host_meta_circuit_break_threshold(), Some(5));
+        assert_eq!(
+            config.host_meta_circuit_open_duration(),
+            Duration::from_secs(30)
+        );
+
+        let config = config_from_str!("");
+        assert_eq!(config.host_meta_circuit_break_threshold(), None);
+        assert_eq!(
+            config.host_meta_circuit_open_duration(),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn should_parse_consul_integration() {
+        let config = config_from_str!(
+            r#"
+            [consul_integration]
+            address = 'http://consul:8500'
+            token = 'acl-token'
+            "#
+        );
+
+        let consul_config = config.consul_integration().unwrap();
+        assert_eq!(consul_config.address().as_str(), "http://consul:8500/");
+        assert_eq!(consul_config.token(), Some(&SecUtf8::from("acl-token")));
+        assert_eq!(consul_config.check_interval(), Duration::from_secs(10));
+        assert_eq!(consul_config.timeout(), Duration::from_secs(5));
+        assert_eq!(config_from_str!("").consul_integration(), None);
+    }
+
+    #[test]
+    fn should_parse_additional_reserved_names() {
+        let config = config_from_str!(
+            r#"
+            [applications]
+            additional_reserved_names = [ 'prevant' ]
+            "#
+        );
+        assert_eq!(
+            config.additional_reserved_app_names(),
+            &[String::from("prevant")]
+        );
+    }
+
+    #[test]
+    fn should_parse_bulk_operation_parallelism() {
+        let config = config_from_str!(
+            r#"
+            [applications]
+            bulk_operation_parallelism = 10
+            "#
+        );
+        assert_eq!(config.bulk_operation_parallelism(), 10);
+        assert_eq!(config_from_str!("").bulk_operation_parallelism(), 5);
+    }
+
+    #[test]
+    fn should_parse_deployment_lock_timeout() {
+        let config = config_from_str!(
+            r#"
+            [applications]
+            deployment_lock_timeout_secs = 60
+            "#
+        );
+
+        assert_eq!(config.deployment_lock_timeout(), Duration::from_secs(60));
+        assert_eq!(
+            config_from_str!("").deployment_lock_timeout(),
+            Duration::from_secs(300)
+        );
+    }
+
+    #[test]
+    fn should_parse_redis_url() {
+        let config = config_from_str!(
+            r#"
+            [applications]
+            redis_url = 'redis://redis:6379/0'
+            "#
+        );
+
+        assert_eq!(
+            config.redis_url(),
+            Some(&Url::parse("redis://redis:6379/0").unwrap())
+        );
+        assert_eq!(config_from_str!("").redis_url(), None);
+    }
+
+    #[test]
+    fn should_parse_host_meta_rate_limit() {
+        let config = config_from_str!(
+            r#"
+            [host_meta]
+            requests_per_second = 5
+            "#
+        );
+
+        assert_eq!(config.host_meta_requests_per_second(), Some(5));
+        assert_eq!(config_from_str!("").host_meta_requests_per_second(), None);
+    }
+
+    #[test]
+    fn should_redact_sensitive_values() {
+        let config = config_from_str!(
+            r#"
+            [admin]
+            api_key = 'admin-key'
+
+            [registries.'registry.gitlab.com']
+            username = 'oauth2'
+            password = 'private-token'
+
+            [companions.db]
+            serviceName = 'db'
+            type = 'service'
+            image = 'mariadb'
+            env = [ 'MYSQL_USER=admin', 'MYSQL_PASSWORD=secret' ]
+            "#
+        );
+
+        assert_eq!(
+            config.redacted_values(),
+            serde_json::json!({
+                "admin": { "api_key": "<redacted>" },
+                "registries": {
+                    "registry.gitlab.com": { "username": "oauth2", "password": "<redacted>" }
+                },
+                "companions": {
+                    "db": {
+                        "serviceName": "db",
+                        "type": "service",
+                        "image": "mariadb",
+                        "env": [ "MYSQL_USER=admin", "MYSQL_PASSWORD=<redacted>" ]
+                    }
+                }
+            })
+        );
+        assert_eq!(config.admin_api_key(), Some(&SecUtf8::from("admin-key")));
+    }
+
+    #[test]
+    fn should_parse_host_meta_ttl() {
+        let config = config_from_str!(
+            r#"
+            [host_meta]
+            ttl_secs = 60
+            "#
+        );
+
+        assert_eq!(config.host_meta_ttl(), Duration::from_secs(60));
+        assert_eq!(config_from_str!("").host_meta_ttl(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn should_require_client_certificates() {
+        let config = config_from_str!(
+            r#"
+            [tls]
+            cert_path = '/etc/prevant/cert.pem'
+            key_path = '/etc/prevant/key.pem'
+            ca_cert_path = '/etc/prevant/ca.pem'
+            require_client_cert = true
+            "#
+        );
+
+        let tls_config = config.tls().unwrap().to_rocket_tls_config().unwrap();
+
+        assert!(tls_config.mutual().unwrap().mandatory);
+    }
+
+    #[test]
+    fn should_not_require_client_certificates_without_ca() {
+        let config = config_from_str!(
+            r#"
+            [tls]
+            cert_path = '/etc/prevant/cert.pem'
+            key_path = '/etc/prevant/key.pem'
+            require_client_cert = true
+            "#
+        );
+
+        assert!(config.tls().unwrap().to_rocket_tls_config().is_err());
+    }
+
+    #[test]
+    fn should_parse_dns_health_check_flag() {
+        let config = config_from_str!(
+            r#"
+            [monitoring]
+            enable_dns_health_check = true
+            "#
+        );
+
+        assert!(config.is_dns_health_check_enabled());
+        assert!(!config_from_str!("").is_dns_health_check_enabled());
+    }
+
+    #[test]
+    fn should_parse_network_driver() {
+        let config = config_from_str!(
+            r#"
+            [network]
+            driver = 'Macvlan'
+            parent = 'eth0'
+            "#
+        );
+
+        assert_eq!(
+            config.network_driver(),
+            &NetworkDriver::Macvlan {
+                parent: String::from("eth0")
+            }
+        );
+        assert_eq!(
+            config_from_str!("").network_driver(),
+            &NetworkDriver::Bridge
+        );
+    }
+
+    #[test]
+    fn should_parse_subdomain_routing() {
+        let config = config_from_str!(
+            r#"
+            [subdomain_routing]
+            base_domain = 'preview.example.com'
+            "#
+        );
+
+        assert_eq!(
+            config
+                .subdomain_routing()
+                .map(|routing| routing.host_of(&AppName::from_str("MASTER").unwrap(), "db")),
+            Some(String::from("db.master.preview.example.com"))
+        );
+        assert_eq!(
+            config
+                .subdomain_routing()
+                .and_then(|routing| routing.service_url(
+                    &url::Url::parse("https://prevant.example.com:8443/").unwrap(),
+                    &AppName::master(),
+                    "db"
+                )),
+            Some(url::Url::parse("https://db.master.preview.example.com:8443/").unwrap())
+        );
+        assert_eq!(config_from_str!("").subdomain_routing(), None);
+    }
+
     #[test]
     fn should_parse_config_with_default_container_runtime() {
         let config = config_from_str!("");
@@ -49,6 +49,8 @@ pub struct KubernetesRuntimeConfig {
     downward_api: KubernetesDownwardApiConfig,
     #[serde(default)]
     storage_config: KubernetesStorageConfig,
+    #[serde(default)]
+    ingress: Option<KubernetesIngressConfig>,
 }
 
 impl KubernetesRuntimeConfig {
@@ -63,6 +65,35 @@ impl KubernetesRuntimeConfig {
     pub fn annotations(&self) -> &KubernetesAnnotationsConfig {
         &self.annotations
     }
+
+    pub fn ingress(&self) -> Option<&KubernetesIngressConfig> {
+        self.ingress.as_ref()
+    }
+}
+
+/// Configures a plain Kubernetes `Ingress` per app that routes `/{app_name}/{service_name}` to
+/// the Kubernetes services of the app. This is useful for clusters that do not run Traefik.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KubernetesIngressConfig {
+    #[serde(default = "KubernetesIngressConfig::default_class_name")]
+    class_name: String,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+}
+
+impl KubernetesIngressConfig {
+    pub fn class_name(&self) -> &str {
+        &self.class_name
+    }
+
+    pub fn annotations(&self) -> &BTreeMap<String, String> {
+        &self.annotations
+    }
+
+    fn default_class_name() -> String {
+        String::from("traefik")
+    }
 }
 
 #[derive(Debug, Clone, Default, Deserialize, PartialEq)]
@@ -253,4 +284,66 @@ mod tests {
             Some(&String::from("rancher-project-id"))
         );
     }
+
+    #[test]
+    fn parse_without_ingress() {
+        let runtime_toml = r#"
+        type = 'Kubernetes'
+        "#;
+
+        let Runtime::Kubernetes(config) = toml::de::from_str::<Runtime>(runtime_toml).unwrap()
+        else {
+            panic!("Need a K8s config")
+        };
+
+        assert_eq!(config.ingress(), None);
+    }
+
+    #[test]
+    fn parse_with_ingress() {
+        let runtime_toml = r#"
+        type = 'Kubernetes'
+
+        [ingress]
+        className = 'nginx'
+
+        [ingress.annotations]
+        'nginx.ingress.kubernetes.io/proxy-body-size' = '8m'
+        "#;
+
+        let Runtime::Kubernetes(config) = toml::de::from_str::<Runtime>(runtime_toml).unwrap()
+        else {
+            panic!("Need a K8s config")
+        };
+
+        assert_eq!(
+            config.ingress(),
+            Some(&KubernetesIngressConfig {
+                class_name: String::from("nginx"),
+                annotations: BTreeMap::from([(
+                    String::from("nginx.ingress.kubernetes.io/proxy-body-size"),
+                    String::from("8m")
+                )]),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_with_ingress_and_default_class_name() {
+        let runtime_toml = r#"
+        type = 'Kubernetes'
+
+        [ingress]
+        "#;
+
+        let Runtime::Kubernetes(config) = toml::de::from_str::<Runtime>(runtime_toml).unwrap()
+        else {
+            panic!("Need a K8s config")
+        };
+
+        assert_eq!(
+            config.ingress().map(|ingress| ingress.class_name()),
+            Some("traefik")
+        );
+    }
 }
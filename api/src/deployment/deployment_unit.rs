@@ -33,6 +33,7 @@ use crate::models::{AppName, ContainerType, Image, ServiceConfig};
 use crate::registry::ImageInfo;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::time::Duration;
 
 pub struct Initialized {
     app_name: AppName,
@@ -111,6 +112,31 @@ pub struct DeploymentUnit {
     app_name: AppName,
     services: Vec<DeployableService>,
     route: TraefikIngressRoute,
+    rollout_strategy: RolloutStrategy,
+}
+
+/// Determines how the running services of an app are replaced by the deployment.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RolloutStrategy {
+    /// Each running service is stopped before its new version is started.
+    Recreate,
+    /// The new versions of all services are started next to the running ones and replace them
+    /// once all of them are healthy. Otherwise, the new versions are removed and the running
+    /// services are kept.
+    BlueGreen { health_check_timeout: Duration },
+    /// The new versions of the services are started next to the running ones and receive
+    /// `weight_percent` of the traffic until they are promoted, either manually or after
+    /// `promote_after`. Services without running version are started as usual.
+    Canary {
+        weight_percent: u8,
+        promote_after: Option<Duration>,
+    },
+}
+
+impl Default for RolloutStrategy {
+    fn default() -> Self {
+        Self::Recreate
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -183,6 +209,14 @@ impl DeploymentUnit {
     pub fn app_base_route(&self) -> &TraefikIngressRoute {
         &self.route
     }
+
+    pub fn rollout_strategy(&self) -> &RolloutStrategy {
+        &self.rollout_strategy
+    }
+
+    pub fn set_rollout_strategy(&mut self, rollout_strategy: RolloutStrategy) {
+        self.rollout_strategy = rollout_strategy;
+    }
 }
 
 impl DeploymentUnitBuilder<Initialized> {
@@ -217,6 +251,14 @@ impl DeploymentUnitBuilder<Initialized> {
 }
 
 impl DeploymentUnitBuilder<WithCompanions> {
+    /// Drops the companions configured in PREvant's configuration, e.g. for deployments that
+    /// provide their own infrastructure services.
+    pub fn without_companions(mut self) -> Self {
+        self.stage.service_companions.clear();
+        self.stage.app_companions.clear();
+        self
+    }
+
     pub fn extend_with_templating_only_service_configs(
         self,
         templating_only_service_configs: Vec<ServiceConfig>,
@@ -619,6 +661,7 @@ impl DeploymentUnitBuilder<WithAppliedHooks> {
             app_name: self.stage.app_name,
             services: self.stage.services,
             route,
+            rollout_strategy: RolloutStrategy::default(),
         }
     }
 }
@@ -629,6 +672,7 @@ impl DeploymentUnitBuilder<WithAppliedIngressRoute> {
             app_name: self.stage.app_name,
             services: self.stage.services,
             route: self.stage.route,
+            rollout_strategy: RolloutStrategy::default(),
         }
     }
 }
@@ -24,7 +24,7 @@
  * =========================LICENSE_END==================================
  */
 
-pub use crate::deployment::deployment_unit::DeploymentUnit;
+pub use crate::deployment::deployment_unit::{DeploymentUnit, RolloutStrategy};
 
 pub mod deployment_unit;
 pub mod hooks;
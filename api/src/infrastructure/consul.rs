@@ -0,0 +1,244 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2024 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::config::ConsulConfig;
+use crate::models::AppName;
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use http::header::{CONTENT_TYPE, HOST};
+use http_body_util::{BodyExt, Full};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+/// Registers services with the HTTP API of a Consul agent which adds them to Consul's catalog.
+pub struct ConsulClient<'a> {
+    config: &'a ConsulConfig,
+}
+
+/// The payload of `PUT /v1/agent/service/register`.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConsulServiceRegistration {
+    #[serde(rename = "ID")]
+    id: String,
+    name: String,
+    tags: Vec<String>,
+    address: String,
+    port: u16,
+    check: ConsulCheck,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct ConsulCheck {
+    #[serde(rename = "HTTP", skip_serializing_if = "Option::is_none")]
+    http: Option<String>,
+    #[serde(rename = "TCP", skip_serializing_if = "Option::is_none")]
+    tcp: Option<String>,
+    interval: String,
+}
+
+/// Returns the ID under which the service of the app is registered. Other than the name, it is
+/// unique across all apps: app and service names may contain `-`, thus, the length of the app name
+/// determines where the service name starts.
+pub fn consul_service_id(app_name: &AppName, service_name: &str) -> String {
+    format!("prevant-{}-{app_name}-{service_name}", app_name.len())
+}
+
+impl ConsulServiceRegistration {
+    /// Describes the service of the app that is reachable at `address:port`. Consul checks the
+    /// service's web host meta data if `provides_host_meta` is set and, otherwise, whether the
+    /// port accepts TCP connections.
+    pub fn new(
+        app_name: &AppName,
+        service_name: &str,
+        address: String,
+        port: u16,
+        provides_host_meta: bool,
+        check_interval: Duration,
+    ) -> Self {
+        let (http, tcp) = if provides_host_meta {
+            (
+                Some(format!(
+                    "http://{address}:{port}/.well-known/host-meta.json"
+                )),
+                None,
+            )
+        } else {
+            (None, Some(format!("{address}:{port}")))
+        };
+
+        Self {
+            id: consul_service_id(app_name, service_name),
+            name: service_name.to_string(),
+            tags: vec![app_name.to_string()],
+            address,
+            port,
+            check: ConsulCheck {
+                http,
+                tcp,
+                interval: format!("{}s", check_interval.as_secs().max(1)),
+            },
+        }
+    }
+}
+
+impl<'a> ConsulClient<'a> {
+    pub fn new(config: &'a ConsulConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn register(&self, registration: &ConsulServiceRegistration) -> Result<()> {
+        self.put(
+            "/v1/agent/service/register",
+            serde_json::to_vec(registration)?,
+        )
+        .await
+    }
+
+    pub async fn deregister(&self, service_id: &str) -> Result<()> {
+        self.put(
+            &format!("/v1/agent/service/deregister/{service_id}"),
+            Vec::new(),
+        )
+        .await
+    }
+
+    async fn put(&self, path: &str, body: Vec<u8>) -> Result<()> {
+        let timeout = self.config.timeout();
+        tokio::time::timeout(timeout, self.send_put(path, body))
+            .await
+            .map_err(|_| anyhow!("Consul did not respond within {timeout:?}"))?
+    }
+
+    async fn send_put(&self, path: &str, body: Vec<u8>) -> Result<()> {
+        let address = self.config.address();
+        if address.scheme() != "http" {
+            return Err(anyhow!(
+                "The Consul address {address} must use the scheme http"
+            ));
+        }
+        let host = address
+            .host_str()
+            .ok_or_else(|| anyhow!("The Consul address {address} does not contain a host"))?;
+        let port = address.port_or_known_default().unwrap_or(80);
+
+        let stream = TcpStream::connect((host, port)).await?;
+        let (mut sender, connection) =
+            hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                warn!("Error in connection: {}", e);
+            }
+        });
+
+        let mut request =
+            http::Request::put(format!("{}{path}", address.path().trim_end_matches('/')))
+                .header(HOST, format!("{host}:{port}"))
+                .header(CONTENT_TYPE, "application/json");
+        if let Some(token) = self.config.token() {
+            request = request.header("X-Consul-Token", token.unsecure());
+        }
+
+        let response = sender
+            .send_request(request.body(Full::new(Bytes::from(body)))?)
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.into_body().collect().await?.to_bytes();
+            return Err(anyhow!(
+                "Consul responded with {status}: {}",
+                String::from_utf8_lossy(&body)
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn should_serialize_registration_with_host_meta_check() {
+        let registration = ConsulServiceRegistration::new(
+            &AppName::master(),
+            "db",
+            String::from("172.17.0.2"),
+            3306,
+            true,
+            Duration::from_secs(10),
+        );
+
+        assert_eq!(
+            serde_json::to_value(&registration).unwrap(),
+            json!({
+                "ID": "prevant-6-master-db",
+                "Name": "db",
+                "Tags": [ "master" ],
+                "Address": "172.17.0.2",
+                "Port": 3306,
+                "Check": {
+                    "HTTP": "http://172.17.0.2:3306/.well-known/host-meta.json",
+                    "Interval": "10s"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn should_derive_unambiguous_service_ids() {
+        assert_ne!(
+            consul_service_id(&AppName::from_str("a-b").unwrap(), "c"),
+            consul_service_id(&AppName::from_str("a").unwrap(), "b-c")
+        );
+    }
+
+    #[test]
+    fn should_serialize_registration_with_tcp_check() {
+        let registration = ConsulServiceRegistration::new(
+            &AppName::master(),
+            "db",
+            String::from("172.17.0.2"),
+            3306,
+            false,
+            Duration::from_secs(10),
+        );
+
+        assert_eq!(
+            serde_json::to_value(&registration).unwrap()["Check"],
+            json!({
+                "TCP": "172.17.0.2:3306",
+                "Interval": "10s"
+            })
+        );
+    }
+}
@@ -0,0 +1,383 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::models::AppName;
+use redis::aio::MultiplexedConnection;
+use redis::RedisError;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use url::Url;
+use uuid::Uuid;
+
+static REDIS_LOCK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// The locks in Redis expire after this duration unless they are renewed by the instance that
+/// holds them, thus, the locks of crashed instances are released soon.
+static REDIS_LOCK_EXPIRY: Duration = Duration::from_secs(30);
+
+tokio::task_local! {
+    /// The apps whose locks are held by [`DeploymentLockManager::lock_all`] for the current task.
+    static HELD_LOCKS: HashSet<AppName>;
+}
+/// Deletes the lock only if it is still held by the given token. Otherwise, the lock expired and
+/// has been acquired by another instance in the meantime.
+static REDIS_RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end
+"#;
+/// Extends the expiry of the lock only if it is still held by the given token.
+static REDIS_RENEW_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+else
+    return 0
+end
+"#;
+
+/// Makes sure that only one deployment per app is running at the same time, e.g. if two CI
+/// pipelines trigger a deployment of the same app simultaneously.
+#[derive(Clone, Default)]
+pub struct DeploymentLockManager {
+    locks: Arc<Mutex<HashMap<AppName, Arc<AsyncMutex<()>>>>>,
+    redis_lock: Option<RedisDistributedLock>,
+}
+
+/// Releases the deployment lock when it is dropped.
+pub struct DeploymentLockGuard {
+    _local: Option<OwnedMutexGuard<()>>,
+    _redis: Option<RedisLockGuard>,
+}
+
+impl DeploymentLockManager {
+    /// Creates a manager whose locks are additionally held in Redis so that the deployments of
+    /// multiple PREvant instances are serialized, too.
+    pub fn with_redis(redis_lock: RedisDistributedLock) -> Self {
+        Self {
+            locks: Default::default(),
+            redis_lock: Some(redis_lock),
+        }
+    }
+
+    /// Acquires the deployment lock of the app. The lock will be released when the returned
+    /// guard is dropped. Returns `None` if the lock could not be acquired within `timeout` and
+    /// fails if Redis cannot be reached.
+    pub async fn lock(
+        &self,
+        app_name: &AppName,
+        timeout: Duration,
+    ) -> Result<Option<DeploymentLockGuard>, RedisError> {
+        if HELD_LOCKS
+            .try_with(|held_locks| held_locks.contains(app_name))
+            .unwrap_or(false)
+        {
+            return Ok(Some(DeploymentLockGuard {
+                _local: None,
+                _redis: None,
+            }));
+        }
+
+        let lock = {
+            let mut locks = self.locks.lock().unwrap();
+            // Locks that are neither held nor awaited are only referenced by the map.
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(app_name.clone()).or_default().clone()
+        };
+
+        let deadline = Instant::now() + timeout;
+        let Ok(local) = tokio::time::timeout(timeout, lock.lock_owned()).await else {
+            return Ok(None);
+        };
+
+        let redis = match &self.redis_lock {
+            Some(redis_lock) => match redis_lock.lock(app_name, deadline).await? {
+                Some(redis) => Some(redis),
+                None => return Ok(None),
+            },
+            None => None,
+        };
+
+        Ok(Some(DeploymentLockGuard {
+            _local: Some(local),
+            _redis: redis,
+        }))
+    }
+
+    /// Acquires the deployment locks of all apps and runs `operation` while holding them, e.g. to
+    /// move the services from one app to another. Within `operation`, [`lock`](Self::lock) returns
+    /// immediately for these apps. Returns `None` if the locks could not be acquired within
+    /// `timeout` and fails if Redis cannot be reached.
+    pub async fn lock_all<F: Future>(
+        &self,
+        app_names: &[AppName],
+        timeout: Duration,
+        operation: F,
+    ) -> Result<Option<F::Output>, RedisError> {
+        // Acquiring the locks in a deterministic order avoids deadlocks between two operations
+        // that lock the same apps.
+        let mut app_names = app_names.to_vec();
+        app_names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        app_names.dedup();
+
+        let deadline = Instant::now() + timeout;
+        let mut guards = Vec::with_capacity(app_names.len());
+        for app_name in &app_names {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.lock(app_name, timeout).await? {
+                Some(guard) => guards.push(guard),
+                None => return Ok(None),
+            }
+        }
+
+        let output = HELD_LOCKS
+            .scope(app_names.into_iter().collect(), operation)
+            .await;
+        drop(guards);
+        Ok(Some(output))
+    }
+}
+
+/// A lock in Redis that is acquired with `SET NX PX`. The lock expires automatically so that
+/// PREvant instances that crashed during a deployment do not hold it forever. While the lock is
+/// held, its expiry is renewed periodically so that long-running deployments keep it.
+#[derive(Clone)]
+pub struct RedisDistributedLock {
+    redis_url: Url,
+    expiry: Duration,
+}
+
+struct RedisLockGuard {
+    connection: MultiplexedConnection,
+    key: String,
+    token: String,
+    renewal: JoinHandle<()>,
+}
+
+impl RedisDistributedLock {
+    pub fn new(redis_url: Url) -> Self {
+        Self {
+            redis_url,
+            expiry: REDIS_LOCK_EXPIRY,
+        }
+    }
+
+    async fn lock(
+        &self,
+        app_name: &AppName,
+        deadline: Instant,
+    ) -> Result<Option<RedisLockGuard>, RedisError> {
+        let mut connection = redis::Client::open(self.redis_url.as_str())?
+            .get_multiplexed_async_connection()
+            .await?;
+        let key = format!("prevant:deployment-lock:{app_name}");
+        let token = Uuid::new_v4().to_string();
+
+        loop {
+            let acquired = redis::cmd("SET")
+                .arg(&key)
+                .arg(&token)
+                .arg("NX")
+                .arg("PX")
+                .arg(self.expiry.as_millis() as u64)
+                .query_async::<Option<String>>(&mut connection)
+                .await?;
+
+            if acquired.is_some() {
+                let renewal = tokio::spawn(Self::renew(
+                    connection.clone(),
+                    key.clone(),
+                    token.clone(),
+                    self.expiry,
+                ));
+                return Ok(Some(RedisLockGuard {
+                    connection,
+                    key,
+                    token,
+                    renewal,
+                }));
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            tokio::time::sleep(REDIS_LOCK_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    /// Extends the expiry of the lock every third of the expiry until the task is aborted by
+    /// dropping the [`RedisLockGuard`].
+    async fn renew(
+        mut connection: MultiplexedConnection,
+        key: String,
+        token: String,
+        expiry: Duration,
+    ) {
+        loop {
+            tokio::time::sleep(expiry / 3).await;
+
+            match redis::cmd("EVAL")
+                .arg(REDIS_RENEW_SCRIPT)
+                .arg(1)
+                .arg(&key)
+                .arg(&token)
+                .arg(expiry.as_millis() as u64)
+                .query_async::<i64>(&mut connection)
+                .await
+            {
+                Ok(1) => trace!("Renewed the deployment lock {key} in Redis"),
+                Ok(_) => {
+                    error!("The deployment lock {key} expired in Redis before it could be renewed");
+                    return;
+                }
+                // The next attempt might succeed before the lock expires.
+                Err(err) => warn!("Cannot renew the deployment lock {key} in Redis: {err}"),
+            }
+        }
+    }
+}
+
+impl Drop for RedisLockGuard {
+    fn drop(&mut self) {
+        self.renewal.abort();
+
+        let mut connection = self.connection.clone();
+        let key = std::mem::take(&mut self.key);
+        let token = std::mem::take(&mut self.token);
+
+        tokio::spawn(async move {
+            if let Err(err) = redis::cmd("EVAL")
+                .arg(REDIS_RELEASE_SCRIPT)
+                .arg(1)
+                .arg(&key)
+                .arg(&token)
+                .query_async::<i64>(&mut connection)
+                .await
+            {
+                warn!("Cannot release the deployment lock {key} in Redis: {err}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn should_not_lock_app_twice() {
+        let manager = DeploymentLockManager::default();
+        let app_name = AppName::from_str("master").unwrap();
+
+        let guard = manager
+            .lock(&app_name, Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert!(guard.is_some());
+
+        assert!(manager
+            .lock(&app_name, Duration::from_millis(10))
+            .await
+            .unwrap()
+            .is_none());
+
+        drop(guard);
+        assert!(manager
+            .lock(&app_name, Duration::from_millis(10))
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn should_reuse_locks_held_by_lock_all() {
+        let manager = DeploymentLockManager::default();
+        let master = AppName::from_str("master").unwrap();
+        let feature = AppName::from_str("feature-x").unwrap();
+
+        let locked = manager
+            .lock_all(
+                &[master.clone(), feature.clone()],
+                Duration::from_millis(10),
+                async {
+                    let other_manager = manager.clone();
+                    let is_locked_by_other_task = tokio::spawn(async move {
+                        other_manager
+                            .lock(
+                                &AppName::from_str("master").unwrap(),
+                                Duration::from_millis(10),
+                            )
+                            .await
+                            .unwrap()
+                            .is_none()
+                    });
+
+                    manager
+                        .lock(&feature, Duration::from_millis(10))
+                        .await
+                        .unwrap()
+                        .is_some()
+                        && is_locked_by_other_task.await.unwrap()
+                },
+            )
+            .await;
+
+        assert_eq!(locked.unwrap(), Some(true));
+        assert!(manager
+            .lock(&master, Duration::from_millis(10))
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn should_lock_different_apps() {
+        let manager = DeploymentLockManager::default();
+
+        let _master = manager
+            .lock(
+                &AppName::from_str("master").unwrap(),
+                Duration::from_millis(10),
+            )
+            .await;
+
+        assert!(manager
+            .lock(
+                &AppName::from_str("feature-x").unwrap(),
+                Duration::from_millis(10)
+            )
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
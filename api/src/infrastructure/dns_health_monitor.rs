@@ -0,0 +1,248 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+use crate::infrastructure::{APP_NAME_LABEL, CONTAINER_TYPE_LABEL, SERVICE_NAME_LABEL};
+use crate::models::{AppName, ContainerType};
+use bollard::container::ListContainersOptions;
+use bollard::exec::{CreateExecOptions, StartExecResults};
+use bollard::service::ContainerSummary;
+use bollard::Docker;
+use futures::StreamExt;
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Keeps track of the apps whose containers cannot resolve the names of the app's services.
+#[derive(Clone, Default)]
+pub struct DnsHealth {
+    degraded_apps: Arc<RwLock<HashSet<AppName>>>,
+}
+
+impl DnsHealth {
+    pub fn is_degraded(&self, app_name: &AppName) -> bool {
+        self.degraded_apps.read().unwrap().contains(app_name)
+    }
+
+    fn update(&self, degraded_apps: HashSet<AppName>) {
+        *self.degraded_apps.write().unwrap() = degraded_apps;
+    }
+}
+
+enum Resolution {
+    Resolved,
+    Failed,
+    /// The container does not provide `nslookup`.
+    Unsupported,
+}
+
+/// Checks every two minutes if the embedded DNS server of Docker resolves the service names of each
+/// app, e.g. because it might stop doing so after a restart of the Docker daemon. For that purpose,
+/// `nslookup` is executed in a random instance container of each app.
+pub struct DnsHealthMonitor {
+    dns_health: DnsHealth,
+}
+
+impl DnsHealthMonitor {
+    pub fn new(dns_health: DnsHealth) -> Self {
+        Self { dns_health }
+    }
+
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = self.check().await {
+                    error!("Cannot check DNS health of the apps: {}", err);
+                }
+                sleep(Duration::from_secs(120)).await;
+            }
+        });
+    }
+
+    async fn check(&self) -> anyhow::Result<()> {
+        let docker = Docker::connect_with_socket_defaults()?;
+
+        let containers = docker
+            .list_containers(Some(ListContainersOptions::<&str> {
+                filters: HashMap::from([
+                    ("label", vec![APP_NAME_LABEL]),
+                    ("status", vec!["running"]),
+                ]),
+                ..Default::default()
+            }))
+            .await?;
+
+        let mut degraded_apps = HashSet::new();
+        // A failing exec, e.g. because the container stopped in the meantime, must not prevent
+        // the checks of the remaining services and apps.
+        let mut errors = Vec::new();
+        for (app_name, containers) in group_by_app(containers) {
+            let instances = containers
+                .iter()
+                .filter(|container| {
+                    label_of(container, CONTAINER_TYPE_LABEL)
+                        == Some(&ContainerType::Instance.to_string())
+                })
+                .collect::<Vec<_>>();
+            let Some(container) = pick_random(&instances) else {
+                continue;
+            };
+            let Some(container_id) = container.id.as_deref() else {
+                continue;
+            };
+            let own_service_name = label_of(container, SERVICE_NAME_LABEL);
+
+            for service_name in containers
+                .iter()
+                .filter_map(|container| label_of(container, SERVICE_NAME_LABEL))
+                .filter(|service_name| Some(*service_name) != own_service_name)
+            {
+                match resolve(&docker, container_id, service_name).await {
+                    Ok(Resolution::Resolved) => {}
+                    Ok(Resolution::Failed) => {
+                        warn!(
+                            "Container {container_id} of app {app_name} cannot resolve the service name {service_name}"
+                        );
+                        degraded_apps.insert(app_name.clone());
+                    }
+                    Ok(Resolution::Unsupported) => {
+                        debug!("Cannot check DNS health of {app_name} because {container_id} does not provide nslookup");
+                        break;
+                    }
+                    Err(err) => errors.push(format!(
+                        "Cannot resolve {service_name} in container {container_id} of app {app_name}: {err}"
+                    )),
+                }
+            }
+        }
+
+        self.dns_health.update(degraded_apps);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(errors.join(", ")))
+        }
+    }
+}
+
+async fn resolve(
+    docker: &Docker,
+    container_id: &str,
+    service_name: &str,
+) -> anyhow::Result<Resolution> {
+    let exec = docker
+        .create_exec(
+            container_id,
+            CreateExecOptions {
+                cmd: Some(vec!["nslookup", service_name]),
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    if let StartExecResults::Attached { mut output, .. } = docker.start_exec(&exec.id, None).await?
+    {
+        while output.next().await.is_some() {}
+    }
+
+    Ok(match docker.inspect_exec(&exec.id).await?.exit_code {
+        Some(0) => Resolution::Resolved,
+        // the shell reports that the command cannot be found or executed
+        Some(126) | Some(127) => Resolution::Unsupported,
+        _ => Resolution::Failed,
+    })
+}
+
+fn label_of<'a>(container: &'a ContainerSummary, label: &str) -> Option<&'a String> {
+    container.labels.as_ref()?.get(label)
+}
+
+fn group_by_app(containers: Vec<ContainerSummary>) -> HashMap<AppName, Vec<ContainerSummary>> {
+    let mut apps: HashMap<AppName, Vec<ContainerSummary>> = HashMap::new();
+    for container in containers {
+        let Some(app_name) = label_of(&container, APP_NAME_LABEL)
+            .and_then(|app_name| AppName::from_str(app_name).ok())
+        else {
+            continue;
+        };
+        apps.entry(app_name).or_default().push(container);
+    }
+    apps
+}
+
+fn pick_random<'a, T>(items: &[&'a T]) -> Option<&'a T> {
+    if items.is_empty() {
+        return None;
+    }
+    // RandomState is seeded randomly, which suffices to spread the checks over the containers
+    let random = RandomState::new().build_hasher().finish() as usize;
+    Some(items[random % items.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn container(app_name: &str, service_name: &str) -> ContainerSummary {
+        ContainerSummary {
+            id: Some(format!("{app_name}-{service_name}")),
+            labels: Some(HashMap::from([
+                (String::from(APP_NAME_LABEL), String::from(app_name)),
+                (String::from(SERVICE_NAME_LABEL), String::from(service_name)),
+            ])),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_group_containers_by_app() {
+        let apps = group_by_app(vec![
+            container("master", "db"),
+            container("master", "wordpress"),
+            container("feature", "db"),
+            ContainerSummary::default(),
+        ]);
+
+        assert_eq!(apps.len(), 2);
+        assert_eq!(apps[&AppName::master()].len(), 2);
+        assert_eq!(apps[&AppName::from_str("feature").unwrap()].len(), 1);
+    }
+
+    #[test]
+    fn should_track_degraded_apps() {
+        let dns_health = DnsHealth::default();
+
+        dns_health.update(HashSet::from([AppName::master()]));
+
+        assert!(dns_health.is_degraded(&AppName::master()));
+        assert!(!dns_health.is_degraded(&AppName::from_str("feature").unwrap()));
+    }
+}
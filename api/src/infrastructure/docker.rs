@@ -24,55 +24,163 @@
  * =========================LICENSE_END==================================
  */
 
-use crate::config::{Config, ContainerConfig};
-use crate::deployment::deployment_unit::{DeployableService, DeploymentStrategy};
+use crate::client_certificate::ClientCertificate;
+use crate::config::{
+    Config, ConsulConfig, ContainerConfig, Ipv6Subnet, NetworkDriver, PullPolicy,
+    RestartPolicy as ContainerRestartPolicy, SubdomainRoutingConfig,
+};
+use crate::deployment::deployment_unit::{DeployableService, DeploymentStrategy, RolloutStrategy};
 use crate::deployment::DeploymentUnit;
+use crate::infrastructure::consul::{consul_service_id, ConsulClient, ConsulServiceRegistration};
+use crate::infrastructure::deployment_lock::{DeploymentLockManager, RedisDistributedLock};
+use crate::infrastructure::image_puller::{DockerPuller, ImagePullError, ImagePuller, OciPuller};
 use crate::infrastructure::{
-    HttpForwarder, Infrastructure, APP_NAME_LABEL, CONTAINER_TYPE_LABEL, IMAGE_LABEL,
-    REPLICATED_ENV_LABEL, SERVICE_NAME_LABEL, STATUS_ID,
+    is_sensitive_environment_variable, parse_tags, AppDeployment, HttpForwarder, Infrastructure,
+    NetworkInfo, StoppedService, APP_NAME_LABEL, CONTAINER_TYPE_LABEL, ENV_LABEL, IMAGE_LABEL,
+    REDACTED_VALUE, REPLICATED_ENV_LABEL, SERVICE_NAME_LABEL, STATUS_ID, TAGS_LABEL,
 };
 use crate::models::service::{ContainerType, Service, ServiceError, ServiceStatus};
 use crate::models::{
-    AppName, Environment, Image, ServiceBuilder, ServiceBuilderError, ServiceConfig, WebHostMeta,
+    AppName, DeviceMapping, Environment, Image, IpcMode, LogLine, LogStream, NamedVolumeMount,
+    SecretEnvRef, ServiceBuilder, ServiceBuilderError, ServiceConfig, ServiceConfigBuilder,
+    ServiceConfigBuilderError, ServiceQuery, ServiceSearchResult, StartupProbe, UlimitConfig,
+    WebHostMeta,
 };
+use crate::secrets::SecretError;
 use anyhow::{anyhow, Result};
-use async_stream::stream;
+use async_stream::{stream, try_stream};
 use async_trait::async_trait;
-use bollard::auth::DockerCredentials;
 use bollard::container::{
-    CreateContainerOptions, ListContainersOptions, LogOutput, StartContainerOptions,
-    UploadToContainerOptions,
+    CreateContainerOptions, ListContainersOptions, LogOutput, LogsOptions, RemoveContainerOptions,
+    RenameContainerOptions, StartContainerOptions, UploadToContainerOptions, WaitContainerOptions,
 };
 use bollard::errors::Error as BollardError;
-use bollard::image::CreateImageOptions;
 use bollard::network::{
     ConnectNetworkOptions, CreateNetworkOptions, DisconnectNetworkOptions, ListNetworksOptions,
 };
 use bollard::secret::Port;
 use bollard::service::{
     ContainerCreateResponse, ContainerInspectResponse, ContainerStateStatusEnum, ContainerSummary,
-    CreateImageInfo, EndpointSettings, HostConfig, RestartPolicy, RestartPolicyNameEnum,
-    VolumeListResponse,
+    DeviceMapping as DockerDeviceMapping, EndpointSettings, HealthStatusEnum, HostConfig, Ipam,
+    IpamConfig, MountPointTypeEnum, Network, PortBinding as DockerPortBinding, PortMap,
+    ResourcesUlimits, RestartPolicy, RestartPolicyNameEnum, Volume, VolumeListResponse,
 };
 use bollard::volume::{CreateVolumeOptions, ListVolumesOptions};
 use bollard::Docker;
-use chrono::{DateTime, FixedOffset};
+use bytesize::ByteSize;
+use chrono::{DateTime, FixedOffset, Utc};
+use futures::future::BoxFuture;
 use futures::stream::BoxStream;
 use futures::stream::FuturesUnordered;
 use futures::{StreamExt, TryStreamExt};
 use http_body_util::BodyExt;
 use hyper_util::rt::TokioIo;
 use multimap::MultiMap;
+use redis::RedisError;
 use rocket::form::validate::Contains;
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::{From, TryFrom};
+use std::hash::Hasher;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 use std::str::FromStr;
+use std::time::Duration;
 use tokio::net::TcpStream;
+use uuid::Uuid;
 
 static CONTAINER_PORT_LABEL: &str = "traefik.port";
+/// Groups the containers of a service, e.g. its running container and its canary, in one Traefik
+/// backend so that Traefik balances the requests between them according to their weights.
+static TRAEFIK_BACKEND_LABEL: &str = "traefik.backend";
+static TRAEFIK_WEIGHT_LABEL: &str = "traefik.weight";
+/// Excludes the container from Traefik's routing, e.g. the readiness probe of a service, see
+/// [`DockerInfrastructure::probe_readiness`].
+static TRAEFIK_ENABLE_LABEL: &str = "traefik.enable";
+/// The Traefik weight of the containers that are not canaries. The weights of the canaries are
+/// relative to it.
+const TRAEFIK_DEFAULT_WEIGHT: u32 = 100;
+/// Name of the network that connects the containers of all peer apps so that they can reach each
+/// other via `{service_name}.{app_name}.prevant.local`.
+static PEER_NETWORK_NAME: &str = "prevant-peer-network";
+static PEER_NETWORK_DOMAIN: &str = "prevant.local";
+/// The time when the first container of a service has been created. The label is carried forward
+/// when the container is replaced.
+static CREATED_AT_LABEL: &str = "com.aixigo.preview.servant.created-at";
+/// The version of PREvant that created the container.
+static VERSION_LABEL: &str = "com.aixigo.preview.servant.version";
+/// Marks Docker objects, e.g. networks, that have been created by PREvant.
+static MANAGED_BY_LABEL: &str = "com.aixigo.preview.servant.managed-by";
+static MANAGED_BY_VALUE: &str = "prevant";
+/// The slot of a blue/green deployment in which the container has been started. Docker cannot
+/// change the labels of existing containers, thus, promoted containers keep the label `green`.
+static DEPLOYMENT_SLOT_LABEL: &str = "com.aixigo.preview.servant.deployment-slot";
+static GREEN_DEPLOYMENT_SLOT: &str = "green";
+static CANARY_DEPLOYMENT_SLOT: &str = "canary";
+/// Groups the containers that have been created by the same deployment of an app.
+static DEPLOY_ID_LABEL: &str = "com.aixigo.preview.servant.deploy-id";
+/// Marks init containers. They carry the app name label so that they are removed with the app but
+/// they are never listed as services.
+static INIT_CONTAINER_OF_LABEL: &str = "com.aixigo.preview.servant.init-container-of";
+/// Marks the volumes of [`NamedVolumeMount`]s which can be preserved when the app is deleted.
+static NAMED_VOLUME_LABEL: &str = "com.aixigo.preview.servant.named-volume";
+/// Keeps the `envInherit` configuration of the service so that it is applied again when the
+/// container is recreated, e.g. by renaming the app or patching its environment variables.
+static ENV_INHERIT_LABEL: &str = "com.aixigo.preview.servant.env-inherit";
+/// Keeps the references of `envFromSecrets`, but not the values of the secrets, so that the
+/// secrets are resolved again when the container is recreated.
+static ENV_FROM_SECRETS_LABEL: &str = "com.aixigo.preview.servant.env-from-secrets";
+/// The number of containers that should run the service so that a redeployment keeps the number,
+/// e.g. after PREvant has been restarted.
+static REPLICAS_LABEL: &str = "com.aixigo.preview.servant.replicas";
+/// Marks the additional containers of a service that runs more than one replica. Like init
+/// containers, they carry the app name label but they are never listed as services.
+static REPLICA_OF_LABEL: &str = "com.aixigo.preview.servant.replica-of";
+/// Keeps the configured IPC mode of the service so that the IPC modes of later deployments can be
+/// checked against the services that keep running, see [`DockerInfrastructure::check_ipc_modes`].
+static IPC_MODE_LABEL: &str = "com.aixigo.preview.servant.ipc-mode";
+/// Comma-separated names of the services that the service depends on so that the dependencies can
+/// be described, e.g. in the Compose file of the app.
+static DEPENDS_ON_LABEL: &str = "com.aixigo.preview.servant.depends-on";
+/// Bounds the number of containers of a service because each replica is a full container on the
+/// Docker host.
+const MAX_REPLICAS: u32 = 10;
+/// Bounds the attempts to create a network with a free IPv6 subnet if another Docker client took
+/// the subnet in the meantime.
+const MAX_IPV6_SUBNET_ATTEMPTS: usize = 3;
+lazy_static! {
+    /// Serialises the allocation of IPv6 subnets because a subnet is only taken once the network
+    /// has been created, i.e. concurrent deployments would pick the same free subnet.
+    static ref IPV6_SUBNET_ALLOCATION: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+static HEALTH_CHECK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+static HEALTH_CHECK_GRACE_PERIOD: Duration = Duration::from_secs(1);
+static READINESS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// The POSIX signals that services can configure as stop signal of their containers.
+static STOP_SIGNALS: [&str; 19] = [
+    "SIGABRT", "SIGALRM", "SIGCONT", "SIGHUP", "SIGINT", "SIGKILL", "SIGPIPE", "SIGQUIT",
+    "SIGSTOP", "SIGTERM", "SIGTSTP", "SIGTTIN", "SIGTTOU", "SIGUSR1", "SIGUSR2", "SIGPWR",
+    "SIGWINCH", "SIGXCPU", "SIGXFSZ",
+];
 
 pub struct DockerInfrastructure {
     config: Config,
+    deployment_locks: DeploymentLockManager,
+}
+
+/// The slot in which a container is started next to the running container of its service.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DeploymentSlot {
+    Green,
+    Canary { weight_percent: u8 },
+}
+
+impl DeploymentSlot {
+    fn label_value(&self) -> &'static str {
+        match self {
+            DeploymentSlot::Green => GREEN_DEPLOYMENT_SLOT,
+            DeploymentSlot::Canary { .. } => CANARY_DEPLOYMENT_SLOT,
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -93,11 +201,145 @@ pub enum DockerInfrastructureError {
     InvalidContainerState { container_id: String },
     #[error("Unexpected image details for container: {container_id}")]
     InvalidContainerImage { container_id: String },
+    #[error("Pulling image {image} did not finish within {timeout_secs} seconds.")]
+    PullTimeout { image: String, timeout_secs: u64 },
+    #[error("The image {image} does not belong to one of the allowed registries {allowed:?}.")]
+    UnauthorizedRegistry { image: String, allowed: Vec<String> },
+    #[error("Timed out waiting for another deployment of {app_name} to finish.")]
+    DeploymentLockTimeout { app_name: String },
+    #[error("The deployment of {app_name} did not finish within {duration_secs} seconds.")]
+    DeploymentTimeout {
+        app_name: String,
+        duration_secs: u64,
+    },
+    #[error("The device {host_path} is not permitted to be exposed to containers.")]
+    DeviceNotPermitted { host_path: String },
+    #[error("The memory swap limit {memory_swap_bytes} of service {service_name} must be -1 or at least its memory limit ({memory_bytes:?}).")]
+    InvalidMemorySwap {
+        service_name: String,
+        memory_swap_bytes: i64,
+        memory_bytes: Option<i64>,
+    },
+    #[error("The memory swappiness {memory_swappiness} of service {service_name} must be between 0 and 100.")]
+    InvalidMemorySwappiness {
+        service_name: String,
+        memory_swappiness: u8,
+    },
+    #[error("The PID limit {pid_limit} of service {service_name} must be greater than 1.")]
+    InvalidPidLimit {
+        service_name: String,
+        pid_limit: u64,
+    },
+    #[error(
+        "The working directory {working_dir} of service {service_name} must be an absolute path."
+    )]
+    InvalidWorkingDir {
+        service_name: String,
+        working_dir: String,
+    },
+    #[error(
+        "The stop signal {stop_signal} of service {service_name} is not a known POSIX signal."
+    )]
+    InvalidStopSignal {
+        service_name: String,
+        stop_signal: String,
+    },
+    #[error("The DNS server {address} of service {service_name} is not a valid IP address.")]
+    InvalidDnsServer {
+        service_name: String,
+        address: String,
+    },
+    #[error("The service {service_name} must run with 1 to {MAX_REPLICAS} replicas but requested {replicas}.")]
+    InvalidReplicas { service_name: String, replicas: u32 },
+    #[error("The port {port} of service {service_name} cannot be forwarded because the service only exposes the ports {exposed_ports:?}.")]
+    PortNotExposed {
+        service_name: String,
+        port: u16,
+        exposed_ports: Vec<u16>,
+    },
+    #[error("The timezone {timezone} of service {service_name} is not a known IANA timezone.")]
+    InvalidTimezone {
+        service_name: String,
+        timezone: String,
+    },
+    #[error("The named volume {volume_name} of service {service_name} must consist of alphanumeric characters, '_' or '-' and start with an alphanumeric character.")]
+    InvalidNamedVolume {
+        service_name: String,
+        volume_name: String,
+    },
+    #[error("The volume {volume_name} exists already but does not belong to the app {app_name}.")]
+    NamedVolumeConflict {
+        app_name: String,
+        volume_name: String,
+    },
+    #[error("Service {service_name} cannot join the IPC namespace of {ipc_service_name}, which must be another service that neither is private nor joins an IPC namespace itself.")]
+    InvalidIpcMode {
+        service_name: String,
+        ipc_service_name: String,
+    },
+    #[error("The services {services:?} cannot be started because they depend on each other.")]
+    CyclicDependencies { services: Vec<String> },
+    #[error("Service {service_name} cannot join the IPC namespace of {ipc_service_name} because it is not running.")]
+    IpcContainerNotRunning {
+        service_name: String,
+        ipc_service_name: String,
+    },
+    #[error("Service {service_name} cannot join the IPC namespace of {ipc_service_name} because its container does not share it. Deploy {ipc_service_name} together with {service_name}.")]
+    IpcContainerNotShareable {
+        service_name: String,
+        ipc_service_name: String,
+    },
+    #[error("The environment variable {name} is not permitted to be inherited by service {service_name}.")]
+    EnvNotInheritable { service_name: String, name: String },
+    #[error("The environment variable {name} that service {service_name} inherits is not set.")]
+    InheritedEnvNotFound { service_name: String, name: String },
+    #[error("Cannot resolve the secrets of service {service_name}: {err}")]
+    SecretResolution {
+        service_name: String,
+        err: SecretError,
+    },
+    #[error("The container name {container_name} is already in use by another container.")]
+    ContainerNameConflict { container_name: String },
+    #[error("The container {container_id} did not become healthy within {timeout:?}.")]
+    HealthCheckTimeout {
+        container_id: String,
+        timeout: Duration,
+    },
+    #[error(
+        "The init container ({image}) of service {service_name} exited with code {exit_code}."
+    )]
+    InitContainerFailure {
+        service_name: String,
+        image: String,
+        exit_code: i64,
+    },
+    #[error("The service {service_name} did not respond with 200 to {readiness_url} within {timeout:?}.")]
+    ReadinessTimeout {
+        service_name: String,
+        readiness_url: String,
+        timeout: Duration,
+    },
+    #[error("The service {service_name} did not respond successfully to {url_path} after {failures} attempts.")]
+    StartupProbeFailure {
+        service_name: String,
+        url_path: String,
+        failures: u8,
+    },
 }
 
 impl DockerInfrastructure {
     pub fn new(config: Config) -> Self {
-        Self { config }
+        let deployment_locks = match config.redis_url() {
+            Some(redis_url) => {
+                DeploymentLockManager::with_redis(RedisDistributedLock::new(redis_url.clone()))
+            }
+            None => DeploymentLockManager::default(),
+        };
+
+        Self {
+            config,
+            deployment_locks,
+        }
     }
 
     async fn find_status_change_container(
@@ -129,7 +371,7 @@ impl DockerInfrastructure {
 
         let image = Image::from_str("docker.io/library/busybox:stable").unwrap();
 
-        pull(&image, &self.config).await?;
+        DockerPuller.pull(&image, &self.config).await?;
 
         let mut labels: HashMap<&str, &str> = HashMap::new();
         labels.insert(APP_NAME_LABEL, app_name);
@@ -152,40 +394,176 @@ impl DockerInfrastructure {
         Ok(docker.inspect_container(&container_info.id, None).await?)
     }
 
-    async fn create_or_get_network_id(&self, app_name: &AppName) -> Result<String, BollardError> {
+    /// Returns the ID of the app's network or `None` if the containers must not be connected to
+    /// any network.
+    async fn create_or_get_network_id(
+        &self,
+        app_name: &AppName,
+        container_config: &ContainerConfig,
+    ) -> Result<Option<String>, BollardError> {
         trace!("Resolve network id for {app_name}");
 
+        let network_driver = self.config.network_driver();
+        if network_driver == &NetworkDriver::None {
+            return Ok(None);
+        }
+
         let network_name = format!("{app_name}-net");
+        Self::create_or_get_network(
+            &network_name,
+            Some(app_name),
+            network_driver,
+            container_config.network_mtu(),
+            container_config.ipv6_subnet(),
+        )
+        .await
+        .map(Some)
+    }
+
+    async fn create_or_get_network(
+        network_name: &str,
+        app_name: Option<&AppName>,
+        network_driver: &NetworkDriver,
+        network_mtu: Option<u16>,
+        ipv6_subnet: Option<Ipv6Subnet>,
+    ) -> Result<String, BollardError> {
+        let _ipv6_subnet_allocation = match ipv6_subnet {
+            Some(_) => Some(IPV6_SUBNET_ALLOCATION.lock().await),
+            None => None,
+        };
+
+        let mut attempt = 1;
+        loop {
+            match Self::try_create_or_get_network(
+                network_name,
+                app_name,
+                network_driver,
+                network_mtu,
+                ipv6_subnet,
+            )
+            .await
+            {
+                Err(BollardError::DockerResponseServerError { message, .. })
+                    if ipv6_subnet.is_some()
+                        && message.contains("overlaps")
+                        && attempt < MAX_IPV6_SUBNET_ATTEMPTS =>
+                {
+                    debug!("The IPv6 subnet of network {network_name} has been taken in the meantime: {message}");
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
 
+    async fn try_create_or_get_network(
+        network_name: &str,
+        app_name: Option<&AppName>,
+        network_driver: &NetworkDriver,
+        network_mtu: Option<u16>,
+        ipv6_subnet: Option<Ipv6Subnet>,
+    ) -> Result<String, BollardError> {
         let docker = Docker::connect_with_socket_defaults()?;
-        let network_id = docker
+        let networks = docker
             .list_networks(None::<ListNetworksOptions<&str>>)
-            .await?
-            .into_iter()
-            .find(|n| n.name.as_ref() == Some(&network_name))
-            .and_then(|n| n.id);
+            .await?;
+        let network_id = networks
+            .iter()
+            .find(|n| n.name.as_deref() == Some(network_name))
+            .and_then(|n| n.id.clone());
 
         if let Some(n) = network_id {
             return Ok(n);
         }
 
-        debug!("Creating network for app {app_name}.");
+        debug!("Creating network {network_name}.");
 
+        let created_at = Utc::now().to_rfc3339();
+        let mut labels = HashMap::from([
+            (MANAGED_BY_LABEL, MANAGED_BY_VALUE),
+            (CREATED_AT_LABEL, created_at.as_str()),
+        ]);
+        if let Some(app_name) = app_name {
+            labels.insert(APP_NAME_LABEL, app_name.as_str());
+        }
+
+        let network_mtu = network_mtu.map(|mtu| mtu.to_string());
+        let network_ipv6_subnet = ipv6_subnet.and_then(|ipv6_subnet| {
+            let subnet = free_ipv6_subnet(&ipv6_subnet, &networks);
+            if subnet.is_none() {
+                warn!("There is no free IPv6 subnet in {ipv6_subnet} left, creating network {network_name} without IPv6.");
+            }
+            subnet.map(|subnet| subnet.to_string())
+        });
         let network_create_info = docker
-            .create_network(CreateNetworkOptions::<&str> {
-                name: &network_name,
-                ..Default::default()
-            })
+            .create_network(Self::create_network_options(
+                network_name,
+                labels,
+                network_driver,
+                network_mtu.as_deref(),
+                network_ipv6_subnet.as_deref(),
+            ))
             .await?;
         let network_id = network_create_info
             .id
             .expect("id is mandatory for a Docker Network.");
 
-        debug!("Created network for app {app_name} with id {network_id}");
+        debug!("Created network {network_name} with id {network_id}");
 
         Ok(network_id)
     }
 
+    fn create_network_options<'a>(
+        network_name: &'a str,
+        labels: HashMap<&'a str, &'a str>,
+        network_driver: &'a NetworkDriver,
+        network_mtu: Option<&'a str>,
+        ipv6_subnet: Option<&'a str>,
+    ) -> CreateNetworkOptions<&'a str> {
+        let options = CreateNetworkOptions::<&str> {
+            name: network_name,
+            labels,
+            ..Default::default()
+        };
+
+        let mut options = match network_driver {
+            NetworkDriver::Bridge | NetworkDriver::None => CreateNetworkOptions {
+                driver: "bridge",
+                ..options
+            },
+            NetworkDriver::Overlay => CreateNetworkOptions {
+                driver: "overlay",
+                // standalone containers can only join overlay networks that are attachable
+                attachable: true,
+                ..options
+            },
+            NetworkDriver::Macvlan { parent } => CreateNetworkOptions {
+                driver: "macvlan",
+                options: HashMap::from([("parent", parent.as_str())]),
+                ..options
+            },
+        };
+
+        if let Some(network_mtu) = network_mtu {
+            options
+                .options
+                .insert("com.docker.network.driver.mtu", network_mtu);
+        }
+
+        if let Some(ipv6_subnet) = ipv6_subnet {
+            options.enable_ipv6 = true;
+            options.ipam = Ipam {
+                config: Some(vec![IpamConfig {
+                    subnet: Some(ipv6_subnet.to_string()),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            };
+        }
+
+        options
+    }
+
     async fn connect_traefik(&self, network_id: &str) -> Result<(), BollardError> {
         let docker = Docker::connect_with_socket_defaults()?;
 
@@ -242,33 +620,75 @@ impl DockerInfrastructure {
     }
 
     async fn delete_network(&self, app_name: &AppName) -> Result<(), BollardError> {
-        let network_name = format!("{app_name}-net");
+        let app_name_filter = format!("{APP_NAME_LABEL}={app_name}");
+        let managed_by_filter = format!("{MANAGED_BY_LABEL}={MANAGED_BY_VALUE}");
 
         let docker = Docker::connect_with_socket_defaults()?;
 
-        for n in docker
+        // Only networks carrying the labels of PREvant are deleted, so that networks created by
+        // users with the same naming pattern, e.g. `master-net`, stay untouched.
+        let networks = docker
             .list_networks(Some(ListNetworksOptions::<&str> {
-                filters: HashMap::from([("name", vec![network_name.as_str()])]),
+                filters: HashMap::from([(
+                    "label",
+                    vec![app_name_filter.as_str(), managed_by_filter.as_str()],
+                )]),
             }))
-            .await?
-        {
-            let network_id =
-                n.id.as_ref()
-                    .expect("id is mandatory for a Docker Network.");
-            self.disconnect_traefik(network_id).await?;
-            docker.remove_network(network_id).await?;
+            .await?;
+
+        let network_ids = networks
+            .into_iter()
+            .map(|n| n.id.expect("id is mandatory for a Docker Network."))
+            .chain(
+                Self::list_legacy_app_networks()
+                    .await?
+                    .into_iter()
+                    .filter(|network| network.app_name() == Some(app_name))
+                    .map(|network| network.id().to_string()),
+            )
+            .collect::<HashSet<_>>();
+
+        for network_id in network_ids {
+            self.disconnect_traefik(&network_id).await?;
+            docker.remove_network(&network_id).await?;
         }
 
         Ok(())
     }
 
-    async fn delete_volume_mount(&self, app_name: &AppName) -> Result<(), BollardError> {
+    /// Returns the networks of apps that have been deployed by PREvant versions which did not
+    /// label the networks yet, i.e. the unlabeled networks named `{app_name}-net`.
+    async fn list_legacy_app_networks() -> Result<Vec<NetworkInfo>, BollardError> {
+        let docker = Docker::connect_with_socket_defaults()?;
+
+        // The name filter of Docker matches substrings, the exact name is checked afterwards.
+        Ok(docker
+            .list_networks(Some(ListNetworksOptions::<&str> {
+                filters: HashMap::from([("name", vec!["-net"])]),
+            }))
+            .await?
+            .into_iter()
+            .filter_map(|network| {
+                let app_name = legacy_app_name_of_network(&network)?;
+                Some(NetworkInfo::new(network.id?, network.name?, Some(app_name)))
+            })
+            .collect())
+    }
+
+    async fn delete_volume_mount(
+        &self,
+        app_name: &AppName,
+        preserve_named_volumes: bool,
+    ) -> Result<(), BollardError> {
         let docker = Docker::connect_with_socket_defaults()?;
         for volume in Self::fetch_existing_volumes(app_name)
             .await?
             .volumes
             .into_iter()
             .flatten()
+            .filter(|volume| {
+                !(preserve_named_volumes && volume.labels.contains_key(NAMED_VOLUME_LABEL))
+            })
         {
             docker.remove_volume(&volume.name, None).await?;
         }
@@ -276,1207 +696,5515 @@ impl DockerInfrastructure {
         Ok(())
     }
 
-    async fn deploy_services_impl(
+    /// Waits for the deployment lock of the app and deploys the services while holding it. The
+    /// maximum deployment duration starts once the lock has been acquired, thus, waiting for a
+    /// concurrent deployment is only bounded by the deployment lock timeout.
+    async fn deploy_services_exclusively(
         &self,
         deployment_unit: &DeploymentUnit,
         container_config: &ContainerConfig,
     ) -> Result<Vec<Service>, DockerInfrastructureError> {
         let app_name = deployment_unit.app_name();
         let services = deployment_unit.services();
-        let network_id = self.create_or_get_network_id(app_name).await?;
 
-        self.connect_traefik(&network_id).await?;
-        let existing_volumes = Self::fetch_existing_volumes(app_name).await?;
-        let mut futures = services
+        if let Some(image) = services
             .iter()
-            .map(|service| {
-                self.start_container(
-                    app_name,
-                    &network_id,
-                    service,
-                    container_config,
-                    &existing_volumes,
+            .flat_map(|service| {
+                std::iter::once(service.image()).chain(
+                    service
+                        .init_containers()
+                        .iter()
+                        .map(|init_container| init_container.image()),
                 )
             })
-            .map(Box::pin)
-            .collect::<FuturesUnordered<_>>();
-
-        let mut services: Vec<Service> = Vec::new();
-        while let Some(service) = futures.next().await {
-            services.push(service?);
+            .find(|image| !container_config.is_image_allowed(image))
+        {
+            return Err(DockerInfrastructureError::UnauthorizedRegistry {
+                image: image.to_string(),
+                allowed: container_config.allowed_image_registries().to_vec(),
+            });
         }
+        // The services are started concurrently, thus, an invalid service must be rejected before
+        // any container of the app has been replaced.
+        for service in services {
+            Self::check_service(service, container_config)?;
+        }
+        Self::deployment_waves(services)?;
 
-        Ok(services)
+        let _deployment_lock = self
+            .deployment_locks
+            .lock(app_name, self.config.deployment_lock_timeout())
+            .await?
+            .ok_or_else(|| DockerInfrastructureError::DeploymentLockTimeout {
+                app_name: app_name.to_string(),
+            })?;
+
+        match container_config.max_deployment_duration() {
+            Some(max_deployment_duration) => {
+                self.deploy_services_with_timeout(
+                    deployment_unit,
+                    container_config,
+                    max_deployment_duration,
+                )
+                .await
+            }
+            None => {
+                self.deploy_services_impl(deployment_unit, container_config)
+                    .await
+            }
+        }
     }
 
-    async fn stop_services_impl(
+    async fn deploy_services_impl(
         &self,
-        app_name: &AppName,
+        deployment_unit: &DeploymentUnit,
+        container_config: &ContainerConfig,
     ) -> Result<Vec<Service>, DockerInfrastructureError> {
-        let container_details = match self
-            .get_container_details(Some(app_name), None)
-            .await?
-            .get_vec(app_name)
-        {
-            None => return Ok(vec![]),
-            Some(services) => services.clone(),
-        };
-
-        let docker = Docker::connect_with_socket_defaults()?;
-
-        let mut futures = container_details
-            .clone()
-            .into_iter()
-            .filter(|p| {
-                p.state.as_ref().and_then(|state| state.status)
-                    == Some(ContainerStateStatusEnum::RUNNING)
-            })
-            .map(|details| async {
-                let id = details
-                    .id
-                    .as_ref()
-                    .expect("id is mandatory for a docker container");
-
-                docker.stop_container(id, None).await?;
-
-                Ok::<ContainerInspectResponse, BollardError>(details)
-            })
-            .map(Box::pin)
-            .collect::<FuturesUnordered<_>>();
+        let app_name = deployment_unit.app_name();
+        let services = deployment_unit.services();
 
-        while let Some(result) = futures.next().await {
-            let container = result?;
-            let id = container
-                .id
-                .expect("id is mandatory for a docker container");
-            trace!("Stopped container {id} for {app_name}");
+        // A deployment that has been interrupted, e.g. because PREvant crashed, might have left
+        // superseded exited containers behind that would be listed as services otherwise.
+        match Self::prune_exited_containers(app_name).await {
+            Ok(0) => {}
+            Ok(count) => info!("Removed {count} exited containers of {app_name}"),
+            Err(err) => warn!("Cannot remove exited containers of {app_name}: {err}"),
         }
 
-        let mut futures = container_details
-            .into_iter()
-            .map(|details| async {
-                let id = details
-                    .id
-                    .as_ref()
-                    .expect("id is mandatory for a docker container");
-
-                docker.remove_container(id, None).await?;
-                trace!("Deleted container {id} for {app_name}");
-
-                Ok::<ContainerInspectResponse, BollardError>(details)
-            })
-            .map(Box::pin)
-            .collect::<FuturesUnordered<_>>();
+        let network_id = self
+            .create_or_get_network_id(app_name, container_config)
+            .await?;
 
-        let mut services = Vec::with_capacity(futures.len());
-        while let Some(result) = futures.next().await {
-            let container = result?;
-            services.push(Service::try_from(container)?);
+        if let Some(network_id) = &network_id {
+            self.connect_traefik(network_id).await?;
         }
+        let peer_network_id = if network_id.is_some() && self.config.is_peer_app(app_name) {
+            Some(
+                Self::create_or_get_network(
+                    PEER_NETWORK_NAME,
+                    None,
+                    &NetworkDriver::Bridge,
+                    container_config.network_mtu(),
+                    None,
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+        let existing_volumes = Self::fetch_existing_volumes(app_name).await?;
 
-        self.delete_network(app_name).await?;
-        self.delete_volume_mount(app_name).await?;
+        let running_ipc_modes = Self::running_ipc_modes(app_name, services).await?;
+        Self::check_ipc_modes(services, &running_ipc_modes)?;
+        let waves = Self::deployment_waves(services)?;
+
+        let (slot, health_check_timeout) = match deployment_unit.rollout_strategy() {
+            RolloutStrategy::Recreate => (None, None),
+            RolloutStrategy::BlueGreen {
+                health_check_timeout,
+            } => (Some(DeploymentSlot::Green), Some(*health_check_timeout)),
+            RolloutStrategy::Canary { weight_percent, .. } => (
+                Some(DeploymentSlot::Canary {
+                    weight_percent: *weight_percent,
+                }),
+                None,
+            ),
+        };
+        let blue_containers = match health_check_timeout {
+            Some(_) => Self::get_app_containers(Some(app_name), None).await?,
+            None => Vec::new(),
+        };
 
-        Ok(services)
-    }
+        let deploy_id = Uuid::new_v4().to_string();
+        let mut results = Vec::with_capacity(services.len());
+        let wave_count = waves.len();
+        for (wave_index, wave) in waves.into_iter().enumerate() {
+            let mut futures = wave
+                .into_iter()
+                .map(|service| {
+                    self.start_container(
+                        app_name,
+                        network_id.as_deref(),
+                        peer_network_id.as_deref(),
+                        service,
+                        container_config,
+                        &existing_volumes,
+                        slot,
+                        Self::is_ipc_shared(service, services, &running_ipc_modes),
+                        &deploy_id,
+                    )
+                })
+                .map(Box::pin)
+                .collect::<FuturesUnordered<_>>();
 
-    async fn start_container(
-        &self,
-        app_name: &AppName,
-        network_id: &str,
-        service: &DeployableService,
-        container_config: &ContainerConfig,
-        existing_volumes: &VolumeListResponse,
-    ) -> Result<Service, DockerInfrastructureError> {
-        let docker = Docker::connect_with_socket_defaults()?;
-        let service_name = service.service_name();
-        let service_image = service.image();
+            let mut container_ids = Vec::new();
+            while let Some(result) = futures.next().await {
+                if let Ok(service) = &result {
+                    container_ids.push(service.id().clone());
+                }
+                match health_check_timeout {
+                    Some(_) => results.push(result),
+                    None => results.push(Ok(result?)),
+                }
+            }
 
-        if let Image::Named { .. } = service_image {
-            self.pull_image(app_name, service).await?;
+            // The services of the next wave depend on the services of this wave.
+            if wave_index + 1 < wave_count {
+                let ready = futures::future::try_join_all(container_ids.iter().map(|id| {
+                    Self::wait_for_service_healthy(id, container_config.readiness_timeout())
+                }))
+                .await;
+                if let Err(err) = ready {
+                    match health_check_timeout {
+                        Some(_) => {
+                            results.push(Err(err));
+                            break;
+                        }
+                        None => return Err(err),
+                    }
+                }
+            }
         }
-        let mut image_to_delete = None;
-        if let Some(ref container_info) = Self::get_app_container(app_name, service_name).await? {
-            let container_details = docker
-                .inspect_container(
-                    container_info
-                        .id
+
+        if let RolloutStrategy::Canary {
+            promote_after: Some(promote_after),
+            ..
+        } = deployment_unit.rollout_strategy()
+        {
+            let canary_containers = Self::get_slot_containers(app_name, CANARY_DEPLOYMENT_SLOT)
+                .await?
+                .into_iter()
+                .filter(|container| {
+                    container
+                        .labels
                         .as_ref()
-                        .expect("id is mandatory for a docker container"),
-                    None,
-                )
-                .await?;
+                        .and_then(|labels| labels.get(DEPLOY_ID_LABEL))
+                        == Some(&deploy_id)
+                })
+                .collect::<Vec<_>>();
+            self.schedule_canary_promotion(app_name.clone(), canary_containers, *promote_after);
+        }
 
-            match service.strategy() {
-                DeploymentStrategy::RedeployOnImageUpdate(image_id)
-                    if container_details.image.as_ref() == Some(image_id) =>
-                {
-                    debug!("Container {container_info:?} of review app {app_name:?} is still running with the desired image id {image_id}");
-                    return Service::try_from(container_details);
-                }
-                DeploymentStrategy::RedeployNever => {
-                    debug!(
-                        "Container {container_info:?} of review app {app_name:?} already deployed."
-                    );
-                    return Service::try_from(container_details);
-                }
-                DeploymentStrategy::RedeployAlways
-                | DeploymentStrategy::RedeployOnImageUpdate(_) => {}
-            };
+        let Some(health_check_timeout) = health_check_timeout else {
+            return results.into_iter().collect();
+        };
 
-            info!("Removing container {container_info:?} of review app {app_name:?}");
+        let green_containers = Self::get_slot_containers(app_name, GREEN_DEPLOYMENT_SLOT)
+            .await?
+            .into_iter()
+            .filter(|container| {
+                !blue_containers
+                    .iter()
+                    .any(|blue_container| blue_container.id == container.id)
+            })
+            .collect::<Vec<_>>();
 
-            if container_details
-                .state
-                .map(|state| state.running == Some(true))
-                .is_some()
-            {
-                docker
-                    .stop_container(
-                        container_details
+        let services = match results.into_iter().collect::<Result<Vec<_>, _>>() {
+            Ok(services) => {
+                futures::future::try_join_all(green_containers.iter().map(|container| {
+                    Self::wait_for_service_healthy(
+                        container
                             .id
-                            .as_ref()
+                            .as_deref()
                             .expect("id is mandatory for a docker container"),
-                        None,
+                        health_check_timeout,
                     )
-                    .await?;
+                }))
+                .await
+                .map(|_| services)
             }
-            docker
-                .remove_container(
-                    container_details
-                        .id
-                        .as_ref()
-                        .expect("id is mandatory for a docker container"),
-                    None,
+            Err(err) => Err(err),
+        };
+
+        match services {
+            Ok(services) => {
+                Self::promote_containers(
+                    &blue_containers,
+                    &green_containers,
+                    GREEN_DEPLOYMENT_SLOT,
                 )
                 .await?;
-            image_to_delete = container_details.image;
+                Ok(services)
+            }
+            Err(err) => {
+                info!("Blue/green deployment of {app_name} failed, removing the new containers: {err}");
+                for container in &green_containers {
+                    Self::force_remove_container(container).await?;
+                }
+                Err(err)
+            }
         }
+    }
 
-        info!(
-            "Creating new review app container for {app_name:?}: service={service_name:?} with image={service_image:?} ({:?})",
-            service.container_type(),
-        );
-
-        let host_config_binds =
-            Self::create_host_config_binds(app_name, existing_volumes, service).await?;
-
-        let options =
-            Self::create_container_options(app_name, service, container_config, &host_config_binds);
-
-        let container_info = docker
-            .create_container::<&str, String>(None, options)
-            .await?;
-        let container_id = container_info.id.as_ref();
+    /// Aborts the deployment if it does not finish within `max_deployment_duration`. In that case,
+    /// the containers that have been started by the aborted deployment are removed. The caller
+    /// must hold the deployment lock of the app, otherwise the containers of a concurrent
+    /// deployment would be considered as started by the aborted deployment.
+    async fn deploy_services_with_timeout(
+        &self,
+        deployment_unit: &DeploymentUnit,
+        container_config: &ContainerConfig,
+        max_deployment_duration: Duration,
+    ) -> Result<Vec<Service>, DockerInfrastructureError> {
+        let app_name = deployment_unit.app_name();
+        let existing_container_ids = Self::get_app_containers(Some(app_name), None)
+            .await?
+            .into_iter()
+            .chain(Self::get_replica_containers(Some(app_name), None).await?)
+            .filter_map(|container| container.id)
+            .collect::<HashSet<_>>();
 
-        debug!("Created container: {container_info:?}");
-        self.copy_file_data(&container_info, service).await?;
+        if let Ok(result) = tokio::time::timeout(
+            max_deployment_duration,
+            self.deploy_services_impl(deployment_unit, container_config),
+        )
+        .await
+        {
+            return result;
+        }
 
-        docker
-            .start_container(container_id, None::<StartContainerOptions<&str>>)
-            .await?;
-        debug!("Started container: {container_info:?}");
+        let started_containers = Self::get_app_containers(Some(app_name), None)
+            .await?
+            .into_iter()
+            .filter(|container| {
+                container
+                    .id
+                    .as_ref()
+                    .map_or(false, |id| !existing_container_ids.contains(id))
+            })
+            .collect::<Vec<_>>();
+        let started_services = started_containers
+            .iter()
+            .filter_map(|container| container.labels.as_ref()?.get(SERVICE_NAME_LABEL))
+            .collect::<HashSet<_>>();
+        let pending_services = deployment_unit
+            .services()
+            .iter()
+            .map(|service| service.service_name())
+            .filter(|service_name| !started_services.contains(service_name))
+            .collect::<Vec<_>>();
+        error!(
+            "Deployment of {app_name} did not finish within {max_deployment_duration:?} (started: {started_services:?}, pending: {pending_services:?}), removing the started containers."
+        );
 
-        docker
-            .connect_network(
-                network_id,
-                ConnectNetworkOptions::<&str> {
-                    container: container_id,
-                    endpoint_config: EndpointSettings {
-                        aliases: Some(vec![service_name.to_string()]),
-                        ..Default::default()
-                    },
-                },
-            )
-            .await?;
+        for container in &started_containers {
+            Self::force_remove_container(container).await?;
+        }
+        for container in Self::get_replica_containers(Some(app_name), None).await? {
+            if container
+                .id
+                .as_ref()
+                .map_or(false, |id| !existing_container_ids.contains(id))
+            {
+                Self::force_remove_container(&container).await?;
+            }
+        }
+        // The deployment might have been aborted while an init container was running.
+        for container in &Self::get_init_containers(app_name).await? {
+            Self::force_remove_container(container).await?;
+        }
 
-        debug!("Connected container {container_id} to {network_id}");
+        Err(DockerInfrastructureError::DeploymentTimeout {
+            app_name: app_name.to_string(),
+            duration_secs: max_deployment_duration.as_secs(),
+        })
+    }
 
-        let container_details = docker.inspect_container(container_id, None).await?;
+    /// Returns the containers of the app that have been started in the given deployment slot.
+    /// Docker cannot change the labels of existing containers, thus, the result includes promoted
+    /// containers as well.
+    async fn get_slot_containers(
+        app_name: &AppName,
+        slot: &str,
+    ) -> Result<Vec<ContainerSummary>, BollardError> {
+        let filters = HashMap::from([(
+            String::from("label"),
+            vec![
+                format!("{APP_NAME_LABEL}={app_name}"),
+                format!("{DEPLOYMENT_SLOT_LABEL}={slot}"),
+            ],
+        )]);
 
-        if let Some(image) = image_to_delete {
-            info!("Clean up image {image:?} of app {app_name:?}");
-            match docker.remove_image(&image, None, None).await {
-                Ok(output) => {
-                    for o in output {
-                        debug!("{o:?}");
-                    }
-                }
-                Err(err) => debug!("Could not clean up image: {err:?}"),
-            };
-        }
-        Service::try_from(container_details)
+        Self::get_containers(filters).await
     }
 
-    fn create_container_options<'a>(
-        app_name: &'a str,
-        service_config: &'a ServiceConfig,
-        container_config: &'a ContainerConfig,
-        host_config_binds: &'a [String],
-    ) -> bollard::container::Config<String> {
-        let env = service_config.env().map(|env| {
-            env.iter()
-                .map(|v| format!("{}={}", v.key(), v.value().unsecure()))
-                .collect::<Vec<String>>()
-        });
+    /// Replaces the containers by their successors of the given slot: the replaced containers of
+    /// the services are removed and the successors are renamed to the names of their predecessors.
+    async fn promote_containers(
+        replaced_containers: &[ContainerSummary],
+        successors: &[ContainerSummary],
+        slot: &str,
+    ) -> Result<(), BollardError> {
+        let docker = Docker::connect_with_socket_defaults()?;
 
-        let mut labels: HashMap<String, String> = HashMap::new();
+        for successor in successors {
+            let service_name = successor
+                .labels
+                .as_ref()
+                .and_then(|labels| labels.get(SERVICE_NAME_LABEL));
 
-        let traefik_frontend = format!(
-            "PathPrefixStrip: /{app_name}/{service_name}/; PathPrefix:/{app_name}/{service_name}/;",
-            app_name = app_name,
-            service_name = service_config.service_name()
-        );
-        labels.insert("traefik.frontend.rule".to_string(), traefik_frontend);
+            for replaced_container in replaced_containers.iter().filter(|replaced_container| {
+                replaced_container.id != successor.id
+                    && replaced_container
+                        .labels
+                        .as_ref()
+                        .and_then(|labels| labels.get(SERVICE_NAME_LABEL))
+                        == service_name
+            }) {
+                info!(
+                    "Removing container {:?} replaced by its {slot} successor",
+                    replaced_container.id
+                );
+                Self::force_remove_container(replaced_container).await?;
+            }
 
-        if let Some(config_labels) = service_config.labels() {
-            for (k, v) in config_labels {
-                labels.insert(k.to_string(), v.to_string());
+            let container_name = successor
+                .names
+                .as_ref()
+                .and_then(|names| names.first())
+                .and_then(|name| {
+                    name.trim_start_matches('/')
+                        .strip_suffix(&format!("-{slot}"))
+                });
+            if let (Some(id), Some(container_name)) = (&successor.id, container_name) {
+                docker
+                    .rename_container(
+                        id,
+                        RenameContainerOptions {
+                            name: container_name,
+                        },
+                    )
+                    .await?;
             }
         }
 
-        labels.insert(APP_NAME_LABEL.to_string(), app_name.to_string());
-        labels.insert(
-            SERVICE_NAME_LABEL.to_string(),
-            service_config.service_name().to_string(),
-        );
-        let container_type = service_config.container_type().to_string();
-        labels.insert(CONTAINER_TYPE_LABEL.to_string(), container_type);
-        let image_name = service_config.image().to_string();
-        labels.insert(IMAGE_LABEL.to_string(), image_name);
-
-        let replicated_env = service_config
-            .env()
-            .and_then(super::replicated_environment_variable_to_json)
-            .map(|value| value.to_string());
+        Ok(())
+    }
 
-        if let Some(replicated_env) = replicated_env {
-            labels.insert(REPLICATED_ENV_LABEL.to_string(), replicated_env);
-        }
+    /// Returns the canaries of the app that have been neither promoted nor rolled back yet.
+    async fn get_canary_containers(
+        app_name: &AppName,
+        service_name: Option<&str>,
+    ) -> Result<Vec<ContainerSummary>, BollardError> {
+        Ok(Self::get_slot_containers(app_name, CANARY_DEPLOYMENT_SLOT)
+            .await?
+            .into_iter()
+            .filter(|container| {
+                let labels = container.labels.as_ref();
+                service_name.map_or(true, |service_name| {
+                    labels
+                        .and_then(|labels| labels.get(SERVICE_NAME_LABEL))
+                        .map(String::as_str)
+                        == Some(service_name)
+                })
+            })
+            // Promoted canaries have been renamed to the names of their predecessors.
+            .filter(|container| {
+                container
+                    .names
+                    .as_ref()
+                    .and_then(|names| names.first())
+                    .map_or(false, |name| name.ends_with("-canary"))
+            })
+            .collect())
+    }
 
-        let memory = container_config
-            .memory_limit()
-            .map(|mem| mem.as_u64() as i64);
+    /// Replaces the running containers of the services by their canaries.
+    async fn promote_canary_containers(
+        app_name: &AppName,
+        canary_containers: &[ContainerSummary],
+    ) -> Result<(), BollardError> {
+        let replaced_containers = Self::get_app_containers(Some(app_name), None)
+            .await?
+            .into_iter()
+            .filter(|container| {
+                !canary_containers
+                    .iter()
+                    .any(|canary_container| canary_container.id == container.id)
+            })
+            .collect::<Vec<_>>();
 
-        bollard::container::Config {
-            image: Some(service_config.image().to_string()),
-            env,
-            labels: Some(labels),
-            host_config: Some(HostConfig {
-                restart_policy: Some(RestartPolicy {
-                    name: Some(RestartPolicyNameEnum::ALWAYS),
-                    ..Default::default()
-                }),
-                binds: Some(host_config_binds.to_vec()),
-                memory,
-                memory_swap: memory,
-                ..Default::default()
-            }),
-            ..Default::default()
-        }
+        Self::promote_containers(
+            &replaced_containers,
+            canary_containers,
+            CANARY_DEPLOYMENT_SLOT,
+        )
+        .await
     }
 
-    async fn copy_file_data(
+    /// Promotes the canaries after `promote_after` unless they have been promoted or rolled back
+    /// in the meantime. Like a deployment, the promotion holds the deployment lock of the app.
+    fn schedule_canary_promotion(
         &self,
-        container_info: &ContainerCreateResponse,
-        service_config: &ServiceConfig,
-    ) -> Result<(), BollardError> {
-        let files = match service_config.files() {
-            None => return Ok(()),
-            Some(files) => files.clone(),
-        };
-
-        debug!(
-            "Copy data to container: {container_info:?} (service = {})",
-            service_config.service_name()
-        );
+        app_name: AppName,
+        canary_containers: Vec<ContainerSummary>,
+        promote_after: Duration,
+    ) {
+        if canary_containers.is_empty() {
+            return;
+        }
 
-        let docker = Docker::connect_with_socket_defaults()?;
+        let deployment_locks = self.deployment_locks.clone();
+        let deployment_lock_timeout = self.config.deployment_lock_timeout();
+        tokio::spawn(async move {
+            tokio::time::sleep(promote_after).await;
 
-        let mut tar_builder = tar::Builder::new(Vec::new());
+            let _deployment_lock = match deployment_locks
+                .lock(&app_name, deployment_lock_timeout)
+                .await
+            {
+                Ok(Some(deployment_lock)) => deployment_lock,
+                Ok(None) => {
+                    warn!("Cannot promote the canaries of {app_name}: another deployment holds the lock");
+                    return;
+                }
+                Err(err) => {
+                    warn!("Cannot promote the canaries of {app_name}: {err}");
+                    return;
+                }
+            };
 
-        for (path, data) in files.into_iter() {
-            let mut header = tar::Header::new_gnu();
-            let file_contents = data.into_unsecure();
-            header.set_size(file_contents.as_bytes().len() as u64);
-            header.set_mode(0o644);
-            tar_builder.append_data(
-                &mut header,
-                path.to_path_buf()
-                    .iter()
-                    .skip(1)
-                    .collect::<std::path::PathBuf>(),
-                file_contents.as_bytes(),
-            )?;
-        }
+            let result = async {
+                let canary_containers = Self::get_canary_containers(&app_name, None)
+                    .await?
+                    .into_iter()
+                    .filter(|container| {
+                        canary_containers
+                            .iter()
+                            .any(|scheduled| scheduled.id == container.id)
+                    })
+                    .collect::<Vec<_>>();
+                Self::promote_canary_containers(&app_name, &canary_containers).await
+            }
+            .await;
 
-        docker
-            .upload_to_container(
-                &container_info.id,
-                Some(UploadToContainerOptions {
-                    path: "/",
-                    ..Default::default()
-                }),
-                tar_builder.into_inner()?.into(),
-            )
-            .await?;
+            match result {
+                Ok(()) => info!("Promoted the canaries of {app_name}"),
+                Err(err) => warn!("Cannot promote the canaries of {app_name}: {err}"),
+            }
+        });
+    }
 
-        Ok(())
+    async fn force_remove_container(container: &ContainerSummary) -> Result<(), BollardError> {
+        Self::force_stop_container(
+            container
+                .id
+                .as_deref()
+                .expect("id is mandatory for a docker container"),
+        )
+        .await
     }
 
-    async fn fetch_existing_volumes(
-        app_name: &AppName,
-    ) -> Result<VolumeListResponse, BollardError> {
-        let docker = Docker::connect_with_socket_defaults()?;
-        docker
-            .list_volumes(Some(ListVolumesOptions {
-                filters: HashMap::from([(
-                    "label".to_string(),
-                    vec![format!("{APP_NAME_LABEL}={app_name}")],
-                )]),
-            }))
-            .await
+    /// Removes the exited containers of the app that have been superseded by other containers of
+    /// the same service. The exited containers of stopped services are kept.
+    async fn prune_exited_containers(app_name: &AppName) -> Result<usize, BollardError> {
+        let exited_containers =
+            superseded_exited_containers(Self::get_app_containers(Some(app_name), None).await?);
+
+        for container in &exited_containers {
+            debug!("Removing exited container {:?} of {app_name}", container.id);
+            Self::force_remove_container(container).await?;
+        }
+
+        Ok(exited_containers.len())
     }
 
-    async fn create_docker_volume(
-        app_name: &AppName,
-        service: &DeployableService,
-    ) -> Result<String, BollardError> {
+    /// Kills and removes the container, equivalent to `docker rm -f`.
+    async fn force_stop_container(container_id: &str) -> Result<(), BollardError> {
         let docker = Docker::connect_with_socket_defaults()?;
 
-        let mut labels: HashMap<&str, &str> = HashMap::new();
-        labels.insert(APP_NAME_LABEL, app_name);
-        labels.insert(SERVICE_NAME_LABEL, service.service_name());
-
         docker
-            .create_volume(CreateVolumeOptions {
-                labels,
-                ..Default::default()
-            })
+            .remove_container(
+                container_id,
+                Some(RemoveContainerOptions {
+                    force: true,
+                    ..Default::default()
+                }),
+            )
             .await
-            .map(|vol| vol.name)
     }
 
-    async fn create_host_config_binds(
+    async fn stop_services_impl(
+        &self,
         app_name: &AppName,
-        existing_volume: &VolumeListResponse,
-        service: &DeployableService,
-    ) -> Result<Vec<String>, BollardError> {
-        let mut host_binds = Vec::new();
+        preserve_volumes: bool,
+    ) -> Result<Vec<Service>, DockerInfrastructureError> {
+        let container_details = match self
+            .get_container_details(Some(app_name), None)
+            .await?
+            .get_vec(app_name)
+        {
+            None => return Ok(vec![]),
+            Some(services) => services.clone(),
+        };
 
-        if service.declared_volumes().is_empty() {
-            return Ok(host_binds);
-        }
+        let docker = Docker::connect_with_socket_defaults()?;
 
-        let service_volume = existing_volume
-            .volumes
-            .as_ref()
-            .and_then(|volume| {
-                volume
-                    .iter()
-                    .find(|vol| vol.labels.get(SERVICE_NAME_LABEL) == Some(service.service_name()))
+        let mut futures = container_details
+            .clone()
+            .into_iter()
+            .filter(|p| {
+                p.state.as_ref().and_then(|state| state.status)
+                    == Some(ContainerStateStatusEnum::RUNNING)
             })
-            .map(|info| info.name.clone());
-
-        let volume_name = match service_volume {
-            Some(name) => name,
-            None => Self::create_docker_volume(app_name, service).await?,
-        };
-
-        for declared_volume in service.declared_volumes() {
-            host_binds.push(format!("{volume_name}:{declared_volume}"));
-        }
-
-        Ok(host_binds)
-    }
-
-    async fn pull_image(
-        &self,
-        app_name: &AppName,
-        config: &ServiceConfig,
-    ) -> Result<(), BollardError> {
-        let image = config.image();
+            .map(|details| async {
+                let id = details
+                    .id
+                    .as_ref()
+                    .expect("id is mandatory for a docker container");
 
-        info!(
-            "Pulling {image:?} for {:?} of app {app_name:?}",
-            config.service_name()
-        );
+                docker.stop_container(id, None).await?;
 
-        let pull_results = pull(image, &self.config).await?;
+                Ok::<ContainerInspectResponse, BollardError>(details)
+            })
+            .map(Box::pin)
+            .collect::<FuturesUnordered<_>>();
 
-        for pull_result in pull_results {
-            debug!("{pull_result:?}");
+        while let Some(result) = futures.next().await {
+            let container = result?;
+            let id = container
+                .id
+                .expect("id is mandatory for a docker container");
+            trace!("Stopped container {id} for {app_name}");
         }
 
-        Ok(())
-    }
+        let mut futures = container_details
+            .into_iter()
+            .map(|details| async {
+                let id = details
+                    .id
+                    .as_ref()
+                    .expect("id is mandatory for a docker container");
 
-    async fn get_containers(
-        filters: HashMap<String, Vec<String>>,
-    ) -> Result<Vec<ContainerSummary>, BollardError> {
-        let docker = Docker::connect_with_socket_defaults()?;
+                docker.remove_container(id, None).await?;
+                trace!("Deleted container {id} for {app_name}");
 
-        let list_options = Some(ListContainersOptions {
-            all: true,
-            filters,
-            ..Default::default()
-        });
+                Ok::<ContainerInspectResponse, BollardError>(details)
+            })
+            .map(Box::pin)
+            .collect::<FuturesUnordered<_>>();
 
-        docker.list_containers(list_options).await
-    }
+        let mut services = Vec::with_capacity(futures.len());
+        while let Some(result) = futures.next().await {
+            let container = result?;
+            services.push(Service::try_from(container)?);
+        }
 
-    async fn get_app_containers(
-        app_name: Option<&AppName>,
-        service_name: Option<&str>,
-    ) -> Result<Vec<ContainerSummary>, BollardError> {
-        let mut filters = HashMap::new();
-        if let Some(app_name_filter) =
-            label_filter(APP_NAME_LABEL, app_name.map(|app_name| app_name.as_str()))
-        {
-            filters
-                .entry("label".to_string())
-                .or_insert_with(Vec::new)
-                .push(app_name_filter);
+        // Init containers of an aborted deployment and replicas would prevent the removal of the
+        // network.
+        for container in &Self::get_init_containers(app_name).await? {
+            Self::force_remove_container(container).await?;
+        }
+        for container in &Self::get_replica_containers(Some(app_name), None).await? {
+            Self::force_remove_container(container).await?;
         }
 
-        if let Some(service_name_filter) = label_filter(SERVICE_NAME_LABEL, service_name) {
-            filters
-                .entry("label".to_string())
-                .or_insert_with(Vec::new)
-                .push(service_name_filter);
+        if let Some(consul_config) = self.config.consul_integration() {
+            let consul = ConsulClient::new(consul_config);
+            for service in &services {
+                let service_id = consul_service_id(app_name, service.service_name());
+                if let Err(err) = consul.deregister(&service_id).await {
+                    warn!("Cannot deregister {service_id} from Consul: {err}");
+                }
+            }
         }
 
-        Self::get_containers(filters).await
+        self.delete_network(app_name).await?;
+        self.delete_volume_mount(app_name, preserve_volumes).await?;
+
+        Ok(services)
     }
 
-    async fn get_status_change_containers(
+    async fn start_container(
         &self,
-        app_name: Option<&AppName>,
-        status_id: Option<&str>,
-    ) -> Result<Vec<ContainerSummary>, BollardError> {
-        let mut label_filters = vec![];
-        if let Some(app_name_filter) =
-            label_filter(APP_NAME_LABEL, app_name.map(|app_name| app_name.as_str()))
-        {
-            label_filters.push(app_name_filter);
+        app_name: &AppName,
+        network_id: Option<&str>,
+        peer_network_id: Option<&str>,
+        service: &DeployableService,
+        container_config: &ContainerConfig,
+        existing_volumes: &VolumeListResponse,
+        slot: Option<DeploymentSlot>,
+        ipc_shareable: bool,
+        deploy_id: &str,
+    ) -> Result<Service, DockerInfrastructureError> {
+        let docker = Docker::connect_with_socket_defaults()?;
+        let service_name = service.service_name();
+        let service_image = service.image();
+
+        if !service.config_map_mounts().is_empty() || !service.secret_mounts().is_empty() {
+            debug!("Ignoring ConfigMap and Secret mounts of {service_name} because they are only supported by Kubernetes");
         }
 
-        if let Some(status_id_filter) = label_filter(STATUS_ID, status_id) {
-            label_filters.push(status_id_filter);
+        if let Some(shm_size_bytes) = service.shm_size_bytes() {
+            match docker.info().await.map(|info| info.mem_total) {
+                Ok(Some(mem_total)) if shm_size_bytes > mem_total as u64 => {
+                    warn!(
+                        "Shared memory size of {} for {service_name} in {app_name} exceeds the memory of the Docker host ({})",
+                        ByteSize(shm_size_bytes),
+                        ByteSize(mem_total as u64)
+                    );
+                }
+                Ok(_) => {}
+                Err(err) => debug!("Cannot determine memory of the Docker host: {err}"),
+            }
         }
 
-        let filters = HashMap::from([("label".to_string(), label_filters)]);
-        Self::get_containers(filters).await
-    }
+        self.pull_image_if_needed(app_name, service, service_image, container_config)
+            .await?;
+        let existing_container = match Self::get_app_container(app_name, service_name).await? {
+            Some(container_info) => {
+                let container_details = docker
+                    .inspect_container(
+                        container_info
+                            .id
+                            .as_ref()
+                            .expect("id is mandatory for a docker container"),
+                        None,
+                    )
+                    .await?;
+                Some((container_info, container_details))
+            }
+            None => None,
+        };
+        let replicas = Self::replica_count(
+            service,
+            existing_container
+                .as_ref()
+                .map(|(_, container_details)| container_details),
+        )?;
+        // A canary shares the traffic of the running container, without it, the service is
+        // deployed as usual.
+        let slot = match slot {
+            Some(DeploymentSlot::Canary { .. }) if existing_container.is_none() => None,
+            slot => slot,
+        };
 
-    async fn get_app_container(
-        app_name: &AppName,
-        service_name: &str,
-    ) -> Result<Option<ContainerSummary>, BollardError> {
-        Self::get_app_containers(Some(app_name), Some(service_name))
-            .await
-            .map(|list| list.into_iter().next())
-    }
+        if let Some((container_info, container_details)) = &existing_container {
+            match service.strategy() {
+                DeploymentStrategy::RedeployOnImageUpdate(image_id)
+                    if container_details.image.as_ref() == Some(image_id) =>
+                {
+                    debug!("Container {container_info:?} of review app {app_name:?} is still running with the desired image id {image_id}");
+                    return Service::try_from(container_details.clone());
+                }
+                DeploymentStrategy::RedeployNever => {
+                    debug!(
+                        "Container {container_info:?} of review app {app_name:?} already deployed."
+                    );
+                    return Service::try_from(container_details.clone());
+                }
+                DeploymentStrategy::RedeployAlways
+                | DeploymentStrategy::RedeployOnImageUpdate(_) => {}
+            };
+        }
 
-    async fn get_container_details(
-        &self,
-        app_name: Option<&AppName>,
-        service_name: Option<&str>,
-    ) -> Result<MultiMap<AppName, ContainerInspectResponse>, DockerInfrastructureError> {
-        debug!("Resolve container details for app {app_name:?}");
+        self.run_init_containers(app_name, network_id, service, container_config)
+            .await?;
 
-        let container_list = Self::get_app_containers(app_name, service_name).await?;
+        let created_at = existing_container
+            .as_ref()
+            .and_then(|(_, container_details)| container_details.config.as_ref()?.labels.as_ref())
+            .and_then(|labels| labels.get(CREATED_AT_LABEL).cloned());
 
-        let mut container_details = MultiMap::new();
-        for container in container_list.into_iter() {
-            if let Some(details) = not_found_to_none(inspect(container).await)? {
-                let app_name = match app_name {
-                    Some(app_name) => app_name.clone(),
-                    None => details
-                        .config
-                        .as_ref()
-                        .and_then(|con| {
-                            con.labels.as_ref().and_then(|lab| {
-                                lab.get(APP_NAME_LABEL)
-                                    .and_then(|app_name| AppName::from_str(app_name).ok())
-                            })
-                        })
-                        .unwrap(),
-                };
-                container_details.insert(app_name, details);
-            }
-        }
+        info!(
+            "Creating new review app container for {app_name:?}: service={service_name:?} with image={service_image:?} ({:?})",
+            service.container_type(),
+        );
 
-        Ok(container_details)
-    }
-}
+        let host_config_binds =
+            Self::create_host_config_binds(app_name, existing_volumes, service).await?;
 
-#[async_trait]
-impl Infrastructure for DockerInfrastructure {
-    async fn get_services(&self) -> Result<MultiMap<AppName, Service>> {
-        let mut apps = MultiMap::new();
-        let container_details = self.get_container_details(None, None).await?;
+        let secret_env = self.resolve_secret_env(app_name, service).map_err(|err| {
+            DockerInfrastructureError::SecretResolution {
+                service_name: service_name.clone(),
+                err,
+            }
+        })?;
 
-        for (app_name, details_vec) in container_details.into_iter() {
-            for details in details_vec {
-                let service = match Service::try_from(details) {
-                    Ok(service) => service,
-                    Err(e) => {
-                        debug!("Container does not provide required data: {e:?}");
-                        continue;
-                    }
-                };
+        let inherited_env = Self::resolve_inherited_env(service, container_config, |name| {
+            std::env::var(name).ok()
+        })?;
 
-                apps.insert(app_name.clone(), service);
+        let mut options =
+            Self::create_container_options(app_name, service, container_config, &host_config_binds);
+        if !inherited_env.is_empty() {
+            options
+                .env
+                .get_or_insert_with(Vec::new)
+                .extend(inherited_env);
+        }
+        if !secret_env.is_empty() {
+            options.env.get_or_insert_with(Vec::new).extend(secret_env);
+        }
+        let labels = options.labels.get_or_insert_with(HashMap::new);
+        labels.insert(
+            CREATED_AT_LABEL.to_string(),
+            created_at.unwrap_or_else(|| Utc::now().to_rfc3339()),
+        );
+        labels.insert(
+            VERSION_LABEL.to_string(),
+            clap::crate_version!().to_string(),
+        );
+        labels.insert(DEPLOY_ID_LABEL.to_string(), deploy_id.to_string());
+        labels.insert(REPLICAS_LABEL.to_string(), replicas.to_string());
+        if let Some(ipc_mode) = service.ipc_mode() {
+            labels.insert(IPC_MODE_LABEL.to_string(), ipc_mode.to_string());
+        }
+        if !service.depends_on().is_empty() {
+            labels.insert(DEPENDS_ON_LABEL.to_string(), service.depends_on().join(","));
+        }
+        if let Some(subdomain_routing) = self.config.subdomain_routing() {
+            Self::apply_subdomain_routing(labels, app_name, service_name, subdomain_routing);
+        }
+        if let Some(slot) = slot {
+            labels.insert(
+                DEPLOYMENT_SLOT_LABEL.to_string(),
+                String::from(slot.label_value()),
+            );
+        }
+        Self::apply_traefik_backend(labels, app_name, service_name);
+        if let Some(DeploymentSlot::Canary { weight_percent }) = slot {
+            Self::apply_canary_weight(labels, app_name, service_name, weight_percent);
+        }
+        if let Some(sticky_sessions) = service.sticky_sessions() {
+            Self::apply_sticky_sessions(
+                labels,
+                app_name,
+                service_name,
+                sticky_sessions.cookie_name(),
+            );
+        }
+        if network_id.is_none() {
+            options
+                .host_config
+                .get_or_insert_with(HostConfig::default)
+                .network_mode = Some(String::from("none"));
+        }
+        match service.ipc_mode() {
+            Some(IpcMode::Container {
+                service_name: ipc_service_name,
+            }) => {
+                let ipc_container_id =
+                    Self::ipc_container_id(app_name, service_name, ipc_service_name, slot).await?;
+                options
+                    .host_config
+                    .get_or_insert_with(HostConfig::default)
+                    .ipc_mode = Some(format!("container:{ipc_container_id}"));
             }
+            None if ipc_shareable => {
+                options
+                    .host_config
+                    .get_or_insert_with(HostConfig::default)
+                    .ipc_mode = Some(String::from("shareable"));
+            }
+            _ => {}
         }
 
-        Ok(apps)
-    }
+        let mut container_name = Self::container_name_of(app_name, service);
+        if let Some(slot) = slot {
+            container_name.push('-');
+            container_name.push_str(slot.label_value());
+        }
 
-    async fn deploy_services(
-        &self,
-        status_id: &str,
-        deployment_unit: &DeploymentUnit,
-        container_config: &ContainerConfig,
-    ) -> Result<Vec<Service>> {
-        let deployment_container = self
-            .create_status_change_container(status_id, deployment_unit.app_name())
+        // The successors of blue/green and canary deployments are not routed to before they have
+        // been promoted, the running container is only replaced after a regular deployment is
+        // known to become ready.
+        if let (None, Some(readiness_url)) = (slot, service.readiness_url()) {
+            self.probe_readiness(
+                service,
+                network_id,
+                &container_name,
+                options.clone(),
+                readiness_url,
+                container_config.readiness_timeout(),
+            )
             .await?;
+        }
 
-        let result = self
-            .deploy_services_impl(deployment_unit, container_config)
-            .await;
-
-        delete(deployment_container).await?;
-
-        Ok(result?)
-    }
+        let mut image_to_delete = None;
+        if let Some((container_info, container_details)) = existing_container {
+            if let Some(slot) = slot {
+                debug!(
+                    "Keeping container {container_info:?} of review app {app_name:?} next to its {} successor",
+                    slot.label_value()
+                );
+            } else {
+                info!("Removing container {container_info:?} of review app {app_name:?}");
+
+                if container_details
+                    .state
+                    .as_ref()
+                    .map(|state| state.running == Some(true))
+                    .is_some()
+                {
+                    docker
+                        .stop_container(
+                            container_details
+                                .id
+                                .as_ref()
+                                .expect("id is mandatory for a docker container"),
+                            None,
+                        )
+                        .await?;
+                }
+                docker
+                    .remove_container(
+                        container_details
+                            .id
+                            .as_ref()
+                            .expect("id is mandatory for a docker container"),
+                        None,
+                    )
+                    .await?;
+                image_to_delete = container_details.image;
+            }
+        }
 
-    async fn get_status_change(&self, status_id: &str) -> Result<Option<Vec<Service>>> {
-        Ok(
-            match self
-                .find_status_change_container(status_id)
-                .await?
-                .as_ref()
-                .and_then(|c| {
-                    c.labels
-                        .as_ref()
-                        .and_then(|label| label.get(APP_NAME_LABEL))
-                })
-                .and_then(|app_name| AppName::from_str(app_name).ok())
-            {
-                Some(app_name) => {
-                    let mut services = Vec::new();
-                    if let Some(container_details) = self
-                        .get_container_details(Some(&app_name), None)
-                        .await?
-                        .remove(&app_name)
-                    {
-                        for container in container_details {
-                            services.push(Service::try_from(container)?);
-                        }
-                    }
+        if Self::is_container_name_in_use(&container_name).await? {
+            return Err(DockerInfrastructureError::ContainerNameConflict { container_name });
+        }
 
-                    Some(services)
+        // The replicas are started next to the container of a blue/green or canary deployment
+        // once the container has been promoted by a regular deployment.
+        let replica_options = match slot {
+            None => Some(options.clone()),
+            Some(slot) => {
+                if replicas > 1 {
+                    debug!(
+                        "Starting {service_name} of {app_name} with one container instead of {replicas} in the {} slot",
+                        slot.label_value()
+                    );
                 }
-                None => None,
-            },
-        )
-    }
+                None
+            }
+        };
 
-    /// Deletes all services for the given `app_name`.
-    async fn stop_services(&self, status_id: &str, app_name: &AppName) -> Result<Vec<Service>> {
-        let deployment_container = self
-            .create_status_change_container(status_id, app_name)
+        let container_info = docker
+            .create_container(
+                Some(CreateContainerOptions {
+                    name: container_name.as_str(),
+                    platform: None,
+                }),
+                options,
+            )
             .await?;
+        let container_id = container_info.id.as_ref();
 
-        let result = self.stop_services_impl(app_name).await;
-
-        delete(deployment_container).await?;
-
-        Ok(result?)
-    }
+        debug!("Created container: {container_info:?}");
+        self.copy_file_data(&container_info, service).await?;
 
-    async fn get_logs<'a>(
-        &'a self,
-        app_name: &'a AppName,
-        service_name: &'a str,
-        from: &'a Option<DateTime<FixedOffset>>,
-        limit: &'a Option<usize>,
-        follow: bool,
-    ) -> BoxStream<'a, Result<(DateTime<FixedOffset>, String)>> {
-        stream! {
-            match Self::
-                get_app_container(&AppName::from_str(app_name).unwrap(), service_name)
-                .await
-            {
-                Ok(None) => {}
-                Ok(Some(container)) => {
-                    let docker = Docker::connect_with_socket_defaults()?;
-                    let container_id = container
-                        .id
-                        .as_ref()
-                        .expect("id is mandatory for docker container");
-                    trace!("Acquiring logs of container {container_id} since {from:?}");
+        docker
+            .start_container(container_id, None::<StartContainerOptions<&str>>)
+            .await?;
+        debug!("Started container: {container_info:?}");
 
-                    let log_options = match from {
-                        Some(from) => bollard::container::LogsOptions::<&str> {
-                            stdout: true,
-                            stderr: true,
-                            since: from.timestamp(),
-                            timestamps: true,
-                            follow,
+        if let Some(network_id) = network_id {
+            docker
+                .connect_network(
+                    network_id,
+                    ConnectNetworkOptions::<&str> {
+                        container: container_id,
+                        endpoint_config: EndpointSettings {
+                            aliases: Some(vec![service_name.to_string()]),
                             ..Default::default()
                         },
-                        None => bollard::container::LogsOptions::<&str> {
-                            stdout: true,
-                            stderr: true,
-                            timestamps: true,
-                            follow,
+                    },
+                )
+                .await?;
+
+            debug!("Connected container {container_id} to {network_id}");
+        }
+
+        if let Some(peer_network_id) = peer_network_id {
+            docker
+                .connect_network(
+                    peer_network_id,
+                    ConnectNetworkOptions::<&str> {
+                        container: container_id,
+                        endpoint_config: EndpointSettings {
+                            aliases: Some(vec![format!(
+                                "{service_name}.{app_name}.{PEER_NETWORK_DOMAIN}"
+                            )]),
                             ..Default::default()
                         },
-                    };
+                    },
+                )
+                .await?;
 
-                    let logs = docker.logs(container_id, Some(log_options));
+            debug!("Connected container {container_id} to peer network {peer_network_id}");
+        }
 
-                    let mut logs = match limit {
-                        Some(log_limit) => Box::pin(logs.take(*log_limit))
-                            as BoxStream<Result<LogOutput, BollardError>>,
-                        None => Box::pin(logs) as BoxStream<Result<LogOutput, BollardError>>,
-                    };
+        let container_details = docker.inspect_container(container_id, None).await?;
 
-                    while let Some(result) = logs.next().await {
-                        match result {
-                            Ok(chunk) => {
-                                let line = chunk.to_string();
+        if let Some(probe) = service.startup_probe() {
+            let probe_duration = Duration::from_secs(
+                (probe.timeout_secs() + probe.interval_secs())
+                    * u64::from(probe.failure_threshold()),
+            );
+            Self::wait_for_service_healthy(container_id, probe_duration).await?;
+            wait_for_startup_probe(service_name, &container_details, probe).await?;
+        }
 
-                                let mut iter = line.splitn(2, ' ');
-                                let timestamp = iter.next()
-                                    .expect("This should never happen: docker should return timestamps, separated by space");
+        if let Some(readiness_url) = service.readiness_url() {
+            wait_for_readiness(
+                service_name,
+                &container_details,
+                readiness_url,
+                container_config.readiness_timeout(),
+            )
+            .await?;
+        }
 
-                                let datetime = DateTime::parse_from_rfc3339(timestamp)
-                                    .expect("Expecting a valid timestamp");
-                                let log_line: String = iter.collect::<Vec<&str>>().join(" ");
-                                yield Ok((datetime, log_line))
-                            }
-                            Err(e) => yield Err(e.into()),
-                        }
+        if let Some(replica_options) = replica_options {
+            self.replace_replicas(
+                app_name,
+                service,
+                network_id,
+                &container_name,
+                replica_options,
+                replicas,
+            )
+            .await?;
+        }
+
+        if let Some(consul_config) = self.config.consul_integration() {
+            if let Err(err) =
+                register_in_consul(app_name, service_name, &container_details, consul_config).await
+            {
+                warn!("Cannot register service {service_name} of {app_name} in Consul: {err}");
+            }
+        }
+
+        if let Some(image) = image_to_delete {
+            info!("Clean up image {image:?} of app {app_name:?}");
+            match docker.remove_image(&image, None, None).await {
+                Ok(output) => {
+                    for o in output {
+                        debug!("{o:?}");
                     }
                 }
-                Err(e) => yield Err(e.into()),
+                Err(err) => debug!("Could not clean up image: {err:?}"),
+            };
+        }
+        Service::try_from(container_details)
+    }
+
+    /// Starts a copy of the service's container that Traefik does not route to and waits until its
+    /// readiness URL responds with `200 OK`, so that the running container of the service keeps
+    /// serving the requests until the new configuration is known to become ready. The copy is
+    /// removed afterwards, regardless of the result.
+    async fn probe_readiness(
+        &self,
+        service: &ServiceConfig,
+        network_id: Option<&str>,
+        container_name: &str,
+        options: bollard::container::Config<String>,
+        readiness_url: &str,
+        timeout: Duration,
+    ) -> Result<(), DockerInfrastructureError> {
+        let docker = Docker::connect_with_socket_defaults()?;
+        let service_name = service.service_name();
+        let probe_name = format!("{container_name}-readiness");
+
+        // A probe that has been left behind, e.g. because PREvant has been restarted, would block
+        // the container name.
+        if Self::is_container_name_in_use(&probe_name).await? {
+            Self::force_stop_container(&probe_name).await?;
+        }
+
+        info!("Probing the readiness of {service_name} in container {probe_name}");
+        let container_info = docker
+            .create_container(
+                Some(CreateContainerOptions {
+                    name: probe_name.as_str(),
+                    platform: None,
+                }),
+                readiness_probe_options(options),
+            )
+            .await?;
+        let container_id = container_info.id.as_str();
+
+        let result = async {
+            self.copy_file_data(&container_info, service).await?;
+            docker
+                .start_container(container_id, None::<StartContainerOptions<&str>>)
+                .await?;
+            if let Some(network_id) = network_id {
+                docker
+                    .connect_network(
+                        network_id,
+                        ConnectNetworkOptions::<&str> {
+                            container: container_id,
+                            endpoint_config: EndpointSettings::default(),
+                        },
+                    )
+                    .await?;
             }
-        }.boxed()
+
+            let container_details = docker.inspect_container(container_id, None).await?;
+            wait_for_readiness(service_name, &container_details, readiness_url, timeout).await
+        }
+        .await;
+
+        Self::force_stop_container(container_id).await?;
+        result
     }
 
-    async fn change_status(
+    /// Returns the number of containers of the service: either the number of the service
+    /// configuration or, without it, the number of the running service so that a redeployment
+    /// keeps the service scaled.
+    fn replica_count(
+        service_config: &ServiceConfig,
+        existing_container: Option<&ContainerInspectResponse>,
+    ) -> Result<u32, DockerInfrastructureError> {
+        let replicas = service_config
+            .replicas()
+            .or_else(|| {
+                existing_container?
+                    .config
+                    .as_ref()?
+                    .labels
+                    .as_ref()?
+                    .get(REPLICAS_LABEL)?
+                    .parse::<u32>()
+                    .ok()
+            })
+            .unwrap_or(1);
+
+        if replicas == 0 || replicas > MAX_REPLICAS {
+            return Err(DockerInfrastructureError::InvalidReplicas {
+                service_name: service_config.service_name().clone(),
+                replicas,
+            });
+        }
+        Ok(replicas)
+    }
+
+    /// Replaces the additional containers of the service by `replicas - 1` containers that are
+    /// created with the options of the service's container. They share its network alias and
+    /// its Traefik labels, so that the requests are balanced among all containers, but they do not
+    /// bind host ports because the ports are bound by the service's container already.
+    async fn replace_replicas(
         &self,
         app_name: &AppName,
-        service_name: &str,
-        status: ServiceStatus,
-    ) -> Result<Option<Service>> {
-        match Self::get_app_container(app_name, service_name).await? {
-            Some(container) => {
-                let docker = Docker::connect_with_socket_defaults()?;
-                let details = docker
-                    .inspect_container(
-                        container
-                            .id
-                            .as_ref()
-                            .expect("id is mandatory for a docker container"),
-                        None,
+        service: &DeployableService,
+        network_id: Option<&str>,
+        container_name: &str,
+        options: bollard::container::Config<String>,
+        replicas: u32,
+    ) -> Result<(), DockerInfrastructureError> {
+        let docker = Docker::connect_with_socket_defaults()?;
+        let service_name = service.service_name();
+
+        for container in
+            &Self::get_replica_containers(Some(app_name), Some(service_name.as_str())).await?
+        {
+            Self::force_remove_container(container).await?;
+        }
+
+        for replica in 2..=replicas {
+            let mut options = options.clone();
+            options
+                .labels
+                .get_or_insert_with(HashMap::new)
+                .insert(REPLICA_OF_LABEL.to_string(), service_name.clone());
+            if let Some(host_config) = options.host_config.as_mut() {
+                host_config.port_bindings = None;
+            }
+
+            let replica_name = format!("{container_name}-{replica}");
+            let container_info = docker
+                .create_container(
+                    Some(CreateContainerOptions {
+                        name: replica_name.as_str(),
+                        platform: None,
+                    }),
+                    options,
+                )
+                .await?;
+            self.copy_file_data(&container_info, service).await?;
+            docker
+                .start_container(
+                    container_info.id.as_str(),
+                    None::<StartContainerOptions<&str>>,
+                )
+                .await?;
+
+            if let Some(network_id) = network_id {
+                docker
+                    .connect_network(
+                        network_id,
+                        ConnectNetworkOptions::<&str> {
+                            container: container_info.id.as_str(),
+                            endpoint_config: EndpointSettings {
+                                aliases: Some(vec![service_name.to_string()]),
+                                ..Default::default()
+                            },
+                        },
                     )
                     .await?;
+            }
+            debug!("Started replica {replica_name} of {service_name} in {app_name}");
+        }
 
-                macro_rules! run_future_and_map_err {
-                    ( $future:expr, $log_format:expr ) => {
-                        if let Err(err) = $future.await {
-                            match err {
-                                BollardError::DockerResponseServerError {
-                                    status_code,
-                                    message,
-                                    ..
-                                } if status_code == 304 => {
-                                    trace!(
-                                        "Container {} already in desired state: {message}",
-                                        details
-                                            .id
-                                            .as_ref()
-                                            .expect("id is mandatory for a docker container")
-                                    );
-                                }
-                                err => {
-                                    error!($log_format, err);
-                                    return Err(anyhow::Error::new(err));
-                                }
-                            };
-                        }
-                    };
-                }
+        Ok(())
+    }
 
-                match status {
-                    ServiceStatus::Running => {
-                        if !details
-                            .state
-                            .as_ref()
-                            .map(|state| state.running.unwrap_or_default())
-                            .unwrap()
-                        {
-                            run_future_and_map_err!(
-                                docker.start_container(
-                                    container
-                                        .id
-                                        .as_ref()
-                                        .expect("id is mandatory for a docker container"),
-                                    None::<StartContainerOptions::<&str>>,
-                                ),
-                                "Could not start container: {}"
-                            );
-                        }
+    /// Runs the init containers of the service one after another and waits for each of them to
+    /// exit successfully. Afterwards, the containers are removed because they are not part of the
+    /// app's services.
+    async fn run_init_containers(
+        &self,
+        app_name: &AppName,
+        network_id: Option<&str>,
+        service: &ServiceConfig,
+        container_config: &ContainerConfig,
+    ) -> Result<(), DockerInfrastructureError> {
+        let docker = Docker::connect_with_socket_defaults()?;
+        let service_name = service.service_name();
+
+        for (index, init_container) in service.init_containers().iter().enumerate() {
+            let image = init_container.image();
+            self.pull_image_if_needed(app_name, service, image, container_config)
+                .await?;
+
+            let container_name = format!(
+                "{}-init-{index}",
+                Self::container_name_of(app_name, service)
+            );
+            // A container of an aborted deployment might still exist.
+            not_found_to_none(
+                docker
+                    .remove_container(
+                        &container_name,
+                        Some(RemoveContainerOptions {
+                            force: true,
+                            ..Default::default()
+                        }),
+                    )
+                    .await,
+            )?;
+
+            let options = bollard::container::Config {
+                image: Some(image.to_string()),
+                cmd: if init_container.command().is_empty() {
+                    None
+                } else {
+                    Some(init_container.command().to_vec())
+                },
+                env: Some(init_container.env().to_vec()),
+                labels: Some(HashMap::from([
+                    (MANAGED_BY_LABEL.to_string(), MANAGED_BY_VALUE.to_string()),
+                    (APP_NAME_LABEL.to_string(), app_name.to_string()),
+                    (
+                        INIT_CONTAINER_OF_LABEL.to_string(),
+                        format!("{app_name}/{service_name}"),
+                    ),
+                ])),
+                host_config: Some(HostConfig {
+                    network_mode: Some(String::from(network_id.unwrap_or("none"))),
+                    restart_policy: Some(RestartPolicy {
+                        name: Some(RestartPolicyNameEnum::NO),
+                        maximum_retry_count: None,
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+
+            info!(
+                "Running init container {container_name} ({image}) of {service_name} in {app_name}"
+            );
+            let container_id = docker
+                .create_container(
+                    Some(CreateContainerOptions {
+                        name: container_name.as_str(),
+                        platform: None,
+                    }),
+                    options,
+                )
+                .await?
+                .id;
+            docker
+                .start_container(&container_id, None::<StartContainerOptions<&str>>)
+                .await?;
+
+            let exit_code = match docker
+                .wait_container(&container_id, None::<WaitContainerOptions<&str>>)
+                .try_collect::<Vec<_>>()
+                .await
+            {
+                Ok(responses) => responses.last().map_or(0, |response| response.status_code),
+                Err(BollardError::DockerContainerWaitError { code, .. }) => code,
+                Err(err) => return Err(err.into()),
+            };
+
+            if exit_code != 0 {
+                let logs = docker
+                    .logs(
+                        &container_id,
+                        Some(LogsOptions::<&str> {
+                            stdout: true,
+                            stderr: true,
+                            tail: "20",
+                            ..Default::default()
+                        }),
+                    )
+                    .map_ok(|log_output| log_output.to_string())
+                    .try_collect::<String>()
+                    .await
+                    .unwrap_or_default();
+                warn!("Init container {container_name} of {service_name} in {app_name} exited with code {exit_code}:\n{logs}");
+            }
+
+            docker
+                .remove_container(
+                    &container_id,
+                    Some(RemoveContainerOptions {
+                        force: true,
+                        ..Default::default()
+                    }),
+                )
+                .await?;
+
+            if exit_code != 0 {
+                return Err(DockerInfrastructureError::InitContainerFailure {
+                    service_name: service_name.clone(),
+                    image: image.to_string(),
+                    exit_code,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Polls the health status of the container every two seconds until Docker reports it as
+    /// healthy. Containers without a health check are considered to be healthy after a brief
+    /// pause.
+    async fn wait_for_service_healthy(
+        container_id: &str,
+        timeout: Duration,
+    ) -> Result<(), DockerInfrastructureError> {
+        let docker = Docker::connect_with_socket_defaults()?;
+
+        let wait = async {
+            loop {
+                let details = docker.inspect_container(container_id, None).await?;
+                match health_status(&details) {
+                    None | Some(HealthStatusEnum::EMPTY) | Some(HealthStatusEnum::NONE) => {
+                        tokio::time::sleep(HEALTH_CHECK_GRACE_PERIOD).await;
+                        return Ok(());
                     }
-                    ServiceStatus::Paused => {
-                        if details
-                            .state
-                            .as_ref()
-                            .map(|state| state.running.unwrap_or_default())
-                            .unwrap()
-                        {
-                            run_future_and_map_err!(
-                                docker.stop_container(
-                                    container
-                                        .id
-                                        .as_ref()
-                                        .expect("id is mandatory for a docker container"),
-                                    None
-                                ),
-                                "Could not pause container: {}"
-                            );
-                        }
+                    Some(HealthStatusEnum::HEALTHY) => {
+                        return Ok::<(), DockerInfrastructureError>(())
+                    }
+                    Some(status) => {
+                        trace!("Container {container_id} is not healthy yet: {status}");
+                        tokio::time::sleep(HEALTH_CHECK_POLL_INTERVAL).await;
                     }
                 }
-
-                Ok(Some(Service::try_from(details)?))
             }
-            None => Ok(None),
+        };
+
+        match tokio::time::timeout(timeout, wait).await {
+            Ok(result) => result,
+            Err(_) => Err(DockerInfrastructureError::HealthCheckTimeout {
+                container_id: container_id.to_string(),
+                timeout,
+            }),
         }
     }
 
-    async fn http_forwarder(&self) -> Result<Box<dyn HttpForwarder + Send>> {
-        Ok(Box::new(DockerHttpForwarder {}))
+    /// Replaces the path based Traefik rule with a rule that routes the requests for
+    /// `{service_name}.{app_name}.{base_domain}` to the container.
+    fn apply_subdomain_routing(
+        labels: &mut HashMap<String, String>,
+        app_name: &AppName,
+        service_name: &str,
+        subdomain_routing: &SubdomainRoutingConfig,
+    ) {
+        labels.insert(
+            String::from("traefik.frontend.rule"),
+            format!("Host:{}", subdomain_routing.host_of(app_name, service_name)),
+        );
     }
-}
 
-struct DockerHttpForwarder;
+    /// Assigns the container to the Traefik backend of the app's service with the default weight.
+    fn apply_traefik_backend(
+        labels: &mut HashMap<String, String>,
+        app_name: &AppName,
+        service_name: &str,
+    ) {
+        labels.insert(
+            TRAEFIK_BACKEND_LABEL.to_string(),
+            format!("{app_name}-{service_name}"),
+        );
+        labels.insert(
+            TRAEFIK_WEIGHT_LABEL.to_string(),
+            TRAEFIK_DEFAULT_WEIGHT.to_string(),
+        );
+    }
 
-#[async_trait]
-impl HttpForwarder for DockerHttpForwarder {
-    async fn request_web_host_meta(
-        &self,
+    /// Assigns the weight of the canary within the Traefik backend of the app's service so that
+    /// the canary receives `weight_percent` of the requests next to one running container with the
+    /// default weight. Docker cannot change the labels of the running container, thus, it keeps
+    /// its weight.
+    fn apply_canary_weight(
+        labels: &mut HashMap<String, String>,
         app_name: &AppName,
         service_name: &str,
-        request: http::Request<http_body_util::Empty<bytes::Bytes>>,
-    ) -> Result<Option<WebHostMeta>> {
-        let Some(container_details) =
-            DockerInfrastructure::get_app_container(app_name, service_name).await?
-        else {
-            return Ok(None);
+        weight_percent: u8,
+    ) {
+        let weight_percent = u32::from(weight_percent.clamp(1, 99));
+        let weight = (TRAEFIK_DEFAULT_WEIGHT * weight_percent + (100 - weight_percent) / 2)
+            / (100 - weight_percent);
+
+        labels.insert(
+            TRAEFIK_BACKEND_LABEL.to_string(),
+            format!("{app_name}-{service_name}"),
+        );
+        labels.insert(TRAEFIK_WEIGHT_LABEL.to_string(), weight.to_string());
+    }
+
+    /// Enables the cookie affinity of the Traefik service of the app's service. The labels of
+    /// Traefik v1 and v2 are both set because each version ignores the labels of the other one.
+    fn apply_sticky_sessions(
+        labels: &mut HashMap<String, String>,
+        app_name: &AppName,
+        service_name: &str,
+        cookie_name: &str,
+    ) {
+        labels.insert(
+            String::from("traefik.backend.loadbalancer.stickiness"),
+            String::from("true"),
+        );
+        labels.insert(
+            String::from("traefik.backend.loadbalancer.stickiness.cookieName"),
+            cookie_name.to_string(),
+        );
+        labels.insert(
+            format!(
+                "traefik.http.services.{}-{service_name}.loadbalancer.sticky.cookie.name",
+                app_name.as_str().to_lowercase()
+            ),
+            cookie_name.to_string(),
+        );
+    }
+
+    /// Checks the settings of the service that Docker would reject only once the container is
+    /// created.
+    fn check_service(
+        service_config: &ServiceConfig,
+        container_config: &ContainerConfig,
+    ) -> Result<(), DockerInfrastructureError> {
+        for device in service_config.devices() {
+            if !container_config.is_device_allowed(device.host_path()) {
+                return Err(DockerInfrastructureError::DeviceNotPermitted {
+                    host_path: device.host_path().to_string(),
+                });
+            }
+        }
+
+        Self::check_memory_swap(service_config, container_config)?;
+        Self::check_pid_limit(service_config, container_config)?;
+        Self::check_working_dir(service_config)?;
+        Self::check_stop_signal(service_config)?;
+        Self::check_dns_servers(service_config, container_config)?;
+        Self::check_timezone(service_config)?;
+        Self::check_named_volumes(service_config)?;
+        Ok(())
+    }
+
+    /// Checks the swap settings of the service before Docker rejects them: like with
+    /// `docker run`, `--memory-swap` must be `-1` (unlimited) or at least `--memory` which must be
+    /// set as well.
+    fn check_memory_swap(
+        service_config: &ServiceConfig,
+        container_config: &ContainerConfig,
+    ) -> Result<(), DockerInfrastructureError> {
+        if let Some(memory_swappiness) = service_config.memory_swappiness() {
+            if memory_swappiness > 100 {
+                return Err(DockerInfrastructureError::InvalidMemorySwappiness {
+                    service_name: service_config.service_name().clone(),
+                    memory_swappiness,
+                });
+            }
+        }
+
+        let Some(memory_swap_bytes) = service_config.memory_swap_bytes() else {
+            return Ok(());
+        };
+        let memory_bytes = container_config
+            .memory_limit_of(service_config)
+            .map(|mem| mem.as_u64() as i64);
+
+        let valid = match memory_bytes {
+            _ if memory_swap_bytes == -1 => true,
+            Some(memory_bytes) => memory_swap_bytes >= memory_bytes,
+            None => false,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(DockerInfrastructureError::InvalidMemorySwap {
+                service_name: service_config.service_name().clone(),
+                memory_swap_bytes,
+                memory_bytes,
+            })
+        }
+    }
+
+    /// Rejects PID limits that would not even allow the entrypoint of the container to fork. A
+    /// limit that exceeds the configured default is accepted but logged.
+    fn check_pid_limit(
+        service_config: &ServiceConfig,
+        container_config: &ContainerConfig,
+    ) -> Result<(), DockerInfrastructureError> {
+        let Some(pid_limit) = service_config.pid_limit() else {
+            return Ok(());
+        };
+
+        if pid_limit <= 1 {
+            return Err(DockerInfrastructureError::InvalidPidLimit {
+                service_name: service_config.service_name().clone(),
+                pid_limit,
+            });
+        }
+
+        if let Some(default_pid_limit) = container_config.default_pid_limit() {
+            if pid_limit > default_pid_limit {
+                warn!(
+                    "Service {} requests a PID limit of {pid_limit} which exceeds the default of {default_pid_limit}",
+                    service_config.service_name()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_working_dir(service_config: &ServiceConfig) -> Result<(), DockerInfrastructureError> {
+        match service_config.working_dir() {
+            Some(working_dir) if !working_dir.starts_with('/') => {
+                Err(DockerInfrastructureError::InvalidWorkingDir {
+                    service_name: service_config.service_name().clone(),
+                    working_dir: working_dir.to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_stop_signal(service_config: &ServiceConfig) -> Result<(), DockerInfrastructureError> {
+        match service_config.stop_signal() {
+            Some(stop_signal) if !STOP_SIGNALS.contains(&stop_signal) => {
+                Err(DockerInfrastructureError::InvalidStopSignal {
+                    service_name: service_config.service_name().clone(),
+                    stop_signal: stop_signal.to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_dns_servers(
+        service_config: &ServiceConfig,
+        container_config: &ContainerConfig,
+    ) -> Result<(), DockerInfrastructureError> {
+        match container_config
+            .dns_servers_of(service_config)
+            .iter()
+            .find(|address| address.parse::<IpAddr>().is_err())
+        {
+            Some(address) => Err(DockerInfrastructureError::InvalidDnsServer {
+                service_name: service_config.service_name().clone(),
+                address: address.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the IPC modes of the running services of the app that are not part of the
+    /// deployment, keyed by their service name. Services without IPC mode are included with
+    /// `None` because they can be joined if their container shares its namespace.
+    async fn running_ipc_modes(
+        app_name: &AppName,
+        services: &[DeployableService],
+    ) -> Result<HashMap<String, Option<IpcMode>>, DockerInfrastructureError> {
+        Ok(Self::get_app_containers(Some(app_name), None)
+            .await?
+            .into_iter()
+            .filter(|container| container.state.as_deref() == Some("running"))
+            .filter_map(|container| {
+                let labels = container.labels?;
+                let service_name = labels.get(SERVICE_NAME_LABEL)?;
+                if services.iter().any(|s| s.service_name() == service_name) {
+                    return None;
+                }
+                let ipc_mode = labels
+                    .get(IPC_MODE_LABEL)
+                    .and_then(|ipc_mode| IpcMode::try_from(ipc_mode.clone()).ok());
+                Some((service_name.clone(), ipc_mode))
+            })
+            .collect())
+    }
+
+    /// Groups the services into waves that are started one after another: a service is started
+    /// after the services it [depends on](ServiceConfig::depends_on) and after the service whose
+    /// IPC namespace it joins. Dependencies that are not part of the deployment keep running,
+    /// thus, they do not delay the service.
+    fn deployment_waves(
+        services: &[DeployableService],
+    ) -> Result<Vec<Vec<&DeployableService>>, DockerInfrastructureError> {
+        let service_names = services
+            .iter()
+            .map(|service| service.service_name().as_str())
+            .collect::<HashSet<_>>();
+        let dependencies_of = |service: &DeployableService| {
+            let ipc_service_name = match service.ipc_mode() {
+                Some(IpcMode::Container { service_name }) => Some(service_name.clone()),
+                _ => None,
+            };
+            service
+                .depends_on()
+                .iter()
+                .cloned()
+                .chain(ipc_service_name)
+                .filter(|dependency| {
+                    dependency != service.service_name()
+                        && service_names.contains(dependency.as_str())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut pending = services.iter().collect::<Vec<_>>();
+        let mut started = HashSet::new();
+        let mut waves = Vec::new();
+        while !pending.is_empty() {
+            let (wave, blocked): (Vec<_>, Vec<_>) = pending.into_iter().partition(|service| {
+                dependencies_of(service)
+                    .iter()
+                    .all(|dependency| started.contains(dependency))
+            });
+            if wave.is_empty() {
+                return Err(DockerInfrastructureError::CyclicDependencies {
+                    services: blocked
+                        .iter()
+                        .map(|service| service.service_name().clone())
+                        .collect(),
+                });
+            }
+
+            started.extend(wave.iter().map(|service| service.service_name().clone()));
+            waves.push(wave);
+            pending = blocked;
+        }
+
+        Ok(waves)
+    }
+
+    /// Checks that services only join the IPC namespace of other services whose namespace can be
+    /// shared, i.e. the joined service must neither be private nor join another namespace itself.
+    /// The joined service is either deployed together with the service or keeps running. Likewise,
+    /// the services of the deployment must keep their namespace shareable for running services
+    /// that join it.
+    fn check_ipc_modes(
+        services: &[DeployableService],
+        running_ipc_modes: &HashMap<String, Option<IpcMode>>,
+    ) -> Result<(), DockerInfrastructureError> {
+        for service in services {
+            let Some(IpcMode::Container {
+                service_name: ipc_service_name,
+            }) = service.ipc_mode()
+            else {
+                continue;
+            };
+
+            let ipc_mode = match services
+                .iter()
+                .find(|s| s.service_name() == ipc_service_name)
+            {
+                Some(ipc_service) => ipc_service.ipc_mode(),
+                None => running_ipc_modes
+                    .get(ipc_service_name)
+                    .and_then(Option::as_ref),
+            };
+            if ipc_service_name == service.service_name()
+                || matches!(ipc_mode, Some(IpcMode::Private | IpcMode::Container { .. }))
+            {
+                return Err(DockerInfrastructureError::InvalidIpcMode {
+                    service_name: service.service_name().clone(),
+                    ipc_service_name: ipc_service_name.clone(),
+                });
+            }
+        }
+
+        for (running_service_name, ipc_mode) in running_ipc_modes {
+            let Some(IpcMode::Container {
+                service_name: ipc_service_name,
+            }) = ipc_mode
+            else {
+                continue;
+            };
+
+            let ipc_service = services
+                .iter()
+                .find(|s| s.service_name() == ipc_service_name);
+            if matches!(
+                ipc_service.and_then(|s| s.ipc_mode()),
+                Some(IpcMode::Private | IpcMode::Container { .. })
+            ) {
+                return Err(DockerInfrastructureError::InvalidIpcMode {
+                    service_name: running_service_name.clone(),
+                    ipc_service_name: ipc_service_name.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if another service, of the deployment or running, joins the IPC namespace of
+    /// the service.
+    fn is_ipc_shared(
+        service: &ServiceConfig,
+        services: &[DeployableService],
+        running_ipc_modes: &HashMap<String, Option<IpcMode>>,
+    ) -> bool {
+        let joins_service = |ipc_mode: Option<&IpcMode>| {
+            matches!(
+                ipc_mode,
+                Some(IpcMode::Container { service_name }) if service_name == service.service_name()
+            )
         };
+        services.iter().any(|s| joins_service(s.ipc_mode()))
+            || running_ipc_modes
+                .values()
+                .any(|ipc_mode| joins_service(ipc_mode.as_ref()))
+    }
+
+    /// Returns the ID of the running container whose IPC namespace the service joins. In the green
+    /// slot of a blue/green deployment, the green container of the joined service is preferred,
+    /// and likewise for canaries. Docker only permits to join containers that have been started
+    /// with a shareable namespace, which is not the case for services that have been deployed
+    /// before any other service joined them.
+    async fn ipc_container_id(
+        app_name: &AppName,
+        service_name: &str,
+        ipc_service_name: &str,
+        slot: Option<DeploymentSlot>,
+    ) -> Result<String, DockerInfrastructureError> {
+        let container_id = Self::get_app_containers(Some(app_name), Some(ipc_service_name))
+            .await?
+            .into_iter()
+            .filter(|container| container.state.as_deref() == Some("running"))
+            .max_by_key(|container| {
+                slot.is_some()
+                    && container
+                        .labels
+                        .as_ref()
+                        .and_then(|labels| labels.get(DEPLOYMENT_SLOT_LABEL))
+                        .map(String::as_str)
+                        == slot.map(|slot| slot.label_value())
+            })
+            .and_then(|container| container.id)
+            .ok_or_else(|| DockerInfrastructureError::IpcContainerNotRunning {
+                service_name: service_name.to_string(),
+                ipc_service_name: ipc_service_name.to_string(),
+            })?;
+
+        let docker = Docker::connect_with_socket_defaults()?;
+        let ipc_mode = docker
+            .inspect_container(&container_id, None)
+            .await?
+            .host_config
+            .and_then(|host_config| host_config.ipc_mode);
+        if ipc_mode.as_deref() != Some("shareable") {
+            return Err(DockerInfrastructureError::IpcContainerNotShareable {
+                service_name: service_name.to_string(),
+                ipc_service_name: ipc_service_name.to_string(),
+            });
+        }
+
+        Ok(container_id)
+    }
+
+    fn check_timezone(service_config: &ServiceConfig) -> Result<(), DockerInfrastructureError> {
+        match service_config.timezone() {
+            Some(timezone) if timezone.parse::<chrono_tz::Tz>().is_err() => {
+                Err(DockerInfrastructureError::InvalidTimezone {
+                    service_name: service_config.service_name().clone(),
+                    timezone: timezone.to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Named volumes must not contain a `.` because the app name and the volume name are joined by
+    /// the last `.` of the Docker volume name, see [`named_volume_name`].
+    fn check_named_volumes(
+        service_config: &ServiceConfig,
+    ) -> Result<(), DockerInfrastructureError> {
+        for named_volume in service_config.named_volumes() {
+            let volume_name = named_volume.volume_name();
+            let mut chars = volume_name.chars();
+            let is_valid = chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !is_valid {
+                return Err(DockerInfrastructureError::InvalidNamedVolume {
+                    service_name: service_config.service_name().clone(),
+                    volume_name: volume_name.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks up the variables that the service inherits from the environment of PREvant via
+    /// `lookup`. Only variables that are permitted by the container configuration can be
+    /// inherited, so that services cannot read credentials of PREvant.
+    fn resolve_inherited_env<F>(
+        service_config: &ServiceConfig,
+        container_config: &ContainerConfig,
+        lookup: F,
+    ) -> Result<Vec<String>, DockerInfrastructureError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let service_name = service_config.service_name();
+        let mut env = Vec::new();
+
+        for name in service_config.env_inherit() {
+            if !container_config.is_env_inheritable(name) {
+                return Err(DockerInfrastructureError::EnvNotInheritable {
+                    service_name: service_name.clone(),
+                    name: name.clone(),
+                });
+            }
+
+            match lookup(name) {
+                Some(value) => env.push(format!("{name}={value}")),
+                None if service_config.env_inherit_strict() => {
+                    return Err(DockerInfrastructureError::InheritedEnvNotFound {
+                        service_name: service_name.clone(),
+                        name: name.clone(),
+                    })
+                }
+                None => debug!("Skipping {name} for {service_name} because it is not set"),
+            }
+        }
+
+        Ok(env)
+    }
+
+    fn create_container_options<'a>(
+        app_name: &'a str,
+        service_config: &'a ServiceConfig,
+        container_config: &'a ContainerConfig,
+        host_config_binds: &'a [String],
+    ) -> bollard::container::Config<String> {
+        let mut env = service_config.env().map(|env| {
+            env.iter()
+                .map(|v| format!("{}={}", v.key(), v.value().unsecure()))
+                .collect::<Vec<String>>()
+        });
+        if let Some(timezone) = service_config.timezone() {
+            let env = env.get_or_insert_with(Vec::new);
+            // Variables of the service take precedence
+            if !env.iter().any(|variable| variable.starts_with("TZ=")) {
+                env.push(format!("TZ={timezone}"));
+            }
+        }
+
+        let mut labels: HashMap<String, String> = container_config
+            .global_container_labels()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        let traefik_frontend = format!(
+            "PathPrefixStrip: /{app_name}/{service_name}/; PathPrefix:/{app_name}/{service_name}/;",
+            app_name = app_name,
+            service_name = service_config.service_name()
+        );
+        labels.insert("traefik.frontend.rule".to_string(), traefik_frontend);
+
+        if let Some(config_labels) = service_config.labels() {
+            for (k, v) in config_labels {
+                labels.insert(k.to_string(), v.to_string());
+            }
+        }
+
+        labels.insert(APP_NAME_LABEL.to_string(), app_name.to_string());
+        labels.insert(
+            SERVICE_NAME_LABEL.to_string(),
+            service_config.service_name().to_string(),
+        );
+        let container_type = service_config.container_type().to_string();
+        labels.insert(CONTAINER_TYPE_LABEL.to_string(), container_type);
+        let image_name = service_config.image().to_string();
+        labels.insert(IMAGE_LABEL.to_string(), image_name);
+
+        let replicated_env = service_config
+            .env()
+            .and_then(super::replicated_environment_variable_to_json)
+            .map(|value| value.to_string());
+
+        if let Some(replicated_env) = replicated_env {
+            labels.insert(REPLICATED_ENV_LABEL.to_string(), replicated_env);
+        }
+        if let Some(env) = service_config
+            .env()
+            .and_then(super::environment_variable_to_json)
+        {
+            labels.insert(ENV_LABEL.to_string(), env.to_string());
+        }
+        if !service_config.env_from_secrets().is_empty() {
+            labels.insert(
+                ENV_FROM_SECRETS_LABEL.to_string(),
+                serde_json::to_string(service_config.env_from_secrets())
+                    .expect("References of secrets are serializable"),
+            );
+        }
+        if !service_config.env_inherit().is_empty() {
+            let env_inherit = serde_json::json!({
+                "names": service_config.env_inherit(),
+                "strict": service_config.env_inherit_strict(),
+            });
+            labels.insert(ENV_INHERIT_LABEL.to_string(), env_inherit.to_string());
+        }
+
+        let memory = container_config
+            .memory_limit_of(service_config)
+            .map(|mem| mem.as_u64() as i64);
+
+        let (exposed_ports, port_bindings) = if service_config.host_port_bindings().is_empty() {
+            (None, None)
+        } else {
+            let mut exposed_ports = HashMap::new();
+            let mut port_bindings = PortMap::new();
+            for binding in service_config.host_port_bindings() {
+                let port = format!("{}/{}", binding.container_port(), binding.protocol());
+                exposed_ports.insert(port.clone(), HashMap::new());
+                port_bindings.insert(
+                    port,
+                    Some(vec![DockerPortBinding {
+                        host_ip: None,
+                        host_port: binding.host_port().map(|port| port.to_string()),
+                    }]),
+                );
+            }
+            (Some(exposed_ports), Some(port_bindings))
+        };
+
+        let ulimits = if service_config.ulimits().is_empty() {
+            None
+        } else {
+            Some(
+                service_config
+                    .ulimits()
+                    .iter()
+                    .map(|ulimit| ResourcesUlimits {
+                        name: Some(ulimit.name().to_string()),
+                        soft: Some(ulimit.soft() as i64),
+                        hard: Some(ulimit.hard() as i64),
+                    })
+                    .collect(),
+            )
+        };
+
+        let devices = if service_config.devices().is_empty() {
+            None
+        } else {
+            Some(
+                service_config
+                    .devices()
+                    .iter()
+                    .map(|device| DockerDeviceMapping {
+                        path_on_host: Some(device.host_path().to_string()),
+                        path_in_container: Some(device.container_path().to_string()),
+                        cgroup_permissions: Some(device.permissions().to_string()),
+                    })
+                    .collect(),
+            )
+        };
+
+        bollard::container::Config {
+            image: Some(service_config.image().to_string()),
+            env,
+            labels: Some(labels),
+            host_config: Some(HostConfig {
+                restart_policy: Some(RestartPolicy {
+                    name: Some(match container_config.restart_policy() {
+                        ContainerRestartPolicy::Always => RestartPolicyNameEnum::ALWAYS,
+                        ContainerRestartPolicy::UnlessStopped => {
+                            RestartPolicyNameEnum::UNLESS_STOPPED
+                        }
+                        ContainerRestartPolicy::OnFailure => RestartPolicyNameEnum::ON_FAILURE,
+                        ContainerRestartPolicy::No => RestartPolicyNameEnum::NO,
+                    }),
+                    ..Default::default()
+                }),
+                binds: Some(host_config_binds.to_vec()),
+                memory,
+                memory_swap: service_config.memory_swap_bytes().or(memory),
+                memory_swappiness: service_config
+                    .memory_swappiness()
+                    .map(|swappiness| swappiness as i64),
+                port_bindings,
+                devices,
+                shm_size: service_config.shm_size_bytes().map(|size| size as i64),
+                pids_limit: container_config
+                    .pid_limit_of(service_config)
+                    .map(|pid_limit| pid_limit as i64),
+                ulimits,
+                dns: Some(container_config.dns_servers_of(service_config).to_vec())
+                    .filter(|dns| !dns.is_empty()),
+                dns_search: Some(container_config.dns_search_domains().to_vec())
+                    .filter(|dns_search| !dns_search.is_empty()),
+                ipc_mode: match service_config.ipc_mode() {
+                    Some(IpcMode::Private) => Some(String::from("private")),
+                    Some(IpcMode::Host) => Some(String::from("host")),
+                    Some(IpcMode::Container { .. }) | None => None,
+                },
+                ..Default::default()
+            }),
+            exposed_ports,
+            working_dir: service_config.working_dir().map(str::to_string),
+            stop_signal: service_config.stop_signal().map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    async fn copy_file_data(
+        &self,
+        container_info: &ContainerCreateResponse,
+        service_config: &ServiceConfig,
+    ) -> Result<(), BollardError> {
+        let files = match service_config.files() {
+            None => return Ok(()),
+            Some(files) => files.clone(),
+        };
+
+        debug!(
+            "Copy data to container: {container_info:?} (service = {})",
+            service_config.service_name()
+        );
+
+        let docker = Docker::connect_with_socket_defaults()?;
+
+        let mut tar_builder = tar::Builder::new(Vec::new());
+
+        for (path, data) in files.into_iter() {
+            let mut header = tar::Header::new_gnu();
+            let file_contents = data.into_unsecure();
+            header.set_size(file_contents.as_bytes().len() as u64);
+            header.set_mode(0o644);
+            tar_builder.append_data(
+                &mut header,
+                path.to_path_buf()
+                    .iter()
+                    .skip(1)
+                    .collect::<std::path::PathBuf>(),
+                file_contents.as_bytes(),
+            )?;
+        }
+
+        docker
+            .upload_to_container(
+                &container_info.id,
+                Some(UploadToContainerOptions {
+                    path: "/",
+                    ..Default::default()
+                }),
+                tar_builder.into_inner()?.into(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_existing_volumes(
+        app_name: &AppName,
+    ) -> Result<VolumeListResponse, BollardError> {
+        let docker = Docker::connect_with_socket_defaults()?;
+        docker
+            .list_volumes(Some(ListVolumesOptions {
+                filters: HashMap::from([(
+                    "label".to_string(),
+                    vec![format!("{APP_NAME_LABEL}={app_name}")],
+                )]),
+            }))
+            .await
+    }
+
+    /// Returns the volumes that store the tags of the apps. Because the labels of containers cannot
+    /// be changed, the tags are kept in the labels of an empty volume that belongs to the app and,
+    /// thus, is deleted together with the app.
+    async fn fetch_tag_volumes(app_name: Option<&AppName>) -> Result<Vec<Volume>, BollardError> {
+        let mut label_filters = vec![TAGS_LABEL.to_string()];
+        if let Some(app_name) = app_name {
+            label_filters.push(format!("{APP_NAME_LABEL}={app_name}"));
+        }
+
+        let docker = Docker::connect_with_socket_defaults()?;
+        Ok(docker
+            .list_volumes(Some(ListVolumesOptions {
+                filters: HashMap::from([("label".to_string(), label_filters)]),
+            }))
+            .await?
+            .volumes
+            .unwrap_or_default())
+    }
+
+    async fn create_docker_volume(
+        app_name: &AppName,
+        service: &DeployableService,
+    ) -> Result<String, BollardError> {
+        let docker = Docker::connect_with_socket_defaults()?;
+
+        let mut labels: HashMap<&str, &str> = HashMap::new();
+        labels.insert(APP_NAME_LABEL, app_name);
+        labels.insert(SERVICE_NAME_LABEL, service.service_name());
+
+        docker
+            .create_volume(CreateVolumeOptions {
+                labels,
+                ..Default::default()
+            })
+            .await
+            .map(|vol| vol.name)
+    }
+
+    async fn create_host_config_binds(
+        app_name: &AppName,
+        existing_volume: &VolumeListResponse,
+        service: &DeployableService,
+    ) -> Result<Vec<String>, DockerInfrastructureError> {
+        let mut host_binds = Self::create_named_volume_binds(app_name, service).await?;
+
+        if service.declared_volumes().is_empty() {
+            return Ok(host_binds);
+        }
+
+        let service_volume = existing_volume
+            .volumes
+            .as_ref()
+            .and_then(|volume| {
+                volume
+                    .iter()
+                    .find(|vol| vol.labels.get(SERVICE_NAME_LABEL) == Some(service.service_name()))
+            })
+            .map(|info| info.name.clone());
+
+        let volume_name = match service_volume {
+            Some(name) => name,
+            None => Self::create_docker_volume(app_name, service).await?,
+        };
+
+        for declared_volume in service.declared_volumes() {
+            host_binds.push(format!("{volume_name}:{declared_volume}"));
+        }
+
+        Ok(host_binds)
+    }
+
+    /// Creates the named volumes of the service unless they exist already, e.g. because they have
+    /// been preserved during the last deletion of the app.
+    async fn create_named_volume_binds(
+        app_name: &AppName,
+        service: &DeployableService,
+    ) -> Result<Vec<String>, DockerInfrastructureError> {
+        let mut host_binds = Vec::new();
+        if service.named_volumes().is_empty() {
+            return Ok(host_binds);
+        }
+
+        let docker = Docker::connect_with_socket_defaults()?;
+        for named_volume in service.named_volumes() {
+            let volume_name = named_volume_name(app_name, named_volume.volume_name());
+
+            match docker.inspect_volume(&volume_name).await {
+                Ok(existing_volume) => {
+                    Self::check_named_volume_owner(app_name, &existing_volume)?;
+                }
+                Err(BollardError::DockerResponseServerError {
+                    status_code: 404, ..
+                }) => {
+                    docker
+                        .create_volume(CreateVolumeOptions {
+                            name: volume_name.as_str(),
+                            labels: HashMap::from([
+                                (APP_NAME_LABEL, app_name.as_str()),
+                                (NAMED_VOLUME_LABEL, named_volume.volume_name()),
+                            ]),
+                            ..Default::default()
+                        })
+                        .await?;
+                }
+                Err(err) => return Err(err.into()),
+            }
+
+            host_binds.push(format!("{volume_name}:{}", named_volume.container_path()));
+        }
+
+        Ok(host_binds)
+    }
+
+    /// Ensures that a preserved volume is only reused by the app that created it.
+    fn check_named_volume_owner(
+        app_name: &AppName,
+        volume: &Volume,
+    ) -> Result<(), DockerInfrastructureError> {
+        if volume.labels.get(APP_NAME_LABEL).map(String::as_str) != Some(app_name.as_str()) {
+            return Err(DockerInfrastructureError::NamedVolumeConflict {
+                app_name: app_name.to_string(),
+                volume_name: volume.name.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Pulls the image, which is either the image of the service or of one of its init containers,
+    /// according to the pull policy of the service.
+    async fn pull_image_if_needed(
+        &self,
+        app_name: &AppName,
+        config: &ServiceConfig,
+        image: &Image,
+        container_config: &ContainerConfig,
+    ) -> Result<(), DockerInfrastructureError> {
+        if let Image::Digest { .. } = image {
+            return Ok(());
+        }
+
+        match container_config.pull_policy_of(config) {
+            PullPolicy::Always => {
+                self.pull_image(app_name, config, image, container_config)
+                    .await
+            }
+            PullPolicy::IfNotPresent => {
+                let docker = Docker::connect_with_socket_defaults()?;
+                if not_found_to_none(docker.inspect_image(&image.to_string()).await)?.is_none() {
+                    self.pull_image(app_name, config, image, container_config)
+                        .await?;
+                }
+                Ok(())
+            }
+            PullPolicy::Never => {
+                debug!(
+                    "Skip pulling {image} due to pull policy of {}",
+                    config.service_name()
+                );
+                Ok(())
+            }
+        }
+    }
+
+    async fn pull_image(
+        &self,
+        app_name: &AppName,
+        config: &ServiceConfig,
+        image: &Image,
+        container_config: &ContainerConfig,
+    ) -> Result<(), DockerInfrastructureError> {
+        let timeout_secs = container_config.pull_timeout_secs_of(config);
+
+        info!(
+            "Pulling {image:?} for {:?} of app {app_name:?}",
+            config.service_name()
+        );
+
+        match tokio::time::timeout(
+            std::time::Duration::from_secs(timeout_secs),
+            self.pull_with_oci_fallback(image, container_config),
+        )
+        .await
+        {
+            Ok(pull_result) => Ok(pull_result?),
+            Err(_) => {
+                warn!("Pulling {image} did not finish within {timeout_secs} seconds, aborting.");
+                return Err(DockerInfrastructureError::PullTimeout {
+                    image: image.to_string(),
+                    timeout_secs,
+                });
+            }
+        }
+    }
+
+    /// Pulls the image through the OCI Distribution API if its registry is configured to serve
+    /// only OCI image manifests. Otherwise, the image is pulled by the Docker daemon and the OCI
+    /// Distribution API is used as fallback if the daemon rejects the manifest format.
+    async fn pull_with_oci_fallback(
+        &self,
+        image: &Image,
+        container_config: &ContainerConfig,
+    ) -> Result<(), ImagePullError> {
+        if container_config.is_pulled_via_oci(image) {
+            return OciPuller.pull(image, &self.config).await;
+        }
+
+        match DockerPuller.pull(image, &self.config).await {
+            Err(err) if err.is_unsupported_manifest() => {
+                warn!(
+                    "Docker cannot pull {image} ({err}), falling back to the OCI Distribution API."
+                );
+                OciPuller.pull(image, &self.config).await
+            }
+            result => result,
+        }
+    }
+
+    async fn get_containers(
+        filters: HashMap<String, Vec<String>>,
+    ) -> Result<Vec<ContainerSummary>, BollardError> {
+        let docker = Docker::connect_with_socket_defaults()?;
+
+        let list_options = Some(ListContainersOptions {
+            all: true,
+            filters,
+            ..Default::default()
+        });
+
+        docker.list_containers(list_options).await
+    }
+
+    async fn get_app_containers(
+        app_name: Option<&AppName>,
+        service_name: Option<&str>,
+    ) -> Result<Vec<ContainerSummary>, BollardError> {
+        let mut filters = HashMap::new();
+        if let Some(app_name_filter) =
+            label_filter(APP_NAME_LABEL, app_name.map(|app_name| app_name.as_str()))
+        {
+            filters
+                .entry("label".to_string())
+                .or_insert_with(Vec::new)
+                .push(app_name_filter);
+        }
+
+        if let Some(service_name_filter) = label_filter(SERVICE_NAME_LABEL, service_name) {
+            filters
+                .entry("label".to_string())
+                .or_insert_with(Vec::new)
+                .push(service_name_filter);
+        }
+
+        Ok(Self::get_containers(filters)
+            .await?
+            .into_iter()
+            .filter(|container| !is_init_container(container) && !is_replica_container(container))
+            .collect())
+    }
+
+    /// Returns the additional containers of services that run more than one replica.
+    async fn get_replica_containers(
+        app_name: Option<&AppName>,
+        service_name: Option<&str>,
+    ) -> Result<Vec<ContainerSummary>, BollardError> {
+        let mut label_filters = Vec::new();
+        if let Some(app_name_filter) =
+            label_filter(APP_NAME_LABEL, app_name.map(|app_name| app_name.as_str()))
+        {
+            label_filters.push(app_name_filter);
+        }
+        label_filters.extend(label_filter(REPLICA_OF_LABEL, service_name));
+
+        let filters = HashMap::from([(String::from("label"), label_filters)]);
+        Self::get_containers(filters).await
+    }
+
+    /// Returns the init containers of the app that still exist, e.g. because a deployment has
+    /// been aborted while they were running.
+    async fn get_init_containers(
+        app_name: &AppName,
+    ) -> Result<Vec<ContainerSummary>, BollardError> {
+        let filters = HashMap::from([(
+            String::from("label"),
+            vec![
+                format!("{APP_NAME_LABEL}={app_name}"),
+                INIT_CONTAINER_OF_LABEL.to_string(),
+            ],
+        )]);
+        Self::get_containers(filters).await
+    }
+
+    async fn get_status_change_containers(
+        &self,
+        app_name: Option<&AppName>,
+        status_id: Option<&str>,
+    ) -> Result<Vec<ContainerSummary>, BollardError> {
+        let mut label_filters = vec![];
+        if let Some(app_name_filter) =
+            label_filter(APP_NAME_LABEL, app_name.map(|app_name| app_name.as_str()))
+        {
+            label_filters.push(app_name_filter);
+        }
+
+        if let Some(status_id_filter) = label_filter(STATUS_ID, status_id) {
+            label_filters.push(status_id_filter);
+        }
+
+        let filters = HashMap::from([("label".to_string(), label_filters)]);
+        Self::get_containers(filters).await
+    }
+
+    /// Resolves the environment variables that the service references via `envFromSecrets`. The
+    /// variables are passed to the container only and in contrast to the service's environment
+    /// only the references are stored in the labels of the container. Each secret must be
+    /// accessible by the app.
+    fn resolve_secret_env(
+        &self,
+        app_name: &AppName,
+        service: &ServiceConfig,
+    ) -> Result<Vec<String>, SecretError> {
+        if service.env_from_secrets().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let secret_provider = self.config.secret_provider();
+        service
+            .env_from_secrets()
+            .iter()
+            .map(|secret_env_ref| {
+                if !self
+                    .config
+                    .is_secret_accessible(secret_env_ref.secret_name(), app_name)
+                {
+                    return Err(SecretError::NotPermitted {
+                        name: secret_env_ref.secret_name().to_string(),
+                        app_name: app_name.to_string(),
+                    });
+                }
+                let secret_provider =
+                    secret_provider
+                        .as_ref()
+                        .ok_or_else(|| SecretError::NoProvider {
+                            name: secret_env_ref.secret_name().to_string(),
+                        })?;
+                let value = secret_provider.get_secret(secret_env_ref.secret_name())?;
+                Ok(format!("{}={}", secret_env_ref.env_key(), value.unsecure()))
+            })
+            .collect()
+    }
+
+    /// Returns the configured container name or `{app_name}-{service_name}-{hash}`. The hash is
+    /// computed over app and service name because app names may contain dashes, too. Otherwise,
+    /// the service `b-c` of app `a` and the service `c` of app `a-b` would share their name.
+    fn container_name_of(app_name: &AppName, service: &ServiceConfig) -> String {
+        service
+            .container_name()
+            .map(String::from)
+            .unwrap_or_else(|| {
+                let service_name = service.service_name();
+                let mut hasher = DefaultHasher::new();
+                // App names cannot contain slashes, thus, the input of the hash is unambiguous.
+                hasher.write(format!("{app_name}/{service_name}").as_bytes());
+                format!("{app_name}-{service_name}-{:08x}", hasher.finish() as u32)
+            })
+    }
+
+    async fn is_container_name_in_use(container_name: &str) -> Result<bool, BollardError> {
+        // Docker matches names partially, thus, the filter must be anchored to the exact name.
+        let filters = HashMap::from([(String::from("name"), vec![format!("^/{container_name}$")])]);
+
+        Ok(!Self::get_containers(filters).await?.is_empty())
+    }
+
+    async fn get_app_container(
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Result<Option<ContainerSummary>, BollardError> {
+        Self::get_app_containers(Some(app_name), Some(service_name))
+            .await
+            .map(|list| list.into_iter().next())
+    }
+
+    /// Looks up the service running in the container by asking Docker directly for the
+    /// container instead of scanning the containers of all apps.
+    pub(super) async fn find_service_by_container_id(
+        container_id: &str,
+    ) -> Result<Option<(AppName, Service)>, DockerInfrastructureError> {
+        let filters = HashMap::from([
+            (String::from("id"), vec![container_id.to_string()]),
+            (String::from("label"), vec![APP_NAME_LABEL.to_string()]),
+        ]);
+
+        let Some(container) = Self::get_containers(filters)
+            .await?
+            .into_iter()
+            .find(|container| !is_init_container(container))
+        else {
+            return Ok(None);
+        };
+        let Some(details) = not_found_to_none(inspect(container).await)? else {
+            return Ok(None);
+        };
+
+        let service = Service::try_from(details)?;
+        let app_name = AppName::from_str(service.app_name()).map_err(|err| {
+            DockerInfrastructureError::UnexpectedError {
+                err: anyhow::Error::new(err),
+            }
+        })?;
+
+        Ok(Some((app_name, service)))
+    }
+
+    async fn get_container_details(
+        &self,
+        app_name: Option<&AppName>,
+        service_name: Option<&str>,
+    ) -> Result<MultiMap<AppName, ContainerInspectResponse>, DockerInfrastructureError> {
+        debug!("Resolve container details for app {app_name:?}");
+
+        let container_list = Self::get_app_containers(app_name, service_name).await?;
+
+        let mut container_details = MultiMap::new();
+        for container in container_list.into_iter() {
+            if let Some(details) = not_found_to_none(inspect(container).await)? {
+                let app_name = match app_name {
+                    Some(app_name) => app_name.clone(),
+                    None => details
+                        .config
+                        .as_ref()
+                        .and_then(|con| {
+                            con.labels.as_ref().and_then(|lab| {
+                                lab.get(APP_NAME_LABEL)
+                                    .and_then(|app_name| AppName::from_str(app_name).ok())
+                            })
+                        })
+                        .unwrap(),
+                };
+                container_details.insert(app_name, details);
+            }
+        }
+
+        Ok(container_details)
+    }
+}
+
+#[async_trait]
+impl Infrastructure for DockerInfrastructure {
+    async fn get_services(&self) -> Result<MultiMap<AppName, Service>> {
+        let mut apps = MultiMap::new();
+        let container_details = self.get_container_details(None, None).await?;
+        let running_replicas = running_replicas(Self::get_replica_containers(None, None).await?);
+
+        for (app_name, details_vec) in container_details.into_iter() {
+            for details in details_vec {
+                let service = match Service::try_from(details) {
+                    Ok(service) => service,
+                    Err(e) => {
+                        debug!("Container does not provide required data: {e:?}");
+                        continue;
+                    }
+                };
+
+                let service = match running_replicas
+                    .get(&(app_name.to_string(), service.service_name().clone()))
+                {
+                    Some(count) => {
+                        let actual_replicas = service.actual_replicas() + count;
+                        ServiceBuilder::from(service)
+                            .actual_replicas(actual_replicas)
+                            .build()?
+                    }
+                    None => service,
+                };
+
+                apps.insert(app_name.clone(), service);
+            }
+        }
+
+        Ok(apps)
+    }
+
+    async fn get_app_names(&self) -> Result<HashSet<AppName>> {
+        let containers = Self::get_app_containers(None, None).await?;
+
+        Ok(containers
+            .into_iter()
+            .filter_map(|container| {
+                container
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.get(APP_NAME_LABEL))
+                    .and_then(|app_name| AppName::from_str(app_name).ok())
+            })
+            .collect())
+    }
+
+    async fn search_services(&self, query: &ServiceQuery) -> Result<Vec<ServiceSearchResult>> {
+        // The labels of the container summaries suffice, thus, no container needs to be inspected.
+        // Containers that are not running, e.g. exited ones, are listed as stopped services instead.
+        let containers = Self::get_app_containers(None, query.service_name.as_deref()).await?;
+        let tags_of_apps = match query.tag {
+            Some(_) => self.get_tags_of_apps().await?,
+            None => HashMap::new(),
+        };
+
+        Ok(containers
+            .into_iter()
+            .filter(|container| container.state.as_deref() == Some("running"))
+            .filter_map(|container| {
+                let mut labels = container.labels?;
+                let app_name = AppName::from_str(&labels.remove(APP_NAME_LABEL)?).ok()?;
+                let container_type = match labels.remove(CONTAINER_TYPE_LABEL) {
+                    Some(container_type) => container_type.parse::<ContainerType>().ok()?,
+                    None => ContainerType::Instance,
+                };
+                let tags = tags_of_apps.get(&app_name).cloned().unwrap_or_default();
+
+                Some(ServiceSearchResult::new(
+                    app_name,
+                    labels.remove(SERVICE_NAME_LABEL)?,
+                    labels.remove(IMAGE_LABEL).or(container.image)?,
+                    ServiceStatus::Running,
+                    container_type,
+                    tags,
+                ))
+            })
+            .filter(|result| query.matches(result))
+            .collect())
+    }
+
+    async fn get_stopped_services(&self, app_name: &AppName) -> Result<Vec<StoppedService>> {
+        let containers = Self::get_app_containers(Some(app_name), None).await?;
+
+        let mut stopped_services = Vec::new();
+        for container in containers
+            .into_iter()
+            .filter(|container| container.state.as_deref() != Some("running"))
+        {
+            if let Some(details) = not_found_to_none(inspect(container).await)? {
+                stopped_services.extend(stopped_service(details));
+            }
+        }
+
+        Ok(stopped_services)
+    }
+
+    async fn promote_canary(&self, app_name: &AppName, service_name: &str) -> Result<bool> {
+        let _deployment_lock = self
+            .deployment_locks
+            .lock(app_name, self.config.deployment_lock_timeout())
+            .await?
+            .ok_or_else(|| DockerInfrastructureError::DeploymentLockTimeout {
+                app_name: app_name.to_string(),
+            })?;
+
+        let canary_containers = Self::get_canary_containers(app_name, Some(service_name)).await?;
+        if canary_containers.is_empty() {
+            return Ok(false);
+        }
+
+        Self::promote_canary_containers(app_name, &canary_containers).await?;
+        Ok(true)
+    }
+
+    async fn rollback_canary(&self, app_name: &AppName, service_name: &str) -> Result<bool> {
+        let _deployment_lock = self
+            .deployment_locks
+            .lock(app_name, self.config.deployment_lock_timeout())
+            .await?
+            .ok_or_else(|| DockerInfrastructureError::DeploymentLockTimeout {
+                app_name: app_name.to_string(),
+            })?;
+
+        let canary_containers = Self::get_canary_containers(app_name, Some(service_name)).await?;
+        for container in &canary_containers {
+            info!("Rolling back canary {:?} of {app_name}", container.id);
+            Self::force_remove_container(container).await?;
+        }
+
+        Ok(!canary_containers.is_empty())
+    }
+
+    async fn prune_stopped_containers(&self, app_name: &AppName) -> Result<usize> {
+        Ok(Self::prune_exited_containers(app_name).await?)
+    }
+
+    async fn get_deployments(&self, app_name: &AppName) -> Result<Vec<AppDeployment>> {
+        let containers = Self::get_app_containers(Some(app_name), None).await?;
+        Ok(deployments(containers))
+    }
+
+    async fn deploy_services(
+        &self,
+        status_id: &str,
+        deployment_unit: &DeploymentUnit,
+        container_config: &ContainerConfig,
+    ) -> Result<Vec<Service>> {
+        let deployment_container = self
+            .create_status_change_container(status_id, deployment_unit.app_name())
+            .await?;
+
+        let result = self
+            .deploy_services_exclusively(deployment_unit, container_config)
+            .await;
+
+        let images = deployment_unit
+            .services()
+            .iter()
+            .map(|service| service.image().to_string())
+            .collect::<Vec<_>>();
+        let triggered_by = ClientCertificate::current()
+            .map(|client_certificate| format!(" triggered by {client_certificate}"))
+            .unwrap_or_default();
+        match &result {
+            Ok(_) => log::info!(
+                target: "prevant::audit",
+                "Deployed app {} with images {images:?}{triggered_by}",
+                deployment_unit.app_name()
+            ),
+            Err(err) => log::info!(
+                target: "prevant::audit",
+                "Failed to deploy app {} with images {images:?}{triggered_by}: {err}",
+                deployment_unit.app_name()
+            ),
+        }
+
+        delete(deployment_container).await?;
+
+        Ok(result?)
+    }
+
+    async fn get_status_change(&self, status_id: &str) -> Result<Option<Vec<Service>>> {
+        Ok(
+            match self
+                .find_status_change_container(status_id)
+                .await?
+                .as_ref()
+                .and_then(|c| {
+                    c.labels
+                        .as_ref()
+                        .and_then(|label| label.get(APP_NAME_LABEL))
+                })
+                .and_then(|app_name| AppName::from_str(app_name).ok())
+            {
+                Some(app_name) => {
+                    let mut services = Vec::new();
+                    if let Some(container_details) = self
+                        .get_container_details(Some(&app_name), None)
+                        .await?
+                        .remove(&app_name)
+                    {
+                        for container in container_details {
+                            services.push(Service::try_from(container)?);
+                        }
+                    }
+
+                    Some(services)
+                }
+                None => None,
+            },
+        )
+    }
+
+    /// Deletes all services for the given `app_name`.
+    async fn stop_services(
+        &self,
+        status_id: &str,
+        app_name: &AppName,
+        preserve_volumes: bool,
+    ) -> Result<Vec<Service>> {
+        let deployment_container = self
+            .create_status_change_container(status_id, app_name)
+            .await?;
+
+        let result = self.stop_services_impl(app_name, preserve_volumes).await;
+
+        delete(deployment_container).await?;
+
+        Ok(result?)
+    }
+
+    async fn with_deployment_locks<'a>(
+        &self,
+        app_names: &[AppName],
+        operation: BoxFuture<'a, ()>,
+    ) -> Result<()> {
+        self.deployment_locks
+            .lock_all(app_names, self.config.deployment_lock_timeout(), operation)
+            .await?
+            .ok_or_else(|| DockerInfrastructureError::DeploymentLockTimeout {
+                app_name: app_names
+                    .iter()
+                    .map(|app_name| app_name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            })?;
+        Ok(())
+    }
+
+    async fn get_logs<'a>(
+        &'a self,
+        app_name: &'a AppName,
+        service_name: &'a str,
+        from: &'a Option<DateTime<FixedOffset>>,
+        limit: &'a Option<usize>,
+        follow: bool,
+    ) -> BoxStream<'a, Result<(DateTime<FixedOffset>, String)>> {
+        stream! {
+            match Self::
+                get_app_container(&AppName::from_str(app_name).unwrap(), service_name)
+                .await
+            {
+                Ok(None) => {}
+                Ok(Some(container)) => {
+                    let docker = Docker::connect_with_socket_defaults()?;
+                    let container_id = container
+                        .id
+                        .as_ref()
+                        .expect("id is mandatory for docker container");
+                    trace!("Acquiring logs of container {container_id} since {from:?}");
+
+                    let log_options = match from {
+                        Some(from) => bollard::container::LogsOptions::<&str> {
+                            stdout: true,
+                            stderr: true,
+                            since: from.timestamp(),
+                            timestamps: true,
+                            follow,
+                            ..Default::default()
+                        },
+                        None => bollard::container::LogsOptions::<&str> {
+                            stdout: true,
+                            stderr: true,
+                            timestamps: true,
+                            follow,
+                            ..Default::default()
+                        },
+                    };
+
+                    let logs = docker.logs(container_id, Some(log_options));
+
+                    let mut logs = match limit {
+                        Some(log_limit) => Box::pin(logs.take(*log_limit))
+                            as BoxStream<Result<LogOutput, BollardError>>,
+                        None => Box::pin(logs) as BoxStream<Result<LogOutput, BollardError>>,
+                    };
+
+                    while let Some(result) = logs.next().await {
+                        match result {
+                            Ok(chunk) => {
+                                let line = chunk.to_string();
+
+                                let mut iter = line.splitn(2, ' ');
+                                let timestamp = iter.next()
+                                    .expect("This should never happen: docker should return timestamps, separated by space");
+
+                                let datetime = DateTime::parse_from_rfc3339(timestamp)
+                                    .expect("Expecting a valid timestamp");
+                                let log_line: String = iter.collect::<Vec<&str>>().join(" ");
+                                yield Ok((datetime, log_line))
+                            }
+                            Err(e) => yield Err(e.into()),
+                        }
+                    }
+                }
+                Err(e) => yield Err(e.into()),
+            }
+        }.boxed()
+    }
+
+    async fn get_app_tags(&self, app_name: &AppName) -> Result<Vec<String>> {
+        let mut tags = Self::fetch_tag_volumes(Some(app_name))
+            .await?
+            .iter()
+            .filter_map(|volume| volume.labels.get(TAGS_LABEL))
+            .flat_map(|tags| parse_tags(tags))
+            .collect::<Vec<_>>();
+        tags.sort();
+        tags.dedup();
+        Ok(tags)
+    }
+
+    async fn get_tags_of_apps(&self) -> Result<HashMap<AppName, Vec<String>>> {
+        let mut tags_of_apps = HashMap::<AppName, Vec<String>>::new();
+        for volume in Self::fetch_tag_volumes(None).await? {
+            let (Some(app_name), Some(tags)) = (
+                volume
+                    .labels
+                    .get(APP_NAME_LABEL)
+                    .and_then(|app_name| AppName::from_str(app_name).ok()),
+                volume.labels.get(TAGS_LABEL),
+            ) else {
+                continue;
+            };
+
+            let app_tags = tags_of_apps.entry(app_name).or_default();
+            app_tags.extend(parse_tags(tags));
+            app_tags.sort();
+            app_tags.dedup();
+        }
+        Ok(tags_of_apps)
+    }
+
+    async fn set_app_tags(&self, app_name: &AppName, tags: &[String]) -> Result<()> {
+        let previous_volumes = Self::fetch_tag_volumes(Some(app_name)).await?;
+
+        // The volume with the new tags is created first so that the app never appears untagged
+        // while the tags are replaced.
+        let docker = Docker::connect_with_socket_defaults()?;
+        let tags = tags.join(",");
+        let new_volume = if tags.is_empty() {
+            None
+        } else {
+            Some(
+                docker
+                    .create_volume(CreateVolumeOptions {
+                        labels: HashMap::from([
+                            (APP_NAME_LABEL, app_name.as_str()),
+                            (TAGS_LABEL, tags.as_str()),
+                        ]),
+                        ..Default::default()
+                    })
+                    .await?
+                    .name,
+            )
+        };
+
+        for volume in previous_volumes {
+            if Some(&volume.name) != new_volume.as_ref() {
+                docker.remove_volume(&volume.name, None).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_service_logs_since<'a>(
+        &'a self,
+        app_name: &'a AppName,
+        service_name: &'a str,
+        since: DateTime<Utc>,
+    ) -> BoxStream<'a, Result<LogLine>> {
+        try_stream! {
+            let Some(container) = Self::get_app_container(app_name, service_name).await? else {
+                return;
+            };
+            let docker = Docker::connect_with_socket_defaults()?;
+            let container_id = container
+                .id
+                .as_ref()
+                .expect("id is mandatory for docker container");
+
+            let mut logs = docker.logs(
+                container_id,
+                Some(LogsOptions::<&str> {
+                    stdout: true,
+                    stderr: true,
+                    since: since.timestamp(),
+                    timestamps: true,
+                    ..Default::default()
+                }),
+            );
+
+            // The chunks of containers with TTY contain whatever the terminal has buffered, thus,
+            // the chunks are split at the line ends.
+            let mut pending_stdout = String::new();
+            let mut pending_stderr = String::new();
+            while let Some(chunk) = logs.next().await {
+                let chunk = chunk?;
+                let (stream, pending) = match &chunk {
+                    LogOutput::StdErr { .. } => (LogStream::StdErr, &mut pending_stderr),
+                    _ => (LogStream::StdOut, &mut pending_stdout),
+                };
+
+                pending.push_str(&chunk.to_string());
+                for line in take_complete_lines(pending) {
+                    if let Some(log_line) = parse_log_line(&line, stream, since)? {
+                        yield log_line;
+                    }
+                }
+            }
+
+            for (stream, line) in [
+                (LogStream::StdOut, pending_stdout),
+                (LogStream::StdErr, pending_stderr),
+            ] {
+                if let Some(log_line) = parse_log_line(&line, stream, since)? {
+                    yield log_line;
+                }
+            }
+        }
+        .boxed()
+    }
+
+    async fn change_status(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+        status: ServiceStatus,
+    ) -> Result<Option<Service>> {
+        match Self::get_app_container(app_name, service_name).await? {
+            Some(container) => {
+                let docker = Docker::connect_with_socket_defaults()?;
+                let details = docker
+                    .inspect_container(
+                        container
+                            .id
+                            .as_ref()
+                            .expect("id is mandatory for a docker container"),
+                        None,
+                    )
+                    .await?;
+
+                macro_rules! run_future_and_map_err {
+                    ( $future:expr, $log_format:expr ) => {
+                        if let Err(err) = $future.await {
+                            match err {
+                                BollardError::DockerResponseServerError {
+                                    status_code,
+                                    message,
+                                    ..
+                                } if status_code == 304 => {
+                                    trace!(
+                                        "Container {} already in desired state: {message}",
+                                        details
+                                            .id
+                                            .as_ref()
+                                            .expect("id is mandatory for a docker container")
+                                    );
+                                }
+                                err => {
+                                    error!($log_format, err);
+                                    return Err(anyhow::Error::new(err));
+                                }
+                            };
+                        }
+                    };
+                }
+
+                match status {
+                    ServiceStatus::Running => {
+                        if !details
+                            .state
+                            .as_ref()
+                            .map(|state| state.running.unwrap_or_default())
+                            .unwrap()
+                        {
+                            run_future_and_map_err!(
+                                docker.start_container(
+                                    container
+                                        .id
+                                        .as_ref()
+                                        .expect("id is mandatory for a docker container"),
+                                    None::<StartContainerOptions::<&str>>,
+                                ),
+                                "Could not start container: {}"
+                            );
+                        }
+                    }
+                    ServiceStatus::Paused => {
+                        if details
+                            .state
+                            .as_ref()
+                            .map(|state| state.running.unwrap_or_default())
+                            .unwrap()
+                        {
+                            run_future_and_map_err!(
+                                docker.stop_container(
+                                    container
+                                        .id
+                                        .as_ref()
+                                        .expect("id is mandatory for a docker container"),
+                                    None
+                                ),
+                                "Could not pause container: {}"
+                            );
+                        }
+                    }
+                }
+
+                Ok(Some(Service::try_from(details)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn inspect_service(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        let Some(container) = Self::get_app_container(app_name, service_name).await? else {
+            return Ok(None);
+        };
+
+        let Some(mut details) = not_found_to_none(inspect(container).await)? else {
+            return Ok(None);
+        };
+
+        let secret_env_keys = details
+            .config
+            .as_ref()
+            .and_then(|config| config.labels.as_ref())
+            .and_then(|labels| labels.get(ENV_FROM_SECRETS_LABEL))
+            .and_then(|env_from_secrets| {
+                serde_json::from_str::<Vec<SecretEnvRef>>(env_from_secrets).ok()
+            })
+            .unwrap_or_default()
+            .iter()
+            .map(|secret_env_ref| secret_env_ref.env_key().to_string())
+            .collect::<HashSet<_>>();
+        if let Some(env) = details
+            .config
+            .as_mut()
+            .and_then(|config| config.env.as_mut())
+        {
+            redact_sensitive_environment_variables(env, &secret_env_keys);
+        }
+        if let Some(labels) = details
+            .config
+            .as_mut()
+            .and_then(|config| config.labels.as_mut())
+        {
+            for label in [REPLICATED_ENV_LABEL, ENV_LABEL] {
+                if let Some(env) = labels.get_mut(label) {
+                    redact_sensitive_replicated_environment_variables(env);
+                }
+            }
+        }
+
+        Ok(Some(serde_json::to_value(details)?))
+    }
+
+    async fn is_service_healthy(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Result<Option<bool>> {
+        let Some(container) = Self::get_app_container(app_name, service_name).await? else {
+            return Ok(None);
+        };
+
+        let Some(details) = not_found_to_none(inspect(container).await)? else {
+            return Ok(None);
+        };
+
+        Ok(match health_status(&details) {
+            None | Some(HealthStatusEnum::EMPTY) | Some(HealthStatusEnum::NONE) => None,
+            Some(status) => Some(status == HealthStatusEnum::HEALTHY),
+        })
+    }
+
+    async fn force_stop_service(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Result<Option<Service>> {
+        let Some(container) = Self::get_app_container(app_name, service_name).await? else {
+            return Ok(None);
+        };
+
+        let Some(details) = not_found_to_none(inspect(container).await)? else {
+            return Ok(None);
+        };
+        let container_id = details
+            .id
+            .clone()
+            .expect("id is mandatory for a docker container");
+        let service = Service::try_from(details)?;
+
+        warn!("Force stopping container {container_id} of {service_name} in {app_name}");
+        not_found_to_none(Self::force_stop_container(&container_id).await)?;
+
+        Ok(Some(service))
+    }
+
+    async fn get_service_by_container_id(
+        &self,
+        container_id: &str,
+    ) -> Result<Option<(AppName, Service)>> {
+        Ok(Self::find_service_by_container_id(container_id).await?)
+    }
+
+    async fn list_managed_networks(&self) -> Result<Vec<NetworkInfo>> {
+        let docker = Docker::connect_with_socket_defaults()?;
+        let managed_by_filter = format!("{MANAGED_BY_LABEL}={MANAGED_BY_VALUE}");
+
+        Ok(docker
+            .list_networks(Some(ListNetworksOptions::<&str> {
+                filters: HashMap::from([("label", vec![managed_by_filter.as_str()])]),
+            }))
+            .await?
+            .into_iter()
+            .filter_map(|network| {
+                let app_name = network
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.get(APP_NAME_LABEL))
+                    .and_then(|app_name| AppName::from_str(app_name).ok());
+                Some(NetworkInfo::new(network.id?, network.name?, app_name))
+            })
+            .collect())
+    }
+
+    async fn delete_orphaned_networks(&self, dry_run: bool) -> Result<Vec<NetworkInfo>> {
+        let docker = Docker::connect_with_socket_defaults()?;
+
+        let mut orphaned_networks = Vec::new();
+        let networks = self
+            .list_managed_networks()
+            .await?
+            .into_iter()
+            .chain(Self::list_legacy_app_networks().await?);
+        for network in networks {
+            // Shared networks, e.g. the peer network, do not belong to a single app.
+            let Some(app_name) = network.app_name() else {
+                continue;
+            };
+            // The apps of other shards are managed by other instances of PREvant.
+            if !self.config.is_app_in_shard(app_name) {
+                continue;
+            }
+
+            // The network of an app that is being deployed might not have containers yet.
+            let _deployment_lock = match self.deployment_locks.lock(app_name, Duration::ZERO).await
+            {
+                Ok(Some(deployment_lock)) => deployment_lock,
+                Ok(None) => continue,
+                Err(err) => {
+                    warn!("Cannot lock {app_name} to delete its orphaned network: {err}");
+                    continue;
+                }
+            };
+
+            let app_name_filter = format!("{APP_NAME_LABEL}={app_name}");
+            let containers = docker
+                .list_containers(Some(ListContainersOptions {
+                    all: true,
+                    filters: HashMap::from([("label", vec![app_name_filter.as_str()])]),
+                    ..Default::default()
+                }))
+                .await?;
+            if !containers.is_empty() {
+                continue;
+            }
+
+            if dry_run {
+                log::info!(
+                    "Would delete orphaned network {} ({})",
+                    network.name(),
+                    network.id()
+                );
+            } else {
+                log::info!(
+                    "Deleting orphaned network {} ({})",
+                    network.name(),
+                    network.id()
+                );
+                self.disconnect_traefik(network.id()).await?;
+                docker.remove_network(network.id()).await?;
+            }
+            orphaned_networks.push(network);
+        }
+
+        Ok(orphaned_networks)
+    }
+
+    async fn api_version(&self) -> Result<Option<String>> {
+        let docker = Docker::connect_with_socket_defaults()?;
+        Ok(docker.version().await?.api_version)
+    }
+
+    async fn http_forwarder(&self) -> Result<Box<dyn HttpForwarder + Send>> {
+        Ok(Box::new(DockerHttpForwarder {}))
+    }
+
+    async fn open_port_forward(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+        port: u16,
+    ) -> Result<Option<TcpStream>> {
+        let Some(container_details) = Self::get_app_container(app_name, service_name).await? else {
+            return Ok(None);
+        };
+
+        let exposed_ports = exposed_ports(&container_details);
+        if !exposed_ports.contains(&port) {
+            return Err(DockerInfrastructureError::PortNotExposed {
+                service_name: service_name.to_string(),
+                port,
+                exposed_ports: exposed_ports.into_iter().collect(),
+            }
+            .into());
+        }
+
+        Ok(Some(
+            connect_to_container(app_name, service_name, &container_details, port).await?,
+        ))
+    }
+}
+
+struct DockerHttpForwarder;
+
+/// Returns the ports that the container exposes, e.g. by the `EXPOSE` instruction of its image, and
+/// the port that Traefik routes the requests to. Only these ports can be forwarded so that the port
+/// forwarding cannot reach ports that the service does not offer, e.g. debugging ports.
+fn exposed_ports(container: &ContainerSummary) -> BTreeSet<u16> {
+    let traefik_port = container
+        .labels
+        .as_ref()
+        .and_then(|labels| labels.get(CONTAINER_PORT_LABEL))
+        .and_then(|port| port.parse::<u16>().ok());
+
+    container
+        .ports
+        .iter()
+        .flatten()
+        .map(|port| port.private_port)
+        .chain(traefik_port)
+        .collect()
+}
+
+/// Opens a TCP connection to the port of the container, trying each of its IP addresses.
+async fn connect_to_container(
+    app_name: &AppName,
+    service_name: &str,
+    container_details: &ContainerSummary,
+    port: u16,
+) -> Result<TcpStream> {
+    let ip_addresses = ip_addresses(
+        container_details
+            .network_settings
+            .as_ref()
+            .and_then(|network_settings| network_settings.networks.as_ref())
+            .into_iter()
+            .flat_map(|networks| networks.values()),
+    );
+    if ip_addresses.is_empty() {
+        return Err(anyhow::Error::msg("Found no IP address")
+            .context(format!("app {app_name}, service name {service_name}")));
+    }
+
+    // Dual-stack containers might not listen on their IPv4 address, thus, the IPv6 addresses
+    // are tried as well.
+    for ip in &ip_addresses {
+        match TcpStream::connect(SocketAddr::new(*ip, port)).await {
+            Ok(stream) => return Ok(stream),
+            Err(err) => debug!("Cannot connect to {ip} of {service_name} in {app_name}: {err}"),
+        }
+    }
+    Err(anyhow::Error::msg("Found no reachable IP address")
+        .context(format!("app {app_name}, service name {service_name}")))
+}
+
+#[async_trait]
+impl HttpForwarder for DockerHttpForwarder {
+    async fn request_web_host_meta(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+        request: http::Request<http_body_util::Empty<bytes::Bytes>>,
+    ) -> Result<Option<WebHostMeta>> {
+        let Some(container_details) =
+            DockerInfrastructure::get_app_container(app_name, service_name).await?
+        else {
+            return Ok(None);
+        };
+
+        let port = find_port(
+            container_details.ports.as_deref().unwrap_or_default(),
+            &container_details.labels,
+        )?;
+
+        let stream = connect_to_container(app_name, service_name, &container_details, port).await?;
+        let (mut sender, connection) =
+            hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                warn!("Error in connection: {}", e);
+            }
+        });
+
+        let (_parts, body) = sender.send_request(request).await?.into_parts();
+
+        let body_bytes = body.collect().await?.to_bytes();
+
+        Ok(serde_json::from_slice::<WebHostMeta>(&body_bytes).ok())
+    }
+}
+
+fn health_status(container_details: &ContainerInspectResponse) -> Option<HealthStatusEnum> {
+    container_details
+        .state
+        .as_ref()
+        .and_then(|state| state.health.as_ref())
+        .and_then(|health| health.status)
+}
+
+/// Returns the IP address and the port under which the service's container is reachable from
+/// other containers.
+fn container_ip_and_port(
+    service_name: &str,
+    container_details: &ContainerInspectResponse,
+) -> Result<(String, u16), DockerInfrastructureError> {
+    let ip = container_details
+        .network_settings
+        .as_ref()
+        .and_then(|network_settings| network_settings.networks.as_ref())
+        .and_then(|networks| {
+            networks
+                .values()
+                .filter_map(|network| network.ip_address.clone())
+                .find(|ip| !ip.is_empty())
+        })
+        .ok_or_else(|| DockerInfrastructureError::UnexpectedError {
+            err: anyhow!("Found no IP address of service {service_name}"),
+        })?;
+
+    let exposed_ports = container_details
+        .config
+        .as_ref()
+        .and_then(|config| config.exposed_ports.as_ref())
+        .map(|exposed_ports| {
+            exposed_ports
+                .keys()
+                .filter_map(|port| port.split('/').next()?.parse::<u16>().ok())
+                .map(|port| Port {
+                    private_port: port,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let labels = container_details
+        .config
+        .as_ref()
+        .and_then(|config| config.labels.clone());
+    let port = find_port(&exposed_ports, &labels)?;
+
+    Ok((ip, port))
+}
+
+/// Polls the URL of the startup probe until the container responds with a 2xx status code or
+/// until the failure threshold of the probe has been reached.
+async fn wait_for_startup_probe(
+    service_name: &str,
+    container_details: &ContainerInspectResponse,
+    probe: &StartupProbe,
+) -> Result<(), DockerInfrastructureError> {
+    let (ip, port) = container_ip_and_port(service_name, container_details)?;
+
+    let mut failures = 0u8;
+    loop {
+        match tokio::time::timeout(
+            std::time::Duration::from_secs(probe.timeout_secs()),
+            request_status(&ip, port, probe.url_path()),
+        )
+        .await
+        {
+            Ok(Ok(status)) if status.is_success() => return Ok(()),
+            Ok(Ok(status)) => {
+                debug!("Startup probe of {service_name} responded with {status}")
+            }
+            Ok(Err(err)) => debug!("Startup probe of {service_name} failed: {err}"),
+            Err(_) => debug!("Startup probe of {service_name} timed out"),
+        }
+
+        failures = failures.saturating_add(1);
+        if failures >= probe.failure_threshold() {
+            return Err(DockerInfrastructureError::StartupProbeFailure {
+                service_name: service_name.to_string(),
+                url_path: probe.url_path().to_string(),
+                failures,
+            });
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(probe.interval_secs())).await;
+    }
+}
+
+/// Derives the options of a service's readiness probe from the options of its container: Traefik
+/// must not route to the probe, it must not bind the host ports of the running container, and it
+/// must not be listed as a container of the app.
+fn readiness_probe_options(
+    mut options: bollard::container::Config<String>,
+) -> bollard::container::Config<String> {
+    if let Some(labels) = options.labels.as_mut() {
+        labels.remove(APP_NAME_LABEL);
+        labels.insert(TRAEFIK_ENABLE_LABEL.to_string(), String::from("false"));
+    }
+    if let Some(host_config) = options.host_config.as_mut() {
+        host_config.port_bindings = None;
+    }
+    options
+}
+
+/// Polls the readiness URL of the service every two seconds until the container responds with
+/// `200 OK` or until the timeout expires.
+async fn wait_for_readiness(
+    service_name: &str,
+    container_details: &ContainerInspectResponse,
+    readiness_url: &str,
+    timeout: Duration,
+) -> Result<(), DockerInfrastructureError> {
+    let (ip, port) = container_ip_and_port(service_name, container_details)?;
+    let readiness_url = format!("/{}", readiness_url.trim_start_matches('/'));
+
+    let wait = async {
+        loop {
+            match request_status(&ip, port, &readiness_url).await {
+                Ok(http::StatusCode::OK) => return,
+                Ok(status) => trace!("Service {service_name} is not ready yet: {status}"),
+                Err(err) => trace!("Service {service_name} is not ready yet: {err}"),
+            }
+            tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+        }
+    };
+
+    tokio::time::timeout(timeout, wait).await.map_err(|_| {
+        DockerInfrastructureError::ReadinessTimeout {
+            service_name: service_name.to_string(),
+            readiness_url,
+            timeout,
+        }
+    })
+}
+
+/// Registers the service's container in Consul. Consul checks the health of the service via its
+/// web host meta data if the service provides them.
+async fn register_in_consul(
+    app_name: &AppName,
+    service_name: &str,
+    container_details: &ContainerInspectResponse,
+    consul_config: &ConsulConfig,
+) -> Result<()> {
+    let (ip, port) = container_ip_and_port(service_name, container_details)?;
+    let provides_host_meta = matches!(
+        tokio::time::timeout(
+            consul_config.timeout(),
+            request_status(&ip, port, "/.well-known/host-meta.json"),
+        )
+        .await,
+        Ok(Ok(status)) if status.is_success()
+    );
+
+    let registration = ConsulServiceRegistration::new(
+        app_name,
+        service_name,
+        ip,
+        port,
+        provides_host_meta,
+        consul_config.check_interval(),
+    );
+    ConsulClient::new(consul_config)
+        .register(&registration)
+        .await?;
+    debug!("Registered service {service_name} of {app_name} in Consul");
+
+    Ok(())
+}
+
+async fn request_status(ip: &str, port: u16, path: &str) -> Result<http::StatusCode> {
+    let stream = TcpStream::connect(format!("{ip}:{port}")).await?;
+    let (mut sender, connection) =
+        hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            warn!("Error in connection: {}", e);
+        }
+    });
+
+    let request = http::Request::get(path)
+        .header(http::header::HOST, format!("{ip}:{port}"))
+        .body(http_body_util::Empty::<bytes::Bytes>::new())?;
+
+    Ok(sender.send_request(request).await?.status())
+}
+
+fn is_init_container(container: &ContainerSummary) -> bool {
+    container
+        .labels
+        .as_ref()
+        .map_or(false, |labels| labels.contains_key(INIT_CONTAINER_OF_LABEL))
+}
+
+fn is_replica_container(container: &ContainerSummary) -> bool {
+    container
+        .labels
+        .as_ref()
+        .map_or(false, |labels| labels.contains_key(REPLICA_OF_LABEL))
+}
+
+/// Helper function to build Label Filters
+fn label_filter<S>(label_name: S, label_value: Option<S>) -> Option<String>
+where
+    S: AsRef<str>,
+{
+    let label_name = label_name.as_ref().to_string();
+    match label_value.as_ref().map(AsRef::as_ref) {
+        None => Some(label_name.to_string()),
+        Some(value) => Some(format!("{label_name}={value}")),
+    }
+}
+
+/// Helper function to map ShipLift 404 errors to None
+fn not_found_to_none<T>(result: Result<T, BollardError>) -> Result<Option<T>, BollardError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(BollardError::DockerResponseServerError {
+            status_code: 404u16,
+            ..
+        }) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Helper function to delete containers with the aid of futures::future::join_all
+async fn delete(
+    details: ContainerInspectResponse,
+) -> Result<ContainerInspectResponse, BollardError> {
+    let docker = Docker::connect_with_socket_defaults()?;
+    docker
+        .remove_container(
+            details
+                .id
+                .as_ref()
+                .expect("id is mandatory for a docker container"),
+            None,
+        )
+        .await?;
+    Ok(details)
+}
+
+/// Helper function to inspect containers with the aid of futures::future::join_all
+async fn inspect(container: ContainerSummary) -> Result<ContainerInspectResponse, BollardError> {
+    let docker = Docker::connect_with_socket_defaults()?;
+    docker
+        .inspect_container(
+            &container
+                .id
+                .expect("id is mandatory for a docker container"),
+            None,
+        )
+        .await
+}
+
+/// Replaces the values of the `KEY=VALUE` pairs that might contain credentials or that have been
+/// resolved from secrets, see [`ENV_FROM_SECRETS_LABEL`].
+fn redact_sensitive_environment_variables(env: &mut [String], secret_env_keys: &HashSet<String>) {
+    for variable in env.iter_mut() {
+        if let Some((key, _)) = variable.split_once('=') {
+            if is_sensitive_environment_variable(key) || secret_env_keys.contains(key) {
+                *variable = format!("{key}={REDACTED_VALUE}");
+            }
+        }
+    }
+}
+
+/// Replaces the values of the environment variables stored in a label, see [`REPLICATED_ENV_LABEL`]
+/// and [`ENV_LABEL`], that might contain credentials. A label that cannot be parsed is redacted
+/// entirely.
+fn redact_sensitive_replicated_environment_variables(replicated_env: &mut String) {
+    let Ok(mut variables) =
+        serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(replicated_env)
+    else {
+        *replicated_env = REDACTED_VALUE.to_string();
+        return;
+    };
+
+    for (key, variable) in variables.iter_mut() {
+        if !is_sensitive_environment_variable(key) {
+            continue;
+        }
+        if let Some(value) = variable.get_mut("value") {
+            *value = serde_json::Value::from(REDACTED_VALUE);
+        }
+    }
+
+    *replicated_env = serde_json::Value::Object(variables).to_string();
+}
+
+fn find_port(
+    ports: &[Port],
+    labels: &Option<HashMap<String, String>>,
+) -> Result<u16, DockerInfrastructureError> {
+    if let Some(port) = labels
+        .as_ref()
+        .and_then(|labels| labels.get(CONTAINER_PORT_LABEL))
+    {
+        match port.parse::<u16>() {
+            Ok(port) => Ok(port),
+            Err(err) => Err(DockerInfrastructureError::UnexpectedError {
+                err: anyhow::Error::new(err)
+                    .context("Cannot parse traefik port label into port number"),
+            }),
+        }
+    } else {
+        Ok(ports
+            .iter()
+            .map(|port| port.private_port)
+            .min()
+            .unwrap_or(80u16))
+    }
+}
+
+/// Prefixes the volume with the app name so that apps do not share their data. Because named
+/// volumes cannot contain a `.`, the last `.` separates the app name from the volume name.
+fn named_volume_name(app_name: &str, volume_name: &str) -> String {
+    format!("{app_name}.{volume_name}")
+}
+
+/// Returns the name of the [`NamedVolumeMount`] if the Docker volume belongs to the app.
+fn parse_named_volume_name<'a>(app_name: &str, docker_volume_name: &'a str) -> Option<&'a str> {
+    match docker_volume_name.rsplit_once('.') {
+        Some((prefix, volume_name)) if prefix == app_name && !volume_name.is_empty() => {
+            Some(volume_name)
+        }
+        _ => None,
+    }
+}
+
+impl TryFrom<ContainerInspectResponse> for Service {
+    type Error = DockerInfrastructureError;
+
+    fn try_from(
+        container_details: ContainerInspectResponse,
+    ) -> Result<Service, DockerInfrastructureError> {
+        // Docker resolves the image of the container to the ID of the pulled image
+        let image_digest = container_details
+            .image
+            .clone()
+            .filter(|image| image.starts_with("sha256:"));
+        let (mut labels, working_dir, stop_signal) = container_details
+            .config
+            .map(|config| (config.labels, config.working_dir, config.stop_signal))
+            .unwrap_or_default();
+        let container_id = container_details
+            .id
+            .expect("id is mandatory for a docker container");
+        let app_name = match labels
+            .as_mut()
+            .and_then(|labels| labels.remove(APP_NAME_LABEL))
+        {
+            Some(name) => name,
+            None => {
+                return Err(DockerInfrastructureError::MissingAppNameLabel { container_id });
+            }
+        };
+
+        let service_name = match labels
+            .as_mut()
+            .and_then(|labels| labels.remove(SERVICE_NAME_LABEL))
+        {
+            Some(name) => name,
+            None => {
+                return Err(DockerInfrastructureError::MissingServiceNameLabel { container_id });
+            }
+        };
+
+        let image = match labels
+            .as_mut()
+            .and_then(|labels| labels.remove(IMAGE_LABEL))
+        {
+            Some(image_label) => Image::from_str(&image_label).map_err(|err| {
+                DockerInfrastructureError::UnexpectedImageFormat {
+                    img: image_label,
+                    err: anyhow::Error::new(err),
+                }
+            }),
+            None => {
+                let Some(image) = container_details.image else {
+                    return Err(DockerInfrastructureError::InvalidContainerImage { container_id });
+                };
+                Image::from_str(&image).map_err(|err| {
+                    DockerInfrastructureError::UnexpectedImageFormat {
+                        img: image,
+                        err: anyhow::Error::new(err),
+                    }
+                })
+            }
+        }?;
+        let mut config_builder = ServiceConfigBuilder::new()
+            .service_name(service_name.clone())
+            .image(image);
+
+        if let Some(lb) = labels
+            .as_mut()
+            .and_then(|labels| labels.remove(CONTAINER_TYPE_LABEL))
+        {
+            config_builder = config_builder.container_type(lb.parse::<ContainerType>()?);
+        }
+
+        // Containers created by previous versions of PREvant only provide the replicated variables.
+        let env = labels.as_mut().and_then(|labels| {
+            let replicated_env = labels.remove(REPLICATED_ENV_LABEL);
+            labels.remove(ENV_LABEL).or(replicated_env)
+        });
+        if let Some(env) = env {
+            let env = serde_json::from_str::<Environment>(&env).map_err(|err| {
+                DockerInfrastructureError::UnexpectedError {
+                    err: anyhow::Error::new(err),
+                }
+            })?;
+            config_builder = config_builder.env(env);
+        }
+        let mut config = config_builder.build()?;
+
+        if let Some(env_inherit) = labels
+            .as_mut()
+            .and_then(|labels| labels.remove(ENV_INHERIT_LABEL))
+        {
+            let env_inherit =
+                serde_json::from_str::<serde_json::Value>(&env_inherit).map_err(|err| {
+                    DockerInfrastructureError::UnexpectedError {
+                        err: anyhow::Error::new(err),
+                    }
+                })?;
+            let names = env_inherit["names"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|name| name.as_str().map(String::from))
+                .collect();
+            let strict = env_inherit["strict"].as_bool().unwrap_or_default();
+            config.set_env_inherit(names, strict);
+        }
+        if let Some(env_from_secrets) = labels
+            .as_mut()
+            .and_then(|labels| labels.remove(ENV_FROM_SECRETS_LABEL))
+        {
+            let env_from_secrets = serde_json::from_str::<Vec<SecretEnvRef>>(&env_from_secrets)
+                .map_err(|err| DockerInfrastructureError::UnexpectedError {
+                    err: anyhow::Error::new(err),
+                })?;
+            config.set_env_from_secrets(env_from_secrets);
+        }
+        let replicas = labels
+            .as_mut()
+            .and_then(|labels| labels.remove(REPLICAS_LABEL))
+            .and_then(|replicas| replicas.parse::<u32>().ok());
+        config.set_replicas(replicas);
+        let ipc_mode = labels
+            .as_mut()
+            .and_then(|labels| labels.remove(IPC_MODE_LABEL))
+            .and_then(|ipc_mode| IpcMode::try_from(ipc_mode).ok());
+        config.set_ipc_mode(ipc_mode);
+        let depends_on = labels
+            .as_mut()
+            .and_then(|labels| labels.remove(DEPENDS_ON_LABEL))
+            .map(|depends_on| {
+                depends_on
+                    .split(',')
+                    .filter(|service_name| !service_name.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        config.set_depends_on(depends_on);
+
+        let (devices, shm_size, pids_limit, ulimits) = container_details
+            .host_config
+            .map(|host_config| {
+                (
+                    host_config.devices,
+                    host_config.shm_size,
+                    host_config.pids_limit,
+                    host_config.ulimits,
+                )
+            })
+            .unwrap_or_default();
+
+        config.set_shm_size_bytes(shm_size.map(|size| size as u64));
+        // Docker reports an empty string for images without WORKDIR instruction
+        config.set_working_dir(working_dir.filter(|working_dir| !working_dir.is_empty()));
+        config.set_stop_signal(stop_signal.filter(|stop_signal| !stop_signal.is_empty()));
+        // Docker reports 0 or -1 for containers without PID limit
+        config.set_pid_limit(
+            pids_limit
+                .filter(|pids_limit| *pids_limit > 0)
+                .map(|pids_limit| pids_limit as u64),
+        );
+
+        if let Some(ulimits) = ulimits {
+            config.set_ulimits(
+                ulimits
+                    .into_iter()
+                    .filter_map(|ulimit| {
+                        Some(UlimitConfig::new(
+                            ulimit.name?,
+                            ulimit.soft? as u64,
+                            ulimit.hard? as u64,
+                        ))
+                    })
+                    .collect(),
+            );
+        }
+
+        if let Some(devices) = devices {
+            config.set_devices(
+                devices
+                    .into_iter()
+                    .filter_map(|device| {
+                        Some(DeviceMapping::new(
+                            device.path_on_host?,
+                            device.path_in_container?,
+                            device
+                                .cgroup_permissions
+                                .unwrap_or_else(|| String::from("rwm")),
+                        ))
+                    })
+                    .collect(),
+            );
+        }
+
+        config.set_named_volumes(
+            container_details
+                .mounts
+                .into_iter()
+                .flatten()
+                .filter(|mount| mount.typ == Some(MountPointTypeEnum::VOLUME))
+                .filter_map(|mount| {
+                    let volume_name =
+                        parse_named_volume_name(&app_name, mount.name.as_deref()?)?.to_string();
+                    Some(NamedVolumeMount::new(volume_name, mount.destination?))
+                })
+                .collect(),
+        );
+
+        let Some(state) = container_details.state else {
+            return Err(DockerInfrastructureError::InvalidContainerState { container_id });
+        };
+
+        let app_created_at = labels
+            .as_mut()
+            .and_then(|labels| labels.remove(CREATED_AT_LABEL))
+            .and_then(|created_at| DateTime::parse_from_rfc3339(&created_at).ok())
+            .map(|created_at| created_at.with_timezone(&Utc));
+        let deployed_by_version = labels
+            .as_mut()
+            .and_then(|labels| labels.remove(VERSION_LABEL));
+
+        let ip_addresses = ip_addresses(
+            container_details
+                .network_settings
+                .as_ref()
+                .and_then(|network_settings| network_settings.networks.as_ref())
+                .into_iter()
+                .flat_map(|networks| networks.values()),
+        );
+        let host_ports = container_details
+            .network_settings
+            .and_then(|network_settings| network_settings.ports)
+            .map(|ports| host_ports(&ports))
+            .unwrap_or_default();
+
+        let started_at = state
+            .started_at
+            .as_deref()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .expect("started_at is mandatory for a docker container");
+
+        let status = match state.status.unwrap_or(ContainerStateStatusEnum::PAUSED) {
+            ContainerStateStatusEnum::RUNNING => ServiceStatus::Running,
+            _ => ServiceStatus::Paused,
+        };
+        let actual_replicas = u32::from(status == ServiceStatus::Running);
+
+        Ok(ServiceBuilder::new()
+            .id(container_id.clone())
+            .app_name(app_name.clone())
+            .config(config)
+            .service_status(status)
+            .started_at(started_at.into())
+            .host_ports(host_ports)
+            .app_created_at(app_created_at)
+            .deployed_by_version(deployed_by_version)
+            .image_digest(image_digest)
+            .ip_addresses(ip_addresses)
+            .expected_replicas(replicas.unwrap_or(1))
+            .actual_replicas(actual_replicas)
+            .build()?)
+    }
+}
+
+/// Removes the complete lines, including their line end, from the start of `pending`.
+fn take_complete_lines(pending: &mut String) -> Vec<String> {
+    let Some(end) = pending.rfind('\n') else {
+        return Vec::new();
+    };
+    let rest = pending.split_off(end + 1);
+    let complete = std::mem::replace(pending, rest);
+    complete.split_inclusive('\n').map(String::from).collect()
+}
+
+/// Parses a log line of the form `{timestamp} {content}` and returns it if it has been logged
+/// since the given time.
+fn parse_log_line(line: &str, stream: LogStream, since: DateTime<Utc>) -> Result<Option<LogLine>> {
+    let Some((timestamp, content)) = line.split_once(' ') else {
+        return Ok(None);
+    };
+    let timestamp = DateTime::parse_from_rfc3339(timestamp)?.with_timezone(&Utc);
+    // Docker only supports seconds as `since`
+    if timestamp < since {
+        return Ok(None);
+    }
+    Ok(Some(LogLine::new(timestamp, stream, content.to_string())))
+}
+
+/// Returns the app of an unlabeled network named `{app_name}-net`, which has been created by a
+/// PREvant version that did not label its networks yet.
+fn legacy_app_name_of_network(network: &Network) -> Option<AppName> {
+    if network
+        .labels
+        .as_ref()
+        .map_or(false, |labels| labels.contains_key(MANAGED_BY_LABEL))
+    {
+        return None;
+    }
+    AppName::from_str(network.name.as_deref()?.strip_suffix("-net")?).ok()
+}
+
+/// Counts the running replica containers by app and service name.
+fn running_replicas(containers: Vec<ContainerSummary>) -> HashMap<(String, String), u32> {
+    let mut running_replicas = HashMap::new();
+    for container in containers {
+        if container.state.as_deref() != Some("running") {
+            continue;
+        }
+        let Some(mut labels) = container.labels else {
+            continue;
+        };
+        if let (Some(app_name), Some(service_name)) = (
+            labels.remove(APP_NAME_LABEL),
+            labels.remove(REPLICA_OF_LABEL),
+        ) {
+            *running_replicas
+                .entry((app_name, service_name))
+                .or_insert(0) += 1;
+        }
+    }
+    running_replicas
+}
+
+/// Returns the IPv4 addresses of the container's networks followed by their IPv6 addresses.
+fn ip_addresses<'a>(networks: impl Iterator<Item = &'a EndpointSettings> + Clone) -> Vec<IpAddr> {
+    let ipv4_addresses = networks
+        .clone()
+        .filter_map(|network| network.ip_address.as_deref());
+    let ipv6_addresses = networks.filter_map(|network| network.global_ipv6_address.as_deref());
+
+    ipv4_addresses
+        .chain(ipv6_addresses)
+        .filter_map(|ip| ip.parse::<IpAddr>().ok())
+        .collect()
+}
+
+/// Returns the first `/64` subnet of `ipv6_subnet` that does not overlap with the subnets of the
+/// networks, which may be larger or smaller than `/64`.
+fn free_ipv6_subnet(ipv6_subnet: &Ipv6Subnet, networks: &[Network]) -> Option<Ipv6Subnet> {
+    let used_subnets = networks
+        .iter()
+        .filter_map(|network| network.ipam.as_ref()?.config.as_ref())
+        .flatten()
+        .filter_map(|config| {
+            let (address, prefix_len) = config.subnet.as_deref()?.split_once('/')?;
+            let address = address.parse::<Ipv6Addr>().ok()?;
+            let prefix_len = prefix_len.parse::<u8>().ok().filter(|len| *len <= 128)?;
+            Some((address, prefix_len))
+        })
+        .collect::<Vec<_>>();
+
+    ipv6_subnet.subnets_64().find(|subnet| {
+        !used_subnets
+            .iter()
+            .any(|(address, prefix_len)| subnet.overlaps(*address, *prefix_len))
+    })
+}
+
+/// Groups the running containers by their deploy id. Containers that have been created before
+/// PREvant labeled them with a deploy id are omitted.
+fn deployments(containers: Vec<ContainerSummary>) -> Vec<AppDeployment> {
+    let mut containers_by_deploy_id = BTreeMap::<String, Vec<ContainerSummary>>::new();
+    for container in containers
+        .into_iter()
+        .filter(|container| container.state.as_deref() == Some("running"))
+    {
+        let Some(deploy_id) = container
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(DEPLOY_ID_LABEL))
+            .cloned()
+        else {
+            continue;
+        };
+        containers_by_deploy_id
+            .entry(deploy_id)
+            .or_default()
+            .push(container);
+    }
+
+    let mut deployments = containers_by_deploy_id
+        .into_iter()
+        .map(|(deploy_id, containers)| {
+            let deployed_at = containers
+                .iter()
+                .filter_map(|container| container.created)
+                .min()
+                .and_then(|created| DateTime::from_timestamp(created, 0));
+            let mut service_names = containers
+                .iter()
+                .filter_map(|container| container.labels.as_ref()?.get(SERVICE_NAME_LABEL))
+                .cloned()
+                .collect::<Vec<_>>();
+            service_names.sort();
+            let container_ids = containers
+                .into_iter()
+                .filter_map(|container| container.id)
+                .collect();
+            AppDeployment::new(deploy_id, deployed_at, service_names, container_ids)
+        })
+        .collect::<Vec<_>>();
+    deployments.sort_by(|a, b| a.deployed_at().cmp(&b.deployed_at()));
+    deployments
+}
+
+/// Returns the exited containers that have been superseded by another container of the same
+/// service, i.e. the service runs another container or its newest container belongs to another
+/// deployment. Exited containers of stopped services, e.g. paused ones, are not superseded.
+fn superseded_exited_containers(containers: Vec<ContainerSummary>) -> Vec<ContainerSummary> {
+    let mut containers_by_service = HashMap::<String, Vec<ContainerSummary>>::new();
+    for container in containers {
+        let Some(service_name) = container
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(SERVICE_NAME_LABEL))
+            .cloned()
+        else {
+            continue;
+        };
+        containers_by_service
+            .entry(service_name)
+            .or_default()
+            .push(container);
+    }
+
+    let deploy_id = |container: &ContainerSummary| {
+        container
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(DEPLOY_ID_LABEL))
+            .cloned()
+    };
+
+    let mut superseded_containers = Vec::new();
+    for containers in containers_by_service.into_values() {
+        let is_running = containers
+            .iter()
+            .any(|container| container.state.as_deref() == Some("running"));
+        let current_deploy_id = containers
+            .iter()
+            .max_by_key(|container| container.created)
+            .and_then(deploy_id);
+
+        superseded_containers.extend(containers.into_iter().filter(|container| {
+            container.state.as_deref() == Some("exited")
+                && (is_running || deploy_id(container) != current_deploy_id)
+        }));
+    }
+    superseded_containers
+}
+
+/// Returns the stopped service of the container or `None` if the container is still running
+/// (or paused) or if it does not belong to a service.
+fn stopped_service(container_details: ContainerInspectResponse) -> Option<StoppedService> {
+    let state = container_details.state?;
+    match state.status? {
+        ContainerStateStatusEnum::CREATED
+        | ContainerStateStatusEnum::EXITED
+        | ContainerStateStatusEnum::DEAD => {}
+        _ => return None,
+    }
+
+    let service_name = container_details
+        .config?
+        .labels?
+        .remove(SERVICE_NAME_LABEL)?;
+
+    // Docker reports 0001-01-01T00:00:00Z for containers that never finished.
+    let exited_at = state
+        .finished_at
+        .as_deref()
+        .and_then(|finished_at| DateTime::parse_from_rfc3339(finished_at).ok())
+        .filter(|finished_at| finished_at.timestamp() > 0);
+
+    Some(StoppedService::new(
+        service_name,
+        container_details.id?,
+        exited_at,
+        state.exit_code,
+    ))
+}
+
+/// Extracts the host ports that Docker assigned to the published container ports, e.g.
+/// `{"9092/tcp": [{"HostIp": "0.0.0.0", "HostPort": "19092"}]}` results in `9092 → 19092`.
+fn host_ports(ports: &PortMap) -> BTreeMap<u16, u16> {
+    ports
+        .iter()
+        .filter_map(|(container_port, bindings)| {
+            let container_port = container_port.split('/').next()?.parse::<u16>().ok()?;
+            let host_port = bindings
+                .as_ref()?
+                .iter()
+                .find_map(|binding| binding.host_port.as_ref()?.parse::<u16>().ok())?;
+            Some((container_port, host_port))
+        })
+        .collect()
+}
+
+impl From<BollardError> for DockerInfrastructureError {
+    fn from(err: BollardError) -> Self {
+        match &err {
+            BollardError::DockerResponseServerError {
+                status_code,
+                message,
+            } => match status_code {
+                404u16 => {
+                    return DockerInfrastructureError::ImageNotFound {
+                        internal_message: message.clone(),
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        DockerInfrastructureError::UnexpectedError {
+            err: anyhow::Error::new(err),
+        }
+    }
+}
+
+impl From<ImagePullError> for DockerInfrastructureError {
+    fn from(err: ImagePullError) -> Self {
+        match err {
+            ImagePullError::Docker(err) => DockerInfrastructureError::from(err),
+            err => DockerInfrastructureError::UnexpectedError {
+                err: anyhow::Error::new(err),
+            },
+        }
+    }
+}
+
+impl From<ServiceError> for DockerInfrastructureError {
+    fn from(err: ServiceError) -> Self {
+        match err {
+            ServiceError::InvalidServiceType { label } => {
+                DockerInfrastructureError::UnknownServiceType {
+                    unknown_label: label,
+                }
+            }
+            err => DockerInfrastructureError::UnexpectedError {
+                err: anyhow::Error::new(err),
+            },
+        }
+    }
+}
+
+impl From<RedisError> for DockerInfrastructureError {
+    fn from(err: RedisError) -> Self {
+        DockerInfrastructureError::UnexpectedError {
+            err: anyhow::Error::new(err),
+        }
+    }
+}
+
+impl From<ServiceConfigBuilderError> for DockerInfrastructureError {
+    fn from(err: ServiceConfigBuilderError) -> Self {
+        DockerInfrastructureError::UnexpectedError {
+            err: anyhow::Error::new(err),
+        }
+    }
+}
+
+impl From<ServiceBuilderError> for DockerInfrastructureError {
+    fn from(err: ServiceBuilderError) -> Self {
+        DockerInfrastructureError::UnexpectedError {
+            err: anyhow::Error::new(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Environment, EnvironmentVariable};
+    use crate::sc;
+    use bollard::models::ContainerState;
+    use bollard::models::ContainerStateStatusEnum;
+    use bollard::models::NetworkSettings;
+    use secstr::SecUtf8;
+    use std::collections::HashSet;
+
+    macro_rules! container_details {
+        ($id:expr, $app_name:expr, $service_name:expr, $image:expr, $container_type:expr, $($l_key:expr => $l_value:expr),* ) => {{
+            let mut labels = std::collections::HashMap::new();
+
+            if let Some(app_name) = $app_name {
+                labels.insert(String::from(APP_NAME_LABEL), app_name);
+            }
+            if let Some(service_name) = $service_name {
+                labels.insert(String::from(SERVICE_NAME_LABEL), service_name);
+            }
+            if let Some(container_type) = $container_type {
+                labels.insert(String::from(CONTAINER_TYPE_LABEL), container_type);
+            }
+            if let Some(image) = $image {
+                labels.insert(String::from(IMAGE_LABEL), image);
+            }
+
+            $( labels.insert($l_key, $l_value); )*
+
+
+
+            ContainerInspectResponse {
+                app_armor_profile: Some("".to_string()),
+                args: Some(vec![]),
+                config: Some(bollard::service::ContainerConfig {
+                    attach_stderr: Some(false),
+                    attach_stdin: Some(false),
+                    attach_stdout: Some(false),
+                    cmd: None,
+                    domainname: Some("".to_string()),
+                    entrypoint: None,
+                    env: None,
+                    exposed_ports: None,
+                    hostname: Some("".to_string()),
+                    image: Some("".to_string()),
+                    labels: Some(labels),
+                    on_build: None,
+                    open_stdin: Some(false),
+                    stdin_once: Some(false),
+                    tty: Some(false),
+                    user: Some("".to_string()),
+                    working_dir: Some("".to_string()),
+                    ..Default::default()
+                }),
+                driver: Some("".to_string()),
+                host_config: Some(HostConfig {
+                    cgroup_parent: None,
+                    container_id_file: Some("".to_string()),
+                    cpu_shares: None,
+                    cpuset_cpus: None,
+                    memory: None,
+                    memory_swap: None,
+                    network_mode: Some("".to_string()),
+                    pid_mode: None,
+                    port_bindings: None,
+                    privileged: Some(false),
+                    publish_all_ports: Some(false),
+                    readonly_rootfs: None,
+                    ..Default::default()
+                }),
+                hostname_path: Some("".to_string()),
+                hosts_path: Some("".to_string()),
+                log_path: Some("".to_string()),
+                id: Some($id),
+                image: Some("sha256:9895c9b90b58c9490471b877f6bb6a90e6bdc154da7fbb526a0322ea242fc913".to_string()),
+                mount_label: Some("".to_string()),
+                name: Some("".to_string()),
+                network_settings: Some(NetworkSettings {
+                    bridge: Some("".to_string()),
+                    gateway: Some("".to_string()),
+                    ip_address: Some("".to_string()),
+                    ip_prefix_len: Some(0),
+                    mac_address: Some("".to_string()),
+                    ports: None,
+                    networks: None,
+                    ..Default::default()
+                }),
+                path: Some("".to_string()),
+                process_label: Some("".to_string()),
+                resolv_conf_path: Some("".to_string()),
+                restart_count: Some(0) ,
+                state: Some(ContainerState {
+                    status: Some(ContainerStateStatusEnum::RUNNING),
+                    error: Some("".to_string()),
+                    exit_code: Some(0),
+                    oom_killed: Some(false),
+                    paused: Some(false),
+                    pid: Some(0),
+                    restarting: Some(false),
+                    running: Some(false),
+                    finished_at: Some(chrono::Utc::now().to_rfc3339()),
+                    started_at: Some(chrono::Utc::now().to_rfc3339()),
+                    ..Default::default()
+                }),
+                created: Some(chrono::Utc::now().to_rfc3339()),
+                mounts: Some(vec![]),
+                ..Default::default()
+            }
+        }};
+    }
+
+    #[test]
+    fn should_create_container_options_with_host_port_bindings() {
+        let mut config = sc!("kafka", "bitnami/kafka");
+        config.set_host_port_bindings(
+            serde_json::from_value(serde_json::json!([
+                { "containerPort": 9092, "hostPort": 19092 },
+                { "containerPort": 9093, "protocol": "udp" }
+            ]))
+            .unwrap(),
+        );
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+
+        assert_eq!(
+            options
+                .exposed_ports
+                .as_ref()
+                .map(|ports| ports.keys().cloned().collect::<HashSet<_>>()),
+            Some(HashSet::from([
+                String::from("9092/tcp"),
+                String::from("9093/udp")
+            ]))
+        );
+        let port_bindings = options.host_config.unwrap().port_bindings.unwrap();
+        assert_eq!(
+            port_bindings.get("9092/tcp"),
+            Some(&Some(vec![DockerPortBinding {
+                host_ip: None,
+                host_port: Some(String::from("19092"))
+            }]))
+        );
+        assert_eq!(
+            port_bindings.get("9093/udp"),
+            Some(&Some(vec![DockerPortBinding {
+                host_ip: None,
+                host_port: None
+            }]))
+        );
+    }
+
+    #[test]
+    fn should_exclude_readiness_probe_from_routing() {
+        let mut config = sc!("frontend", "nginx");
+        config.set_host_port_bindings(
+            serde_json::from_value(serde_json::json!([
+                { "containerPort": 80, "hostPort": 8080 }
+            ]))
+            .unwrap(),
+        );
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+
+        let probe_options = readiness_probe_options(options.clone());
+
+        let labels = probe_options.labels.unwrap();
+        assert_eq!(
+            labels.get(TRAEFIK_ENABLE_LABEL),
+            Some(&String::from("false"))
+        );
+        assert_eq!(labels.get(APP_NAME_LABEL), None);
+        assert_eq!(
+            labels.get("traefik.frontend.rule"),
+            options.labels.unwrap().get("traefik.frontend.rule")
+        );
+        assert_eq!(probe_options.host_config.unwrap().port_bindings, None);
+        assert_eq!(probe_options.image, options.image);
+    }
+
+    #[test]
+    fn should_create_container_options_with_devices() {
+        let mut config = sc!("firmware-test", "firmware-test");
+        config.set_devices(vec![DeviceMapping::new(
+            String::from("/dev/ttyUSB0"),
+            String::from("/dev/serial"),
+            String::from("rw"),
+        )]);
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+
+        assert_eq!(
+            options.host_config.unwrap().devices,
+            Some(vec![DockerDeviceMapping {
+                path_on_host: Some(String::from("/dev/ttyUSB0")),
+                path_in_container: Some(String::from("/dev/serial")),
+                cgroup_permissions: Some(String::from("rw")),
+            }])
+        );
+    }
+
+    #[test]
+    fn should_create_container_options_with_shm_size() {
+        let mut config = sc!("selenium", "selenium/standalone-chrome");
+        config.set_shm_size_bytes(Some(ByteSize::mb(256).as_u64()));
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+
+        assert_eq!(
+            options.host_config.unwrap().shm_size,
+            Some(256 * 1000 * 1000)
+        );
+    }
+
+    #[test]
+    fn should_create_container_options_with_pid_limit() {
+        let mut config = sc!("db", "mariadb");
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            default_pid_limit = 200
+            "#,
+        )
+        .unwrap();
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &container_config,
+            &[],
+        );
+        assert_eq!(options.host_config.unwrap().pids_limit, Some(200));
+
+        config.set_pid_limit(Some(50));
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &container_config,
+            &[],
+        );
+        assert_eq!(options.host_config.unwrap().pids_limit, Some(50));
+    }
+
+    #[test]
+    fn should_create_container_options_with_working_dir() {
+        let mut config = sc!("db", "mariadb");
+        config.set_working_dir(Some(String::from("/srv/app")));
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+        assert_eq!(options.working_dir, Some(String::from("/srv/app")));
+    }
+
+    #[test]
+    fn should_check_working_dir() {
+        let mut config = sc!("db", "mariadb");
+        assert!(DockerInfrastructure::check_working_dir(&config).is_ok());
+
+        config.set_working_dir(Some(String::from("/srv/app")));
+        assert!(DockerInfrastructure::check_working_dir(&config).is_ok());
+
+        config.set_working_dir(Some(String::from("srv/app")));
+        assert!(matches!(
+            DockerInfrastructure::check_working_dir(&config),
+            Err(DockerInfrastructureError::InvalidWorkingDir { working_dir, .. }) if working_dir == "srv/app"
+        ));
+    }
+
+    #[test]
+    fn should_check_stop_signal() {
+        let mut config = sc!("nginx", "nginx");
+        assert!(DockerInfrastructure::check_stop_signal(&config).is_ok());
+
+        config.set_stop_signal(Some(String::from("SIGQUIT")));
+        assert!(DockerInfrastructure::check_stop_signal(&config).is_ok());
+
+        config.set_stop_signal(Some(String::from("SIGFOO")));
+        assert!(matches!(
+            DockerInfrastructure::check_stop_signal(&config),
+            Err(DockerInfrastructureError::InvalidStopSignal { stop_signal, .. }) if stop_signal == "SIGFOO"
+        ));
+    }
+
+    #[test]
+    fn should_create_container_options_with_stop_signal() {
+        let mut config = sc!("nginx", "nginx");
+        config.set_stop_signal(Some(String::from("SIGQUIT")));
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+        assert_eq!(options.stop_signal, Some(String::from("SIGQUIT")));
+    }
+
+    #[test]
+    fn should_check_dns_servers() {
+        let mut config = sc!("db", "mariadb");
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            dns_servers = [ '10.0.0.53', 'fd00::53' ]
+            "#,
+        )
+        .unwrap();
+        assert!(DockerInfrastructure::check_dns_servers(&config, &container_config).is_ok());
+
+        config.set_dns_override(Some(vec![String::from("dns.example.com")]));
+        assert!(matches!(
+            DockerInfrastructure::check_dns_servers(&config, &container_config),
+            Err(DockerInfrastructureError::InvalidDnsServer { address, .. }) if address == "dns.example.com"
+        ));
+    }
+
+    #[test]
+    fn should_create_container_options_with_dns_servers() {
+        let mut config = sc!("db", "mariadb");
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            dns_servers = [ '10.0.0.53' ]
+            dns_search_domains = [ 'corp.example.com' ]
+            "#,
+        )
+        .unwrap();
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &container_config,
+            &[],
+        );
+        let host_config = options.host_config.unwrap();
+        assert_eq!(host_config.dns, Some(vec![String::from("10.0.0.53")]));
+        assert_eq!(
+            host_config.dns_search,
+            Some(vec![String::from("corp.example.com")])
+        );
+
+        config.set_dns_override(Some(vec![String::from("192.168.1.1")]));
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &container_config,
+            &[],
+        );
+        assert_eq!(
+            options.host_config.unwrap().dns,
+            Some(vec![String::from("192.168.1.1")])
+        );
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &sc!("db", "mariadb"),
+            &ContainerConfig::default(),
+            &[],
+        );
+        assert_eq!(options.host_config.unwrap().dns, None);
+    }
+
+    #[test]
+    fn should_check_named_volumes() {
+        let mut config = sc!("db", "mariadb");
+        config.set_named_volumes(vec![NamedVolumeMount::new(
+            String::from("db_data-1"),
+            String::from("/var/lib/mysql"),
+        )]);
+        assert!(DockerInfrastructure::check_named_volumes(&config).is_ok());
+
+        config.set_named_volumes(vec![NamedVolumeMount::new(
+            String::from("db.data"),
+            String::from("/var/lib/mysql"),
+        )]);
+        assert!(matches!(
+            DockerInfrastructure::check_named_volumes(&config),
+            Err(DockerInfrastructureError::InvalidNamedVolume { volume_name, .. }) if volume_name == "db.data"
+        ));
+    }
+
+    #[test]
+    fn should_parse_named_volume_names_unambiguously() {
+        let volume_name = named_volume_name("feature_x.y", "db-data");
+
+        assert_eq!(
+            parse_named_volume_name("feature_x.y", &volume_name),
+            Some("db-data")
+        );
+        assert_eq!(parse_named_volume_name("feature_x", &volume_name), None);
+        assert_eq!(parse_named_volume_name("feature", &volume_name), None);
+    }
+
+    #[test]
+    fn should_not_reuse_named_volume_of_other_app() {
+        let app_name = AppName::from_str("master").unwrap();
+        let mut volume = Volume {
+            name: String::from("master.db-data"),
+            labels: HashMap::from([(String::from(APP_NAME_LABEL), String::from("master"))]),
+            ..Default::default()
+        };
+        assert!(DockerInfrastructure::check_named_volume_owner(&app_name, &volume).is_ok());
+
+        volume
+            .labels
+            .insert(String::from(APP_NAME_LABEL), String::from("other"));
+        assert!(matches!(
+            DockerInfrastructure::check_named_volume_owner(&app_name, &volume),
+            Err(DockerInfrastructureError::NamedVolumeConflict { .. })
+        ));
+
+        volume.labels.clear();
+        assert!(matches!(
+            DockerInfrastructure::check_named_volume_owner(&app_name, &volume),
+            Err(DockerInfrastructureError::NamedVolumeConflict { .. })
+        ));
+    }
+
+    #[test]
+    fn should_check_timezone() {
+        let mut config = sc!("cron", "alpine");
+        assert!(DockerInfrastructure::check_timezone(&config).is_ok());
+
+        config.set_timezone(Some(String::from("Europe/Berlin")));
+        assert!(DockerInfrastructure::check_timezone(&config).is_ok());
+
+        config.set_timezone(Some(String::from("Europe/Atlantis")));
+        assert!(matches!(
+            DockerInfrastructure::check_timezone(&config),
+            Err(DockerInfrastructureError::InvalidTimezone { timezone, .. }) if timezone == "Europe/Atlantis"
+        ));
+    }
+
+    #[test]
+    fn should_create_container_options_with_timezone() {
+        let mut config = sc!("cron", "alpine");
+        config.set_timezone(Some(String::from("Europe/Berlin")));
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+        assert_eq!(options.env, Some(vec![String::from("TZ=Europe/Berlin")]));
+
+        config.set_env(Some(Environment::new(vec![EnvironmentVariable::new(
+            String::from("TZ"),
+            SecUtf8::from("UTC"),
+        )])));
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+        assert_eq!(options.env, Some(vec![String::from("TZ=UTC")]));
+    }
+
+    #[test]
+    fn should_check_ipc_modes() {
+        let deployable = |config: ServiceConfig| {
+            DeployableService::new(
+                config,
+                DeploymentStrategy::RedeployAlways,
+                crate::infrastructure::TraefikIngressRoute::empty(),
+                Vec::new(),
+            )
+        };
+        let join_cache = |service_name: &str| {
+            let mut config = sc!(service_name, "app");
+            config.set_ipc_mode(Some(IpcMode::Container {
+                service_name: String::from("cache"),
+            }));
+            config
+        };
+
+        let no_running_services = HashMap::new();
+
+        let services = vec![
+            deployable(sc!("cache", "cache")),
+            deployable(join_cache("app")),
+        ];
+        assert!(DockerInfrastructure::check_ipc_modes(&services, &no_running_services).is_ok());
+        assert!(DockerInfrastructure::is_ipc_shared(
+            &services[0],
+            &services,
+            &no_running_services
+        ));
+        assert!(!DockerInfrastructure::is_ipc_shared(
+            &services[1],
+            &services,
+            &no_running_services
+        ));
+
+        let services = vec![deployable(join_cache("cache"))];
+        assert!(matches!(
+            DockerInfrastructure::check_ipc_modes(&services, &no_running_services),
+            Err(DockerInfrastructureError::InvalidIpcMode { service_name, .. }) if service_name == "cache"
+        ));
+
+        let mut private_cache = sc!("cache", "cache");
+        private_cache.set_ipc_mode(Some(IpcMode::Private));
+        let services = vec![deployable(private_cache), deployable(join_cache("app"))];
+        assert!(matches!(
+            DockerInfrastructure::check_ipc_modes(&services, &no_running_services),
+            Err(DockerInfrastructureError::InvalidIpcMode { service_name, .. }) if service_name == "app"
+        ));
+    }
+
+    #[test]
+    fn should_check_ipc_modes_of_running_services() {
+        let deployable = |config: ServiceConfig| {
+            DeployableService::new(
+                config,
+                DeploymentStrategy::RedeployAlways,
+                crate::infrastructure::TraefikIngressRoute::empty(),
+                Vec::new(),
+            )
+        };
+        let join_cache = Some(IpcMode::Container {
+            service_name: String::from("cache"),
+        });
+
+        let mut app = sc!("app", "app");
+        app.set_ipc_mode(join_cache.clone());
+        let services = vec![deployable(app)];
+        assert!(DockerInfrastructure::check_ipc_modes(
+            &services,
+            &HashMap::from([(String::from("cache"), None)])
+        )
+        .is_ok());
+        assert!(matches!(
+            DockerInfrastructure::check_ipc_modes(
+                &services,
+                &HashMap::from([(String::from("cache"), Some(IpcMode::Private))])
+            ),
+            Err(DockerInfrastructureError::InvalidIpcMode { service_name, .. }) if service_name == "app"
+        ));
+
+        let running_services = HashMap::from([(String::from("app"), join_cache)]);
+        let services = vec![deployable(sc!("cache", "cache"))];
+        assert!(DockerInfrastructure::check_ipc_modes(&services, &running_services).is_ok());
+        assert!(DockerInfrastructure::is_ipc_shared(
+            &services[0],
+            &services,
+            &running_services
+        ));
+
+        let mut private_cache = sc!("cache", "cache");
+        private_cache.set_ipc_mode(Some(IpcMode::Private));
+        let services = vec![deployable(private_cache)];
+        assert!(matches!(
+            DockerInfrastructure::check_ipc_modes(&services, &running_services),
+            Err(DockerInfrastructureError::InvalidIpcMode { service_name, .. }) if service_name == "app"
+        ));
+    }
+
+    #[test]
+    fn should_start_services_after_their_dependencies() {
+        let deployable = |config: ServiceConfig| {
+            DeployableService::new(
+                config,
+                DeploymentStrategy::RedeployAlways,
+                crate::infrastructure::TraefikIngressRoute::empty(),
+                Vec::new(),
+            )
+        };
+        let depends_on = |service_name: &str, dependencies: &[&str]| {
+            let mut config = sc!(service_name, "app");
+            config.set_depends_on(dependencies.iter().map(|d| String::from(*d)).collect());
+            config
+        };
+        let mut app = depends_on("app", &["db", "auth"]);
+        app.set_ipc_mode(Some(IpcMode::Container {
+            service_name: String::from("cache"),
+        }));
+
+        let services = vec![
+            deployable(app),
+            deployable(depends_on("db", &[])),
+            deployable(sc!("cache", "cache")),
+            deployable(depends_on("frontend", &["app"])),
+        ];
+        let waves = DockerInfrastructure::deployment_waves(&services)
+            .unwrap()
+            .into_iter()
+            .map(|wave| {
+                wave.into_iter()
+                    .map(|service| service.service_name().as_str())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            waves,
+            vec![vec!["db", "cache"], vec!["app"], vec!["frontend"]]
+        );
+
+        let services = vec![
+            deployable(depends_on("a", &["b"])),
+            deployable(depends_on("b", &["a"])),
+            deployable(depends_on("c", &[])),
+        ];
+        assert!(matches!(
+            DockerInfrastructure::deployment_waves(&services),
+            Err(DockerInfrastructureError::CyclicDependencies { services }) if services == vec!["a", "b"]
+        ));
+    }
+
+    #[test]
+    fn should_create_container_options_with_ipc_mode() {
+        let mut config = sc!("app", "app");
+        config.set_ipc_mode(Some(IpcMode::Host));
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+        assert_eq!(
+            options
+                .host_config
+                .and_then(|host_config| host_config.ipc_mode),
+            Some(String::from("host"))
+        );
+    }
+
+    #[test]
+    fn should_resolve_inherited_env() {
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            inheritable_env = [ 'NPM_TOKEN', 'GIT_TOKEN' ]
+            "#,
+        )
+        .unwrap();
+        let lookup = |name: &str| (name == "NPM_TOKEN").then(|| String::from("secret"));
+
+        let mut config = sc!("db", "mariadb");
+        config.set_env_inherit(
+            vec![String::from("NPM_TOKEN"), String::from("GIT_TOKEN")],
+            false,
+        );
+        assert_eq!(
+            DockerInfrastructure::resolve_inherited_env(&config, &container_config, lookup)
+                .unwrap(),
+            vec![String::from("NPM_TOKEN=secret")]
+        );
+
+        config.set_env_inherit(
+            vec![String::from("NPM_TOKEN"), String::from("GIT_TOKEN")],
+            true,
+        );
+        assert!(matches!(
+            DockerInfrastructure::resolve_inherited_env(&config, &container_config, lookup),
+            Err(DockerInfrastructureError::InheritedEnvNotFound { name, .. }) if name == "GIT_TOKEN"
+        ));
+
+        config.set_env_inherit(vec![String::from("HOME")], false);
+        assert!(matches!(
+            DockerInfrastructure::resolve_inherited_env(&config, &container_config, lookup),
+            Err(DockerInfrastructureError::EnvNotInheritable { name, .. }) if name == "HOME"
+        ));
+    }
+
+    #[test]
+    fn should_derive_unambiguous_container_names() {
+        let container_name_of = |app_name: &str, service_name: &str| {
+            DockerInfrastructure::container_name_of(
+                &AppName::from_str(app_name).unwrap(),
+                &sc!(service_name, "nginx"),
+            )
+        };
+
+        assert!(container_name_of("master", "db").starts_with("master-db-"));
+        assert_eq!(
+            container_name_of("master", "db"),
+            container_name_of("master", "db")
+        );
+        assert_ne!(container_name_of("a", "b-c"), container_name_of("a-b", "c"));
+
+        let mut config = sc!("db", "mariadb");
+        config.set_container_name(Some(String::from("my-db")));
+        assert_eq!(
+            DockerInfrastructure::container_name_of(&AppName::master(), &config),
+            "my-db"
+        );
+    }
+
+    #[test]
+    fn should_create_container_options_with_env_inherit_label() {
+        let mut config = sc!("db", "mariadb");
+        config.set_env_inherit(vec![String::from("NPM_TOKEN")], true);
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+
+        let labels = options.labels.unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&labels[ENV_INHERIT_LABEL]).unwrap(),
+            serde_json::json!({ "names": [ "NPM_TOKEN" ], "strict": true })
+        );
+    }
+
+    #[test]
+    fn should_check_pid_limit() {
+        let container_config = ContainerConfig::default();
+        let mut config = sc!("db", "mariadb");
+        assert!(DockerInfrastructure::check_pid_limit(&config, &container_config).is_ok());
+
+        config.set_pid_limit(Some(2));
+        assert!(DockerInfrastructure::check_pid_limit(&config, &container_config).is_ok());
+
+        config.set_pid_limit(Some(1));
+        assert!(matches!(
+            DockerInfrastructure::check_pid_limit(&config, &container_config),
+            Err(DockerInfrastructureError::InvalidPidLimit { pid_limit: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn should_create_container_options_with_memory_swap() {
+        let mut config = sc!("db", "mariadb");
+        config.set_memory_limit(Some(ByteSize::gb(1)));
+        config.set_memory_swap_bytes(Some(2_000_000_000));
+        config.set_memory_swappiness(Some(10));
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+
+        let host_config = options.host_config.unwrap();
+        assert_eq!(host_config.memory, Some(1_000_000_000));
+        assert_eq!(host_config.memory_swap, Some(2_000_000_000));
+        assert_eq!(host_config.memory_swappiness, Some(10));
+    }
+
+    #[tokio::test]
+    async fn should_check_all_services_before_touching_containers() {
+        let config = crate::config_from_str!("");
+        let mut invalid_service = sc!("db", "mariadb");
+        invalid_service.set_memory_swap_bytes(Some(2_000_000_000));
+        let deployment_unit = crate::deployment::deployment_unit::DeploymentUnitBuilder::init(
+            AppName::master(),
+            vec![sc!("nginx", "nginx"), invalid_service],
+        )
+        .extend_with_config(&config)
+        .extend_with_templating_only_service_configs(Vec::new())
+        .extend_with_image_infos(HashMap::new())
+        .apply_templating(&None)
+        .unwrap()
+        .apply_hooks(&config)
+        .await
+        .unwrap()
+        .build();
+        let infrastructure = DockerInfrastructure::new(config);
+
+        // The check fails before Docker is contacted, thus, the running containers of the app
+        // are left untouched and the deployment lock is not held.
+        let result = infrastructure
+            .deploy_services_exclusively(&deployment_unit, &ContainerConfig::default())
+            .await;
 
-        let labels = container_details.labels;
-        let port = find_port(
-            container_details.ports.unwrap_or_default().as_slice(),
-            &labels,
-        )?;
+        assert!(matches!(
+            result,
+            Err(DockerInfrastructureError::InvalidMemorySwap { service_name, .. }) if service_name == "db"
+        ));
+        assert!(infrastructure
+            .deployment_locks
+            .lock(&AppName::master(), Duration::ZERO)
+            .await
+            .unwrap()
+            .is_some());
+    }
 
-        let Some(ip) = container_details
-            .network_settings
-            .and_then(|network_settings| {
-                let ip = network_settings
-                    .networks?
-                    .into_iter()
-                    .find_map(|(_, network)| Some(network.ip_address?))?;
+    #[test]
+    fn should_check_memory_swap() {
+        let container_config = ContainerConfig::default();
+        let mut config = sc!("db", "mariadb");
+        config.set_memory_limit(Some(ByteSize::gb(1)));
 
-                Some(ip)
-            })
-        else {
-            return Err(anyhow::Error::msg("Found no IP address")
-                .context(format!("app {app_name}, service name {service_name}")));
-        };
+        config.set_memory_swap_bytes(Some(-1));
+        assert!(DockerInfrastructure::check_memory_swap(&config, &container_config).is_ok());
 
-        let stream = TcpStream::connect(format!("{ip}:{port}")).await?;
-        let (mut sender, connection) =
-            hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                warn!("Error in connection: {}", e);
-            }
-        });
+        config.set_memory_swap_bytes(Some(1_000_000_000));
+        assert!(DockerInfrastructure::check_memory_swap(&config, &container_config).is_ok());
 
-        let (_parts, body) = sender.send_request(request).await?.into_parts();
+        config.set_memory_swap_bytes(Some(500_000_000));
+        assert!(matches!(
+            DockerInfrastructure::check_memory_swap(&config, &container_config),
+            Err(DockerInfrastructureError::InvalidMemorySwap { .. })
+        ));
 
-        let body_bytes = body.collect().await?.to_bytes();
+        config.set_memory_limit(None);
+        config.set_memory_swap_bytes(Some(2_000_000_000));
+        assert!(matches!(
+            DockerInfrastructure::check_memory_swap(&config, &container_config),
+            Err(DockerInfrastructureError::InvalidMemorySwap { .. })
+        ));
 
-        Ok(serde_json::from_slice::<WebHostMeta>(&body_bytes).ok())
+        config.set_memory_swap_bytes(None);
+        config.set_memory_swappiness(Some(101));
+        assert!(matches!(
+            DockerInfrastructure::check_memory_swap(&config, &container_config),
+            Err(DockerInfrastructureError::InvalidMemorySwappiness { .. })
+        ));
     }
-}
 
-/// Helper function to build Label Filters
-fn label_filter<S>(label_name: S, label_value: Option<S>) -> Option<String>
-where
-    S: AsRef<str>,
-{
-    let label_name = label_name.as_ref().to_string();
-    match label_value.as_ref().map(AsRef::as_ref) {
-        None => Some(label_name.to_string()),
-        Some(value) => Some(format!("{label_name}={value}")),
+    #[test]
+    fn should_create_container_options_with_ulimits() {
+        let mut config = sc!("db", "mariadb");
+        config.set_ulimits(vec![UlimitConfig::new(String::from("nofile"), 1024, 65536)]);
+
+        let options = DockerInfrastructure::create_container_options(
+            "master",
+            &config,
+            &ContainerConfig::default(),
+            &[],
+        );
+
+        assert_eq!(
+            options.host_config.unwrap().ulimits,
+            Some(vec![ResourcesUlimits {
+                name: Some(String::from("nofile")),
+                soft: Some(1024),
+                hard: Some(65536),
+            }])
+        );
     }
-}
 
-/// Helper function to map ShipLift 404 errors to None
-fn not_found_to_none<T>(result: Result<T, BollardError>) -> Result<Option<T>, BollardError> {
-    match result {
-        Ok(value) => Ok(Some(value)),
-        Err(BollardError::DockerResponseServerError {
-            status_code: 404u16,
-            ..
-        }) => Ok(None),
-        Err(err) => Err(err),
+    #[test]
+    fn should_extract_host_ports() {
+        let ports = PortMap::from([
+            (
+                String::from("9092/tcp"),
+                Some(vec![DockerPortBinding {
+                    host_ip: Some(String::from("0.0.0.0")),
+                    host_port: Some(String::from("49153")),
+                }]),
+            ),
+            (String::from("80/tcp"), None),
+        ]);
+
+        assert_eq!(host_ports(&ports), BTreeMap::from([(9092, 49153)]));
     }
-}
 
-/// Helper function to pull images
-async fn pull(image: &Image, config: &Config) -> Result<Vec<CreateImageInfo>, BollardError> {
-    let pull_options = CreateImageOptions::<&str> {
-        from_image: &image.to_string(),
-        ..Default::default()
-    };
-    let docker_auth = if let Some(registry) = image.registry() {
-        if let Some((username, password)) = config.registry_credentials(&registry) {
-            Some(DockerCredentials {
-                username: Some(username.to_string()),
-                password: Some(password.unsecure().to_string()),
-                ..Default::default()
-            })
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+    #[test]
+    fn should_redact_sensitive_environment_variables() {
+        let mut env = vec![
+            String::from("DB_PASSWORD=s3cr3t"),
+            String::from("JAVA_OPTS=-Xmx1g"),
+            String::from("GITHUB_TOKEN="),
+            String::from("DB_URL=mysql://admin:s3cr3t@db/app"),
+        ];
 
-    let docker = Docker::connect_with_socket_defaults()?;
-    docker
-        .create_image(Some(pull_options), None, docker_auth)
-        .try_collect()
-        .await
-}
+        redact_sensitive_environment_variables(&mut env, &HashSet::from([String::from("DB_URL")]));
 
-/// Helper function to delete containers with the aid of futures::future::join_all
-async fn delete(
-    details: ContainerInspectResponse,
-) -> Result<ContainerInspectResponse, BollardError> {
-    let docker = Docker::connect_with_socket_defaults()?;
-    docker
-        .remove_container(
-            details
-                .id
-                .as_ref()
-                .expect("id is mandatory for a docker container"),
-            None,
+        assert_eq!(
+            env,
+            vec![
+                String::from("DB_PASSWORD=<redacted>"),
+                String::from("JAVA_OPTS=-Xmx1g"),
+                String::from("GITHUB_TOKEN=<redacted>"),
+                String::from("DB_URL=<redacted>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_redact_sensitive_replicated_environment_variables() {
+        let mut replicated_env = serde_json::json!({
+            "DB_PASSWORD": { "value": "s3cr3t", "templated": false, "replicate": true },
+            "JAVA_OPTS": { "value": "-Xmx1g", "templated": false, "replicate": true }
+        })
+        .to_string();
+
+        redact_sensitive_replicated_environment_variables(&mut replicated_env);
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&replicated_env).unwrap(),
+            serde_json::json!({
+                "DB_PASSWORD": { "value": "<redacted>", "templated": false, "replicate": true },
+                "JAVA_OPTS": { "value": "-Xmx1g", "templated": false, "replicate": true }
+            })
+        );
+    }
+
+    #[test]
+    fn should_create_container_options_with_global_labels() {
+        let mut config = sc!("db", "mariadb:10.3.17");
+        config.set_labels(Some(BTreeMap::from([(
+            String::from("team"),
+            String::from("database"),
+        )])));
+        let container_config = toml::de::from_str::<ContainerConfig>(
+            r#"
+            [global_container_labels]
+            team = 'platform'
+            cost-center = '42'
+            'com.aixigo.preview.servant.app-name' = 'other'
+            "#,
         )
-        .await?;
-    Ok(details)
-}
+        .unwrap();
 
-/// Helper function to inspect containers with the aid of futures::future::join_all
-async fn inspect(container: ContainerSummary) -> Result<ContainerInspectResponse, BollardError> {
-    let docker = Docker::connect_with_socket_defaults()?;
-    docker
-        .inspect_container(
-            &container
-                .id
-                .expect("id is mandatory for a docker container"),
-            None,
+        let labels = DockerInfrastructure::create_container_options(
+            &String::from("master"),
+            &config,
+            &container_config,
+            &Vec::new(),
         )
-        .await
-}
+        .labels
+        .unwrap();
 
-fn find_port(
-    ports: &[Port],
-    labels: &Option<HashMap<String, String>>,
-) -> Result<u16, DockerInfrastructureError> {
-    if let Some(port) = labels
-        .as_ref()
-        .and_then(|labels| labels.get(CONTAINER_PORT_LABEL))
-    {
-        match port.parse::<u16>() {
-            Ok(port) => Ok(port),
-            Err(err) => Err(DockerInfrastructureError::UnexpectedError {
-                err: anyhow::Error::new(err)
-                    .context("Cannot parse traefik port label into port number"),
-            }),
-        }
-    } else {
-        Ok(ports
-            .iter()
-            .map(|port| port.private_port)
-            .min()
-            .unwrap_or(80u16))
+        assert_eq!(labels.get("cost-center"), Some(&String::from("42")));
+        assert_eq!(labels.get("team"), Some(&String::from("database")));
+        assert_eq!(labels.get(APP_NAME_LABEL), Some(&String::from("master")));
     }
-}
 
-impl TryFrom<ContainerInspectResponse> for Service {
-    type Error = DockerInfrastructureError;
+    #[test]
+    fn should_route_requests_by_subdomain() {
+        let subdomain_routing =
+            toml::de::from_str::<SubdomainRoutingConfig>("base_domain = 'preview.example.com'")
+                .unwrap();
+        let mut labels = DockerInfrastructure::create_container_options(
+            &String::from("master"),
+            &sc!("db", "mariadb:10.3.17"),
+            &ContainerConfig::default(),
+            &Vec::new(),
+        )
+        .labels
+        .unwrap();
+
+        DockerInfrastructure::apply_subdomain_routing(
+            &mut labels,
+            &AppName::master(),
+            "db",
+            &subdomain_routing,
+        );
 
-    fn try_from(
-        container_details: ContainerInspectResponse,
-    ) -> Result<Service, DockerInfrastructureError> {
-        let mut labels = container_details.config.and_then(|config| config.labels);
-        let container_id = container_details
-            .id
-            .expect("id is mandatory for a docker container");
-        let app_name = match labels
-            .as_mut()
-            .and_then(|labels| labels.remove(APP_NAME_LABEL))
-        {
-            Some(name) => name,
-            None => {
-                return Err(DockerInfrastructureError::MissingAppNameLabel { container_id });
-            }
-        };
+        assert_eq!(
+            labels.get("traefik.frontend.rule"),
+            Some(&String::from("Host:db.master.preview.example.com"))
+        );
+    }
 
-        let service_name = match labels
-            .as_mut()
-            .and_then(|labels| labels.remove(SERVICE_NAME_LABEL))
-        {
-            Some(name) => name,
-            None => {
-                return Err(DockerInfrastructureError::MissingServiceNameLabel { container_id });
-            }
-        };
+    #[test]
+    fn should_apply_canary_weight() {
+        let mut labels = HashMap::new();
+
+        DockerInfrastructure::apply_canary_weight(
+            &mut labels,
+            &AppName::from_str("Feature-1").unwrap(),
+            "db",
+            20,
+        );
 
-        let image = match labels
-            .as_mut()
-            .and_then(|labels| labels.remove(IMAGE_LABEL))
-        {
-            Some(image_label) => Image::from_str(&image_label).map_err(|err| {
-                DockerInfrastructureError::UnexpectedImageFormat {
-                    img: image_label,
-                    err: anyhow::Error::new(err),
-                }
-            }),
-            None => {
-                let Some(image) = container_details.image else {
-                    return Err(DockerInfrastructureError::InvalidContainerImage { container_id });
-                };
-                Image::from_str(&image).map_err(|err| {
-                    DockerInfrastructureError::UnexpectedImageFormat {
-                        img: image,
-                        err: anyhow::Error::new(err),
-                    }
-                })
-            }
-        }?;
-        let mut config = ServiceConfig::new(service_name.clone(), image);
+        assert_eq!(
+            labels,
+            HashMap::from([
+                (
+                    String::from("traefik.backend"),
+                    String::from("Feature-1-db")
+                ),
+                (String::from("traefik.weight"), String::from("25")),
+            ])
+        );
+    }
 
-        if let Some(lb) = labels
-            .as_mut()
-            .and_then(|labels| labels.remove(CONTAINER_TYPE_LABEL))
-        {
-            config.set_container_type(lb.parse::<ContainerType>()?);
-        }
+    #[test]
+    fn should_apply_sticky_sessions() {
+        let mut labels = HashMap::new();
+
+        DockerInfrastructure::apply_sticky_sessions(
+            &mut labels,
+            &AppName::master(),
+            "frontend",
+            "frontend-affinity",
+        );
 
-        if let Some(replicated_env) = labels
-            .as_mut()
-            .and_then(|labels| labels.remove(REPLICATED_ENV_LABEL))
-        {
-            let env = serde_json::from_str::<Environment>(&replicated_env).map_err(|err| {
-                DockerInfrastructureError::UnexpectedError {
-                    err: anyhow::Error::new(err),
-                }
-            })?;
-            config.set_env(Some(env));
-        }
+        assert_eq!(
+            labels,
+            HashMap::from([
+                (
+                    String::from("traefik.backend.loadbalancer.stickiness"),
+                    String::from("true")
+                ),
+                (
+                    String::from("traefik.backend.loadbalancer.stickiness.cookieName"),
+                    String::from("frontend-affinity")
+                ),
+                (
+                    String::from(
+                        "traefik.http.services.master-frontend.loadbalancer.sticky.cookie.name"
+                    ),
+                    String::from("frontend-affinity")
+                ),
+            ])
+        );
+    }
 
-        let Some(state) = container_details.state else {
-            return Err(DockerInfrastructureError::InvalidContainerState { container_id });
+    #[test]
+    fn should_create_macvlan_network_options() {
+        let network_driver = NetworkDriver::Macvlan {
+            parent: String::from("eth0"),
         };
 
-        let started_at = state
-            .started_at
-            .as_deref()
-            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
-            .expect("started_at is mandatory for a docker container");
+        let options = DockerInfrastructure::create_network_options(
+            "master-net",
+            HashMap::from([(APP_NAME_LABEL, "master")]),
+            &network_driver,
+            None,
+            None,
+        );
 
-        let status = match state.status.unwrap_or(ContainerStateStatusEnum::PAUSED) {
-            ContainerStateStatusEnum::RUNNING => ServiceStatus::Running,
-            _ => ServiceStatus::Paused,
+        assert_eq!(options.name, "master-net");
+        assert_eq!(options.driver, "macvlan");
+        assert_eq!(options.options, HashMap::from([("parent", "eth0")]));
+        assert_eq!(options.labels, HashMap::from([(APP_NAME_LABEL, "master")]));
+    }
+
+    #[test]
+    fn should_create_attachable_overlay_network_options() {
+        let options = DockerInfrastructure::create_network_options(
+            "master-net",
+            HashMap::new(),
+            &NetworkDriver::Overlay,
+            None,
+            None,
+        );
+
+        assert_eq!(options.driver, "overlay");
+        assert!(options.attachable);
+    }
+
+    #[test]
+    fn should_create_network_options_with_mtu() {
+        let network_driver = NetworkDriver::Macvlan {
+            parent: String::from("eth0"),
         };
 
-        Ok(ServiceBuilder::new()
-            .id(container_id.clone())
-            .app_name(app_name.clone())
-            .config(config)
-            .service_status(status)
-            .started_at(started_at.into())
-            .build()?)
+        let options = DockerInfrastructure::create_network_options(
+            "master-net",
+            HashMap::new(),
+            &network_driver,
+            Some("1450"),
+            None,
+        );
+
+        assert_eq!(
+            options.options,
+            HashMap::from([
+                ("parent", "eth0"),
+                ("com.docker.network.driver.mtu", "1450")
+            ])
+        );
     }
-}
 
-impl From<BollardError> for DockerInfrastructureError {
-    fn from(err: BollardError) -> Self {
-        match &err {
-            BollardError::DockerResponseServerError {
-                status_code,
-                message,
-            } => match status_code {
-                404u16 => {
-                    return DockerInfrastructureError::ImageNotFound {
-                        internal_message: message.clone(),
-                    }
-                }
-                _ => {}
-            },
-            _ => {}
-        }
-        DockerInfrastructureError::UnexpectedError {
-            err: anyhow::Error::new(err),
-        }
+    #[test]
+    fn should_create_dual_stack_network_options() {
+        let options = DockerInfrastructure::create_network_options(
+            "master-net",
+            HashMap::new(),
+            &NetworkDriver::Bridge,
+            None,
+            Some("fd00:7072:6576::/64"),
+        );
+
+        assert!(options.enable_ipv6);
+        assert_eq!(
+            options.ipam.config,
+            Some(vec![IpamConfig {
+                subnet: Some(String::from("fd00:7072:6576::/64")),
+                ..Default::default()
+            }])
+        );
     }
-}
 
-impl From<ServiceError> for DockerInfrastructureError {
-    fn from(err: ServiceError) -> Self {
-        match err {
-            ServiceError::InvalidServiceType { label } => {
-                DockerInfrastructureError::UnknownServiceType {
-                    unknown_label: label,
-                }
-            }
-            err => DockerInfrastructureError::UnexpectedError {
-                err: anyhow::Error::new(err),
+    #[test]
+    fn should_list_ipv4_addresses_before_ipv6_addresses() {
+        let networks = HashMap::from([(
+            String::from("master-net"),
+            EndpointSettings {
+                ip_address: Some(String::from("172.18.0.2")),
+                global_ipv6_address: Some(String::from("fd00:7072:6576::2")),
+                ..Default::default()
             },
-        }
+        )]);
+
+        assert_eq!(
+            ip_addresses(networks.values()),
+            vec![
+                IpAddr::from_str("172.18.0.2").unwrap(),
+                IpAddr::from_str("fd00:7072:6576::2").unwrap()
+            ]
+        );
     }
-}
 
-impl From<ServiceBuilderError> for DockerInfrastructureError {
-    fn from(err: ServiceBuilderError) -> Self {
-        DockerInfrastructureError::UnexpectedError {
-            err: anyhow::Error::new(err),
-        }
+    #[test]
+    fn should_only_forward_exposed_ports() {
+        let container = ContainerSummary {
+            labels: Some(HashMap::from([(
+                CONTAINER_PORT_LABEL.to_string(),
+                String::from("8080"),
+            )])),
+            ports: Some(vec![
+                Port {
+                    private_port: 5432,
+                    ..Default::default()
+                },
+                Port {
+                    private_port: 8080,
+                    public_port: Some(18080),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(exposed_ports(&container), BTreeSet::from([5432, 8080]));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::models::{Environment, EnvironmentVariable};
-    use crate::sc;
-    use bollard::models::ContainerState;
-    use bollard::models::ContainerStateStatusEnum;
-    use bollard::models::NetworkSettings;
-    use secstr::SecUtf8;
+    #[test]
+    fn should_split_log_chunks_at_line_ends() {
+        let mut pending = String::from("2024-01-01T12:00:00Z first\n2024-01-01T12:00:01Z sec");
 
-    macro_rules! container_details {
-        ($id:expr, $app_name:expr, $service_name:expr, $image:expr, $container_type:expr, $($l_key:expr => $l_value:expr),* ) => {{
-            let mut labels = std::collections::HashMap::new();
+        assert_eq!(
+            take_complete_lines(&mut pending),
+            vec![String::from("2024-01-01T12:00:00Z first\n")]
+        );
+        assert_eq!(pending, "2024-01-01T12:00:01Z sec");
 
-            if let Some(app_name) = $app_name {
-                labels.insert(String::from(APP_NAME_LABEL), app_name);
-            }
-            if let Some(service_name) = $service_name {
-                labels.insert(String::from(SERVICE_NAME_LABEL), service_name);
-            }
-            if let Some(container_type) = $container_type {
-                labels.insert(String::from(CONTAINER_TYPE_LABEL), container_type);
-            }
-            if let Some(image) = $image {
-                labels.insert(String::from(IMAGE_LABEL), image);
-            }
+        pending.push_str("ond\r\n2024-01-01T12:00:02Z third\n");
+        assert_eq!(
+            take_complete_lines(&mut pending),
+            vec![
+                String::from("2024-01-01T12:00:01Z second\r\n"),
+                String::from("2024-01-01T12:00:02Z third\n")
+            ]
+        );
+        assert!(pending.is_empty());
+        assert!(take_complete_lines(&mut pending).is_empty());
+    }
 
-            $( labels.insert($l_key, $l_value); )*
+    #[test]
+    fn should_parse_log_lines_since() {
+        let since = DateTime::parse_from_rfc3339("2024-01-01T12:00:01Z")
+            .unwrap()
+            .with_timezone(&Utc);
 
+        assert_eq!(
+            parse_log_line("2024-01-01T12:00:00Z first\n", LogStream::StdOut, since).unwrap(),
+            None
+        );
+        assert_eq!(
+            parse_log_line("2024-01-01T12:00:01Z second\n", LogStream::StdErr, since).unwrap(),
+            Some(LogLine::new(
+                since,
+                LogStream::StdErr,
+                String::from("second\n")
+            ))
+        );
+    }
 
+    #[test]
+    fn should_recognize_unlabeled_app_networks() {
+        let network = |name: &str, labels: HashMap<String, String>| Network {
+            name: Some(String::from(name)),
+            labels: Some(labels),
+            ..Default::default()
+        };
 
-            ContainerInspectResponse {
-                app_armor_profile: Some("".to_string()),
-                args: Some(vec![]),
-                config: Some(bollard::service::ContainerConfig {
-                    attach_stderr: Some(false),
-                    attach_stdin: Some(false),
-                    attach_stdout: Some(false),
-                    cmd: None,
-                    domainname: Some("".to_string()),
-                    entrypoint: None,
-                    env: None,
-                    exposed_ports: None,
-                    hostname: Some("".to_string()),
-                    image: Some("".to_string()),
-                    labels: Some(labels),
-                    on_build: None,
-                    open_stdin: Some(false),
-                    stdin_once: Some(false),
-                    tty: Some(false),
-                    user: Some("".to_string()),
-                    working_dir: Some("".to_string()),
-                    ..Default::default()
-                }),
-                driver: Some("".to_string()),
-                host_config: Some(HostConfig {
-                    cgroup_parent: None,
-                    container_id_file: Some("".to_string()),
-                    cpu_shares: None,
-                    cpuset_cpus: None,
-                    memory: None,
-                    memory_swap: None,
-                    network_mode: Some("".to_string()),
-                    pid_mode: None,
-                    port_bindings: None,
-                    privileged: Some(false),
-                    publish_all_ports: Some(false),
-                    readonly_rootfs: None,
-                    ..Default::default()
-                }),
-                hostname_path: Some("".to_string()),
-                hosts_path: Some("".to_string()),
-                log_path: Some("".to_string()),
-                id: Some($id),
-                image: Some("sha256:9895c9b90b58c9490471b877f6bb6a90e6bdc154da7fbb526a0322ea242fc913".to_string()),
-                mount_label: Some("".to_string()),
-                name: Some("".to_string()),
-                network_settings: Some(NetworkSettings {
-                    bridge: Some("".to_string()),
-                    gateway: Some("".to_string()),
-                    ip_address: Some("".to_string()),
-                    ip_prefix_len: Some(0),
-                    mac_address: Some("".to_string()),
-                    ports: None,
-                    networks: None,
+        assert_eq!(
+            legacy_app_name_of_network(&network("master-net", HashMap::new())),
+            Some(AppName::master())
+        );
+        assert_eq!(
+            legacy_app_name_of_network(&network("master-network", HashMap::new())),
+            None
+        );
+        assert_eq!(
+            legacy_app_name_of_network(&network(
+                "master-net",
+                HashMap::from([(
+                    String::from(MANAGED_BY_LABEL),
+                    String::from(MANAGED_BY_VALUE)
+                )])
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn should_find_free_ipv6_subnet() {
+        let network = |subnet: &str| Network {
+            ipam: Some(Ipam {
+                config: Some(vec![IpamConfig {
+                    subnet: Some(subnet.to_string()),
                     ..Default::default()
-                }),
-                path: Some("".to_string()),
-                process_label: Some("".to_string()),
-                resolv_conf_path: Some("".to_string()),
-                restart_count: Some(0) ,
-                state: Some(ContainerState {
-                    status: Some(ContainerStateStatusEnum::RUNNING),
-                    error: Some("".to_string()),
-                    exit_code: Some(0),
-                    oom_killed: Some(false),
-                    paused: Some(false),
-                    pid: Some(0),
-                    restarting: Some(false),
-                    running: Some(false),
-                    finished_at: Some(chrono::Utc::now().to_rfc3339()),
-                    started_at: Some(chrono::Utc::now().to_rfc3339()),
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let subnet = free_ipv6_subnet(
+            &Ipv6Subnet::default(),
+            &[
+                network("172.18.0.0/16"),
+                network("fd00:7072:6576::/64"),
+                network("fd00:7072:6576:2::/64"),
+            ],
+        );
+
+        assert_eq!(
+            subnet.map(|subnet| subnet.to_string()),
+            Some(String::from("fd00:7072:6576:1::/64"))
+        );
+    }
+
+    #[test]
+    fn should_find_free_ipv6_subnet_next_to_overlapping_subnets() {
+        let network = |subnet: &str| Network {
+            ipam: Some(Ipam {
+                config: Some(vec![IpamConfig {
+                    subnet: Some(subnet.to_string()),
                     ..Default::default()
-                }),
-                created: Some(chrono::Utc::now().to_rfc3339()),
-                mounts: Some(vec![]),
+                }]),
                 ..Default::default()
-            }
-        }};
+            }),
+            ..Default::default()
+        };
+
+        let subnet = free_ipv6_subnet(
+            &Ipv6Subnet::default(),
+            &[
+                // contains the subnets fd00:7072:6576::/64 and fd00:7072:6576:1::/64
+                network("fd00:7072:6576::/63"),
+                // is contained in the subnet fd00:7072:6576:2::/64
+                network("fd00:7072:6576:2:1::/80"),
+            ],
+        );
+
+        assert_eq!(
+            subnet.map(|subnet| subnet.to_string()),
+            Some(String::from("fd00:7072:6576:3::/64"))
+        );
     }
 
     #[test]
@@ -1544,6 +6272,13 @@ mod tests {
               "Labels": {
                 "com.aixigo.preview.servant.app-name": "master",
                 "com.aixigo.preview.servant.container-type": "instance",
+                "com.aixigo.preview.servant.env": serde_json::json!({
+                        "MYSQL_ROOT_PASSWORD": {
+                        "value": "example",
+                        "templated": false,
+                        "replicate": false,
+                        }
+                    }).to_string(),
                 "com.aixigo.preview.servant.image": "docker.io/library/mariadb:10.3.17",
                 "com.aixigo.preview.servant.service-name": "db",
                 "traefik.frontend.rule": "PathPrefixStrip: /master/db/; PathPrefix:/master/db/;"
@@ -1586,6 +6321,13 @@ mod tests {
               "Labels": {
                 "com.aixigo.preview.servant.app-name": "master",
                 "com.aixigo.preview.servant.container-type": "instance",
+                "com.aixigo.preview.servant.env": serde_json::json!({
+                        "MYSQL_ROOT_PASSWORD": {
+                        "value": "example",
+                        "templated": false,
+                        "replicate": true,
+                        }
+                    }).to_string(),
                 "com.aixigo.preview.servant.image": "docker.io/library/mariadb:10.3.17",
                 "com.aixigo.preview.servant.replicated-env": serde_json::json!({
                         "MYSQL_ROOT_PASSWORD": {
@@ -1602,24 +6344,77 @@ mod tests {
     }
 
     #[test]
-    fn should_create_service_config_from_container_details() {
-        let details = container_details!(
+    fn should_create_service_config_from_container_details() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("nginx")),
+            Some(String::from("nginx")),
+            None,
+        );
+
+        let service = Service::try_from(details).unwrap();
+
+        assert_eq!(service.id(), "some-random-id");
+        assert_eq!(service.app_name(), "master");
+        assert_eq!(service.config().service_name(), "nginx");
+        assert_eq!(
+            &service.config().image().to_string(),
+            "docker.io/library/nginx:latest"
+        );
+    }
+
+    #[test]
+    fn should_restore_named_volumes_from_container_details() {
+        let mut details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("db")),
+            Some(String::from("mariadb")),
+            None,
+        );
+        details.mounts = Some(vec![
+            bollard::service::MountPoint {
+                typ: Some(MountPointTypeEnum::VOLUME),
+                name: Some(String::from("master.db-data")),
+                destination: Some(String::from("/var/lib/mysql")),
+                ..Default::default()
+            },
+            bollard::service::MountPoint {
+                typ: Some(MountPointTypeEnum::VOLUME),
+                name: Some(String::from(
+                    "4bd5b4e6cbd6e4b6f5e3b93b5a0a26f0b1e5b5a2b1b5d0e6f5a5c8b9e0f1a2b3",
+                )),
+                destination: Some(String::from("/var/cache")),
+                ..Default::default()
+            },
+        ]);
+
+        let service = Service::try_from(details).unwrap();
+
+        assert_eq!(
+            service.config().named_volumes(),
+            &[NamedVolumeMount::new(
+                String::from("db-data"),
+                String::from("/var/lib/mysql")
+            )]
+        );
+    }
+
+    #[test]
+    fn should_restore_working_dir_from_container_details() {
+        let mut details = container_details!(
             "some-random-id".to_string(),
             Some(String::from("master")),
-            Some(String::from("nginx")),
-            Some(String::from("nginx")),
+            Some(String::from("db")),
+            Some(String::from("mariadb")),
             None,
         );
+        details.config.as_mut().unwrap().working_dir = Some(String::from("/srv/app"));
 
         let service = Service::try_from(details).unwrap();
 
-        assert_eq!(service.id(), "some-random-id");
-        assert_eq!(service.app_name(), "master");
-        assert_eq!(service.config().service_name(), "nginx");
-        assert_eq!(
-            &service.config().image().to_string(),
-            "docker.io/library/nginx:latest"
-        );
+        assert_eq!(service.config().working_dir(), Some("/srv/app"));
     }
 
     #[test]
@@ -1686,6 +6481,171 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_create_service_config_from_container_details_with_all_env() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("nginx")),
+            Some(String::from("nginx")),
+            None,
+            String::from(REPLICATED_ENV_LABEL) => serde_json::json!({
+                "MYSQL_ROOT_PASSWORD": { "value": "example", "replicate": true }
+            }).to_string(),
+            String::from(ENV_LABEL) => serde_json::json!({
+                "MYSQL_ROOT_PASSWORD": { "value": "example", "replicate": true },
+                "MYSQL_DATABASE": { "value": "wordpress", "replicate": false }
+            }).to_string()
+        );
+
+        let service = Service::try_from(details).unwrap();
+
+        let env = service.config().env().unwrap();
+        assert!(env.variable("MYSQL_ROOT_PASSWORD").unwrap().replicate());
+        assert!(!env.variable("MYSQL_DATABASE").unwrap().replicate());
+        assert_eq!(
+            env.variable("MYSQL_DATABASE").unwrap().value(),
+            &SecUtf8::from("wordpress")
+        );
+    }
+
+    #[test]
+    fn should_create_service_config_from_container_details_with_env_from_secrets() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("db")),
+            Some(String::from("mariadb")),
+            None,
+            String::from(ENV_FROM_SECRETS_LABEL) => serde_json::json!([
+                { "secretName": "db-password", "envKey": "MYSQL_PASSWORD" }
+            ]).to_string()
+        );
+
+        let service = Service::try_from(details).unwrap();
+
+        let env_from_secrets = service.config().env_from_secrets();
+        assert_eq!(env_from_secrets.len(), 1);
+        assert_eq!(env_from_secrets[0].secret_name(), "db-password");
+        assert_eq!(env_from_secrets[0].env_key(), "MYSQL_PASSWORD");
+    }
+
+    #[test]
+    fn should_create_service_config_from_container_details_with_env_inherit() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("nginx")),
+            Some(String::from("nginx")),
+            None,
+            String::from(ENV_INHERIT_LABEL) => serde_json::json!({
+                "names": [ "NPM_TOKEN", "GIT_TOKEN" ],
+                "strict": true
+            }).to_string()
+        );
+
+        let service = Service::try_from(details).unwrap();
+
+        assert_eq!(
+            service.config().env_inherit(),
+            &[String::from("NPM_TOKEN"), String::from("GIT_TOKEN")]
+        );
+        assert!(service.config().env_inherit_strict());
+    }
+
+    #[test]
+    fn should_create_service_from_container_details_with_created_at() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("nginx")),
+            Some(String::from("nginx")),
+            None,
+            String::from(CREATED_AT_LABEL) => String::from("2024-01-01T12:00:00+00:00")
+        );
+
+        let service = Service::try_from(details).unwrap();
+
+        assert_eq!(
+            service.app_created_at(),
+            Some(
+                &DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc)
+            )
+        );
+    }
+
+    #[test]
+    fn should_not_create_service_from_container_details_with_malformed_image() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("nginx")),
+            Some(String::from("nginx@sha256:9895c9b90b58")),
+            None,
+        );
+
+        assert!(matches!(
+            Service::try_from(details),
+            Err(DockerInfrastructureError::UnexpectedImageFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn should_create_service_from_container_details_with_digest_pinned_image() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("nginx")),
+            Some(String::from(
+                "nginx@sha256:9895c9b90b58c9490471b877f6bb6a90e6bdc154da7fbb526a0322ea242fc913"
+            )),
+            None,
+        );
+
+        let service = Service::try_from(details).unwrap();
+
+        assert_eq!(
+            service.config().image().to_string(),
+            "docker.io/library/nginx@sha256:9895c9b90b58c9490471b877f6bb6a90e6bdc154da7fbb526a0322ea242fc913"
+        );
+    }
+
+    #[test]
+    fn should_create_service_from_container_details_with_version() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("nginx")),
+            Some(String::from("nginx")),
+            None,
+            String::from(VERSION_LABEL) => String::from("0.9.0")
+        );
+
+        let service = Service::try_from(details).unwrap();
+
+        assert_eq!(service.deployed_by_version(), Some("0.9.0"));
+    }
+
+    #[test]
+    fn should_create_service_from_container_details_with_image_digest() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("nginx")),
+            Some(String::from("nginx")),
+            None,
+        );
+
+        let service = Service::try_from(details).unwrap();
+
+        assert_eq!(
+            service.image_digest(),
+            Some("sha256:9895c9b90b58c9490471b877f6bb6a90e6bdc154da7fbb526a0322ea242fc913")
+        );
+    }
+
     #[test]
     fn should_create_container_options_with_host_config_binds() {
         let config = sc!("db", "mariadb:10.3.17");
@@ -1720,4 +6680,299 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn should_read_health_status() {
+        let details = ContainerInspectResponse {
+            state: Some(ContainerState {
+                health: Some(bollard::models::Health {
+                    status: Some(HealthStatusEnum::STARTING),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(health_status(&details), Some(HealthStatusEnum::STARTING));
+    }
+
+    #[test]
+    fn should_not_read_health_status_without_health_check() {
+        let details = ContainerInspectResponse {
+            state: Some(ContainerState::default()),
+            ..Default::default()
+        };
+
+        assert_eq!(health_status(&details), None);
+    }
+
+    #[test]
+    fn should_recognize_init_containers() {
+        let init_container = ContainerSummary {
+            labels: Some(HashMap::from([
+                (String::from(APP_NAME_LABEL), String::from("master")),
+                (
+                    String::from(INIT_CONTAINER_OF_LABEL),
+                    String::from("master/api"),
+                ),
+            ])),
+            ..Default::default()
+        };
+        let service_container = ContainerSummary {
+            labels: Some(HashMap::from([(
+                String::from(APP_NAME_LABEL),
+                String::from("master"),
+            )])),
+            ..Default::default()
+        };
+
+        assert!(is_init_container(&init_container));
+        assert!(!is_init_container(&service_container));
+    }
+
+    #[test]
+    fn should_keep_replica_count_of_running_service() {
+        let existing_container = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("nginx")),
+            Some(String::from("nginx")),
+            None,
+            String::from(REPLICAS_LABEL) => String::from("3")
+        );
+
+        let mut config = sc!("nginx", "nginx");
+        assert_eq!(
+            DockerInfrastructure::replica_count(&config, None).unwrap(),
+            1
+        );
+        assert_eq!(
+            DockerInfrastructure::replica_count(&config, Some(&existing_container)).unwrap(),
+            3
+        );
+
+        config.set_replicas(Some(2));
+        assert_eq!(
+            DockerInfrastructure::replica_count(&config, Some(&existing_container)).unwrap(),
+            2
+        );
+
+        config.set_replicas(Some(MAX_REPLICAS + 1));
+        assert!(matches!(
+            DockerInfrastructure::replica_count(&config, None),
+            Err(DockerInfrastructureError::InvalidReplicas { replicas, .. }) if replicas == MAX_REPLICAS + 1
+        ));
+    }
+
+    #[test]
+    fn should_count_running_replicas() {
+        let replica = |app_name: &str, state: &str| ContainerSummary {
+            state: Some(String::from(state)),
+            labels: Some(HashMap::from([
+                (String::from(APP_NAME_LABEL), String::from(app_name)),
+                (String::from(REPLICA_OF_LABEL), String::from("nginx")),
+            ])),
+            ..Default::default()
+        };
+
+        let running_replicas = running_replicas(vec![
+            replica("master", "running"),
+            replica("master", "running"),
+            replica("master", "exited"),
+            replica("feature", "running"),
+        ]);
+
+        assert_eq!(
+            running_replicas,
+            HashMap::from([
+                ((String::from("master"), String::from("nginx")), 2),
+                ((String::from("feature"), String::from("nginx")), 1),
+            ])
+        );
+        assert!(is_replica_container(&replica("master", "running")));
+    }
+
+    #[test]
+    fn should_create_service_with_expected_replicas_from_container_details() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("nginx")),
+            Some(String::from("nginx")),
+            None,
+            String::from(REPLICAS_LABEL) => String::from("3")
+        );
+
+        let service = Service::try_from(details).unwrap();
+
+        assert_eq!(service.config().replicas(), Some(3));
+        assert_eq!(service.expected_replicas(), 3);
+        assert_eq!(service.actual_replicas(), 1);
+    }
+
+    #[test]
+    fn should_create_service_with_dependencies_from_container_details() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("wordpress")),
+            Some(String::from("wordpress")),
+            None,
+            String::from(DEPENDS_ON_LABEL) => String::from("db,cache")
+        );
+
+        let service = Service::try_from(details).unwrap();
+
+        assert_eq!(
+            service.config().depends_on(),
+            &[String::from("db"), String::from("cache")]
+        );
+    }
+
+    #[test]
+    fn should_create_stopped_service_from_exited_container() {
+        let mut details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("db")),
+            Some(String::from("mariadb")),
+            None,
+        );
+        details.state = Some(ContainerState {
+            status: Some(ContainerStateStatusEnum::EXITED),
+            exit_code: Some(137),
+            finished_at: Some(String::from("2024-01-01T12:00:00Z")),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            stopped_service(details),
+            Some(StoppedService::new(
+                String::from("db"),
+                String::from("some-random-id"),
+                Some(DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap()),
+                Some(137)
+            ))
+        );
+    }
+
+    #[test]
+    fn should_create_stopped_service_from_never_started_container() {
+        let mut details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("db")),
+            Some(String::from("mariadb")),
+            None,
+        );
+        details.state = Some(ContainerState {
+            status: Some(ContainerStateStatusEnum::CREATED),
+            exit_code: Some(0),
+            finished_at: Some(String::from("0001-01-01T00:00:00Z")),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            stopped_service(details),
+            Some(StoppedService::new(
+                String::from("db"),
+                String::from("some-random-id"),
+                None,
+                Some(0)
+            ))
+        );
+    }
+
+    #[test]
+    fn should_not_create_stopped_service_from_running_container() {
+        let details = container_details!(
+            "some-random-id".to_string(),
+            Some(String::from("master")),
+            Some(String::from("db")),
+            Some(String::from("mariadb")),
+            None,
+        );
+
+        assert_eq!(stopped_service(details), None);
+    }
+
+    #[test]
+    fn should_group_running_containers_by_deploy_id() {
+        let container =
+            |id: &str, service_name: &str, deploy_id: Option<&str>, created: i64, state: &str| {
+                let mut labels =
+                    HashMap::from([(SERVICE_NAME_LABEL.to_string(), service_name.to_string())]);
+                if let Some(deploy_id) = deploy_id {
+                    labels.insert(DEPLOY_ID_LABEL.to_string(), deploy_id.to_string());
+                }
+                ContainerSummary {
+                    id: Some(id.to_string()),
+                    labels: Some(labels),
+                    created: Some(created),
+                    state: Some(String::from(state)),
+                    ..Default::default()
+                }
+            };
+
+        let deployments = deployments(vec![
+            container("c1", "db", Some("second"), 1_704_117_600, "running"),
+            container("c2", "api", Some("first"), 1_704_110_400, "running"),
+            container("c3", "web", Some("first"), 1_704_110_401, "running"),
+            container("c4", "cache", Some("first"), 1_704_110_402, "exited"),
+            container("c5", "legacy", None, 1_704_100_000, "running"),
+        ]);
+
+        assert_eq!(
+            deployments,
+            vec![
+                AppDeployment::new(
+                    String::from("first"),
+                    DateTime::from_timestamp(1_704_110_400, 0),
+                    vec![String::from("api"), String::from("web")],
+                    vec![String::from("c2"), String::from("c3")],
+                ),
+                AppDeployment::new(
+                    String::from("second"),
+                    DateTime::from_timestamp(1_704_117_600, 0),
+                    vec![String::from("db")],
+                    vec![String::from("c1")],
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_only_prune_superseded_exited_containers() {
+        let container =
+            |id: &str, service_name: &str, deploy_id: &str, created: i64, state: &str| {
+                ContainerSummary {
+                    id: Some(id.to_string()),
+                    labels: Some(HashMap::from([
+                        (SERVICE_NAME_LABEL.to_string(), service_name.to_string()),
+                        (DEPLOY_ID_LABEL.to_string(), deploy_id.to_string()),
+                    ])),
+                    created: Some(created),
+                    state: Some(String::from(state)),
+                    ..Default::default()
+                }
+            };
+
+        let mut container_ids = superseded_exited_containers(vec![
+            // replaced by a running container
+            container("c1", "db", "first", 1_704_110_400, "exited"),
+            container("c2", "db", "second", 1_704_117_600, "running"),
+            // left behind by an interrupted deployment that started a newer container
+            container("c3", "api", "first", 1_704_110_400, "exited"),
+            container("c4", "api", "second", 1_704_117_600, "created"),
+            // stopped service
+            container("c5", "web", "first", 1_704_110_400, "exited"),
+        ])
+        .into_iter()
+        .filter_map(|container| container.id)
+        .collect::<Vec<_>>();
+        container_ids.sort();
+
+        assert_eq!(container_ids, vec![String::from("c1"), String::from("c3")]);
+    }
 }
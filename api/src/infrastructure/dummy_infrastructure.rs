@@ -35,18 +35,40 @@ use async_trait::async_trait;
 use chrono::{DateTime, FixedOffset, Utc};
 use futures::stream::{self, BoxStream};
 use multimap::MultiMap;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Mutex;
 use std::time::Duration;
 
 use super::TraefikIngressRoute;
 
+/// A call that has been made against the [`DummyInfrastructure`] so that tests can assert on the
+/// interactions of the apps layer or the REST layer with the infrastructure.
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum InfrastructureCall {
+    DeployServices {
+        app_name: AppName,
+        configs: Vec<ServiceConfig>,
+    },
+    StopServices {
+        app_name: AppName,
+    },
+    ChangeStatus {
+        app_name: AppName,
+        service_name: String,
+        status: ServiceStatus,
+    },
+}
+
 #[cfg(test)]
 pub struct DummyInfrastructure {
     delay: Option<Duration>,
     services: Mutex<MultiMap<AppName, DeployableService>>,
     base_ingress_route: Option<TraefikIngressRoute>,
+    fake_services: MultiMap<AppName, Service>,
+    tags: Mutex<HashMap<AppName, Vec<String>>>,
+    calls: Mutex<Vec<InfrastructureCall>>,
 }
 
 #[cfg(test)]
@@ -56,6 +78,9 @@ impl DummyInfrastructure {
             delay: None,
             services: Mutex::new(MultiMap::new()),
             base_ingress_route: None,
+            fake_services: MultiMap::new(),
+            tags: Mutex::new(HashMap::new()),
+            calls: Mutex::new(Vec::new()),
         }
     }
 
@@ -64,6 +89,9 @@ impl DummyInfrastructure {
             delay: Some(delay),
             services: Mutex::new(MultiMap::new()),
             base_ingress_route: None,
+            fake_services: MultiMap::new(),
+            tags: Mutex::new(HashMap::new()),
+            calls: Mutex::new(Vec::new()),
         }
     }
 
@@ -72,6 +100,18 @@ impl DummyInfrastructure {
             delay: None,
             services: Mutex::new(MultiMap::new()),
             base_ingress_route: Some(base_ingress_route),
+            fake_services: MultiMap::new(),
+            tags: Mutex::new(HashMap::new()),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Creates an infrastructure that reports the given services in addition to the deployed ones,
+    /// e.g. to simulate services that are paused or that have been started a moment ago.
+    pub fn with_services(services: MultiMap<AppName, Service>) -> Self {
+        Self {
+            fake_services: services,
+            ..Self::new()
         }
     }
 
@@ -83,6 +123,10 @@ impl DummyInfrastructure {
             .flat_map(|(_, v)| v.iter().cloned())
             .collect::<Vec<_>>()
     }
+
+    pub fn calls(&self) -> Vec<InfrastructureCall> {
+        self.calls.lock().unwrap().clone()
+    }
 }
 
 #[cfg(test)]
@@ -92,13 +136,17 @@ impl DummyInfrastructure {
             tokio::time::sleep(*delay).await;
         }
     }
+
+    fn record(&self, call: InfrastructureCall) {
+        self.calls.lock().unwrap().push(call);
+    }
 }
 
 #[cfg(test)]
 #[async_trait]
 impl Infrastructure for DummyInfrastructure {
     async fn get_services(&self) -> Result<MultiMap<AppName, Service>> {
-        let mut s = MultiMap::new();
+        let mut s = self.fake_services.clone();
 
         let services = self.services.lock().unwrap();
         for (app, configs) in services.iter_all() {
@@ -131,9 +179,17 @@ impl Infrastructure for DummyInfrastructure {
     ) -> Result<Vec<Service>> {
         self.delay_if_configured().await;
 
-        let mut services = self.services.lock().unwrap();
         let app_name = deployment_unit.app_name();
         let deployable_services = deployment_unit.services();
+        self.record(InfrastructureCall::DeployServices {
+            app_name: app_name.clone(),
+            configs: deployable_services
+                .iter()
+                .map(|service| ServiceConfig::clone(service))
+                .collect(),
+        });
+
+        let mut services = self.services.lock().unwrap();
         if let Some(running_services) = services.get_vec_mut(&app_name) {
             let service_names = deployable_services
                 .iter()
@@ -167,8 +223,17 @@ impl Infrastructure for DummyInfrastructure {
             .collect::<Vec<_>>())
     }
 
-    async fn stop_services(&self, _status_id: &str, app_name: &AppName) -> Result<Vec<Service>> {
+    async fn stop_services(
+        &self,
+        _status_id: &str,
+        app_name: &AppName,
+        _preserve_volumes: bool,
+    ) -> Result<Vec<Service>> {
         self.delay_if_configured().await;
+        self.record(InfrastructureCall::StopServices {
+            app_name: app_name.clone(),
+        });
+        self.tags.lock().unwrap().remove(app_name);
 
         let mut services = self.services.lock().unwrap();
 
@@ -223,13 +288,32 @@ impl Infrastructure for DummyInfrastructure {
 
     async fn change_status(
         &self,
-        _app_name: &AppName,
-        _service_name: &str,
-        _status: ServiceStatus,
+        app_name: &AppName,
+        service_name: &str,
+        status: ServiceStatus,
     ) -> Result<Option<Service>> {
+        self.record(InfrastructureCall::ChangeStatus {
+            app_name: app_name.clone(),
+            service_name: service_name.to_string(),
+            status,
+        });
         Ok(None)
     }
 
+    async fn get_tags_of_apps(&self) -> Result<HashMap<AppName, Vec<String>>> {
+        Ok(self.tags.lock().unwrap().clone())
+    }
+
+    async fn set_app_tags(&self, app_name: &AppName, tags: &[String]) -> Result<()> {
+        let mut tags_of_apps = self.tags.lock().unwrap();
+        if tags.is_empty() {
+            tags_of_apps.remove(app_name);
+        } else {
+            tags_of_apps.insert(app_name.clone(), tags.to_vec());
+        }
+        Ok(())
+    }
+
     async fn base_traefik_ingress_route(&self) -> Result<Option<TraefikIngressRoute>> {
         Ok(self.base_ingress_route.clone())
     }
@@ -0,0 +1,307 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2024 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::config::Config;
+use crate::models::Image;
+use crate::registry::Registry;
+use async_stream::stream;
+use async_trait::async_trait;
+use bollard::auth::DockerCredentials;
+use bollard::errors::Error as BollardError;
+use bollard::image::{CreateImageOptions, ImportImageOptions};
+use bollard::Docker;
+use bytes::Bytes;
+use futures::TryStreamExt;
+use oci_client::errors::OciDistributionError;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
+
+/// Abstracts how an image gets into the local image store of the Docker daemon.
+#[async_trait]
+pub trait ImagePuller {
+    async fn pull(&self, image: &Image, config: &Config) -> Result<(), ImagePullError>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImagePullError {
+    #[error("{0}")]
+    Docker(#[from] BollardError),
+    #[error("Cannot pull image through the OCI Distribution API: {0}")]
+    Oci(#[from] OciDistributionError),
+    #[error("Cannot create the image archive: {0}")]
+    Archive(#[from] std::io::Error),
+}
+
+impl ImagePullError {
+    /// Returns `true` if the Docker daemon could not pull the image because the registry served a
+    /// manifest format that it does not accept.
+    pub fn is_unsupported_manifest(&self) -> bool {
+        let message = match self {
+            ImagePullError::Docker(BollardError::DockerResponseServerError {
+                status_code: 406u16,
+                ..
+            }) => return true,
+            ImagePullError::Docker(BollardError::DockerResponseServerError { message, .. }) => {
+                message
+            }
+            ImagePullError::Docker(BollardError::DockerStreamError { error }) => error,
+            _ => return false,
+        }
+        .to_lowercase();
+
+        message.contains("manifest")
+            && (message.contains("unsupported")
+                || message.contains("media type")
+                || message.contains("mediatype"))
+    }
+}
+
+/// Pulls images through the Docker daemon, which requests Docker V2 manifests from the registry.
+pub struct DockerPuller;
+
+#[async_trait]
+impl ImagePuller for DockerPuller {
+    async fn pull(&self, image: &Image, config: &Config) -> Result<(), ImagePullError> {
+        let pull_options = CreateImageOptions::<&str> {
+            from_image: &image.to_string(),
+            ..Default::default()
+        };
+        let docker_auth = image.registry().and_then(|registry| {
+            config
+                .registry_credentials(&registry)
+                .map(|(username, password)| DockerCredentials {
+                    username: Some(username.to_string()),
+                    password: Some(password.unsecure().to_string()),
+                    ..Default::default()
+                })
+        });
+
+        let docker = Docker::connect_with_socket_defaults()?;
+        let pull_results = docker
+            .create_image(Some(pull_options), None, docker_auth)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        for pull_result in pull_results {
+            debug!("{pull_result:?}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Pulls images through the OCI Distribution API and loads them into the Docker daemon. This
+/// supports registries that only serve OCI image manifests. The layers and the archive are kept in
+/// a temporary directory so that large images do not have to fit into memory.
+pub struct OciPuller;
+
+#[async_trait]
+impl ImagePuller for OciPuller {
+    async fn pull(&self, image: &Image, config: &Config) -> Result<(), ImagePullError> {
+        let dir = tempfile::tempdir()?;
+        let pulled_image = Registry::new(config).pull_image(image, dir.path()).await?;
+
+        // The tar builder writes synchronously, thus, it must not block the async runtime.
+        let archive_path = dir.path().join("image.tar");
+        let archive_file = File::create(&archive_path)?;
+        let archive_image = image.clone();
+        tokio::task::spawn_blocking(move || {
+            docker_archive(
+                &archive_image,
+                pulled_image.config.as_bytes(),
+                &pulled_image.layers,
+                archive_file,
+            )?
+            .sync_all()
+        })
+        .await
+        .expect("Creating the archive should not panic")?;
+
+        let docker = Docker::connect_with_socket_defaults()?;
+        let load_results = docker
+            .import_image_stream(
+                ImportImageOptions { quiet: true },
+                read_chunks(
+                    tokio::fs::File::open(&archive_path).await?,
+                    image.to_string(),
+                ),
+                None,
+            )
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        for load_result in load_results {
+            debug!("{load_result:?}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the file in chunks so that the Docker daemon receives the archive without loading it into
+/// memory. A read error ends the stream, which lets the daemon reject the incomplete archive.
+fn read_chunks(
+    mut file: tokio::fs::File,
+    image: String,
+) -> impl futures::Stream<Item = Bytes> + Send + 'static {
+    stream! {
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            match file.read(&mut buffer).await {
+                Ok(0) => break,
+                Ok(read) => yield Bytes::copy_from_slice(&buffer[..read]),
+                Err(err) => {
+                    error!("Cannot read the archive of image {image}: {err}");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Writes a tarball in the format of `docker save` so that the Docker daemon can load the image.
+/// The layers may be compressed because the daemon decompresses them while loading.
+fn docker_archive<W: Write>(
+    image: &Image,
+    config: &[u8],
+    layers: &[PathBuf],
+    out: W,
+) -> std::io::Result<W> {
+    let mut builder = tar::Builder::new(out);
+
+    append_file(&mut builder, "config.json", config)?;
+
+    let mut layer_paths = Vec::new();
+    for (index, layer) in layers.iter().enumerate() {
+        let layer_path = format!("layer-{index}.tar");
+        append_layer(&mut builder, &layer_path, layer)?;
+        layer_paths.push(layer_path);
+    }
+
+    let manifest = serde_json::json!([{
+        "Config": "config.json",
+        "RepoTags": [image.to_string()],
+        "Layers": layer_paths,
+    }]);
+    append_file(
+        &mut builder,
+        "manifest.json",
+        manifest.to_string().as_bytes(),
+    )?;
+
+    builder.into_inner()
+}
+
+fn append_file<W: Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    data: &[u8],
+) -> std::io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, path, data)
+}
+
+/// Copies the layer from its file into the archive without reading it into memory.
+fn append_layer<W: Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    layer: &Path,
+) -> std::io::Result<()> {
+    let mut file = File::open(layer)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(file.metadata()?.len());
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, path, &mut file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::str::FromStr;
+
+    #[test]
+    fn should_create_docker_archive() {
+        let image = Image::from_str("ghcr.io/myorg/app:1.0").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let layers = vec![dir.path().join("0"), dir.path().join("1")];
+        std::fs::write(&layers[0], "layer 0").unwrap();
+        std::fs::write(&layers[1], "layer 1").unwrap();
+
+        let archive =
+            docker_archive(&image, br#"{"architecture":"amd64"}"#, &layers, Vec::new()).unwrap();
+
+        let mut files = std::collections::BTreeMap::new();
+        let mut tar = tar::Archive::new(&archive[..]);
+        for entry in tar.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().to_string();
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            files.insert(path, content);
+        }
+
+        assert_eq!(files["config.json"], r#"{"architecture":"amd64"}"#);
+        assert_eq!(files["layer-0.tar"], "layer 0");
+        assert_eq!(files["layer-1.tar"], "layer 1");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&files["manifest.json"]).unwrap(),
+            serde_json::json!([{
+                "Config": "config.json",
+                "RepoTags": ["ghcr.io/myorg/app:1.0"],
+                "Layers": ["layer-0.tar", "layer-1.tar"],
+            }])
+        );
+    }
+
+    #[test]
+    fn should_detect_unsupported_manifest() {
+        assert!(
+            ImagePullError::Docker(BollardError::DockerResponseServerError {
+                status_code: 406,
+                message: String::from("Not Acceptable"),
+            })
+            .is_unsupported_manifest()
+        );
+        assert!(ImagePullError::Docker(BollardError::DockerStreamError {
+            error: String::from("unsupported manifest media type and no default available"),
+        })
+        .is_unsupported_manifest());
+        assert!(
+            !ImagePullError::Docker(BollardError::DockerResponseServerError {
+                status_code: 404,
+                message: String::from("manifest unknown"),
+            })
+            .is_unsupported_manifest()
+        );
+    }
+}
@@ -28,18 +28,36 @@ use super::traefik::TraefikIngressRoute;
 use crate::config::ContainerConfig;
 use crate::deployment::DeploymentUnit;
 use crate::models::service::{Service, ServiceStatus};
-use crate::models::{AppName, ContainerType, ServiceConfig, WebHostMeta};
+use crate::models::{
+    AppName, AppStateArchive, ContainerType, LogLine, LogStream, ServiceConfig, ServiceQuery,
+    ServiceSearchResult, WebHostMeta,
+};
 use anyhow::Result;
+use async_stream::stream;
 use async_trait::async_trait;
-use chrono::{DateTime, FixedOffset};
-use futures::stream::BoxStream;
+use chrono::{DateTime, FixedOffset, Utc};
+use futures::future::BoxFuture;
+use futures::stream::{BoxStream, StreamExt};
 use multimap::MultiMap;
+use std::collections::{HashMap, HashSet};
+use tokio::net::TcpStream;
 
 #[async_trait]
 pub trait Infrastructure: Send + Sync {
     /// Returns a `MultiMap` of `app-name` and the running services for this app.
     async fn get_services(&self) -> Result<MultiMap<AppName, Service>>;
 
+    /// Returns the names of the deployed apps. Implementations should override this method if they
+    /// can determine the names without resolving the details of every service.
+    async fn get_app_names(&self) -> Result<HashSet<AppName>> {
+        Ok(self
+            .get_services()
+            .await?
+            .into_iter()
+            .map(|(app_name, _)| app_name)
+            .collect())
+    }
+
     /// Deploys the services of the given set of `ServiceConfig`.
     ///
     /// The implementation must ensure that:
@@ -62,8 +80,27 @@ pub trait Infrastructure: Send + Sync {
     /// Stops the services running for the given `app_name`
     ///
     /// The implementation must ensure that it returns the services that have been
-    /// stopped.
-    async fn stop_services(&self, status_id: &str, app_name: &AppName) -> Result<Vec<Service>>;
+    /// stopped. If `preserve_volumes` is set, the named volumes of the services (see
+    /// [`NamedVolumeMount`](crate::models::NamedVolumeMount)) must be kept for the next
+    /// deployment of the app.
+    async fn stop_services(
+        &self,
+        status_id: &str,
+        app_name: &AppName,
+        preserve_volumes: bool,
+    ) -> Result<Vec<Service>>;
+
+    /// Runs `operation` while no other deployment of the given apps can run, e.g. to move the
+    /// services of one app to another. Deployments that `operation` runs itself must not wait for
+    /// these apps. Implementations without deployment locks just run `operation`.
+    async fn with_deployment_locks<'a>(
+        &self,
+        _app_names: &[AppName],
+        operation: BoxFuture<'a, ()>,
+    ) -> Result<()> {
+        operation.await;
+        Ok(())
+    }
 
     /// Streams the log lines with a the corresponding timestamps in it.
     async fn get_logs<'a>(
@@ -75,6 +112,34 @@ pub trait Infrastructure: Send + Sync {
         follow: bool,
     ) -> BoxStream<'a, Result<(DateTime<FixedOffset>, String)>>;
 
+    /// Streams the log lines that the service has produced since the given time, e.g. to forward
+    /// only new log lines to a log aggregation. Infrastructures that cannot distinguish between
+    /// the output streams of a service report all lines as [`LogStream::StdOut`].
+    async fn get_service_logs_since<'a>(
+        &'a self,
+        app_name: &'a AppName,
+        service_name: &'a str,
+        since: DateTime<Utc>,
+    ) -> BoxStream<'a, Result<LogLine>> {
+        stream! {
+            let from = Some(since.fixed_offset());
+            let mut logs = self.get_logs(app_name, service_name, &from, &None, false).await;
+
+            while let Some(result) = logs.next().await {
+                match result {
+                    Ok((timestamp, content)) => {
+                        let timestamp = timestamp.with_timezone(&Utc);
+                        if timestamp >= since {
+                            yield Ok(LogLine::new(timestamp, LogStream::StdOut, content));
+                        }
+                    }
+                    Err(err) => yield Err(err),
+                }
+            }
+        }
+        .boxed()
+    }
+
     /// Changes the status of a service, for example, the service might me stopped or started.
     async fn change_status(
         &self,
@@ -83,6 +148,167 @@ pub trait Infrastructure: Send + Sync {
         status: ServiceStatus,
     ) -> Result<Option<Service>>;
 
+    /// Returns the low-level details of the service's container, e.g. the output of
+    /// `docker inspect`, or `None` if there is no such service. Values of environment variables
+    /// that might contain credentials must be redacted by the implementation.
+    async fn inspect_service(
+        &self,
+        _app_name: &AppName,
+        _service_name: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        Ok(None)
+    }
+
+    /// Returns whether the health check of the service passes, or `None` if there is no such
+    /// service, the service does not define a health check, or the infrastructure does not support
+    /// health checks.
+    async fn is_service_healthy(
+        &self,
+        _app_name: &AppName,
+        _service_name: &str,
+    ) -> Result<Option<bool>> {
+        Ok(None)
+    }
+
+    /// Removes the service's container immediately without waiting for its processes to shut down
+    /// gracefully, e.g. because a process ignores `SIGTERM`. Returns the removed service or `None`
+    /// if there is no such service. Infrastructures that do not support it fail with
+    /// [`UnsupportedOperation`].
+    async fn force_stop_service(
+        &self,
+        _app_name: &AppName,
+        _service_name: &str,
+    ) -> Result<Option<Service>> {
+        Err(UnsupportedOperation {
+            operation: "force-stopping services",
+        }
+        .into())
+    }
+
+    /// Returns the tags of the app, e.g. the team or project that the app belongs to.
+    async fn get_app_tags(&self, app_name: &AppName) -> Result<Vec<String>> {
+        Ok(self
+            .get_tags_of_apps()
+            .await?
+            .remove(app_name)
+            .unwrap_or_default())
+    }
+
+    /// Returns the tags of all tagged apps at once so that callers do not need to look up the tags
+    /// app by app.
+    async fn get_tags_of_apps(&self) -> Result<HashMap<AppName, Vec<String>>> {
+        Ok(HashMap::new())
+    }
+
+    /// Replaces the tags of the app. The tags must be stored apart from the services so that they
+    /// can be changed without redeploying the services. Infrastructures that do not support it
+    /// fail with [`UnsupportedOperation`].
+    async fn set_app_tags(&self, _app_name: &AppName, _tags: &[String]) -> Result<()> {
+        Err(UnsupportedOperation {
+            operation: "tagging apps",
+        }
+        .into())
+    }
+
+    /// Returns the services of all apps that match the query. Implementations should override this
+    /// method if they can filter the services without resolving the details of every service.
+    async fn search_services(&self, query: &ServiceQuery) -> Result<Vec<ServiceSearchResult>> {
+        let mut tags_of_apps = match query.tag {
+            Some(_) => self.get_tags_of_apps().await?,
+            None => HashMap::new(),
+        };
+
+        let mut results = Vec::new();
+        for (app_name, services) in self.get_services().await? {
+            let tags = tags_of_apps.remove(&app_name).unwrap_or_default();
+            results.extend(
+                services
+                    .iter()
+                    .map(|service| {
+                        ServiceSearchResult::from_service(app_name.clone(), service, tags.clone())
+                    })
+                    .filter(|result| query.matches(result)),
+            );
+        }
+        Ok(results)
+    }
+
+    /// Looks up the service that runs in the container with the given ID, e.g. to react on
+    /// events of the container runtime.
+    async fn get_service_by_container_id(
+        &self,
+        container_id: &str,
+    ) -> Result<Option<(AppName, Service)>> {
+        let services = self.get_services().await?;
+        Ok(services.into_iter().find_map(|(app_name, services)| {
+            services
+                .into_iter()
+                .find(|service| service.id() == container_id)
+                .map(|service| (app_name, service))
+        }))
+    }
+
+    /// Returns the services of the given app whose containers are not running anymore, e.g.
+    /// because they crashed, so that users can figure out why a service is missing.
+    async fn get_stopped_services(&self, _app_name: &AppName) -> Result<Vec<StoppedService>> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the deployments of the given app whose containers are still running, i.e. the
+    /// groups of services that have been deployed together by one call of
+    /// [`deploy_services`](Infrastructure::deploy_services), ordered by the time of deployment.
+    async fn get_deployments(&self, _app_name: &AppName) -> Result<Vec<AppDeployment>> {
+        Ok(Vec::new())
+    }
+
+    /// Replaces the running version of the service by its canary and returns `false` if the
+    /// service has no canary.
+    async fn promote_canary(&self, _app_name: &AppName, _service_name: &str) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Removes the canary of the service and returns `false` if the service has no canary.
+    async fn rollback_canary(&self, _app_name: &AppName, _service_name: &str) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Opens a TCP connection to the port of the service's container, bypassing the reverse proxy,
+    /// and returns `None` if the service does not exist. Implementations must reject ports that the
+    /// service does not expose.
+    async fn open_port_forward(
+        &self,
+        _app_name: &AppName,
+        _service_name: &str,
+        _port: u16,
+    ) -> Result<Option<TcpStream>> {
+        Ok(None)
+    }
+
+    /// Removes the exited containers of the given app that have been superseded by other
+    /// containers of their service, e.g. the ones that a crashed deployment left behind, and
+    /// returns the number of removed containers.
+    async fn prune_stopped_containers(&self, _app_name: &AppName) -> Result<usize> {
+        Ok(0)
+    }
+
+    /// Returns the networks that have been created by PREvant, e.g. for diagnostic purposes.
+    async fn list_managed_networks(&self) -> Result<Vec<NetworkInfo>> {
+        Ok(Vec::new())
+    }
+
+    /// Deletes the networks of apps that do not have any containers anymore, e.g. because
+    /// deleting the app failed partway through, and returns them. If `dry_run` is `true`, the
+    /// networks are only returned.
+    async fn delete_orphaned_networks(&self, _dry_run: bool) -> Result<Vec<NetworkInfo>> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the API version of the container runtime, e.g. the Docker Engine API version, if
+    /// the runtime provides such information.
+    async fn api_version(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
     async fn http_forwarder(&self) -> Result<Box<dyn HttpForwarder + Send>>;
 
     /// Determines the [router rule](https://doc.traefik.io/traefik/routing/routers/) that points
@@ -97,6 +323,99 @@ pub trait Infrastructure: Send + Sync {
     }
 }
 
+/// Signals that the container runtime of PREvant cannot perform the requested operation.
+#[derive(Debug, thiserror::Error)]
+#[error("The container runtime does not support {operation}.")]
+pub struct UnsupportedOperation {
+    operation: &'static str,
+}
+
+/// Describes a network that has been created by PREvant.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkInfo {
+    id: String,
+    name: String,
+    /// The app the network belongs to or `None` if the network is shared by multiple apps.
+    app_name: Option<AppName>,
+}
+
+impl NetworkInfo {
+    pub fn new(id: String, name: String, app_name: Option<AppName>) -> Self {
+        Self { id, name, app_name }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn app_name(&self) -> Option<&AppName> {
+        self.app_name.as_ref()
+    }
+}
+
+/// Describes the container of a service that is not running anymore.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoppedService {
+    service_name: String,
+    container_id: String,
+    /// `None` if the container has been created but never finished, e.g. because it never started.
+    exited_at: Option<DateTime<FixedOffset>>,
+    exit_code: Option<i64>,
+}
+
+impl StoppedService {
+    pub fn new(
+        service_name: String,
+        container_id: String,
+        exited_at: Option<DateTime<FixedOffset>>,
+        exit_code: Option<i64>,
+    ) -> Self {
+        Self {
+            service_name,
+            container_id,
+            exited_at,
+            exit_code,
+        }
+    }
+}
+
+/// Describes the services of an app that have been deployed together.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDeployment {
+    deploy_id: String,
+    /// The creation time of the oldest container of the deployment.
+    deployed_at: Option<DateTime<Utc>>,
+    service_names: Vec<String>,
+    container_ids: Vec<String>,
+}
+
+impl AppDeployment {
+    pub fn new(
+        deploy_id: String,
+        deployed_at: Option<DateTime<Utc>>,
+        service_names: Vec<String>,
+        container_ids: Vec<String>,
+    ) -> Self {
+        Self {
+            deploy_id,
+            deployed_at,
+            service_names,
+            container_ids,
+        }
+    }
+
+    pub fn deployed_at(&self) -> Option<&DateTime<Utc>> {
+        self.deployed_at.as_ref()
+    }
+}
+
 /// Makes sure that HTTP requests from PREvant will be forwarded to the running services.
 #[async_trait]
 pub trait HttpForwarder {
@@ -125,4 +444,15 @@ impl dyn Infrastructure {
                     .collect()
             }))
     }
+
+    /// Exports the configurations of the app's services as archive, see [`AppStateArchive`], or
+    /// returns `None` if there is no such app.
+    pub async fn export_app_state(&self, app_name: &AppName) -> Result<Option<AppStateArchive>> {
+        let configs = self.get_configs_of_app(app_name).await?;
+        if configs.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(AppStateArchive::new(app_name, &configs)?))
+    }
 }
@@ -24,14 +24,15 @@
  * =========================LICENSE_END==================================
  */
 use super::super::{
-    APP_NAME_LABEL, CONTAINER_TYPE_LABEL, IMAGE_LABEL, REPLICATED_ENV_LABEL, SERVICE_NAME_LABEL,
-    STORAGE_TYPE_LABEL,
+    is_sensitive_environment_variable, parse_tags, APP_NAME_LABEL, CONTAINER_TYPE_LABEL, ENV_LABEL,
+    IMAGE_LABEL, REDACTED_VALUE, REPLICATED_ENV_LABEL, SERVICE_NAME_LABEL, STORAGE_TYPE_LABEL,
+    TAGS_LABEL,
 };
 use super::deployment_unit::K8sDeploymentUnit;
 use super::payloads::{
-    deployment_payload, image_pull_secret_payload, ingress_route_payload, middleware_payload,
-    namespace_payload, persistent_volume_claim_payload, secrets_payload, service_payload,
-    IngressRoute, Middleware,
+    deployment_payload, image_pull_secret_payload, ingress_payload, ingress_route_payload,
+    ingress_strip_prefix_middleware_payload, middleware_payload, namespace_payload,
+    persistent_volume_claim_payload, secrets_payload, service_payload, IngressRoute, Middleware,
 };
 use crate::config::{Config as PREvantConfig, ContainerConfig, Runtime};
 use crate::deployment::deployment_unit::{DeployableService, DeploymentUnit};
@@ -39,7 +40,8 @@ use crate::infrastructure::traefik::{TraefikIngressRoute, TraefikMiddleware};
 use crate::infrastructure::{HttpForwarder, Infrastructure, TraefikRouterRule};
 use crate::models::service::{ContainerType, Service, ServiceError, ServiceStatus};
 use crate::models::{
-    AppName, Environment, Image, ServiceBuilder, ServiceBuilderError, ServiceConfig, WebHostMeta,
+    AppName, Environment, Image, ServiceBuilder, ServiceBuilderError, ServiceConfig,
+    ServiceConfigBuilder, ServiceConfigBuilderError, WebHostMeta,
 };
 use anyhow::Result;
 use async_stream::stream;
@@ -52,6 +54,7 @@ use futures::{AsyncBufReadExt, TryStreamExt};
 use http_body_util::{BodyExt, Empty};
 use hyper_util::rt::TokioIo;
 use k8s_openapi::api::core::v1::PersistentVolumeClaim;
+use k8s_openapi::api::networking::v1::Ingress;
 use k8s_openapi::api::storage::v1::StorageClass;
 use k8s_openapi::api::{
     apps::v1::Deployment as V1Deployment, core::v1::Namespace as V1Namespace,
@@ -443,6 +446,57 @@ impl KubernetesInfrastructure {
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Creates or updates the Kubernetes `Ingress` of the app so that it routes to all given
+    /// services. Nothing happens if the ingress has not been enabled in the runtime config.
+    async fn create_or_update_ingress(
+        &self,
+        app_name: &AppName,
+        services: &[Service],
+    ) -> Result<(), KubernetesInfrastructureError> {
+        let Runtime::Kubernetes(k8s_config) = self.config.runtime_config() else {
+            return Ok(());
+        };
+        let Some(ingress_config) = k8s_config.ingress() else {
+            return Ok(());
+        };
+
+        let client = self.client().await?;
+        let namespace = app_name.to_rfc1123_namespace_id();
+
+        if ingress_config.class_name() == "traefik" {
+            let middleware = ingress_strip_prefix_middleware_payload(
+                app_name,
+                services.iter().map(|service| service.config()),
+            );
+            Api::<Middleware>::namespaced(client.clone(), &namespace)
+                .patch(
+                    &middleware.metadata.name.clone().unwrap_or_default(),
+                    &PatchParams::apply("prevant").force(),
+                    &Patch::Apply(&middleware),
+                )
+                .await?;
+        }
+
+        let payload = ingress_payload(
+            app_name,
+            services.iter().map(|service| service.config()),
+            ingress_config,
+        );
+        let name = payload.metadata.name.clone().unwrap_or_default();
+
+        debug!("Create or update ingress {name} for app {app_name}");
+
+        Api::<Ingress>::namespaced(client, &namespace)
+            .patch(
+                &name,
+                &PatchParams::apply("prevant").force(),
+                &Patch::Apply(&payload),
+            )
+            .await?;
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -554,10 +608,18 @@ impl Infrastructure for KubernetesInfrastructure {
             }
         }
 
+        self.create_or_update_ingress(app_name, &self.get_services_of_app(app_name).await?)
+            .await?;
+
         Ok(services)
     }
 
-    async fn stop_services(&self, _status_id: &str, app_name: &AppName) -> Result<Vec<Service>> {
+    async fn stop_services(
+        &self,
+        _status_id: &str,
+        app_name: &AppName,
+        _preserve_volumes: bool,
+    ) -> Result<Vec<Service>> {
         let services = self.get_services_of_app(app_name).await?;
         if services.is_empty() {
             return Ok(services);
@@ -573,6 +635,49 @@ impl Infrastructure for KubernetesInfrastructure {
         Ok(services)
     }
 
+    async fn get_tags_of_apps(&self) -> Result<HashMap<AppName, Vec<String>>> {
+        Ok(Api::<V1Namespace>::all(self.client().await?)
+            .list(&ListParams {
+                label_selector: Some(APP_NAME_LABEL.to_string()),
+                ..Default::default()
+            })
+            .await?
+            .into_iter()
+            .filter_map(|ns| {
+                let app_name =
+                    AppName::from_str(ns.metadata.labels.as_ref()?.get(APP_NAME_LABEL)?).ok()?;
+                let tags = parse_tags(ns.metadata.annotations.as_ref()?.get(TAGS_LABEL)?);
+                Some((app_name, tags))
+            })
+            .collect())
+    }
+
+    async fn set_app_tags(&self, app_name: &AppName, tags: &[String]) -> Result<()> {
+        // Other than the labels of the pods, the annotations of the namespace can be changed
+        // without restarting anything. A null value removes the annotation.
+        let tags = if tags.is_empty() {
+            None
+        } else {
+            Some(tags.join(","))
+        };
+
+        Api::<V1Namespace>::all(self.client().await?)
+            .patch(
+                &app_name.to_rfc1123_namespace_id(),
+                &PatchParams::default(),
+                &Patch::Merge(serde_json::json!({
+                    "metadata": {
+                        "annotations": {
+                            TAGS_LABEL: tags
+                        }
+                    }
+                })),
+            )
+            .await?;
+
+        Ok(())
+    }
+
     async fn get_logs<'a>(
         &'a self,
         app_name: &'a AppName,
@@ -663,6 +768,32 @@ impl Infrastructure for KubernetesInfrastructure {
         Ok(Some(service))
     }
 
+    async fn inspect_service(
+        &self,
+        app_name: &AppName,
+        service_name: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        let Some((_deployment, Some(mut pod))) =
+            self.get_deployment_and_pod(app_name, service_name).await?
+        else {
+            return Ok(None);
+        };
+
+        for container in pod
+            .spec
+            .iter_mut()
+            .flat_map(|spec| spec.containers.iter_mut())
+        {
+            for env in container.env.iter_mut().flatten() {
+                if env.value.is_some() && is_sensitive_environment_variable(&env.name) {
+                    env.value = Some(REDACTED_VALUE.to_string());
+                }
+            }
+        }
+
+        Ok(Some(serde_json::to_value(pod)?))
+    }
+
     async fn http_forwarder(&self) -> Result<Box<dyn HttpForwarder + Send>> {
         let client = self.client().await?;
         Ok(Box::new(K8sHttpForwarder { client }))
@@ -919,22 +1050,29 @@ impl TryFrom<&V1Deployment> for ServiceConfig {
                     })?,
             };
 
-            let mut config = ServiceConfig::new(service_name.clone(), image);
+            let mut config_builder = ServiceConfigBuilder::new()
+                .service_name(service_name.clone())
+                .image(image);
 
-            if let Some(replicated_env) = annotations.get(REPLICATED_ENV_LABEL) {
-                let env = serde_json::from_str::<Environment>(replicated_env).map_err(|err| {
+            // Deployments created by previous versions of PREvant only provide the replicated
+            // variables.
+            if let Some(env) = annotations
+                .get(ENV_LABEL)
+                .or_else(|| annotations.get(REPLICATED_ENV_LABEL))
+            {
+                let env = serde_json::from_str::<Environment>(env).map_err(|err| {
                     KubernetesInfrastructureError::UnexpectedError {
                         err: anyhow::Error::new(err),
                     }
                 })?;
-                config.set_env(Some(env));
+                config_builder = config_builder.env(env);
             }
 
             if let Some(lb) = labels.get(CONTAINER_TYPE_LABEL) {
-                config.set_container_type(lb.parse::<ContainerType>()?);
+                config_builder = config_builder.container_type(lb.parse::<ContainerType>()?);
             }
 
-            Ok(config)
+            Ok(config_builder.build()?)
         } else {
             Err(KubernetesInfrastructureError::MissingDeploymentAnnotations)
         }
@@ -964,6 +1102,14 @@ impl From<ServiceError> for KubernetesInfrastructureError {
     }
 }
 
+impl From<ServiceConfigBuilderError> for KubernetesInfrastructureError {
+    fn from(err: ServiceConfigBuilderError) -> Self {
+        KubernetesInfrastructureError::UnexpectedError {
+            err: anyhow::Error::new(err),
+        }
+    }
+}
+
 impl From<ServiceBuilderError> for KubernetesInfrastructureError {
     fn from(err: ServiceBuilderError) -> Self {
         KubernetesInfrastructureError::UnexpectedError {
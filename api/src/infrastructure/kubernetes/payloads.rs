@@ -24,21 +24,21 @@
  * =========================LICENSE_END==================================
  */
 use super::super::{
-    APP_NAME_LABEL, CONTAINER_TYPE_LABEL, IMAGE_LABEL, REPLICATED_ENV_LABEL, SERVICE_NAME_LABEL,
-    STORAGE_TYPE_LABEL,
+    APP_NAME_LABEL, CONTAINER_TYPE_LABEL, ENV_LABEL, IMAGE_LABEL, REPLICATED_ENV_LABEL,
+    SERVICE_NAME_LABEL, STORAGE_TYPE_LABEL,
 };
-use crate::config::{Config, ContainerConfig};
+use crate::config::{Config, ContainerConfig, KubernetesIngressConfig, PullPolicy};
 use crate::deployment::deployment_unit::{DeployableService, DeploymentStrategy};
 use crate::infrastructure::{TraefikIngressRoute, TraefikRouterRule};
-use crate::models::{AppName, ServiceConfig};
+use crate::models::{AppName, ObjectMount, ServiceConfig};
 use base64::{engine::general_purpose, Engine};
 use bytesize::ByteSize;
 use chrono::Utc;
 use k8s_openapi::api::apps::v1::DeploymentSpec;
 use k8s_openapi::api::core::v1::{
-    Container, ContainerPort, EnvVar, KeyToPath, PersistentVolumeClaim, PersistentVolumeClaimSpec,
-    PersistentVolumeClaimVolumeSource, PodSpec, PodTemplateSpec, ResourceRequirements,
-    SecretVolumeSource, Volume, VolumeMount,
+    ConfigMapVolumeSource, Container, ContainerPort, EnvVar, HTTPGetAction, KeyToPath,
+    PersistentVolumeClaim, PersistentVolumeClaimSpec, PersistentVolumeClaimVolumeSource, PodSpec,
+    PodTemplateSpec, Probe, ResourceRequirements, SecretVolumeSource, Volume, VolumeMount,
 };
 use k8s_openapi::api::networking::v1::Ingress;
 use k8s_openapi::api::{
@@ -47,6 +47,7 @@ use k8s_openapi::api::{
 };
 use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
+use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
 use k8s_openapi::ByteString;
 use kube::core::ObjectMeta;
 use kube::CustomResource;
@@ -63,6 +64,10 @@ use std::path::{Component, PathBuf};
 use std::str::FromStr;
 use std::string::ToString;
 
+/// Name of the middleware that strips the path prefixes of the requests that reach the services
+/// through the ingress of the app.
+static INGRESS_STRIP_PREFIX_MIDDLEWARE: &str = "prevant-ingress-strip-prefix";
+
 #[derive(CustomResource, Clone, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq)]
 #[kube(
     derive = "PartialEq",
@@ -363,17 +368,19 @@ pub fn deployment_payload(
             .collect()
     });
 
-    let annotations = if let Some(replicated_env) = service
+    let mut annotations = BTreeMap::from([(IMAGE_LABEL.to_string(), service.image().to_string())]);
+    if let Some(replicated_env) = service
         .env()
         .and_then(super::super::replicated_environment_variable_to_json)
     {
-        BTreeMap::from([
-            (IMAGE_LABEL.to_string(), service.image().to_string()),
-            (REPLICATED_ENV_LABEL.to_string(), replicated_env.to_string()),
-        ])
-    } else {
-        BTreeMap::from([(IMAGE_LABEL.to_string(), service.image().to_string())])
-    };
+        annotations.insert(REPLICATED_ENV_LABEL.to_string(), replicated_env.to_string());
+    }
+    if let Some(env) = service
+        .env()
+        .and_then(super::super::environment_variable_to_json)
+    {
+        annotations.insert(ENV_LABEL.to_string(), env.to_string());
+    }
 
     let volume_mounts = service.files().map(|files| {
         let parent_paths = files
@@ -451,15 +458,65 @@ pub fn deployment_payload(
         None => volumes,
     };
 
-    let resources = container_config
-        .memory_limit()
-        .map(|mem_limit| ResourceRequirements {
-            limits: Some(BTreeMap::from([(
-                String::from("memory"),
-                Quantity(format!("{}", mem_limit.as_u64())),
-            )])),
+    let (volume_mounts, volumes) =
+        if service.config_map_mounts().is_empty() && service.secret_mounts().is_empty() {
+            (volume_mounts, volumes)
+        } else {
+            let mut mounts = volume_mounts.unwrap_or_default();
+            let mut vols = volumes.unwrap_or_default();
+            let mut object_volumes = BTreeMap::new();
+
+            for mount in service.config_map_mounts() {
+                let volume_name = object_volume_name("configmap", mount.name());
+                mounts.push(object_volume_mount_payload(&volume_name, mount));
+                object_volumes.entry(volume_name.clone()).or_insert(Volume {
+                    name: volume_name,
+                    config_map: Some(ConfigMapVolumeSource {
+                        name: Some(mount.name().to_string()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                });
+            }
+            for mount in service.secret_mounts() {
+                let volume_name = object_volume_name("secret", mount.name());
+                mounts.push(object_volume_mount_payload(&volume_name, mount));
+                object_volumes.entry(volume_name.clone()).or_insert(Volume {
+                    name: volume_name,
+                    secret: Some(SecretVolumeSource {
+                        secret_name: Some(mount.name().to_string()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                });
+            }
+
+            vols.extend(object_volumes.into_values());
+            (Some(mounts), Some(vols))
+        };
+
+    let startup_probe = service.startup_probe().map(|probe| Probe {
+        http_get: Some(HTTPGetAction {
+            path: Some(probe.url_path().to_string()),
+            port: IntOrString::Int(service.port() as i32),
             ..Default::default()
-        });
+        }),
+        timeout_seconds: Some(probe.timeout_secs() as i32),
+        period_seconds: Some(probe.interval_secs() as i32),
+        failure_threshold: Some(probe.failure_threshold() as i32),
+        ..Default::default()
+    });
+
+    let resources =
+        container_config
+            .memory_limit_of(service)
+            .map(|mem_limit| ResourceRequirements {
+                limits: Some(BTreeMap::from([(
+                    String::from("memory"),
+                    Quantity(format!("{}", mem_limit.as_u64())),
+                )])),
+                ..Default::default()
+            });
 
     let labels = BTreeMap::from([
         (APP_NAME_LABEL.to_string(), app_name.to_string()),
@@ -473,6 +530,36 @@ pub fn deployment_payload(
         ),
     ]);
 
+    let init_containers = (!service.init_containers().is_empty()).then(|| {
+        service
+            .init_containers()
+            .iter()
+            .enumerate()
+            .map(|(index, init_container)| Container {
+                name: format!("{}-init-{index}", service.service_name()),
+                image: Some(init_container.image().to_string()),
+                // Like Docker's `cmd`, the command is passed to the entrypoint of the image
+                args: (!init_container.command().is_empty())
+                    .then(|| init_container.command().to_vec()),
+                env: Some(
+                    init_container
+                        .env()
+                        .iter()
+                        .map(|env| {
+                            let (name, value) = env.split_once('=').unwrap_or((env, ""));
+                            EnvVar {
+                                name: name.to_string(),
+                                value: Some(value.to_string()),
+                                ..Default::default()
+                            }
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            })
+            .collect()
+    });
+
     V1Deployment {
         metadata: ObjectMeta {
             name: Some(format!(
@@ -499,10 +586,17 @@ pub fn deployment_payload(
                 }),
                 spec: Some(PodSpec {
                     volumes,
+                    init_containers,
                     containers: vec![Container {
                         name: service.service_name().to_string(),
                         image: Some(service.image().to_string()),
-                        image_pull_policy: Some(String::from("Always")),
+                        image_pull_policy: Some(String::from(
+                            match container_config.pull_policy_of(service) {
+                                PullPolicy::Always => "Always",
+                                PullPolicy::IfNotPresent => "IfNotPresent",
+                                PullPolicy::Never => "Never",
+                            },
+                        )),
                         env,
                         volume_mounts,
                         ports: Some(vec![ContainerPort {
@@ -510,6 +604,7 @@ pub fn deployment_payload(
                             ..Default::default()
                         }]),
                         resources,
+                        startup_probe,
                         ..Default::default()
                     }],
                     ..Default::default()
@@ -521,6 +616,32 @@ pub fn deployment_payload(
     }
 }
 
+/// Volume names must be valid DNS labels, thus, dots of object names will be replaced and names
+/// that exceed 63 characters are truncated. A hash of the full name is appended to truncated names
+/// so that objects with a common prefix still get distinct volumes.
+fn object_volume_name(kind: &str, object_name: &str) -> String {
+    let volume_name = format!("{kind}-{}", object_name.replace('.', "-"));
+    if volume_name.len() <= 63 {
+        return volume_name;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    hasher.write(volume_name.as_bytes());
+    let suffix = format!("{:016x}", hasher.finish());
+    let prefix = volume_name[..63 - suffix.len() - 1].trim_end_matches('-');
+    format!("{prefix}-{suffix}")
+}
+
+fn object_volume_mount_payload(volume_name: &str, mount: &ObjectMount) -> VolumeMount {
+    VolumeMount {
+        name: volume_name.to_string(),
+        mount_path: mount.mount_path().to_string(),
+        sub_path: mount.sub_path().map(|sub_path| sub_path.to_string()),
+        read_only: Some(true),
+        ..Default::default()
+    }
+}
+
 /// Creates the value of an [annotations object](https://kubernetes.io/docs/concepts/overview/working-with-objects/annotations/)
 /// so that the underlying pod will be deployed according to its [deployment strategy](`DeploymentStrategy`).
 ///
@@ -653,6 +774,104 @@ pub fn service_payload(app_name: &AppName, service_config: &ServiceConfig) -> V1
     .expect("Cannot convert value to core/v1/Service")
 }
 
+/// Creates a payload for a [Kubernetes
+/// Ingress](https://kubernetes.io/docs/concepts/services-networking/ingress/) that routes
+/// `/{app_name}/{service_name}` to the Kubernetes service of each given service.
+pub fn ingress_payload<'a, I>(
+    app_name: &AppName,
+    services: I,
+    ingress_config: &KubernetesIngressConfig,
+) -> Ingress
+where
+    I: IntoIterator<Item = &'a ServiceConfig>,
+{
+    // The services expect to be served at the root path, like behind the Traefik routes of
+    // PREvant, thus, Traefik has to strip the path prefix. Other ingress controllers have to be
+    // configured by the annotations of the config.
+    let mut annotations = BTreeMap::new();
+    if ingress_config.class_name() == "traefik" {
+        annotations.insert(
+            String::from("traefik.ingress.kubernetes.io/router.middlewares"),
+            format!(
+                "{}-{INGRESS_STRIP_PREFIX_MIDDLEWARE}@kubernetescrd",
+                app_name.to_rfc1123_namespace_id()
+            ),
+        );
+    }
+    annotations.extend(ingress_config.annotations().clone());
+
+    let paths = services
+        .into_iter()
+        .map(|service_config| {
+            serde_json::json!({
+              "path": format!("/{}/{}", app_name, service_config.service_name()),
+              "pathType": "Prefix",
+              "backend": {
+                "service": {
+                  "name": service_config.service_name(),
+                  "port": {
+                    "number": service_config.port()
+                  }
+                }
+              }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::from_value(serde_json::json!({
+      "apiVersion": "networking.k8s.io/v1",
+      "kind": "Ingress",
+      "metadata": {
+        "name": format!("{}-ingress", app_name.to_rfc1123_namespace_id()),
+        "namespace": app_name.to_rfc1123_namespace_id(),
+        "labels": {
+          APP_NAME_LABEL: app_name,
+        },
+        "annotations": annotations,
+      },
+      "spec": {
+        "ingressClassName": ingress_config.class_name(),
+        "rules": [
+          {
+            "http": {
+              "paths": paths
+            }
+          }
+        ]
+      }
+    }))
+    .expect("Cannot convert value to networking.k8s.io/v1/Ingress")
+}
+
+/// Creates the Traefik middleware that the [`ingress_payload`] refers to in order to strip the
+/// `/{app_name}/{service_name}` prefixes.
+pub fn ingress_strip_prefix_middleware_payload<'a, I>(app_name: &AppName, services: I) -> Middleware
+where
+    I: IntoIterator<Item = &'a ServiceConfig>,
+{
+    let prefixes = services
+        .into_iter()
+        .map(|service_config| format!("/{}/{}", app_name, service_config.service_name()))
+        .collect::<Vec<_>>();
+
+    Middleware {
+        metadata: ObjectMeta {
+            name: Some(String::from(INGRESS_STRIP_PREFIX_MIDDLEWARE)),
+            namespace: Some(app_name.to_rfc1123_namespace_id()),
+            labels: Some(BTreeMap::from([(
+                APP_NAME_LABEL.to_string(),
+                app_name.to_string(),
+            )])),
+            ..Default::default()
+        },
+        spec: MiddlewareSpec(serde_json::json!({
+            "stripPrefix": {
+                "prefixes": prefixes
+            }
+        })),
+    }
+}
+
 /// Creates a payload that ensures that Traefik find the correct route in Kubernetes
 ///
 /// See [Traefik Routers](https://docs.traefik.io/v2.0/user-guides/crd-acme/#traefik-routers)
@@ -1214,6 +1433,118 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_create_ingress() {
+        let app_name = AppName::from_str("MY-APP").unwrap();
+        let mut db = sc!("db", "mariadb:10.3.17");
+        db.set_port(3306);
+        let web = sc!("web", "nginx:latest");
+        let ingress_config = toml::de::from_str::<KubernetesIngressConfig>(
+            r#"
+            className = 'nginx'
+
+            [annotations]
+            'nginx.ingress.kubernetes.io/proxy-body-size' = '8m'
+            "#,
+        )
+        .unwrap();
+
+        let payload = ingress_payload(&app_name, [&db, &web], &ingress_config);
+
+        assert_json_diff::assert_json_eq!(
+            payload,
+            serde_json::json!({
+              "apiVersion": "networking.k8s.io/v1",
+              "kind": "Ingress",
+              "metadata": {
+                "name": "my-app-ingress",
+                "namespace": "my-app",
+                "labels": {
+                  "com.aixigo.preview.servant.app-name": "MY-APP",
+                },
+                "annotations": {
+                  "nginx.ingress.kubernetes.io/proxy-body-size": "8m",
+                },
+              },
+              "spec": {
+                "ingressClassName": "nginx",
+                "rules": [
+                  {
+                    "http": {
+                      "paths": [
+                        {
+                          "path": "/MY-APP/db",
+                          "pathType": "Prefix",
+                          "backend": {
+                            "service": {
+                              "name": "db",
+                              "port": { "number": 3306 }
+                            }
+                          }
+                        },
+                        {
+                          "path": "/MY-APP/web",
+                          "pathType": "Prefix",
+                          "backend": {
+                            "service": {
+                              "name": "web",
+                              "port": { "number": 80 }
+                            }
+                          }
+                        }
+                      ]
+                    }
+                  }
+                ]
+              },
+            }),
+        );
+    }
+
+    #[test]
+    fn should_create_traefik_ingress_that_strips_path_prefixes() {
+        let app_name = AppName::from_str("MY-APP").unwrap();
+        let db = sc!("db", "mariadb:10.3.17");
+        let web = sc!("web", "nginx:latest");
+        let ingress_config = toml::de::from_str::<KubernetesIngressConfig>("").unwrap();
+
+        let payload = ingress_payload(&app_name, [&db, &web], &ingress_config);
+
+        assert_json_diff::assert_json_include!(
+            actual: payload,
+            expected: serde_json::json!({
+              "metadata": {
+                "annotations": {
+                  "traefik.ingress.kubernetes.io/router.middlewares":
+                    "my-app-prevant-ingress-strip-prefix@kubernetescrd",
+                },
+              },
+              "spec": {
+                "ingressClassName": "traefik",
+              },
+            }),
+        );
+
+        let middleware = ingress_strip_prefix_middleware_payload(&app_name, [&db, &web]);
+
+        assert_json_diff::assert_json_include!(
+            actual: middleware,
+            expected: serde_json::json!({
+              "apiVersion": "traefik.containo.us/v1alpha1",
+              "kind": "Middleware",
+              "metadata": {
+                "name": "prevant-ingress-strip-prefix",
+                "namespace": "my-app",
+              },
+              "spec": {
+                "stripPrefix": {
+                  "prefixes": [ "/MY-APP/db", "/MY-APP/web" ]
+                }
+              },
+            }),
+        );
+    }
+
     #[test]
     fn should_create_ingress_route_with_app_name_that_is_not_compliant_to_rfc1123() {
         let app_name = AppName::from_str("MY-APP").unwrap();
@@ -1432,6 +1763,147 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_create_deployment_with_config_map_and_secret_mounts() {
+        let mut config = sc!("nginx", "nginx:1.25");
+        config.set_config_map_mounts(vec![
+            ObjectMount::new(
+                String::from("nginx.config"),
+                String::from("/etc/nginx/nginx.conf"),
+                Some(String::from("nginx.conf")),
+            ),
+            ObjectMount::new(
+                String::from("nginx.config"),
+                String::from("/etc/nginx/mime.types"),
+                Some(String::from("mime.types")),
+            ),
+        ]);
+        config.set_secret_mounts(vec![ObjectMount::new(
+            String::from("tls"),
+            String::from("/etc/tls"),
+            None,
+        )]);
+
+        let payload = deployment_payload(
+            &AppName::master(),
+            &DeployableService::new(
+                config,
+                DeploymentStrategy::RedeployAlways,
+                TraefikIngressRoute::with_rule(TraefikRouterRule::path_prefix_rule(&[
+                    "master", "nginx",
+                ])),
+                Vec::new(),
+            ),
+            &ContainerConfig::default(),
+            &None,
+        );
+
+        assert_json_diff::assert_json_include!(
+            actual: payload,
+            expected: serde_json::json!({
+              "spec": {
+                "template": {
+                  "spec": {
+                    "containers": [
+                      {
+                        "name": "nginx",
+                        "volumeMounts": [{
+                          "mountPath": "/etc/nginx/nginx.conf",
+                          "name": "configmap-nginx-config",
+                          "readOnly": true,
+                          "subPath": "nginx.conf"
+                        }, {
+                          "mountPath": "/etc/nginx/mime.types",
+                          "name": "configmap-nginx-config",
+                          "readOnly": true,
+                          "subPath": "mime.types"
+                        }, {
+                          "mountPath": "/etc/tls",
+                          "name": "secret-tls",
+                          "readOnly": true
+                        }]
+                      }
+                    ],
+                    "volumes": [{
+                      "name": "configmap-nginx-config",
+                      "configMap": {
+                        "name": "nginx.config"
+                      }
+                    }, {
+                      "name": "secret-tls",
+                      "secret": {
+                        "secretName": "tls"
+                      }
+                    }]
+                  },
+                }
+              }
+            })
+        );
+    }
+
+    #[test]
+    fn should_truncate_long_object_volume_names() {
+        let object_name = "a".repeat(60);
+
+        let volume_name = object_volume_name("configmap", &object_name);
+        let other_volume_name = object_volume_name("configmap", &format!("{object_name}.b"));
+
+        assert_eq!(volume_name.len(), 63);
+        assert!(volume_name.starts_with("configmap-aaaa"));
+        assert_ne!(volume_name, other_volume_name);
+        assert_eq!(object_volume_name("secret", "tls.crt"), "secret-tls-crt");
+    }
+
+    #[test]
+    fn should_create_deployment_with_init_containers() {
+        let config = serde_json::from_value::<ServiceConfig>(serde_json::json!({
+            "serviceName": "api",
+            "image": "example/api",
+            "initContainers": [{
+                "image": "flyway/flyway:10",
+                "command": [ "flyway", "migrate" ],
+                "env": [ "FLYWAY_URL=jdbc:mariadb://db/api" ]
+            }]
+        }))
+        .unwrap();
+
+        let payload = deployment_payload(
+            &AppName::master(),
+            &DeployableService::new(
+                config,
+                DeploymentStrategy::RedeployAlways,
+                TraefikIngressRoute::with_rule(TraefikRouterRule::path_prefix_rule(&[
+                    "master", "api",
+                ])),
+                Vec::new(),
+            ),
+            &ContainerConfig::default(),
+            &None,
+        );
+
+        assert_json_diff::assert_json_include!(
+            actual: payload,
+            expected: serde_json::json!({
+              "spec": {
+                "template": {
+                  "spec": {
+                    "initContainers": [{
+                      "name": "api-init-0",
+                      "image": "docker.io/flyway/flyway:10",
+                      "args": [ "flyway", "migrate" ],
+                      "env": [{
+                        "name": "FLYWAY_URL",
+                        "value": "jdbc:mariadb://db/api"
+                      }]
+                    }]
+                  },
+                }
+              }
+            })
+        );
+    }
+
     #[test]
     fn should_create_deployment_for_config_containing_file_data() {
         let mut config = sc!("db", "mariadb:10.3.17");
@@ -24,45 +24,97 @@
  * =========================LICENSE_END==================================
  */
 
-use crate::models::Environment;
+use crate::models::{Environment, EnvironmentVariable};
+pub use dns_health_monitor::{DnsHealth, DnsHealthMonitor};
 pub use docker::DockerInfrastructure as Docker;
+pub use docker::DockerInfrastructureError;
 #[cfg(test)]
 pub use dummy_infrastructure::DummyInfrastructure as Dummy;
-pub use infrastructure::{HttpForwarder, Infrastructure};
+#[cfg(test)]
+pub use dummy_infrastructure::InfrastructureCall;
+pub use infrastructure::{
+    AppDeployment, HttpForwarder, Infrastructure, NetworkInfo, StoppedService, UnsupportedOperation,
+};
 pub use kubernetes::KubernetesInfrastructure as Kubernetes;
+pub use oom_monitor::OomMonitor;
+use regex::Regex;
 use serde_json::{map::Map, Value};
 pub use traefik::{TraefikIngressRoute, TraefikMiddleware, TraefikRouterRule};
 
+mod consul;
+mod deployment_lock;
+mod dns_health_monitor;
 mod docker;
 #[cfg(test)]
 mod dummy_infrastructure;
+mod image_puller;
 mod infrastructure;
 mod kubernetes;
+mod oom_monitor;
 mod traefik;
 
 static APP_NAME_LABEL: &str = "com.aixigo.preview.servant.app-name";
 static SERVICE_NAME_LABEL: &str = "com.aixigo.preview.servant.service-name";
 static CONTAINER_TYPE_LABEL: &str = "com.aixigo.preview.servant.container-type";
 static REPLICATED_ENV_LABEL: &str = "com.aixigo.preview.servant.replicated-env";
+/// All environment variables of the service, including the ones that are not replicated, so that
+/// the configuration can be restored from a running service, e.g. when the app is renamed.
+static ENV_LABEL: &str = "com.aixigo.preview.servant.env";
 static IMAGE_LABEL: &str = "com.aixigo.preview.servant.image";
 static STATUS_ID: &str = "com.aixigo.preview.servant.status-id";
 static STORAGE_TYPE_LABEL: &str = "com.aixigo.preview.servant.storage-type";
+/// Comma-separated tags of the app, e.g. `frontend,team-a`. Because the labels of containers cannot
+/// be changed, the tags are stored apart from the services, e.g. in the annotations of a namespace.
+pub(crate) static TAGS_LABEL: &str = "com.aixigo.preview.servant.tags";
+pub(crate) static REDACTED_VALUE: &str = "<redacted>";
+
+/// Parses the value of the [`TAGS_LABEL`].
+pub(crate) fn parse_tags(value: &str) -> Vec<String> {
+    let mut tags = value
+        .split(',')
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect::<Vec<_>>();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Determines if the value of an environment variable with the given key might contain credentials
+/// and, therefore, must not be exposed to the outside, e.g. when inspecting a service.
+pub(crate) fn is_sensitive_environment_variable(key: &str) -> bool {
+    lazy_static! {
+        static ref SENSITIVE_KEY_REGEX: Regex =
+            Regex::new(r"(?i)(passw(or)?d|secret|token|credential|api_?key|private_?key)").unwrap();
+    }
+    SENSITIVE_KEY_REGEX.is_match(key)
+}
 
 /// This function converts the environment variables and adds all variables, that
 /// must be replicated, into a JSON object. This function should be used by implementations
 /// to serialize the environment variable so that it can be deserialized when service configurations
 /// will be cloned from a running service.
 fn replicated_environment_variable_to_json(env: &Environment) -> Option<Value> {
-    let replicated_env = env
-        .iter()
-        .filter(|ev| ev.replicate())
+    environment_variables_to_json(env.iter().filter(|ev| ev.replicate()))
+}
+
+/// Like [`replicated_environment_variable_to_json`] but converts all environment variables, see
+/// [`ENV_LABEL`].
+fn environment_variable_to_json(env: &Environment) -> Option<Value> {
+    environment_variables_to_json(env.iter())
+}
+
+fn environment_variables_to_json<'a>(
+    variables: impl Iterator<Item = &'a EnvironmentVariable>,
+) -> Option<Value> {
+    let env = variables
         .map(|ev| {
             (
                 ev.key(),
                 serde_json::json!({
                     "value": ev.original().value().unsecure(),
                     "templated": ev.templated(),
-                    "replicate": true
+                    "replicate": ev.replicate()
                 }),
             )
         })
@@ -71,9 +123,37 @@ fn replicated_environment_variable_to_json(env: &Environment) -> Option<Value> {
             acc
         });
 
-    if !replicated_env.is_empty() {
-        Some(Value::Object(replicated_env))
+    if !env.is_empty() {
+        Some(Value::Object(env))
     } else {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_sensitive_environment_variables() {
+        assert!(is_sensitive_environment_variable("DB_PASSWORD"));
+        assert!(is_sensitive_environment_variable("github_token"));
+        assert!(is_sensitive_environment_variable("CLIENT_SECRET"));
+        assert!(is_sensitive_environment_variable("ApiKey"));
+    }
+
+    #[test]
+    fn should_not_detect_regular_environment_variables_as_sensitive() {
+        assert!(!is_sensitive_environment_variable("JAVA_OPTS"));
+        assert!(!is_sensitive_environment_variable("SPRING_PROFILES_ACTIVE"));
+    }
+
+    #[test]
+    fn should_parse_tags() {
+        assert_eq!(
+            parse_tags("team-a,,frontend,team-a"),
+            vec![String::from("frontend"), String::from("team-a")]
+        );
+        assert!(parse_tags("").is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::infrastructure::{Docker as DockerInfrastructure, APP_NAME_LABEL};
+use bollard::service::EventMessage;
+use bollard::system::EventsOptions;
+use bollard::Docker;
+use bytesize::ByteSize;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Watches the Docker events for containers of review apps that have been killed because they
+/// ran out of memory and logs a warning with the affected app and service.
+#[derive(Default)]
+pub struct OomMonitor {}
+
+impl OomMonitor {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = self.monitor().await {
+                    error!("Cannot monitor OOM events: {}", err);
+                }
+                sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    async fn monitor(&self) -> anyhow::Result<()> {
+        let docker = Docker::connect_with_socket_defaults()?;
+
+        let mut filters = HashMap::new();
+        filters.insert("type", vec!["container"]);
+        filters.insert("event", vec!["oom"]);
+        filters.insert("label", vec![APP_NAME_LABEL]);
+
+        let mut events = docker.events(Some(EventsOptions::<&str> {
+            filters,
+            ..Default::default()
+        }));
+
+        while let Some(event) = events.next().await {
+            let Some(container_id) = container_id_of(event?) else {
+                continue;
+            };
+
+            let Some((app_name, service)) =
+                DockerInfrastructure::find_service_by_container_id(&container_id).await?
+            else {
+                debug!("Cannot find service of container {container_id} after OOM event");
+                continue;
+            };
+
+            let memory_limit = match docker.inspect_container(&container_id, None).await {
+                Ok(details) => details
+                    .host_config
+                    .and_then(|host_config| host_config.memory)
+                    .filter(|memory| *memory > 0)
+                    .map(|memory| ByteSize(memory as u64).to_string()),
+                Err(err) => {
+                    debug!("Cannot inspect container {container_id} after OOM event: {err}");
+                    None
+                }
+            };
+
+            warn!(
+                "Container {} of service {} in app {} ran out of memory (memory limit: {})",
+                container_id,
+                service.service_name(),
+                app_name,
+                memory_limit.as_deref().unwrap_or("unlimited")
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn container_id_of(event: EventMessage) -> Option<String> {
+    event.actor?.id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::service::EventActor;
+
+    #[test]
+    fn should_extract_container_id_from_event() {
+        let event = EventMessage {
+            action: Some(String::from("oom")),
+            actor: Some(EventActor {
+                id: Some(String::from("0123456789")),
+                attributes: Some(HashMap::from([(
+                    String::from(APP_NAME_LABEL),
+                    String::from("master"),
+                )])),
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(container_id_of(event), Some(String::from("0123456789")));
+    }
+
+    #[test]
+    fn should_ignore_events_without_actor() {
+        let event = EventMessage {
+            action: Some(String::from("oom")),
+            ..Default::default()
+        };
+
+        assert_eq!(container_id_of(event), None);
+    }
+}
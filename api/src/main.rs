@@ -33,24 +33,36 @@ extern crate serde_derive;
 
 use crate::apps::host_meta_crawling;
 use crate::apps::Apps;
+use crate::apps::ConfigDirWatcher;
+use crate::apps::NetworkCleaner;
 use crate::config::{Config, Runtime};
-use crate::infrastructure::{Docker, Infrastructure, Kubernetes};
+use crate::infrastructure::{
+    DnsHealth, DnsHealthMonitor, Docker, Infrastructure, Kubernetes, OomMonitor,
+};
 use crate::models::request_info::RequestInfo;
+use crate::request_id::{RequestId, RequestIdFairing};
+use crate::version::VersionInfo;
 use clap::Parser;
 use rocket::fs::{FileServer, Options};
 use serde_yaml::{from_reader, to_string, Value};
 use std::fs::File;
+use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
 
+mod admin;
 mod apps;
+mod client_certificate;
 mod config;
 mod deployment;
 mod http_result;
 mod infrastructure;
 mod models;
 mod registry;
+mod request_id;
+mod secrets;
 mod tickets;
+mod version;
 mod webhooks;
 
 #[get("/")]
@@ -88,15 +100,46 @@ fn create_infrastructure(config: &Config) -> Box<dyn Infrastructure> {
 
 #[rocket::main]
 async fn main() -> Result<(), StartUpError> {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
-
     let cli = crate::config::CliArgs::parse();
 
     let config = Config::from_figment(&cli).map_err(|err| StartUpError::InvalidConfiguration {
         err: err.to_string(),
     })?;
 
+    let mut logger = env_logger::Builder::new();
+    logger.filter_level(log::LevelFilter::Info);
+    for (module, level) in config.log_filters() {
+        logger.filter_module(module, *level);
+    }
+    // RUST_LOG takes precedence over the log filters of the configuration
+    logger
+        .parse_env(env_logger::Env::default())
+        .format(|buf, record| {
+            let request_id = RequestId::current()
+                .map(|request_id| format!(" request_id={request_id}"))
+                .unwrap_or_default();
+            writeln!(
+                buf,
+                "[{} {:<5} {}{}] {}",
+                buf.timestamp(),
+                record.level(),
+                record.target(),
+                request_id,
+                record.args()
+            )
+        })
+        .init();
+
     let infrastructure = create_infrastructure(&config);
+    let api_version = match infrastructure.api_version().await {
+        Ok(api_version) => api_version,
+        Err(err) => {
+            log::warn!("Cannot determine API version of the infrastructure: {err}");
+            None
+        }
+    };
+    let version_info = VersionInfo::new(config.runtime_config(), api_version);
+
     let apps = Apps::new(config.clone(), infrastructure)
         .map_err(|e| StartUpError::CannotCreateApps { err: e.to_string() })?;
 
@@ -106,20 +149,62 @@ async fn main() -> Result<(), StartUpError> {
     let apps = Arc::new(apps);
 
     let (host_meta_cache, host_meta_crawler) = host_meta_crawling();
-    host_meta_crawler.spawn(apps.clone());
+    host_meta_crawler
+        .with_rate_limit(config.host_meta_requests_per_second())
+        .with_circuit_breaker(
+            config.host_meta_circuit_break_threshold(),
+            config.host_meta_circuit_open_duration(),
+        )
+        .with_crawl_priorities(config.host_meta_crawl_priorities())
+        .spawn(apps.clone());
+
+    let dns_health = DnsHealth::default();
+    if let Runtime::Docker = config.runtime_config() {
+        OomMonitor::new().spawn();
+        NetworkCleaner::new(config.network_cleanup_dry_run()).spawn(apps.clone());
+        if config.is_dns_health_check_enabled() {
+            DnsHealthMonitor::new(dns_health.clone()).spawn();
+        }
+    }
+
+    if let Some(dir) = config.watch_config_dir() {
+        ConfigDirWatcher::new(dir.clone()).spawn(apps.clone());
+    }
 
-    let _rocket = rocket::build()
+    let mut figment = rocket::Config::figment();
+    if let Some(tls_config) = config.tls() {
+        let tls_config = tls_config
+            .to_rocket_tls_config()
+            .map_err(|err| StartUpError::InvalidConfiguration { err })?;
+        figment = figment.merge(("tls", tls_config));
+    }
+
+    let _rocket = rocket::custom(figment)
+        .attach(RequestIdFairing)
         .manage(config)
         .manage(apps)
         .manage(host_meta_cache)
+        .manage(dns_health)
+        .manage(version_info)
         .mount(
             "/",
             FileServer::new(Path::new("frontend"), Options::Index | Options::Missing),
         )
         .mount("/openapi.yaml", routes![openapi])
         .mount("/api/apps", crate::apps::apps_routes())
+        .mount("/api", crate::apps::services_routes())
         .mount("/api", routes![tickets::tickets])
         .mount("/api", routes![webhooks::webhooks])
+        .mount("/api", routes![version::version])
+        .mount(
+            "/api",
+            routes![
+                admin::network_cleanup,
+                admin::host_meta_metrics,
+                admin::cache_stats,
+                admin::config
+            ],
+        )
         .launch()
         .await?;
 
@@ -33,13 +33,128 @@ use std::collections::HashSet;
 use std::ops::Deref;
 use std::str::{FromStr, Utf8Error};
 
+/// Names that conflict with the routes of PREvant's REST API, e.g. `/api/apps/bulk/start`.
+static RESERVED_NAMES: [&str; 6] = ["admin", "bulk", "events", "health", "metrics", "version"];
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct AppName(String);
 
 impl AppName {
+    /// Returns `true` if the name cannot be used for deployments because it conflicts with the
+    /// routes of PREvant.
+    pub fn is_reserved(name: &str) -> bool {
+        RESERVED_NAMES.contains(&name)
+    }
+
+    /// Checks that apps with this name can be deployed: the name must neither be reserved, see
+    /// [`is_reserved`](Self::is_reserved), nor be part of `additional_reserved_names`, and it
+    /// must be usable as DNS label.
+    pub fn check_deployable(
+        &self,
+        additional_reserved_names: &[String],
+    ) -> Result<(), AppNameError> {
+        if Self::is_reserved(&self.0) || additional_reserved_names.contains(&self.0) {
+            return Err(AppNameError::Reserved {
+                name: self.0.clone(),
+            });
+        }
+
+        if self.0.starts_with(['_', '-']) || self.0.len() > 63 {
+            return Err(AppNameError::InvalidDnsLabel {
+                name: self.0.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn master() -> Self {
         Self(String::from("master"))
     }
+
+    /// Derives the app name from a branch name. If the branch name contains a ticket id, e.g.
+    /// `feature/TICKET-123_do-thing`, the ticket id becomes the app name. Otherwise, slashes and
+    /// whitespaces are replaced by `-` and the name is shortened so that it is a valid DNS label.
+    pub fn from_branch(branch: &str) -> Self {
+        lazy_static! {
+            static ref INVALID_CHARS_REGEX: Regex = Regex::new(r"[\s/]+").unwrap();
+        }
+
+        if let Some(ticket_id) = Self::from_ticket_id_of_branch(branch) {
+            return ticket_id;
+        }
+
+        let name = INVALID_CHARS_REGEX.replace_all(branch, "-");
+        Self(
+            name.trim_start_matches(['-', '_'])
+                .chars()
+                .take(63)
+                .collect(),
+        )
+    }
+
+    /// Returns the ticket id that the branch name contains, e.g. `TICKET-123` for
+    /// `feature/TICKET-123_do-thing`, as app name.
+    pub fn from_ticket_id_of_branch(branch: &str) -> Option<Self> {
+        lazy_static! {
+            static ref TICKET_ID_REGEX: Regex = Regex::new(r"[A-Z]{3,}-\d+").unwrap();
+        }
+
+        TICKET_ID_REGEX
+            .find(branch)
+            .map(|ticket_id| Self(ticket_id.as_str().to_string()))
+    }
+
+    /// Derives the app name from a git ref as it is provided by CI systems, e.g.
+    /// `refs/heads/feature/TICKET-123_do-thing`, `refs/tags/v1.0.0`, or `refs/pull/42/merge`.
+    /// Refs of pull requests result in the same name as [`from_pr_number`](Self::from_pr_number)
+    /// and all other refs are sanitized by [`from_branch`](Self::from_branch). Refs of other
+    /// namespaces, e.g. `refs/remotes/…`, and refs without a name, e.g. `refs/heads/`, are
+    /// rejected.
+    pub fn from_git_ref(git_ref: &str) -> Result<Self, AppNameError> {
+        let app_name = if let Some(pull) = git_ref.strip_prefix("refs/pull/") {
+            let pr_number = pull.split('/').next().unwrap_or_default();
+            match pr_number.parse::<u64>() {
+                Ok(pr_number) => Self::from_pr_number(pr_number),
+                Err(_) => Self::from_branch(pull),
+            }
+        } else if let Some(name) = git_ref
+            .strip_prefix("refs/heads/")
+            .or_else(|| git_ref.strip_prefix("refs/tags/"))
+        {
+            Self::from_branch(name)
+        } else if git_ref.starts_with("refs/") {
+            return Err(AppNameError::InvalidGitRef {
+                git_ref: git_ref.to_string(),
+            });
+        } else {
+            Self::from_branch(git_ref)
+        };
+
+        if app_name.0.is_empty() {
+            return Err(AppNameError::InvalidGitRef {
+                git_ref: git_ref.to_string(),
+            });
+        }
+        app_name.check_deployable(&[])?;
+        Ok(app_name)
+    }
+
+    pub fn from_pr_number(pr_number: u64) -> Self {
+        Self(format!("pr-{pr_number}"))
+    }
+
+    /// Returns the index of the bucket `0..n` that the app belongs to. The index is derived from
+    /// the [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function)
+    /// hash of the name, thus, it is the same for every PREvant instance and version.
+    pub fn shard(&self, n: usize) -> usize {
+        assert!(n > 0, "The number of shards must be positive");
+
+        let hash = self.0.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+        (hash % n as u64) as usize
+    }
 }
 
 impl serde::Serialize for AppName {
@@ -113,12 +228,18 @@ impl<'r> FromFormField<'r> for AppName {
     }
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Clone, Debug, thiserror::Error)]
 pub enum AppNameError {
     #[error("Invalid characters in app name: “{invalid_chars}” are invalid.")]
     InvalidChars { invalid_chars: String },
     #[error("Invalid url encoded parameter: {err}")]
     InvalidUrlDecodedParam { err: String },
+    #[error("The app name “{name}” is reserved.")]
+    Reserved { name: String },
+    #[error("The app name “{name}” must neither start with “_” or “-” nor be longer than 63 characters.")]
+    InvalidDnsLabel { name: String },
+    #[error("The git ref “{git_ref}” does not name a branch, a tag, or a pull request.")]
+    InvalidGitRef { git_ref: String },
 }
 
 impl From<Utf8Error> for AppNameError {
@@ -162,6 +283,138 @@ mod tests {
         assert!(app_name.is_err());
     }
 
+    #[test]
+    fn should_assign_apps_to_shards_consistently() {
+        assert_eq!(AppName::master().shard(4), 3);
+        assert_eq!(AppName::from_str("feature-1").unwrap().shard(3), 0);
+        assert_eq!(AppName::from_str("feature-2").unwrap().shard(3), 1);
+        assert_eq!(AppName::from_str("feature-3").unwrap().shard(3), 2);
+        assert_eq!(AppName::master().shard(1), 0);
+    }
+
+    #[test]
+    fn should_not_deploy_reserved_app_names() {
+        assert!(AppName::is_reserved("admin"));
+        assert!(!AppName::is_reserved("master"));
+
+        assert!(AppName::from_str("version")
+            .unwrap()
+            .check_deployable(&[])
+            .is_err());
+        assert!(AppName::from_str("prevant")
+            .unwrap()
+            .check_deployable(&[String::from("prevant")])
+            .is_err());
+        assert!(AppName::master().check_deployable(&[]).is_ok());
+    }
+
+    #[test]
+    fn should_not_deploy_app_names_that_are_invalid_dns_labels() {
+        assert!(AppName::from_str("_feature")
+            .unwrap()
+            .check_deployable(&[])
+            .is_err());
+        assert!(AppName::from_str("-feature")
+            .unwrap()
+            .check_deployable(&[])
+            .is_err());
+        assert!(AppName::from_str(&"a".repeat(64))
+            .unwrap()
+            .check_deployable(&[])
+            .is_err());
+        assert!(AppName::from_str(&"a".repeat(63))
+            .unwrap()
+            .check_deployable(&[])
+            .is_ok());
+    }
+
+    #[test]
+    fn should_create_app_name_from_branch() {
+        assert_eq!(AppName::from_branch("master"), AppName::master());
+        assert_eq!(
+            AppName::from_branch("feature/TICKET-123_do-thing").0,
+            "TICKET-123"
+        );
+        assert_eq!(
+            AppName::from_branch("bugfix/fix login page").0,
+            "bugfix-fix-login-page"
+        );
+        assert_eq!(AppName::from_branch("_wip//x").0, "wip-x");
+        assert_eq!(AppName::from_branch(&"a".repeat(100)).0.len(), 63);
+    }
+
+    #[test]
+    fn should_create_app_name_from_git_ref() {
+        assert_eq!(
+            AppName::from_git_ref("refs/heads/feature/TICKET-123_do-thing")
+                .unwrap()
+                .0,
+            "TICKET-123"
+        );
+        assert_eq!(
+            AppName::from_git_ref("refs/heads/renovate/rocket-0.x")
+                .unwrap()
+                .0,
+            "renovate-rocket-0.x"
+        );
+        assert_eq!(
+            AppName::from_git_ref("refs/heads/master").unwrap(),
+            AppName::master()
+        );
+        assert_eq!(
+            AppName::from_git_ref("refs/tags/v1.2.3").unwrap().0,
+            "v1.2.3"
+        );
+        assert_eq!(
+            AppName::from_git_ref("refs/pull/42/merge").unwrap().0,
+            "pr-42"
+        );
+        assert_eq!(
+            AppName::from_git_ref("refs/pull/42/head").unwrap().0,
+            "pr-42"
+        );
+        assert_eq!(
+            AppName::from_git_ref("feature/xxx").unwrap().0,
+            "feature-xxx"
+        );
+    }
+
+    #[test]
+    fn should_not_create_app_name_from_git_ref_without_name() {
+        for git_ref in [
+            "refs/heads/",
+            "refs/tags/",
+            "refs/",
+            "refs/pull/",
+            "refs/heads/__",
+        ] {
+            assert!(
+                matches!(
+                    AppName::from_git_ref(git_ref),
+                    Err(AppNameError::InvalidGitRef { .. })
+                ),
+                "{git_ref} must be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn should_not_create_reserved_app_name_from_git_ref() {
+        assert!(matches!(
+            AppName::from_git_ref("refs/heads/admin"),
+            Err(AppNameError::Reserved { .. })
+        ));
+        assert!(matches!(
+            AppName::from_git_ref("refs/remotes/origin/master"),
+            Err(AppNameError::InvalidGitRef { .. })
+        ));
+    }
+
+    #[test]
+    fn should_create_app_name_from_pr_number() {
+        assert_eq!(AppName::from_pr_number(1337).0, "pr-1337");
+    }
+
     #[test]
     fn should_not_create_app_name_app_name_contains_slashes() {
         let app_name = AppName::from_str("feature/xxx");
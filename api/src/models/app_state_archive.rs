@@ -0,0 +1,240 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2024 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+use crate::infrastructure::{is_sensitive_environment_variable, REDACTED_VALUE};
+use crate::models::{AppName, ComposeWriter, ServiceConfig};
+use serde_json::{Map, Value};
+use std::io::{Cursor, Write};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// A ZIP archive that captures the configurations of an app's services so that the app can be
+/// reproduced later, e.g. for a post-incident review. The archive contains:
+///
+/// - `manifest.json`: the service configurations in the format of `POST /api/apps/{appName}`
+/// - `docker-compose.yml`: the services as Compose file, see [`ComposeWriter`]
+/// - `README.md`: instructions on how to redeploy the app
+///
+/// The values of environment variables and the content of files that might contain credentials are
+/// redacted because the archive is meant to be handed over. The Compose file refers to these values
+/// by `${VARIABLE}` placeholders instead.
+pub struct AppStateArchive(Vec<u8>);
+
+impl AppStateArchive {
+    pub fn new(app_name: &AppName, service_configs: &[ServiceConfig]) -> Result<Self, ZipError> {
+        let mut service_configs = service_configs.iter().collect::<Vec<_>>();
+        service_configs.sort_by(|a, b| a.service_name().cmp(b.service_name()));
+
+        let manifest = Value::Array(
+            service_configs
+                .iter()
+                .map(|config| Self::manifest_entry(config))
+                .collect(),
+        );
+        let configs = service_configs.into_iter().cloned().collect::<Vec<_>>();
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+
+        zip.start_file("manifest.json", options)?;
+        zip.write_all(
+            serde_json::to_string_pretty(&manifest)
+                .expect("A JSON value is always serializable")
+                .as_bytes(),
+        )?;
+
+        zip.start_file("docker-compose.yml", options)?;
+        zip.write_all(ComposeWriter::write(&configs).as_bytes())?;
+
+        zip.start_file("README.md", options)?;
+        zip.write_all(Self::readme(app_name).as_bytes())?;
+
+        Ok(Self(zip.finish()?.into_inner()))
+    }
+
+    fn manifest_entry(config: &ServiceConfig) -> Value {
+        let mut entry = Map::new();
+        entry.insert(
+            String::from("serviceName"),
+            Value::from(config.service_name().as_str()),
+        );
+        entry.insert(
+            String::from("image"),
+            Value::from(config.image().to_string()),
+        );
+
+        if let Some(env) = config.env() {
+            entry.insert(
+                String::from("env"),
+                env.iter()
+                    .map(|variable| {
+                        let value = if is_sensitive_environment_variable(variable.key()) {
+                            REDACTED_VALUE
+                        } else {
+                            variable.value().unsecure()
+                        };
+                        (variable.key().clone(), Value::from(value))
+                    })
+                    .collect::<Map<_, _>>()
+                    .into(),
+            );
+        }
+
+        if let Some(files) = config.files() {
+            entry.insert(
+                String::from("volumes"),
+                files
+                    .iter()
+                    .map(|(path, content)| {
+                        let path = path.to_string_lossy().to_string();
+                        // Files such as `/run/secrets/db-password` are treated like variables.
+                        let content = if is_sensitive_environment_variable(&path) {
+                            REDACTED_VALUE
+                        } else {
+                            content.unsecure()
+                        };
+                        (path, Value::from(content))
+                    })
+                    .collect::<Map<_, _>>()
+                    .into(),
+            );
+        }
+
+        #[allow(deprecated)]
+        let labels = config.labels();
+        if let Some(labels) = labels.filter(|labels| !labels.is_empty()) {
+            entry.insert(
+                String::from("labels"),
+                labels
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Value::from(value.as_str())))
+                    .collect::<Map<_, _>>()
+                    .into(),
+            );
+        }
+
+        Value::Object(entry)
+    }
+
+    fn readme(app_name: &AppName) -> String {
+        format!(
+            r#"# Exported state of the app {app_name}
+
+This archive captures the configurations of the services of the app `{app_name}`.
+
+## Redeploy with PREvant
+
+`manifest.json` contains the service configurations in the format that PREvant accepts for
+deployments:
+
+```bash
+curl -X POST -H 'Content-Type: application/json' -d @manifest.json \
+    https://<prevant-host>/api/apps/{app_name}
+```
+
+Labels are listed for reference only because PREvant manages the labels of the services itself.
+Values of environment variables and files that might contain credentials, e.g. passwords or
+tokens, are replaced by `{REDACTED_VALUE}` and have to be filled in before redeploying.
+
+## Run locally
+
+`docker-compose.yml` describes the services as Compose file. Settings without counterpart in the
+Compose specification, e.g. the files of the services, are left out. Environment variables that
+might contain credentials refer to variables of the same name, e.g. `${{DB_PASSWORD}}`, which
+Compose reads from the shell or from a `.env` file next to `docker-compose.yml`:
+
+```bash
+DB_PASSWORD=<password> docker compose up
+```
+"#
+        )
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Environment, EnvironmentVariable};
+    use crate::sc;
+    use secstr::SecUtf8;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    #[test]
+    fn should_create_archive_with_manifest_compose_file_and_readme() {
+        let mut config = sc!("db", "mariadb:10.3.17");
+        config.set_env(Some(Environment::new(vec![
+            EnvironmentVariable::new(String::from("MYSQL_USER"), SecUtf8::from("admin")),
+            EnvironmentVariable::new(String::from("MYSQL_PASSWORD"), SecUtf8::from("secret")),
+        ])));
+
+        let archive = AppStateArchive::new(&AppName::master(), &[sc!("web", "nginx"), config])
+            .unwrap()
+            .into_bytes();
+
+        let mut zip = ZipArchive::new(Cursor::new(archive)).unwrap();
+        assert_eq!(
+            zip.file_names().collect::<Vec<_>>().len(),
+            3,
+            "Expected manifest, compose file, and readme"
+        );
+
+        let mut manifest = String::new();
+        zip.by_name("manifest.json")
+            .unwrap()
+            .read_to_string(&mut manifest)
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&manifest).unwrap(),
+            serde_json::json!([
+                {
+                    "serviceName": "db",
+                    "image": "docker.io/library/mariadb:10.3.17",
+                    "env": { "MYSQL_USER": "admin", "MYSQL_PASSWORD": "<redacted>" }
+                },
+                {
+                    "serviceName": "web",
+                    "image": "docker.io/library/nginx:latest"
+                }
+            ])
+        );
+
+        let mut compose_file = String::new();
+        zip.by_name("docker-compose.yml")
+            .unwrap()
+            .read_to_string(&mut compose_file)
+            .unwrap();
+        assert!(compose_file.contains("mariadb:10.3.17"));
+        assert!(!compose_file.contains("secret"));
+        assert!(compose_file.contains("${MYSQL_PASSWORD}"));
+
+        assert!(zip.by_name("README.md").is_ok());
+    }
+}
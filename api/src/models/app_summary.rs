@@ -0,0 +1,196 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::models::service::{Service, ServiceStatus};
+use crate::models::AppName;
+use chrono::{DateTime, Utc};
+
+/// Summarizes the state of an app so that clients do not need to inspect each service in order to
+/// determine whether the app is usable.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSummary {
+    app_name: AppName,
+    services: Vec<Service>,
+    overall_status: AppHealthStatus,
+    /// The creation time of the app's oldest service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AppHealthStatus {
+    /// All services are running.
+    Healthy,
+    /// Some services are not running.
+    Degraded,
+    /// None of the services is running.
+    Failed,
+    /// All services are running but they cannot resolve each other's names.
+    DnsDegraded,
+}
+
+impl AppSummary {
+    pub fn new(app_name: AppName, services: Vec<Service>) -> Self {
+        let running_services = services
+            .iter()
+            .filter(|service| *service.status() == ServiceStatus::Running)
+            .count();
+        let overall_status = if running_services == services.len() {
+            AppHealthStatus::Healthy
+        } else if running_services == 0 {
+            AppHealthStatus::Failed
+        } else {
+            AppHealthStatus::Degraded
+        };
+
+        let created_at = services
+            .iter()
+            .map(|service| {
+                *service
+                    .app_created_at()
+                    .unwrap_or_else(|| service.started_at())
+            })
+            .min();
+
+        Self {
+            app_name,
+            services,
+            overall_status,
+            created_at,
+        }
+    }
+
+    /// Marks the app as [`DnsDegraded`](AppHealthStatus::DnsDegraded) if the name resolution of
+    /// its services does not work although all services are running.
+    pub fn with_dns_degraded(mut self, dns_degraded: bool) -> Self {
+        if dns_degraded && self.overall_status == AppHealthStatus::Healthy {
+            self.overall_status = AppHealthStatus::DnsDegraded;
+        }
+        self
+    }
+
+    #[cfg(test)]
+    pub fn overall_status(&self) -> &AppHealthStatus {
+        &self.overall_status
+    }
+
+    #[cfg(test)]
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ServiceBuilder;
+    use crate::sc;
+    use chrono::TimeZone;
+    use std::str::FromStr;
+
+    fn service(name: &str, status: ServiceStatus, started_at: DateTime<Utc>) -> Service {
+        ServiceBuilder::new()
+            .id(name.to_string())
+            .app_name(String::from("master"))
+            .config(sc!(name))
+            .service_status(status)
+            .started_at(started_at)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_summarize_running_app_as_healthy() {
+        let summary = AppSummary::new(
+            AppName::from_str("master").unwrap(),
+            vec![
+                service(
+                    "db",
+                    ServiceStatus::Running,
+                    Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+                ),
+                service(
+                    "app",
+                    ServiceStatus::Running,
+                    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+                ),
+            ],
+        );
+
+        assert_eq!(summary.overall_status(), &AppHealthStatus::Healthy);
+        assert_eq!(
+            summary.created_at(),
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn should_summarize_partially_running_app_as_degraded() {
+        let summary = AppSummary::new(
+            AppName::from_str("master").unwrap(),
+            vec![
+                service("db", ServiceStatus::Running, Utc::now()),
+                service("app", ServiceStatus::Paused, Utc::now()),
+            ],
+        );
+
+        assert_eq!(summary.overall_status(), &AppHealthStatus::Degraded);
+    }
+
+    #[test]
+    fn should_summarize_stopped_app_as_failed() {
+        let summary = AppSummary::new(
+            AppName::from_str("master").unwrap(),
+            vec![service("db", ServiceStatus::Paused, Utc::now())],
+        );
+
+        assert_eq!(summary.overall_status(), &AppHealthStatus::Failed);
+    }
+
+    #[test]
+    fn should_summarize_running_app_with_broken_dns_as_dns_degraded() {
+        let summary = AppSummary::new(
+            AppName::from_str("master").unwrap(),
+            vec![service("db", ServiceStatus::Running, Utc::now())],
+        )
+        .with_dns_degraded(true);
+
+        assert_eq!(summary.overall_status(), &AppHealthStatus::DnsDegraded);
+    }
+
+    #[test]
+    fn should_keep_failed_status_of_app_with_broken_dns() {
+        let summary = AppSummary::new(
+            AppName::from_str("master").unwrap(),
+            vec![service("db", ServiceStatus::Paused, Utc::now())],
+        )
+        .with_dns_degraded(true);
+
+        assert_eq!(summary.overall_status(), &AppHealthStatus::Failed);
+    }
+}
@@ -38,6 +38,8 @@ pub enum Image {
         registry: Option<String>,
         image_user: Option<String>,
         image_tag: Option<String>,
+        /// The digest, e.g. `sha256:…`, that pins the image in addition to or instead of the tag.
+        image_digest: Option<String>,
     },
     Digest {
         hash: String,
@@ -52,6 +54,7 @@ impl Hash for Image {
                 registry,
                 image_user,
                 image_tag,
+                image_digest,
             } => {
                 state.write(
                     registry
@@ -72,6 +75,9 @@ impl Hash for Image {
                         .map_or("latest", |image_tag| image_tag.as_str())
                         .as_bytes(),
                 );
+                if let Some(image_digest) = image_digest {
+                    state.write(image_digest.as_bytes());
+                }
             }
             Image::Digest { hash } => {
                 state.write(hash.as_bytes());
@@ -90,15 +96,18 @@ impl PartialEq for Image {
                     registry,
                     image_user,
                     image_tag,
+                    image_digest,
                 },
                 Named {
                     image_repository: image_repository_other,
                     registry: registry_other,
                     image_user: image_user_other,
                     image_tag: image_tag_other,
+                    image_digest: image_digest_other,
                 },
             ) => {
-                if image_repository != image_repository_other {
+                if image_repository != image_repository_other || image_digest != image_digest_other
+                {
                     return false;
                 }
 
@@ -126,7 +135,6 @@ impl PartialEq for Image {
 }
 
 impl Image {
-    #[cfg(test)]
     pub fn tag(&self) -> Option<String> {
         match &self {
             Image::Digest { .. } => None,
@@ -135,9 +143,11 @@ impl Image {
                 registry: _,
                 image_user: _,
                 image_tag,
-            } => match &image_tag {
-                None => Some(String::from("latest")),
-                Some(tag) => Some(tag.clone()),
+                image_digest,
+            } => match (&image_tag, &image_digest) {
+                (Some(tag), _) => Some(tag.clone()),
+                (None, Some(_)) => None,
+                (None, None) => Some(String::from("latest")),
             },
         }
     }
@@ -151,6 +161,7 @@ impl Image {
                 registry: _,
                 image_user,
                 image_tag: _,
+                image_digest: _,
             } => {
                 let user = match &image_user {
                     None => String::from("library"),
@@ -162,6 +173,26 @@ impl Image {
         }
     }
 
+    /// Returns the fully qualified image name without the tag, e.g.
+    /// `docker.io/library/nginx`, or the hash of an image digest.
+    pub fn repository(&self) -> String {
+        match &self {
+            Image::Digest { hash } => hash.clone(),
+            Image::Named {
+                image_repository,
+                registry,
+                image_user,
+                image_tag: _,
+                image_digest: _,
+            } => format!(
+                "{}/{}/{}",
+                registry.as_deref().unwrap_or("docker.io"),
+                image_user.as_deref().unwrap_or("library"),
+                image_repository
+            ),
+        }
+    }
+
     pub fn registry(&self) -> Option<String> {
         match &self {
             Image::Digest { .. } => None,
@@ -170,6 +201,7 @@ impl Image {
                 registry,
                 image_user: _,
                 image_tag: _,
+                image_digest: _,
             } => Some(
                 registry
                     .clone()
@@ -192,7 +224,7 @@ impl FromStr for Image {
         }
 
         regex = Regex::new(
-            r"^(((?P<registry>([\w\.-]|:)+)/)?(?P<user>[\w/-]+)/)?(?P<repo>[\w-]+)(:(?P<tag>[\w\.-]+))?$",
+            r"^(((?P<registry>([\w\.-]|:)+)/)?(?P<user>[\w/-]+)/)?(?P<repo>[\w-]+)(:(?P<tag>[\w\.-]+))?(@(?P<digest>sha256:[a-fA-F0-9]{64}))?$",
         )
         .unwrap();
         let captures = match regex.captures(s) {
@@ -211,12 +243,14 @@ impl FromStr for Image {
         let registry = captures.name("registry").map(|m| String::from(m.as_str()));
         let user = captures.name("user").map(|m| String::from(m.as_str()));
         let tag = captures.name("tag").map(|m| String::from(m.as_str()));
+        let digest = captures.name("digest").map(|m| String::from(m.as_str()));
 
         let named = Image::Named {
             image_repository: repo,
             registry,
             image_user: user,
             image_tag: tag,
+            image_digest: digest,
         };
 
         // FIXME: eventually replace Image with oci_client::Reference
@@ -243,6 +277,7 @@ impl Display for Image {
                 registry,
                 image_user,
                 image_tag,
+                image_digest,
             } => {
                 let registry = match &registry {
                     None => String::from("docker.io"),
@@ -254,12 +289,13 @@ impl Display for Image {
                     Some(user) => user.clone(),
                 };
 
-                let tag = match &image_tag {
-                    None => "latest".to_owned(),
-                    Some(tag) => tag.clone(),
-                };
-
-                write!(f, "{}/{}/{}:{}", registry, user, image_repository, tag)
+                write!(f, "{}/{}/{}", registry, user, image_repository)?;
+                match (&image_tag, &image_digest) {
+                    (Some(tag), Some(digest)) => write!(f, ":{}@{}", tag, digest),
+                    (None, Some(digest)) => write!(f, "@{}", digest),
+                    (Some(tag), None) => write!(f, ":{}", tag),
+                    (None, None) => write!(f, ":latest"),
+                }
             }
         }
     }
@@ -340,6 +376,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_parse_digest_pinned_image() {
+        let image = Image::from_str(
+            "ghcr.io/myorg/app@sha256:9895c9b90b58c9490471b877f6bb6a90e6bdc154da7fbb526a0322ea242fc913",
+        )
+        .unwrap();
+
+        assert_eq!(
+            &image.to_string(),
+            "ghcr.io/myorg/app@sha256:9895c9b90b58c9490471b877f6bb6a90e6bdc154da7fbb526a0322ea242fc913"
+        );
+        assert_eq!(&image.registry().unwrap(), "ghcr.io");
+        assert_eq!(image.tag(), None);
+        assert_ne!(image, Image::from_str("ghcr.io/myorg/app").unwrap());
+    }
+
+    #[test]
+    fn should_parse_digest_pinned_image_with_tag() {
+        let image = Image::from_str(
+            "nginx:1.27@sha256:9895c9b90b58c9490471b877f6bb6a90e6bdc154da7fbb526a0322ea242fc913",
+        )
+        .unwrap();
+
+        assert_eq!(
+            &image.to_string(),
+            "docker.io/library/nginx:1.27@sha256:9895c9b90b58c9490471b877f6bb6a90e6bdc154da7fbb526a0322ea242fc913"
+        );
+        assert_eq!(&image.tag().unwrap(), "1.27");
+    }
+
+    #[test]
+    fn should_parse_image_without_registry_and_user() {
+        let image = Image::from_str("nginx").unwrap();
+
+        assert_eq!(&image.registry().unwrap(), "docker.io");
+        assert_eq!(&image.name().unwrap(), "library/nginx");
+        assert_eq!(&image.tag().unwrap(), "latest");
+    }
+
     #[test]
     fn should_parse_image_id() {
         let image = Image::from_str("9895c9b90b58").unwrap();
@@ -445,5 +520,11 @@ mod tests {
                 invalid_string: String::from("private-registry.example.com/_/postgres")
             })
         );
+        assert_eq!(
+            Image::from_str("nginx:"),
+            Err(ServiceError::InvalidImageString {
+                invalid_string: String::from("nginx:")
+            })
+        );
     }
 }
@@ -24,8 +24,34 @@
  * =========================LICENSE_END==================================
  */
 use chrono::{DateTime, FixedOffset, Utc};
+use serde::Serialize;
 use std::convert::From;
 
+/// A single log line of a service together with the output stream that it has been written to.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LogLine {
+    timestamp: DateTime<Utc>,
+    stream: LogStream,
+    content: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    StdOut,
+    StdErr,
+}
+
+impl LogLine {
+    pub fn new(timestamp: DateTime<Utc>, stream: LogStream, content: String) -> Self {
+        Self {
+            timestamp,
+            stream,
+            content,
+        }
+    }
+}
+
 pub struct LogChunk {
     since: DateTime<FixedOffset>,
     until: DateTime<FixedOffset>,
@@ -25,22 +25,33 @@
  */
 
 pub use app_name::{AppName, AppNameError};
+pub use app_state_archive::AppStateArchive;
 pub use app_status_change_id::{AppStatusChangeId, AppStatusChangeIdError};
+pub use app_summary::AppSummary;
 pub use image::Image;
-pub use logs_chunks::LogChunk;
+pub use logs_chunks::{LogChunk, LogLine, LogStream};
 pub use request_info::RequestInfo;
 pub use service::{ContainerType, ServiceBuilder, ServiceBuilderError};
-pub use service_config::{Environment, EnvironmentVariable, ServiceConfig};
+pub use service_config::{
+    ComposeError, ComposeParser, ComposeWriter, DeviceMapping, Environment, EnvironmentVariable,
+    InitContainerConfig, IpcMode, K8sConversionError, K8sConversionWarning, NamedVolumeMount,
+    ObjectMount, PortBinding, PortProtocol, SecretEnvRef, ServiceConfig, ServiceConfigBuilder,
+    ServiceConfigBuilderError, StartupProbe, UlimitConfig,
+};
+pub use service_search::{ServiceQuery, ServiceSearchResult};
 pub use web_host_meta::WebHostMeta;
 
 mod app_name;
+mod app_state_archive;
 mod app_status_change_id;
+mod app_summary;
 mod image;
 mod logs_chunks;
 pub mod request_info;
 #[cfg_attr(test, macro_use)]
 pub mod service;
 mod service_config;
+mod service_search;
 pub mod ticket_info;
 pub mod web_hook_info;
 pub mod web_host_meta;
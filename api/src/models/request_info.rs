@@ -32,12 +32,18 @@ use url::Url;
 #[derive(Clone)]
 pub struct RequestInfo {
     base_url: Url,
+    /// The path under which a reverse proxy in front of PREvant serves it, e.g. `/prevant`.
+    forwarded_prefix: Option<String>,
 }
 
 impl RequestInfo {
     pub fn get_base_url(&self) -> &Url {
         &self.base_url
     }
+
+    pub fn get_forwarded_prefix(&self) -> Option<&str> {
+        self.forwarded_prefix.as_deref()
+    }
 }
 
 #[rocket::async_trait]
@@ -67,9 +73,19 @@ impl<'r> FromRequest<'r> for RequestInfo {
             .map(|port| format!(":{port}"))
             .unwrap_or_default();
 
+        let forwarded_prefix = request
+            .headers()
+            .get_one("x-forwarded-prefix")
+            .map(|prefix| prefix.trim_matches('/'))
+            .filter(|prefix| !prefix.is_empty())
+            .map(|prefix| format!("/{prefix}"));
+
         let host_url = format!("{}://{}{}", forwarded_proto, forwarded_host, forwarded_port);
         match Url::parse(&host_url) {
-            Ok(url) => Outcome::Success(RequestInfo { base_url: url }),
+            Ok(url) => Outcome::Success(RequestInfo {
+                base_url: url,
+                forwarded_prefix,
+            }),
             Err(_) => Outcome::Error((Status::BadRequest, ())),
         }
     }
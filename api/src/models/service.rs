@@ -24,11 +24,14 @@
  * =========================LICENSE_END==================================
  */
 
-use crate::models::{web_host_meta::WebHostMeta, ServiceConfig};
+use crate::models::{web_host_meta::WebHostMeta, AppName, Image, ServiceConfig};
 use chrono::{DateTime, Utc};
+use multimap::MultiMap;
 use serde::ser::{Serialize, Serializer};
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fmt::Display;
+use std::net::IpAddr;
 use std::str::FromStr;
 use url::Url;
 
@@ -38,9 +41,28 @@ pub struct Service {
     id: String,
     app_name: String,
     base_url: Option<Url>,
+    /// The URL of the service if it is not reachable under a path of `base_url`, e.g. when it is
+    /// routed by its subdomain.
+    service_url: Option<Url>,
     web_host_meta: Option<WebHostMeta>,
     state: State,
     config: ServiceConfig,
+    /// Maps the published container ports to the ports on the host
+    host_ports: BTreeMap<u16, u16>,
+    /// The time when the service has been deployed for the first time. In contrast to
+    /// `started_at`, it is preserved when the service is redeployed or restarted.
+    app_created_at: Option<DateTime<Utc>>,
+    /// The version of PREvant that deployed the service.
+    deployed_by_version: Option<String>,
+    /// The immutable ID of the image that the container runs, e.g. `sha256:…`. In contrast to
+    /// the tag of the image, it identifies the exact image content.
+    image_digest: Option<String>,
+    /// The IPv4 and IPv6 addresses under which the service is reachable from other containers.
+    ip_addresses: Vec<IpAddr>,
+    /// The number of containers that should run the service.
+    expected_replicas: u32,
+    /// The number of containers that actually run the service.
+    actual_replicas: u32,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize)]
@@ -64,10 +86,22 @@ impl Service {
     }
 
     fn service_url(&self) -> Option<Url> {
-        self.base_url.clone().map(|url| {
-            url.join(&format!("/{}/{}/", &self.app_name, self.service_name()))
-                .unwrap()
-        })
+        self.base_url
+            .as_ref()
+            .map(|base_url| self.external_url(base_url))
+    }
+
+    /// Returns the URL that users open in their browser to reach the service: either the URL
+    /// derived from the service's subdomain, if subdomain routing is enabled, or `base_url` with
+    /// the app and service name as path segments, e.g. `https://example.com/master/nginx/`.
+    pub fn external_url(&self, base_url: &Url) -> Url {
+        if let Some(service_url) = &self.service_url {
+            return service_url.clone();
+        }
+
+        base_url
+            .join(&format!("/{}/{}/", &self.app_name, self.service_name()))
+            .unwrap()
     }
 
     pub fn id(&self) -> &String {
@@ -86,6 +120,10 @@ impl Service {
         &self.config
     }
 
+    pub fn image(&self) -> &Image {
+        self.config.image()
+    }
+
     pub fn started_at(&self) -> &DateTime<Utc> {
         &self.state.started_at
     }
@@ -93,6 +131,47 @@ impl Service {
     pub fn status(&self) -> &ServiceStatus {
         &self.state.status
     }
+
+    pub fn host_ports(&self) -> &BTreeMap<u16, u16> {
+        &self.host_ports
+    }
+
+    pub fn app_created_at(&self) -> Option<&DateTime<Utc>> {
+        self.app_created_at.as_ref()
+    }
+
+    pub fn deployed_by_version(&self) -> Option<&str> {
+        self.deployed_by_version.as_deref()
+    }
+
+    pub fn image_digest(&self) -> Option<&str> {
+        self.image_digest.as_deref()
+    }
+
+    pub fn expected_replicas(&self) -> u32 {
+        self.expected_replicas
+    }
+
+    pub fn actual_replicas(&self) -> u32 {
+        self.actual_replicas
+    }
+
+    /// Returns `true` if the service has been deployed by a PREvant version that is older than
+    /// the given version. Services without version information are considered to be deployed by
+    /// an older version because the information has been introduced later.
+    pub fn is_deployed_by_older_version(&self, current_version: &str) -> bool {
+        fn parse(version: &str) -> Vec<u64> {
+            version
+                .split(|c| c == '.' || c == '-')
+                .map_while(|part| part.parse::<u64>().ok())
+                .collect()
+        }
+
+        match &self.deployed_by_version {
+            Some(version) => parse(version) < parse(current_version),
+            None => true,
+        }
+    }
 }
 
 impl Serialize for Service {
@@ -104,8 +183,13 @@ impl Serialize for Service {
         #[serde(rename_all = "camelCase")]
         struct Service<'a> {
             name: &'a String,
+            image: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            tag: Option<String>,
             #[serde(skip_serializing_if = "Option::is_none")]
             url: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            external_url: Option<String>,
             #[serde(rename = "type")]
             service_type: String,
             #[serde(skip_serializing_if = "Option::is_none")]
@@ -113,6 +197,18 @@ impl Serialize for Service {
             #[serde(skip_serializing_if = "Option::is_none")]
             open_api_url: Option<Url>,
             state: &'a State,
+            #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+            host_ports: &'a BTreeMap<u16, u16>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            app_created_at: Option<DateTime<Utc>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            deployed_by_version: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            image_digest: Option<&'a str>,
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            ip_addresses: &'a Vec<IpAddr>,
+            expected_replicas: u32,
+            actual_replicas: u32,
         }
 
         #[derive(Serialize)]
@@ -138,14 +234,24 @@ impl Serialize for Service {
 
         let s = Service {
             name: self.service_name(),
+            image: self.image().repository(),
+            tag: self.image().tag(),
             url: match self.web_host_meta {
                 Some(ref meta) if meta.is_valid() => self.service_url().map(|url| url.to_string()),
                 _ => None,
             },
+            external_url: self.service_url().map(|url| url.to_string()),
             service_type: self.container_type().to_string(),
             version,
             open_api_url,
             state: &self.state,
+            host_ports: &self.host_ports,
+            app_created_at: self.app_created_at,
+            deployed_by_version: self.deployed_by_version(),
+            image_digest: self.image_digest(),
+            ip_addresses: &self.ip_addresses,
+            expected_replicas: self.expected_replicas,
+            actual_replicas: self.actual_replicas,
         };
 
         s.serialize(serializer)
@@ -160,7 +266,15 @@ pub struct ServiceBuilder {
     status: Option<ServiceStatus>,
     started_at: Option<DateTime<Utc>>,
     base_url: Option<Url>,
+    service_url: Option<Url>,
     web_host_meta: Option<WebHostMeta>,
+    host_ports: BTreeMap<u16, u16>,
+    app_created_at: Option<DateTime<Utc>>,
+    deployed_by_version: Option<String>,
+    image_digest: Option<String>,
+    ip_addresses: Vec<IpAddr>,
+    expected_replicas: u32,
+    actual_replicas: u32,
 }
 
 impl ServiceBuilder {
@@ -171,8 +285,16 @@ impl ServiceBuilder {
             status: None,
             started_at: None,
             base_url: None,
+            service_url: None,
             web_host_meta: None,
             config: None,
+            host_ports: BTreeMap::new(),
+            app_created_at: None,
+            deployed_by_version: None,
+            image_digest: None,
+            ip_addresses: Vec::new(),
+            expected_replicas: 1,
+            actual_replicas: 1,
         }
     }
 
@@ -189,7 +311,15 @@ impl ServiceBuilder {
             app_name,
             config,
             base_url: self.base_url,
+            service_url: self.service_url,
             web_host_meta: self.web_host_meta,
+            host_ports: self.host_ports,
+            app_created_at: self.app_created_at,
+            deployed_by_version: self.deployed_by_version,
+            image_digest: self.image_digest,
+            ip_addresses: self.ip_addresses,
+            expected_replicas: self.expected_replicas,
+            actual_replicas: self.actual_replicas,
             state: State {
                 started_at,
                 status: self.status.unwrap_or(ServiceStatus::Running),
@@ -222,6 +352,11 @@ impl ServiceBuilder {
         self
     }
 
+    pub fn service_url(mut self, service_url: Url) -> Self {
+        self.service_url = Some(service_url);
+        self
+    }
+
     pub fn web_host_meta(mut self, web_host_meta: WebHostMeta) -> Self {
         self.web_host_meta = Some(web_host_meta);
         self
@@ -231,6 +366,41 @@ impl ServiceBuilder {
         self.config = Some(config);
         self
     }
+
+    pub fn host_ports(mut self, host_ports: BTreeMap<u16, u16>) -> Self {
+        self.host_ports = host_ports;
+        self
+    }
+
+    pub fn app_created_at(mut self, app_created_at: Option<DateTime<Utc>>) -> Self {
+        self.app_created_at = app_created_at;
+        self
+    }
+
+    pub fn deployed_by_version(mut self, deployed_by_version: Option<String>) -> Self {
+        self.deployed_by_version = deployed_by_version;
+        self
+    }
+
+    pub fn image_digest(mut self, image_digest: Option<String>) -> Self {
+        self.image_digest = image_digest;
+        self
+    }
+
+    pub fn ip_addresses(mut self, ip_addresses: Vec<IpAddr>) -> Self {
+        self.ip_addresses = ip_addresses;
+        self
+    }
+
+    pub fn expected_replicas(mut self, expected_replicas: u32) -> Self {
+        self.expected_replicas = expected_replicas;
+        self
+    }
+
+    pub fn actual_replicas(mut self, actual_replicas: u32) -> Self {
+        self.actual_replicas = actual_replicas;
+        self
+    }
 }
 
 #[derive(Debug, thiserror::Error, PartialEq)]
@@ -252,7 +422,15 @@ impl From<Service> for ServiceBuilder {
             status: Some(service.state.status),
             started_at: Some(service.state.started_at),
             base_url: service.base_url,
+            service_url: service.service_url,
             web_host_meta: service.web_host_meta,
+            host_ports: service.host_ports,
+            app_created_at: service.app_created_at,
+            deployed_by_version: service.deployed_by_version,
+            image_digest: service.image_digest,
+            ip_addresses: service.ip_addresses,
+            expected_replicas: service.expected_replicas,
+            actual_replicas: service.actual_replicas,
         }
     }
 }
@@ -305,6 +483,71 @@ pub enum ServiceError {
     InvalidImageString { invalid_string: String },
 }
 
+/// Describes what changed between two snapshots of the deployed services. A service that has been
+/// modified, e.g. because it has been restarted, is part of `removed` (old state) and `added` (new
+/// state).
+#[derive(Debug, PartialEq)]
+pub struct ServiceDiff {
+    added: MultiMap<AppName, Service>,
+    removed: MultiMap<AppName, Service>,
+    unchanged: MultiMap<AppName, Service>,
+}
+
+impl ServiceDiff {
+    pub fn added(&self) -> &MultiMap<AppName, Service> {
+        &self.added
+    }
+
+    pub fn removed(&self) -> &MultiMap<AppName, Service> {
+        &self.removed
+    }
+
+    pub fn unchanged(&self) -> &MultiMap<AppName, Service> {
+        &self.unchanged
+    }
+}
+
+/// Computes which services have been added, removed, or kept unchanged between the snapshots
+/// `before` and `after`, e.g. the results of two consecutive calls of `get_services`.
+pub fn diff_services(
+    before: &MultiMap<AppName, Service>,
+    after: &MultiMap<AppName, Service>,
+) -> ServiceDiff {
+    let contains = |snapshot: &MultiMap<AppName, Service>, app_name: &AppName, service| {
+        snapshot
+            .get_vec(app_name)
+            .map_or(false, |services| services.contains(service))
+    };
+
+    let mut added = MultiMap::new();
+    let mut unchanged = MultiMap::new();
+    for (app_name, services) in after.iter_all() {
+        for service in services {
+            if contains(before, app_name, service) {
+                unchanged.insert(app_name.clone(), service.clone());
+            } else {
+                added.insert(app_name.clone(), service.clone());
+            }
+        }
+    }
+
+    let mut removed = MultiMap::new();
+    for (app_name, services) in before.iter_all() {
+        for service in services
+            .iter()
+            .filter(|service| !contains(after, app_name, service))
+        {
+            removed.insert(app_name.clone(), service.clone());
+        }
+    }
+
+    ServiceDiff {
+        added,
+        removed,
+        unchanged,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,6 +605,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_detect_services_deployed_by_older_versions() {
+        let service = |version: Option<&str>| {
+            ServiceBuilder::new()
+                .id("some-random-id".to_string())
+                .app_name("master".to_string())
+                .config(sc!("nginx", "nginx"))
+                .deployed_by_version(version.map(String::from))
+                .build()
+                .unwrap()
+        };
+
+        assert!(service(Some("0.8.1")).is_deployed_by_older_version("0.9.0"));
+        assert!(service(Some("0.9.0")).is_deployed_by_older_version("0.10.0"));
+        assert!(service(None).is_deployed_by_older_version("0.9.0"));
+        assert!(!service(Some("0.9.0")).is_deployed_by_older_version("0.9.0"));
+        assert!(!service(Some("0.10.0")).is_deployed_by_older_version("0.9.0"));
+    }
+
+    #[test]
+    fn should_prefer_service_url_over_base_url() {
+        let service = ServiceBuilder::new()
+            .id("some-random-id".to_string())
+            .app_name("master".to_string())
+            .config(sc!("nginx", "nginx"))
+            .base_url(Url::parse("http://example.com").unwrap())
+            .service_url(Url::parse("http://nginx.master.example.com").unwrap())
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            service.service_url(),
+            Some(Url::parse("http://nginx.master.example.com/").unwrap())
+        );
+    }
+
+    #[test]
+    fn should_serialize_external_url_without_web_host_meta() {
+        let service = ServiceBuilder::new()
+            .id("some-random-id".to_string())
+            .app_name("master".to_string())
+            .config(sc!("nginx", "nginx"))
+            .base_url(Url::parse("http://example.com/prevant/").unwrap())
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&service).unwrap();
+
+        assert_eq!(json["externalUrl"], "http://example.com/master/nginx/");
+        assert_eq!(json.get("url"), None);
+    }
+
+    #[test]
+    fn should_serialize_replicas() {
+        let service = ServiceBuilder::new()
+            .id("some-random-id".to_string())
+            .app_name("master".to_string())
+            .config(sc!("nginx", "nginx"))
+            .expected_replicas(3)
+            .actual_replicas(2)
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&service).unwrap();
+
+        assert_eq!(json["expectedReplicas"], 3);
+        assert_eq!(json["actualReplicas"], 2);
+    }
+
+    #[test]
+    fn should_serialize_image_and_tag() {
+        let service = ServiceBuilder::new()
+            .id("some-random-id".to_string())
+            .app_name("master".to_string())
+            .config(sc!("nginx", "registry.example.com/myorg/myapp:main"))
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&service).unwrap();
+
+        assert_eq!(json["image"], "registry.example.com/myorg/myapp");
+        assert_eq!(json["tag"], "main");
+    }
+
     #[test]
     fn should_build_service_with_web_host_meta() {
         let meta = WebHostMeta::empty();
@@ -404,4 +731,79 @@ mod tests {
 
         assert_eq!(err, ServiceBuilderError::MissingServiceConfiguration);
     }
+
+    fn service(id: &str, app_name: &str, service_name: &str) -> Service {
+        ServiceBuilder::new()
+            .id(id.to_string())
+            .app_name(app_name.to_string())
+            .config(sc!(service_name, "nginx"))
+            .started_at(
+                DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+                    .unwrap()
+                    .into(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_diff_services() {
+        let mut before = MultiMap::new();
+        before.insert(AppName::master(), service("a", "master", "nginx"));
+        before.insert(AppName::master(), service("b", "master", "db"));
+        before.insert(
+            AppName::from_str("other").unwrap(),
+            service("c", "other", "nginx"),
+        );
+
+        let mut after = MultiMap::new();
+        after.insert(AppName::master(), service("a", "master", "nginx"));
+        after.insert(AppName::master(), service("d", "master", "db"));
+
+        let diff = diff_services(&before, &after);
+
+        let mut added = MultiMap::new();
+        added.insert(AppName::master(), service("d", "master", "db"));
+        let mut removed = MultiMap::new();
+        removed.insert(AppName::master(), service("b", "master", "db"));
+        removed.insert(
+            AppName::from_str("other").unwrap(),
+            service("c", "other", "nginx"),
+        );
+        let mut unchanged = MultiMap::new();
+        unchanged.insert(AppName::master(), service("a", "master", "nginx"));
+        assert_eq!(
+            diff,
+            ServiceDiff {
+                added,
+                removed,
+                unchanged,
+            }
+        );
+    }
+
+    #[test]
+    fn should_diff_modified_service_as_removed_and_added() {
+        let mut before = MultiMap::new();
+        before.insert(AppName::master(), service("a", "master", "nginx"));
+
+        let restarted = ServiceBuilder::from(service("a", "master", "nginx"))
+            .started_at(Utc::now())
+            .build()
+            .unwrap();
+        let mut after = MultiMap::new();
+        after.insert(AppName::master(), restarted.clone());
+
+        let diff = diff_services(&before, &after);
+
+        assert_eq!(
+            diff.added().get_vec(&AppName::master()),
+            Some(&vec![restarted])
+        );
+        assert_eq!(
+            diff.removed().get_vec(&AppName::master()),
+            Some(&vec![service("a", "master", "nginx")])
+        );
+        assert!(diff.unchanged().is_empty());
+    }
 }
@@ -0,0 +1,1076 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+use super::{
+    Environment, EnvironmentVariable, NamedVolumeMount, PortBinding, PortProtocol, ServiceConfig,
+    ServiceConfigBuilder,
+};
+use crate::infrastructure::is_sensitive_environment_variable;
+use crate::models::Image;
+use bytesize::ByteSize;
+use secstr::SecUtf8;
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+
+static SUPPORTED_TOP_LEVEL_FIELDS: [&str; 5] =
+    ["version", "name", "services", "volumes", "networks"];
+static SUPPORTED_SERVICE_FIELDS: [&str; 8] = [
+    "image",
+    "environment",
+    "labels",
+    "mem_limit",
+    "depends_on",
+    "volumes",
+    "ports",
+    "networks",
+];
+
+/// Converts a [Compose file](https://docs.docker.com/compose/compose-file/) into service
+/// configurations. Only a subset of the Compose specification can be mapped to PREvant's model
+/// and any other field will be reported as an error instead of being ignored. The only exception
+/// are networks which are ignored because PREvant connects the services of an app by itself.
+pub struct ComposeParser;
+
+impl ComposeParser {
+    /// Parses the Compose file and returns the service configurations ordered by their
+    /// `depends_on` declarations, i.e. a service is preceded by the services it depends on. The
+    /// declarations are kept in [`ServiceConfig::depends_on`] so that the services are started
+    /// in this order.
+    pub fn parse(compose_file: &str) -> Result<Vec<ServiceConfig>, ComposeError> {
+        let compose = serde_yaml::from_str::<Mapping>(compose_file).map_err(|err| {
+            ComposeError::InvalidYaml {
+                err: err.to_string(),
+            }
+        })?;
+
+        let mut unsupported_fields = Vec::new();
+        for key in keys(&compose)? {
+            if !SUPPORTED_TOP_LEVEL_FIELDS.contains(&key) {
+                unsupported_fields.push(key.to_string());
+            }
+        }
+
+        let services = match compose.get("services") {
+            Some(Value::Mapping(services)) if !services.is_empty() => services,
+            _ => return Err(ComposeError::MissingServices),
+        };
+
+        let volumes = match compose.get("volumes") {
+            None | Some(Value::Null) => HashSet::new(),
+            Some(Value::Mapping(volumes)) => {
+                for (volume_name, volume) in volumes {
+                    let volume_name = as_string(volume_name)?;
+                    match volume {
+                        Value::Null => {}
+                        Value::Mapping(volume) => {
+                            for key in keys(volume)? {
+                                unsupported_fields.push(format!("volumes.{volume_name}.{key}"));
+                            }
+                        }
+                        _ => {
+                            return Err(ComposeError::InvalidYaml {
+                                err: format!("The volume {volume_name} must be a mapping"),
+                            })
+                        }
+                    }
+                }
+                keys(volumes)?.into_iter().collect::<HashSet<_>>()
+            }
+            Some(_) => {
+                return Err(ComposeError::InvalidYaml {
+                    err: String::from("The volumes must be a mapping"),
+                })
+            }
+        };
+
+        if compose.contains_key("networks") {
+            warn!("Ignoring the networks of the Compose file because PREvant connects the services of an app through its own network.");
+        }
+
+        for (service_name, service) in services {
+            let service_name = as_string(service_name)?;
+            let Value::Mapping(service) = service else {
+                return Err(ComposeError::InvalidField {
+                    service_name,
+                    field: "services",
+                    err: String::from("a service must be a mapping"),
+                });
+            };
+
+            for key in keys(service)? {
+                if !SUPPORTED_SERVICE_FIELDS.contains(&key) {
+                    unsupported_fields.push(format!("services.{service_name}.{key}"));
+                }
+            }
+        }
+
+        if !unsupported_fields.is_empty() {
+            return Err(ComposeError::UnsupportedFields {
+                fields: unsupported_fields,
+            });
+        }
+
+        let mut service_configs = Vec::with_capacity(services.len());
+        for (service_name, service) in services {
+            let service_name = as_string(service_name)?;
+            let service = service
+                .as_mapping()
+                .expect("Services have been validated as mappings");
+            service_configs.push(Self::parse_service(service_name, service, &volumes)?);
+        }
+
+        Self::order_by_dependencies(service_configs)
+    }
+
+    fn parse_service(
+        service_name: String,
+        service: &Mapping,
+        volumes: &HashSet<&str>,
+    ) -> Result<(ServiceConfig, Vec<String>), ComposeError> {
+        let invalid_field = |field: &'static str, err: String| ComposeError::InvalidField {
+            service_name: service_name.clone(),
+            field,
+            err,
+        };
+
+        let image = match service.get("image") {
+            Some(Value::String(image)) => {
+                Image::from_str(image).map_err(|err| invalid_field("image", err.to_string()))?
+            }
+            Some(_) => {
+                return Err(invalid_field(
+                    "image",
+                    String::from("the image must be a string"),
+                ))
+            }
+            None => {
+                return Err(invalid_field(
+                    "image",
+                    String::from("the image is required because PREvant does not build images"),
+                ))
+            }
+        };
+
+        let mut config_builder = ServiceConfigBuilder::new()
+            .service_name(service_name.clone())
+            .image(image);
+        if let Some(environment) = service.get("environment") {
+            let variables = key_value_pairs(environment)
+                .map_err(|err| invalid_field("environment", err))?
+                .into_iter()
+                .map(|(key, value)| EnvironmentVariable::new(key, SecUtf8::from(value)))
+                .collect();
+            config_builder = config_builder.env(Environment::new(variables));
+        }
+        let mut config = config_builder
+            .build()
+            .expect("service name and image are mandatory for a compose service");
+
+        if let Some(labels) = service.get("labels") {
+            let labels = key_value_pairs(labels)
+                .map_err(|err| invalid_field("labels", err))?
+                .into_iter()
+                .collect::<BTreeMap<_, _>>();
+            #[allow(deprecated)]
+            config.set_labels(Some(labels));
+        }
+
+        if let Some(mem_limit) = service.get("mem_limit") {
+            let mem_limit = match mem_limit {
+                Value::String(mem_limit) => mem_limit
+                    .parse::<ByteSize>()
+                    .map_err(|err| invalid_field("mem_limit", err))?,
+                Value::Number(bytes) => match bytes.as_u64() {
+                    Some(bytes) => ByteSize(bytes),
+                    None => {
+                        return Err(invalid_field(
+                            "mem_limit",
+                            format!("{bytes} is not a valid number of bytes"),
+                        ))
+                    }
+                },
+                _ => {
+                    return Err(invalid_field(
+                        "mem_limit",
+                        String::from("the memory limit must be a string or a number"),
+                    ))
+                }
+            };
+            config.set_memory_limit(Some(mem_limit));
+        }
+
+        if let Some(service_volumes) = service.get("volumes") {
+            let Value::Sequence(service_volumes) = service_volumes else {
+                return Err(invalid_field(
+                    "volumes",
+                    String::from("the volumes must be a list"),
+                ));
+            };
+            let named_volumes = service_volumes
+                .iter()
+                .map(|volume| {
+                    named_volume_mount(volume, volumes).map_err(|err| invalid_field("volumes", err))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            config.set_named_volumes(named_volumes);
+        }
+
+        if let Some(ports) = service.get("ports") {
+            let Value::Sequence(ports) = ports else {
+                return Err(invalid_field(
+                    "ports",
+                    String::from("the ports must be a list"),
+                ));
+            };
+            let host_port_bindings = ports
+                .iter()
+                .map(|port| port_binding(port).map_err(|err| invalid_field("ports", err)))
+                .collect::<Result<Vec<_>, _>>()?;
+            config.set_host_port_bindings(host_port_bindings);
+        }
+
+        let dependencies = match service.get("depends_on") {
+            None => Vec::new(),
+            Some(Value::Sequence(dependencies)) => dependencies
+                .iter()
+                .map(|dependency| match dependency {
+                    Value::String(dependency) => Ok(dependency.clone()),
+                    _ => Err(invalid_field(
+                        "depends_on",
+                        String::from("dependencies must be service names"),
+                    )),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(Value::Mapping(dependencies)) => {
+                keys(dependencies)?.into_iter().map(String::from).collect()
+            }
+            Some(_) => {
+                return Err(invalid_field(
+                    "depends_on",
+                    String::from("dependencies must be a list or a mapping"),
+                ))
+            }
+        };
+
+        Ok((config, dependencies))
+    }
+
+    fn order_by_dependencies(
+        mut service_configs: Vec<(ServiceConfig, Vec<String>)>,
+    ) -> Result<Vec<ServiceConfig>, ComposeError> {
+        let service_names = service_configs
+            .iter()
+            .map(|(config, _)| config.service_name().clone())
+            .collect::<HashSet<_>>();
+
+        for (config, dependencies) in &service_configs {
+            if let Some(dependency) = dependencies
+                .iter()
+                .find(|dependency| !service_names.contains(*dependency))
+            {
+                return Err(ComposeError::UnknownDependency {
+                    service_name: config.service_name().clone(),
+                    dependency: dependency.clone(),
+                });
+            }
+        }
+
+        let mut ordered = Vec::with_capacity(service_configs.len());
+        let mut resolved = HashSet::new();
+        while !service_configs.is_empty() {
+            let (ready, pending): (Vec<_>, Vec<_>) =
+                service_configs.into_iter().partition(|(_, dependencies)| {
+                    dependencies
+                        .iter()
+                        .all(|dependency| resolved.contains(dependency))
+                });
+
+            if ready.is_empty() {
+                return Err(ComposeError::CyclicDependencies {
+                    services: pending
+                        .into_iter()
+                        .map(|(config, _)| config.service_name().clone())
+                        .collect(),
+                });
+            }
+
+            for (mut config, dependencies) in ready {
+                resolved.insert(config.service_name().clone());
+                config.set_depends_on(dependencies);
+                ordered.push(config);
+            }
+            service_configs = pending;
+        }
+
+        Ok(ordered)
+    }
+}
+
+/// Converts service configurations into a Compose file that can be started with `docker compose
+/// up`. Settings without counterpart in the Compose specification, e.g. the files of a service,
+/// are left out. The values of environment variables that might contain credentials are replaced
+/// by a placeholder and need to be filled in before starting the services.
+pub struct ComposeWriter;
+
+impl ComposeWriter {
+    /// Writes the service configurations as Compose file. The values of environment variables that
+    /// might contain credentials are not written, instead they are replaced by a `${VARIABLE}`
+    /// placeholder that Compose interpolates from the shell environment or a `.env` file.
+    pub fn write(service_configs: &[ServiceConfig]) -> String {
+        let mut service_configs = service_configs.iter().collect::<Vec<_>>();
+        service_configs.sort_by(|a, b| a.service_name().cmp(b.service_name()));
+        let service_names = service_configs
+            .iter()
+            .map(|config| config.service_name().as_str())
+            .collect::<HashSet<_>>();
+
+        let mut services = Mapping::new();
+        let mut volumes = Mapping::new();
+        for config in service_configs {
+            for named_volume in config.named_volumes() {
+                volumes.insert(
+                    Value::from(named_volume.volume_name()),
+                    Value::Mapping(Mapping::new()),
+                );
+            }
+            services.insert(
+                Value::from(config.service_name().as_str()),
+                Value::Mapping(Self::write_service(config, &service_names)),
+            );
+        }
+
+        let mut compose = Mapping::new();
+        compose.insert(Value::from("services"), Value::Mapping(services));
+        if !volumes.is_empty() {
+            compose.insert(Value::from("volumes"), Value::Mapping(volumes));
+        }
+
+        serde_yaml::to_string(&compose).expect("A YAML mapping is always serializable")
+    }
+
+    fn write_service(config: &ServiceConfig, service_names: &HashSet<&str>) -> Mapping {
+        let mut service = Mapping::new();
+        service.insert(
+            Value::from("image"),
+            Value::from(config.image().to_string()),
+        );
+
+        if let Some(env) = config.env() {
+            let environment = env
+                .iter()
+                .map(|variable| {
+                    let value = if is_sensitive_environment_variable(variable.key()) {
+                        format!("${{{}}}", variable.key())
+                    } else {
+                        variable.value().unsecure().to_string()
+                    };
+                    (Value::from(variable.key().as_str()), Value::from(value))
+                })
+                .collect::<Mapping>();
+            if !environment.is_empty() {
+                service.insert(Value::from("environment"), Value::Mapping(environment));
+            }
+        }
+
+        #[allow(deprecated)]
+        let labels = config.labels();
+        if let Some(labels) = labels.filter(|labels| !labels.is_empty()) {
+            service.insert(
+                Value::from("labels"),
+                Value::Mapping(
+                    labels
+                        .iter()
+                        .map(|(key, value)| {
+                            (Value::from(key.as_str()), Value::from(value.as_str()))
+                        })
+                        .collect(),
+                ),
+            );
+        }
+
+        if let Some(memory_limit) = config.memory_limit() {
+            service.insert(Value::from("mem_limit"), Value::from(memory_limit.as_u64()));
+        }
+
+        // Dependencies on services that are not part of the file would make the file invalid.
+        let depends_on = config
+            .depends_on()
+            .iter()
+            .filter(|dependency| service_names.contains(dependency.as_str()))
+            .map(|dependency| Value::from(dependency.as_str()))
+            .collect::<Vec<_>>();
+        if !depends_on.is_empty() {
+            service.insert(Value::from("depends_on"), Value::Sequence(depends_on));
+        }
+
+        if !config.named_volumes().is_empty() {
+            service.insert(
+                Value::from("volumes"),
+                Value::Sequence(
+                    config
+                        .named_volumes()
+                        .iter()
+                        .map(|named_volume| {
+                            Value::from(format!(
+                                "{}:{}",
+                                named_volume.volume_name(),
+                                named_volume.container_path()
+                            ))
+                        })
+                        .collect(),
+                ),
+            );
+        }
+
+        if !config.host_port_bindings().is_empty() {
+            service.insert(
+                Value::from("ports"),
+                Value::Sequence(
+                    config
+                        .host_port_bindings()
+                        .iter()
+                        .map(|binding| {
+                            let ports = match binding.host_port() {
+                                Some(host_port) => {
+                                    format!("{host_port}:{}", binding.container_port())
+                                }
+                                None => binding.container_port().to_string(),
+                            };
+                            Value::from(format!("{ports}/{}", binding.protocol()))
+                        })
+                        .collect(),
+                ),
+            );
+        }
+
+        service
+    }
+}
+
+fn keys(mapping: &Mapping) -> Result<Vec<&str>, ComposeError> {
+    mapping
+        .keys()
+        .map(|key| {
+            key.as_str().ok_or_else(|| ComposeError::InvalidYaml {
+                err: format!("{key:?} is not a valid key"),
+            })
+        })
+        .collect()
+}
+
+fn as_string(value: &Value) -> Result<String, ComposeError> {
+    value
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| ComposeError::InvalidYaml {
+            err: format!("{value:?} is not a valid key"),
+        })
+}
+
+/// Parses the short notation `VOLUME:CONTAINER_PATH[:MODE]` and the long notation of a service's
+/// volume, ignoring the access mode. Only named volumes that are declared in the top-level
+/// `volumes` are supported because bind mounts refer to the file system of the machine that runs
+/// Compose.
+fn named_volume_mount(
+    volume: &Value,
+    declared_volumes: &HashSet<&str>,
+) -> Result<NamedVolumeMount, String> {
+    let (source, target) = match volume {
+        Value::String(volume) => {
+            let mut parts = volume.split(':');
+            match (parts.next(), parts.next()) {
+                (Some(source), Some(target)) => (source.to_string(), target.to_string()),
+                _ => return Err(format!("{volume} is an anonymous volume")),
+            }
+        }
+        Value::Mapping(volume) => {
+            match volume.get("type").and_then(Value::as_str) {
+                Some("volume") => {}
+                Some(volume_type) => {
+                    return Err(format!("volumes of type {volume_type} are not supported"))
+                }
+                None => return Err(String::from("the volume does not define its type")),
+            }
+            match (
+                volume.get("source").and_then(Value::as_str),
+                volume.get("target").and_then(Value::as_str),
+            ) {
+                (Some(source), Some(target)) => (source.to_string(), target.to_string()),
+                _ => return Err(String::from("the volume requires a source and a target")),
+            }
+        }
+        _ => return Err(format!("{volume:?} is not a valid volume")),
+    };
+
+    if !declared_volumes.contains(source.as_str()) {
+        return Err(format!(
+            "{source} is not declared as volume, bind mounts are not supported"
+        ));
+    }
+
+    Ok(NamedVolumeMount::new(source, target))
+}
+
+/// Parses the short notation `[HOST_PORT:]CONTAINER_PORT[/PROTOCOL]` and the long notation of a
+/// service's port. Port ranges and host IPs are not supported.
+fn port_binding(port: &Value) -> Result<PortBinding, String> {
+    let parse_port = |port: &str| {
+        port.parse::<u16>()
+            .map_err(|_| format!("{port} is not a valid port"))
+    };
+    let parse_protocol = |protocol: &str| match protocol {
+        "tcp" => Ok(PortProtocol::Tcp),
+        "udp" => Ok(PortProtocol::Udp),
+        _ => Err(format!("{protocol} is not a supported protocol")),
+    };
+
+    match port {
+        Value::Number(container_port) => Ok(PortBinding::new(
+            parse_port(&container_port.to_string())?,
+            None,
+            PortProtocol::Tcp,
+        )),
+        Value::String(port) => {
+            let (ports, protocol) = match port.split_once('/') {
+                Some((ports, protocol)) => (ports, parse_protocol(protocol)?),
+                None => (port.as_str(), PortProtocol::Tcp),
+            };
+            match ports.split(':').collect::<Vec<_>>().as_slice() {
+                [container_port] => Ok(PortBinding::new(
+                    parse_port(container_port)?,
+                    None,
+                    protocol,
+                )),
+                [host_port, container_port] => Ok(PortBinding::new(
+                    parse_port(container_port)?,
+                    Some(parse_port(host_port)?),
+                    protocol,
+                )),
+                _ => Err(format!("{port} binds a host IP, which is not supported")),
+            }
+        }
+        Value::Mapping(port) => {
+            let as_port = |value: &Value| match value {
+                Value::Number(port) => parse_port(&port.to_string()),
+                Value::String(port) => parse_port(port),
+                _ => Err(format!("{value:?} is not a valid port")),
+            };
+            let container_port = match port.get("target") {
+                Some(target) => as_port(target)?,
+                None => return Err(String::from("the port requires a target")),
+            };
+            let host_port = port.get("published").map(as_port).transpose()?;
+            let protocol = match port.get("protocol").and_then(Value::as_str) {
+                Some(protocol) => parse_protocol(protocol)?,
+                None => PortProtocol::Tcp,
+            };
+            Ok(PortBinding::new(container_port, host_port, protocol))
+        }
+        _ => Err(format!("{port:?} is not a valid port")),
+    }
+}
+
+/// Parses the two notations Compose supports for environment variables and labels: a list of
+/// `KEY=VALUE` strings or a mapping.
+fn key_value_pairs(value: &Value) -> Result<Vec<(String, String)>, String> {
+    match value {
+        Value::Sequence(entries) => entries
+            .iter()
+            .map(|entry| {
+                let entry = entry
+                    .as_str()
+                    .ok_or_else(|| format!("{entry:?} is not a KEY=VALUE string"))?;
+                entry
+                    .split_once('=')
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .ok_or_else(|| format!("{entry} does not provide a value"))
+            })
+            .collect(),
+        Value::Mapping(entries) => entries
+            .iter()
+            .map(|(key, value)| {
+                let key = key
+                    .as_str()
+                    .ok_or_else(|| format!("{key:?} is not a valid key"))?;
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    Value::Number(value) => value.to_string(),
+                    Value::Bool(value) => value.to_string(),
+                    _ => return Err(format!("{key} does not provide a scalar value")),
+                };
+                Ok((key.to_string(), value))
+            })
+            .collect(),
+        _ => Err(String::from("must be a list or a mapping")),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ComposeError {
+    #[error("Invalid Compose file: {err}")]
+    InvalidYaml { err: String },
+    #[error("The Compose file does not define any services.")]
+    MissingServices,
+    #[error("The Compose file contains unsupported fields: {}", .fields.join(", "))]
+    UnsupportedFields { fields: Vec<String> },
+    #[error("Invalid field {field} of service {service_name}: {err}")]
+    InvalidField {
+        service_name: String,
+        field: &'static str,
+        err: String,
+    },
+    #[error("Service {service_name} depends on unknown service {dependency}.")]
+    UnknownDependency {
+        service_name: String,
+        dependency: String,
+    },
+    #[error("The services {} depend on each other.", .services.join(", "))]
+    CyclicDependencies { services: Vec<String> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_services() {
+        let configs = ComposeParser::parse(
+            r#"
+            version: '3'
+            services:
+              db:
+                image: mariadb:10.3
+                environment:
+                  MYSQL_USER: admin
+                  MYSQL_PORT: 3306
+                mem_limit: 512m
+              wordpress:
+                image: wordpress
+                environment:
+                  - WORDPRESS_DB_HOST=db
+                labels:
+                  traefik.frontend.priority: '1000'
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(configs.len(), 2);
+
+        let db = &configs[0];
+        assert_eq!(db.service_name(), "db");
+        assert_eq!(db.image().to_string(), "docker.io/library/mariadb:10.3");
+        assert_eq!(
+            db.env(),
+            Some(&Environment::new(vec![
+                EnvironmentVariable::new("MYSQL_USER".to_string(), SecUtf8::from("admin")),
+                EnvironmentVariable::new("MYSQL_PORT".to_string(), SecUtf8::from("3306"))
+            ]))
+        );
+        assert_eq!(db.memory_limit(), Some(ByteSize::mb(512)));
+
+        let wordpress = &configs[1];
+        assert_eq!(wordpress.service_name(), "wordpress");
+        assert_eq!(
+            wordpress.env(),
+            Some(&Environment::new(vec![EnvironmentVariable::new(
+                "WORDPRESS_DB_HOST".to_string(),
+                SecUtf8::from("db")
+            )]))
+        );
+        #[allow(deprecated)]
+        let labels = wordpress.labels();
+        assert_eq!(
+            labels.and_then(|labels| labels.get("traefik.frontend.priority")),
+            Some(&String::from("1000"))
+        );
+    }
+
+    #[test]
+    fn should_order_services_by_dependencies() {
+        let configs = ComposeParser::parse(
+            r#"
+            services:
+              frontend:
+                image: nginx
+                depends_on:
+                  - backend
+              backend:
+                image: openjdk
+                depends_on:
+                  db:
+                    condition: service_started
+              db:
+                image: postgres
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            configs
+                .iter()
+                .map(|config| config.service_name().as_str())
+                .collect::<Vec<_>>(),
+            vec!["db", "backend", "frontend"]
+        );
+    }
+
+    #[test]
+    fn should_report_all_unsupported_fields() {
+        let err = ComposeParser::parse(
+            r#"
+            services:
+              db:
+                image: postgres
+                build: .
+                healthcheck:
+                  test: pg_isready
+            volumes:
+              db-data:
+                driver: local
+            secrets:
+              db-password:
+                file: ./password.txt
+            "#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "The Compose file contains unsupported fields: secrets, volumes.db-data.driver, services.db.build, services.db.healthcheck"
+        );
+    }
+
+    #[test]
+    fn should_parse_named_volumes_and_ignore_networks() {
+        let configs = ComposeParser::parse(
+            r#"
+            services:
+              db:
+                image: postgres
+                volumes:
+                  - db-data:/var/lib/postgresql/data
+                  - type: volume
+                    source: db-backup
+                    target: /backup
+                networks:
+                  - backend
+            volumes:
+              db-data:
+              db-backup: {}
+            networks:
+              backend:
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            configs[0].named_volumes(),
+            &[
+                NamedVolumeMount::new(
+                    String::from("db-data"),
+                    String::from("/var/lib/postgresql/data")
+                ),
+                NamedVolumeMount::new(String::from("db-backup"), String::from("/backup")),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_not_parse_bind_mounts() {
+        let err = ComposeParser::parse(
+            r#"
+            services:
+              db:
+                image: postgres
+                volumes:
+                  - ./data:/var/lib/postgresql/data
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ComposeError::InvalidField {
+                field: "volumes",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn should_not_parse_services_without_image() {
+        let err = ComposeParser::parse(
+            r#"
+            services:
+              app:
+                environment:
+                  - JAVA_OPTS=-Xmx1g
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ComposeError::InvalidField { field: "image", .. }
+        ));
+    }
+
+    #[test]
+    fn should_write_parsable_compose_file() {
+        let configs = ComposeParser::parse(
+            r#"
+            services:
+              db:
+                image: mariadb:10.3
+                environment:
+                  MYSQL_USER: admin
+                mem_limit: 512m
+              wordpress:
+                image: wordpress
+                labels:
+                  com.example.team: blog
+            "#,
+        )
+        .unwrap();
+
+        let compose_file = ComposeWriter::write(&configs);
+        let parsed_configs = ComposeParser::parse(&compose_file).unwrap();
+
+        assert_eq!(ComposeWriter::write(&parsed_configs), compose_file);
+        assert_eq!(parsed_configs.len(), 2);
+        assert_eq!(parsed_configs[0].memory_limit(), Some(ByteSize::mb(512)));
+        assert_eq!(
+            parsed_configs[0]
+                .env()
+                .unwrap()
+                .variable("MYSQL_USER")
+                .unwrap()
+                .value(),
+            &SecUtf8::from("admin")
+        );
+    }
+
+    #[test]
+    fn should_write_volumes_and_ports() {
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("db"))
+            .image(Image::from_str("mariadb:10.3").unwrap())
+            .build()
+            .unwrap();
+        config.set_named_volumes(vec![NamedVolumeMount::new(
+            String::from("db-data"),
+            String::from("/var/lib/mysql"),
+        )]);
+        config.set_host_port_bindings(vec![PortBinding::new(3306, Some(13306), PortProtocol::Tcp)]);
+
+        let compose = serde_yaml::from_str::<Value>(&ComposeWriter::write(&[config])).unwrap();
+
+        assert_eq!(
+            compose,
+            serde_yaml::from_str::<Value>(
+                r#"
+                services:
+                  db:
+                    image: docker.io/library/mariadb:10.3
+                    volumes:
+                      - db-data:/var/lib/mysql
+                    ports:
+                      - 13306:3306/tcp
+                volumes:
+                  db-data: {}
+                "#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_parse_written_volumes_and_ports() {
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("db"))
+            .image(Image::from_str("mariadb:10.3").unwrap())
+            .build()
+            .unwrap();
+        config.set_named_volumes(vec![NamedVolumeMount::new(
+            String::from("db-data"),
+            String::from("/var/lib/mysql"),
+        )]);
+        config.set_host_port_bindings(vec![
+            PortBinding::new(3306, Some(13306), PortProtocol::Tcp),
+            PortBinding::new(5353, None, PortProtocol::Udp),
+        ]);
+
+        let parsed_configs = ComposeParser::parse(&ComposeWriter::write(&[config])).unwrap();
+
+        assert_eq!(
+            parsed_configs[0].named_volumes(),
+            &[NamedVolumeMount::new(
+                String::from("db-data"),
+                String::from("/var/lib/mysql"),
+            )]
+        );
+        assert_eq!(
+            parsed_configs[0].host_port_bindings(),
+            &[
+                PortBinding::new(3306, Some(13306), PortProtocol::Tcp),
+                PortBinding::new(5353, None, PortProtocol::Udp),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_long_port_notation() {
+        let configs = ComposeParser::parse(
+            r#"
+            services:
+              web:
+                image: nginx
+                ports:
+                  - 80
+                  - target: 443
+                    published: "8443"
+                    protocol: tcp
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            configs[0].host_port_bindings(),
+            &[
+                PortBinding::new(80, None, PortProtocol::Tcp),
+                PortBinding::new(443, Some(8443), PortProtocol::Tcp),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_not_parse_ports_with_host_ip() {
+        let err = ComposeParser::parse(
+            r#"
+            services:
+              web:
+                image: nginx
+                ports:
+                  - 127.0.0.1:8080:80
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ComposeError::InvalidField { field: "ports", .. }
+        ));
+    }
+
+    #[test]
+    fn should_write_dependencies() {
+        let configs = ComposeParser::parse(
+            r#"
+            services:
+              db:
+                image: mariadb:10.3
+              wordpress:
+                image: wordpress
+                depends_on: [db]
+            "#,
+        )
+        .unwrap();
+
+        let compose_file = ComposeWriter::write(&configs);
+        let parsed_configs = ComposeParser::parse(&compose_file).unwrap();
+
+        assert_eq!(parsed_configs[1].service_name(), "wordpress");
+        assert_eq!(parsed_configs[1].depends_on(), &[String::from("db")]);
+
+        let compose_file = ComposeWriter::write(&configs[1..]);
+        assert!(!compose_file.contains("depends_on"));
+        assert!(ComposeParser::parse(&compose_file).is_ok());
+    }
+
+    #[test]
+    fn should_write_placeholders_for_sensitive_environment_variables() {
+        let configs = ComposeParser::parse(
+            r#"
+            services:
+              db:
+                image: mariadb:10.3
+                environment:
+                  MYSQL_USER: admin
+                  MYSQL_PASSWORD: s3cr3t
+            "#,
+        )
+        .unwrap();
+
+        let compose_file = ComposeWriter::write(&configs);
+
+        assert!(!compose_file.contains("s3cr3t"));
+        let env = ComposeParser::parse(&compose_file).unwrap()[0]
+            .env()
+            .cloned()
+            .unwrap();
+        assert_eq!(
+            env.variable("MYSQL_USER").unwrap().value(),
+            &SecUtf8::from("admin")
+        );
+        assert_eq!(
+            env.variable("MYSQL_PASSWORD").unwrap().value(),
+            &SecUtf8::from("${MYSQL_PASSWORD}")
+        );
+    }
+
+    #[test]
+    fn should_not_parse_cyclic_dependencies() {
+        let err = ComposeParser::parse(
+            r#"
+            services:
+              a:
+                image: nginx
+                depends_on: [b]
+              b:
+                image: nginx
+                depends_on: [a]
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, ComposeError::CyclicDependencies { .. }));
+    }
+
+    #[test]
+    fn should_not_parse_unknown_dependencies() {
+        let err = ComposeParser::parse(
+            r#"
+            services:
+              a:
+                image: nginx
+                depends_on: [b]
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, ComposeError::UnknownDependency { .. }));
+    }
+}
@@ -28,6 +28,7 @@ use secstr::SecUtf8;
 use serde::de::Error as SerdeError;
 use serde::{Deserialize, Deserializer};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
 
@@ -63,9 +64,46 @@ impl Environment {
         None
     }
 
+    /// Returns the variables that are marked for replication or `None` if there are none.
+    pub fn replicated(&self) -> Option<Self> {
+        let values = self
+            .values
+            .iter()
+            .filter(|variable| variable.replicate())
+            .cloned()
+            .collect::<Vec<_>>();
+        (!values.is_empty()).then_some(Environment { values })
+    }
+
     pub(super) fn push(&mut self, variable: EnvironmentVariable) {
         self.values.push(variable);
     }
+
+    /// Applies the `updates` to the variables: existing variables keep their flags but take the
+    /// new value, unknown keys are added, and keys without value are removed.
+    pub fn update(self, updates: &HashMap<String, Option<String>>) -> Self {
+        let mut values = self
+            .values
+            .into_iter()
+            .filter_map(|variable| match updates.get(variable.key()) {
+                None => Some(variable),
+                Some(None) => None,
+                Some(Some(value)) => Some(variable.with_value(SecUtf8::from(value.as_str()))),
+            })
+            .collect::<Vec<_>>();
+
+        let mut new_keys = updates
+            .iter()
+            .filter_map(|(key, value)| Some((key, value.as_ref()?)))
+            .filter(|(key, _)| !values.iter().any(|variable| variable.key() == *key))
+            .collect::<Vec<_>>();
+        new_keys.sort();
+        values.extend(new_keys.into_iter().map(|(key, value)| {
+            EnvironmentVariable::new(key.clone(), SecUtf8::from(value.as_str()))
+        }));
+
+        Environment { values }
+    }
 }
 
 impl<'de> Deserialize<'de> for Environment {
@@ -271,6 +309,32 @@ mod tests {
     use super::*;
     use serde_json::from_value;
 
+    #[test]
+    fn should_update_variables() {
+        let env = Environment::new(vec![
+            EnvironmentVariable::with_replicated(String::from("FEATURE"), SecUtf8::from("off")),
+            EnvironmentVariable::new(String::from("DEBUG"), SecUtf8::from("true")),
+            EnvironmentVariable::new(String::from("USER"), SecUtf8::from("admin")),
+        ]);
+
+        let env = env.update(&HashMap::from([
+            (String::from("FEATURE"), Some(String::from("on"))),
+            (String::from("DEBUG"), None),
+            (String::from("LOG_LEVEL"), Some(String::from("info"))),
+            (String::from("UNKNOWN"), None),
+        ]));
+
+        assert_eq!(
+            env.values,
+            vec![
+                EnvironmentVariable::with_replicated(String::from("FEATURE"), SecUtf8::from("on")),
+                EnvironmentVariable::new(String::from("USER"), SecUtf8::from("admin")),
+                EnvironmentVariable::new(String::from("LOG_LEVEL"), SecUtf8::from("info")),
+            ]
+        );
+        assert!(env.variable("FEATURE").unwrap().replicate());
+    }
+
     #[test]
     fn should_parse_env_from_kv_string() {
         let e = from_value::<Environment>(serde_json::json!(["MYSQL_USER=admin"]))
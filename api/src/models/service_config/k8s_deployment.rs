@@ -0,0 +1,392 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2024 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+use super::{Environment, EnvironmentVariable, ServiceConfig, ServiceConfigBuilder, StartupProbe};
+use crate::models::Image;
+use bytesize::ByteSize;
+use k8s_openapi::api::apps::v1::Deployment;
+use k8s_openapi::api::core::v1::{Container, Probe};
+use secstr::SecUtf8;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+impl ServiceConfig {
+    /// Converts the first container of a Kubernetes `Deployment` manifest into a service
+    /// configuration so that existing manifests can be deployed to Docker backed environments.
+    /// The image, the environment, the memory limit, the working directory and an HTTP liveness
+    /// probe (as startup probe) are taken over. Anything else that cannot be mapped is reported
+    /// as warning instead of failing the conversion.
+    pub fn from_k8s_deployment(
+        yaml: &str,
+    ) -> Result<(ServiceConfig, Vec<K8sConversionWarning>), K8sConversionError> {
+        let deployment = serde_yaml::from_str::<Deployment>(yaml).map_err(|err| {
+            K8sConversionError::InvalidYaml {
+                err: err.to_string(),
+            }
+        })?;
+
+        let mut warnings = Vec::new();
+
+        let spec = deployment
+            .spec
+            .ok_or(K8sConversionError::MissingContainer)?;
+        if let Some(replicas) = spec.replicas.filter(|replicas| *replicas > 1) {
+            warnings.push(K8sConversionWarning::UnsupportedReplicas { replicas });
+        }
+
+        let pod_spec = spec
+            .template
+            .spec
+            .ok_or(K8sConversionError::MissingContainer)?;
+        for (field, unsupported) in [
+            (
+                "initContainers",
+                pod_spec.init_containers.map_or(false, |c| !c.is_empty()),
+            ),
+            ("volumes", pod_spec.volumes.map_or(false, |v| !v.is_empty())),
+        ] {
+            if unsupported {
+                warnings.push(K8sConversionWarning::UnsupportedField {
+                    field: format!("spec.template.spec.{field}"),
+                });
+            }
+        }
+
+        let mut containers = pod_spec.containers.into_iter();
+        let container = containers
+            .next()
+            .ok_or(K8sConversionError::MissingContainer)?;
+        warnings.extend(
+            containers.map(|container| K8sConversionWarning::IgnoredContainer {
+                container_name: container.name,
+            }),
+        );
+
+        let service_name = deployment
+            .metadata
+            .name
+            .unwrap_or_else(|| container.name.clone());
+
+        let config = Self::from_k8s_container(service_name, container, &mut warnings)?;
+        Ok((config, warnings))
+    }
+
+    fn from_k8s_container(
+        service_name: String,
+        container: Container,
+        warnings: &mut Vec<K8sConversionWarning>,
+    ) -> Result<ServiceConfig, K8sConversionError> {
+        let invalid_field = |field: &'static str, err: String| K8sConversionError::InvalidField {
+            field: format!("spec.template.spec.containers[0].{field}"),
+            err,
+        };
+        let mut unsupported_field = |field: &str| {
+            warnings.push(K8sConversionWarning::UnsupportedField {
+                field: format!("spec.template.spec.containers[0].{field}"),
+            })
+        };
+
+        let image = container
+            .image
+            .ok_or_else(|| invalid_field("image", String::from("the image is required")))?;
+        let image =
+            Image::from_str(&image).map_err(|err| invalid_field("image", err.to_string()))?;
+
+        let mut config_builder = ServiceConfigBuilder::new()
+            .service_name(service_name)
+            .image(image);
+        if let Some(env) = container.env.filter(|env| !env.is_empty()) {
+            let mut variables = Vec::with_capacity(env.len());
+            for env_var in env {
+                match (env_var.value, env_var.value_from) {
+                    (_, Some(_)) => unsupported_field(&format!("env.{}.valueFrom", env_var.name)),
+                    (value, None) => variables.push(EnvironmentVariable::new(
+                        env_var.name,
+                        SecUtf8::from(value.unwrap_or_default()),
+                    )),
+                }
+            }
+            config_builder = config_builder.env(Environment::new(variables));
+        }
+        let mut config = config_builder
+            .build()
+            .expect("service name and image are mandatory for a Kubernetes container");
+
+        if let Some(resources) = container.resources {
+            for (resource, quantity) in resources.limits.unwrap_or_default() {
+                if resource == "memory" {
+                    let memory_limit = quantity
+                        .0
+                        .parse::<ByteSize>()
+                        .map_err(|err| invalid_field("resources.limits.memory", err))?;
+                    config.set_memory_limit(Some(memory_limit));
+                } else {
+                    unsupported_field(&format!("resources.limits.{resource}"));
+                }
+            }
+            if resources.requests.map_or(false, |r| !r.is_empty()) {
+                unsupported_field("resources.requests");
+            }
+        }
+
+        if let Some(liveness_probe) = container.liveness_probe {
+            match Self::startup_probe_from_k8s(liveness_probe) {
+                Some(startup_probe) => config.set_startup_probe(Some(startup_probe)),
+                None => unsupported_field("livenessProbe"),
+            }
+        }
+
+        config.set_working_dir(container.working_dir);
+
+        for (field, unsupported) in [
+            ("command", container.command.is_some()),
+            ("args", container.args.is_some()),
+            ("envFrom", container.env_from.is_some()),
+            ("ports", container.ports.is_some()),
+            ("volumeMounts", container.volume_mounts.is_some()),
+            ("readinessProbe", container.readiness_probe.is_some()),
+            ("startupProbe", container.startup_probe.is_some()),
+        ] {
+            if unsupported {
+                unsupported_field(field);
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Only HTTP probes can be mapped because PREvant's startup probes are HTTP GET requests.
+    fn startup_probe_from_k8s(probe: Probe) -> Option<StartupProbe> {
+        let http_get = probe.http_get?;
+        let to_u64 = |value: Option<i32>, default: u64| {
+            value
+                .and_then(|value| u64::try_from(value).ok())
+                .unwrap_or(default)
+        };
+
+        Some(StartupProbe::new(
+            http_get.path.unwrap_or_else(|| String::from("/")),
+            to_u64(probe.timeout_seconds, StartupProbe::default_timeout_secs()),
+            to_u64(probe.period_seconds, StartupProbe::default_interval_secs()),
+            probe
+                .failure_threshold
+                .and_then(|threshold| u8::try_from(threshold).ok())
+                .unwrap_or_else(StartupProbe::default_failure_threshold),
+        ))
+    }
+}
+
+/// A part of a Kubernetes manifest that has been left out while converting it into a service
+/// configuration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum K8sConversionWarning {
+    UnsupportedField { field: String },
+    IgnoredContainer { container_name: String },
+    UnsupportedReplicas { replicas: i32 },
+}
+
+impl Display for K8sConversionWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            K8sConversionWarning::UnsupportedField { field } => {
+                write!(f, "The field {field} is not supported and has been ignored.")
+            }
+            K8sConversionWarning::IgnoredContainer { container_name } => write!(
+                f,
+                "Only the first container is deployed, the container {container_name} has been ignored."
+            ),
+            K8sConversionWarning::UnsupportedReplicas { replicas } => write!(
+                f,
+                "A single container is deployed instead of {replicas} replicas."
+            ),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum K8sConversionError {
+    #[error("Invalid Kubernetes Deployment: {err}")]
+    InvalidYaml { err: String },
+    #[error("The Kubernetes Deployment does not define any container.")]
+    MissingContainer,
+    #[error("Invalid field {field}: {err}")]
+    InvalidField { field: String, err: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_deployment() {
+        let (config, warnings) = ServiceConfig::from_k8s_deployment(
+            r#"
+            apiVersion: apps/v1
+            kind: Deployment
+            metadata:
+              name: orders
+            spec:
+              replicas: 1
+              selector:
+                matchLabels:
+                  app: orders
+              template:
+                metadata:
+                  labels:
+                    app: orders
+                spec:
+                  containers:
+                    - name: orders
+                      image: ghcr.io/myorg/orders:1.0
+                      workingDir: /app
+                      env:
+                        - name: LOG_LEVEL
+                          value: debug
+                      resources:
+                        limits:
+                          memory: 512Mi
+                      livenessProbe:
+                        httpGet:
+                          path: /health
+                          port: 8080
+                        periodSeconds: 5
+            "#,
+        )
+        .unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(config.service_name(), "orders");
+        assert_eq!(
+            config.image(),
+            &Image::from_str("ghcr.io/myorg/orders:1.0").unwrap()
+        );
+        assert_eq!(
+            config.env(),
+            Some(&Environment::new(vec![EnvironmentVariable::new(
+                String::from("LOG_LEVEL"),
+                SecUtf8::from("debug")
+            )]))
+        );
+        assert_eq!(config.memory_limit(), Some(ByteSize::mib(512)));
+        assert_eq!(config.working_dir(), Some("/app"));
+        assert_eq!(
+            config.startup_probe(),
+            Some(&StartupProbe::new(String::from("/health"), 1, 5, 3))
+        );
+    }
+
+    #[test]
+    fn should_warn_about_unsupported_fields() {
+        let (config, warnings) = ServiceConfig::from_k8s_deployment(
+            r#"
+            apiVersion: apps/v1
+            kind: Deployment
+            metadata:
+              name: orders
+            spec:
+              replicas: 3
+              selector:
+                matchLabels:
+                  app: orders
+              template:
+                spec:
+                  containers:
+                    - name: orders
+                      image: ghcr.io/myorg/orders:1.0
+                      command: [ "/bin/orders" ]
+                      resources:
+                        limits:
+                          cpu: 500m
+                      livenessProbe:
+                        tcpSocket:
+                          port: 8080
+                    - name: sidecar
+                      image: envoyproxy/envoy
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.memory_limit(), None);
+        assert_eq!(config.startup_probe(), None);
+        assert_eq!(
+            warnings,
+            vec![
+                K8sConversionWarning::UnsupportedReplicas { replicas: 3 },
+                K8sConversionWarning::IgnoredContainer {
+                    container_name: String::from("sidecar")
+                },
+                K8sConversionWarning::UnsupportedField {
+                    field: String::from("spec.template.spec.containers[0].resources.limits.cpu")
+                },
+                K8sConversionWarning::UnsupportedField {
+                    field: String::from("spec.template.spec.containers[0].livenessProbe")
+                },
+                K8sConversionWarning::UnsupportedField {
+                    field: String::from("spec.template.spec.containers[0].command")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_not_convert_deployment_without_image() {
+        let err = ServiceConfig::from_k8s_deployment(
+            r#"
+            apiVersion: apps/v1
+            kind: Deployment
+            metadata:
+              name: orders
+            spec:
+              selector: {}
+              template:
+                spec:
+                  containers:
+                    - name: orders
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            K8sConversionError::InvalidField { field, .. }
+                if field == "spec.template.spec.containers[0].image"
+        ));
+    }
+
+    #[test]
+    fn should_not_convert_other_kinds() {
+        let err = ServiceConfig::from_k8s_deployment(
+            r#"
+            apiVersion: v1
+            kind: Service
+            metadata:
+              name: orders
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, K8sConversionError::InvalidYaml { .. }));
+    }
+}
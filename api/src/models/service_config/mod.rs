@@ -23,16 +23,22 @@
  * THE SOFTWARE.
  * =========================LICENSE_END==================================
  */
-use crate::config::Routing;
+use crate::config::{PullPolicy, Routing};
 use crate::models::service::ContainerType;
 use crate::models::Image;
+use bytesize::ByteSize;
+pub use compose::{ComposeError, ComposeParser, ComposeWriter};
 pub use environment::{Environment, EnvironmentVariable};
+pub use k8s_deployment::{K8sConversionError, K8sConversionWarning};
 use secstr::SecUtf8;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::path::PathBuf;
 
+mod compose;
 mod environment;
+mod k8s_deployment;
 mod templating;
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
@@ -45,6 +51,62 @@ pub struct ServiceConfig {
     files: Option<BTreeMap<PathBuf, SecUtf8>>,
     #[serde(skip)]
     labels: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    memory_limit: Option<ByteSize>,
+    #[serde(default)]
+    pull_timeout_secs: Option<u64>,
+    #[serde(default)]
+    startup_probe: Option<StartupProbe>,
+    #[serde(default)]
+    pull_policy: Option<PullPolicy>,
+    #[serde(default)]
+    host_port_bindings: Vec<PortBinding>,
+    #[serde(default)]
+    devices: Vec<DeviceMapping>,
+    #[serde(default)]
+    named_volumes: Vec<NamedVolumeMount>,
+    #[serde(default)]
+    shm_size_bytes: Option<u64>,
+    #[serde(default)]
+    memory_swap_bytes: Option<i64>,
+    #[serde(default)]
+    memory_swappiness: Option<u8>,
+    #[serde(default)]
+    pid_limit: Option<u64>,
+    #[serde(default)]
+    working_dir: Option<String>,
+    #[serde(default)]
+    readiness_url: Option<String>,
+    #[serde(default)]
+    stop_signal: Option<String>,
+    #[serde(default)]
+    dns_override: Option<Vec<String>>,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    ipc_mode: Option<IpcMode>,
+    #[serde(default)]
+    env_inherit: Vec<String>,
+    #[serde(default)]
+    env_inherit_strict: bool,
+    #[serde(default)]
+    container_name: Option<String>,
+    #[serde(default)]
+    ulimits: Vec<UlimitConfig>,
+    #[serde(default)]
+    env_from_secrets: Vec<SecretEnvRef>,
+    #[serde(default)]
+    init_containers: Vec<InitContainerConfig>,
+    #[serde(default)]
+    config_map_mounts: Vec<ObjectMount>,
+    #[serde(default)]
+    secret_mounts: Vec<ObjectMount>,
+    #[serde(default)]
+    sticky_sessions: Option<StickySessions>,
+    #[serde(default)]
+    replicas: Option<u32>,
+    #[serde(default)]
+    depends_on: Vec<String>,
     #[serde(skip, default = "ContainerType::default")]
     container_type: ContainerType,
     #[serde(skip)]
@@ -54,13 +116,43 @@ pub struct ServiceConfig {
 }
 
 impl ServiceConfig {
-    pub fn new(service_name: String, image: Image) -> ServiceConfig {
+    /// Creates the configuration with the defaults of all optional parts, see
+    /// [`ServiceConfigBuilder`] which checks that the required parts are provided.
+    fn new(service_name: String, image: Image) -> ServiceConfig {
         ServiceConfig {
             service_name,
             image,
             env: None,
             files: None,
             labels: None,
+            memory_limit: None,
+            pull_timeout_secs: None,
+            startup_probe: None,
+            pull_policy: None,
+            host_port_bindings: Vec::new(),
+            devices: Vec::new(),
+            named_volumes: Vec::new(),
+            shm_size_bytes: None,
+            memory_swap_bytes: None,
+            memory_swappiness: None,
+            pid_limit: None,
+            working_dir: None,
+            readiness_url: None,
+            stop_signal: None,
+            dns_override: None,
+            timezone: None,
+            ipc_mode: None,
+            env_inherit: Vec::new(),
+            env_inherit_strict: false,
+            container_name: None,
+            ulimits: Vec::new(),
+            env_from_secrets: Vec::new(),
+            init_containers: Vec::new(),
+            config_map_mounts: Vec::new(),
+            secret_mounts: Vec::new(),
+            sticky_sessions: None,
+            replicas: None,
+            depends_on: Vec::new(),
             container_type: ContainerType::Instance,
             port: 80,
             routing: None,
@@ -112,6 +204,277 @@ impl ServiceConfig {
         }
     }
 
+    pub fn set_memory_limit(&mut self, memory_limit: Option<ByteSize>) {
+        self.memory_limit = memory_limit;
+    }
+
+    /// Returns the memory limit requested for this service. Note that the limit cannot exceed
+    /// the one configured in [`ContainerConfig`](crate::config::ContainerConfig).
+    pub fn memory_limit(&self) -> Option<ByteSize> {
+        self.memory_limit
+    }
+
+    pub fn set_pull_timeout_secs(&mut self, pull_timeout_secs: Option<u64>) {
+        self.pull_timeout_secs = pull_timeout_secs;
+    }
+
+    pub fn pull_timeout_secs(&self) -> Option<u64> {
+        self.pull_timeout_secs
+    }
+
+    pub fn set_startup_probe(&mut self, startup_probe: Option<StartupProbe>) {
+        self.startup_probe = startup_probe;
+    }
+
+    pub fn startup_probe(&self) -> Option<&StartupProbe> {
+        self.startup_probe.as_ref()
+    }
+
+    pub fn set_pull_policy(&mut self, pull_policy: Option<PullPolicy>) {
+        self.pull_policy = pull_policy;
+    }
+
+    pub fn pull_policy(&self) -> Option<PullPolicy> {
+        self.pull_policy
+    }
+
+    pub fn set_host_port_bindings(&mut self, host_port_bindings: Vec<PortBinding>) {
+        self.host_port_bindings = host_port_bindings;
+    }
+
+    /// Returns the ports that must be published on the host because they cannot be reached
+    /// through the reverse proxy, e.g. non-HTTP protocols.
+    pub fn host_port_bindings(&self) -> &[PortBinding] {
+        &self.host_port_bindings
+    }
+
+    pub fn set_devices(&mut self, devices: Vec<DeviceMapping>) {
+        self.devices = devices;
+    }
+
+    /// Returns the host devices that will be exposed to the service's container. Only devices
+    /// permitted by [`ContainerConfig`](crate::config::ContainerConfig) can be exposed.
+    pub fn devices(&self) -> &[DeviceMapping] {
+        &self.devices
+    }
+
+    pub fn set_named_volumes(&mut self, named_volumes: Vec<NamedVolumeMount>) {
+        self.named_volumes = named_volumes;
+    }
+
+    /// Returns the Docker volumes with fixed names that will be mounted into the service's
+    /// container.
+    pub fn named_volumes(&self) -> &[NamedVolumeMount] {
+        &self.named_volumes
+    }
+
+    pub fn set_shm_size_bytes(&mut self, shm_size_bytes: Option<u64>) {
+        self.shm_size_bytes = shm_size_bytes;
+    }
+
+    /// Returns the size of `/dev/shm` requested for the service's container. If unset, the
+    /// default of the container runtime applies (64 MB for Docker).
+    pub fn shm_size_bytes(&self) -> Option<u64> {
+        self.shm_size_bytes
+    }
+
+    #[cfg(test)]
+    pub fn set_memory_swap_bytes(&mut self, memory_swap_bytes: Option<i64>) {
+        self.memory_swap_bytes = memory_swap_bytes;
+    }
+
+    /// Returns the limit of memory and swap together, similar to `docker run --memory-swap`. The
+    /// value must be at least the memory limit or `-1` for unlimited swap. If unset, the service
+    /// cannot use swap.
+    pub fn memory_swap_bytes(&self) -> Option<i64> {
+        self.memory_swap_bytes
+    }
+
+    #[cfg(test)]
+    pub fn set_memory_swappiness(&mut self, memory_swappiness: Option<u8>) {
+        self.memory_swappiness = memory_swappiness;
+    }
+
+    /// Returns how likely the kernel swaps the anonymous pages of the service's container, from 0
+    /// to 100.
+    pub fn memory_swappiness(&self) -> Option<u8> {
+        self.memory_swappiness
+    }
+
+    pub fn set_pid_limit(&mut self, pid_limit: Option<u64>) {
+        self.pid_limit = pid_limit;
+    }
+
+    /// Returns the maximum number of processes that may run in the service's container.
+    pub fn pid_limit(&self) -> Option<u64> {
+        self.pid_limit
+    }
+
+    pub fn set_working_dir(&mut self, working_dir: Option<String>) {
+        self.working_dir = working_dir;
+    }
+
+    /// Returns the working directory of the service's container which overrides the `WORKDIR`
+    /// instruction of the image.
+    pub fn working_dir(&self) -> Option<&str> {
+        self.working_dir.as_deref()
+    }
+
+    #[cfg(test)]
+    pub fn set_readiness_url(&mut self, readiness_url: Option<String>) {
+        self.readiness_url = readiness_url;
+    }
+
+    /// Returns the path that must respond with `200 OK` before the deployment of the service is
+    /// considered to be finished.
+    pub fn readiness_url(&self) -> Option<&str> {
+        self.readiness_url.as_deref()
+    }
+
+    pub fn set_stop_signal(&mut self, stop_signal: Option<String>) {
+        self.stop_signal = stop_signal;
+    }
+
+    /// Returns the signal, e.g. `SIGQUIT`, that is sent to the main process of the service's
+    /// container when it is stopped. After the signal, the container has Docker's stop timeout
+    /// to shut down before it receives `SIGKILL`.
+    pub fn stop_signal(&self) -> Option<&str> {
+        self.stop_signal.as_deref()
+    }
+
+    #[cfg(test)]
+    pub fn set_dns_override(&mut self, dns_override: Option<Vec<String>>) {
+        self.dns_override = dns_override;
+    }
+
+    /// Returns the DNS servers of the service's container which replace the DNS servers of the
+    /// container configuration.
+    pub fn dns_override(&self) -> Option<&[String]> {
+        self.dns_override.as_deref()
+    }
+
+    #[cfg(test)]
+    pub fn set_timezone(&mut self, timezone: Option<String>) {
+        self.timezone = timezone;
+    }
+
+    /// Returns the IANA name of the timezone of the service's container, e.g. `Europe/Berlin`.
+    pub fn timezone(&self) -> Option<&str> {
+        self.timezone.as_deref()
+    }
+
+    #[cfg(test)]
+    pub fn set_ipc_mode(&mut self, ipc_mode: Option<IpcMode>) {
+        self.ipc_mode = ipc_mode;
+    }
+
+    /// Returns the IPC namespace of the service's container, e.g. to communicate with another
+    /// service of the app through POSIX shared memory.
+    pub fn ipc_mode(&self) -> Option<&IpcMode> {
+        self.ipc_mode.as_ref()
+    }
+
+    pub fn set_env_inherit(&mut self, env_inherit: Vec<String>, strict: bool) {
+        self.env_inherit = env_inherit;
+        self.env_inherit_strict = strict;
+    }
+
+    /// Returns the names of the environment variables that will be copied from the environment
+    /// of PREvant into the service's container.
+    pub fn env_inherit(&self) -> &[String] {
+        &self.env_inherit
+    }
+
+    /// Returns `true` if the deployment must fail when one of the variables of
+    /// [`env_inherit`](Self::env_inherit) is missing in the environment of PREvant. Otherwise,
+    /// missing variables are skipped.
+    pub fn env_inherit_strict(&self) -> bool {
+        self.env_inherit_strict
+    }
+
+    pub fn set_container_name(&mut self, container_name: Option<String>) {
+        self.container_name = container_name;
+    }
+
+    /// Returns the name requested for the service's container. If unset, the container is named
+    /// `{app_name}-{service_name}-{hash}`.
+    pub fn container_name(&self) -> Option<&str> {
+        self.container_name.as_deref()
+    }
+
+    pub fn set_ulimits(&mut self, ulimits: Vec<UlimitConfig>) {
+        self.ulimits = ulimits;
+    }
+
+    pub fn ulimits(&self) -> &[UlimitConfig] {
+        &self.ulimits
+    }
+
+    /// Returns the environment variables whose values are resolved from the configured secret
+    /// provider when the service's container is started.
+    pub fn env_from_secrets(&self) -> &[SecretEnvRef] {
+        &self.env_from_secrets
+    }
+
+    pub fn set_env_from_secrets(&mut self, env_from_secrets: Vec<SecretEnvRef>) {
+        self.env_from_secrets = env_from_secrets;
+    }
+
+    /// Returns the containers that run to completion, one after another, before the service's
+    /// container is started, e.g. for schema migrations.
+    pub fn init_containers(&self) -> &[InitContainerConfig] {
+        &self.init_containers
+    }
+
+    #[cfg(test)]
+    pub fn set_config_map_mounts(&mut self, config_map_mounts: Vec<ObjectMount>) {
+        self.config_map_mounts = config_map_mounts;
+    }
+
+    /// Returns the Kubernetes `ConfigMap` objects that will be mounted into the service's
+    /// container. Only supported by the Kubernetes runtime.
+    pub fn config_map_mounts(&self) -> &[ObjectMount] {
+        &self.config_map_mounts
+    }
+
+    #[cfg(test)]
+    pub fn set_secret_mounts(&mut self, secret_mounts: Vec<ObjectMount>) {
+        self.secret_mounts = secret_mounts;
+    }
+
+    /// Returns the Kubernetes `Secret` objects that will be mounted into the service's container.
+    /// Only supported by the Kubernetes runtime.
+    pub fn secret_mounts(&self) -> &[ObjectMount] {
+        &self.secret_mounts
+    }
+
+    /// Returns the cookie affinity that makes the reverse proxy route the requests of a client to
+    /// the same container while the service runs with multiple containers.
+    pub fn sticky_sessions(&self) -> Option<&StickySessions> {
+        self.sticky_sessions.as_ref()
+    }
+
+    pub fn set_replicas(&mut self, replicas: Option<u32>) {
+        self.replicas = replicas;
+    }
+
+    /// Returns the number of containers that run the service. Without it, a redeployment keeps
+    /// the number of containers of the running service. Only supported by the Docker runtime.
+    pub fn replicas(&self) -> Option<u32> {
+        self.replicas
+    }
+
+    pub fn set_depends_on(&mut self, depends_on: Vec<String>) {
+        self.depends_on = depends_on;
+    }
+
+    /// Returns the names of the services that must be ready before the service is started.
+    /// Services that are not part of the same deployment are already running, thus, they are not
+    /// waited for. Only supported by the Docker runtime.
+    pub fn depends_on(&self) -> &[String] {
+        &self.depends_on
+    }
+
     pub fn add_file(&mut self, path: PathBuf, data: SecUtf8) {
         if let Some(ref mut files) = self.files {
             files.insert(path, data);
@@ -149,22 +512,27 @@ impl ServiceConfig {
         self.routing.as_ref()
     }
 
+    /// Adds the variables of `env` that are not defined in the environment of self.
+    pub fn inherit_env(&mut self, env: &Environment) {
+        self.env = match self.env.take() {
+            Some(mut self_env) => {
+                for env in env.iter() {
+                    if self_env.variable(env.key()).is_some() {
+                        continue;
+                    }
+                    self_env.push(env.clone());
+                }
+                Some(self_env)
+            }
+            None => Some(env.clone()),
+        }
+    }
+
     /// Copy labels, envs and files from other into self.
     /// If something is defined in self and other, self has precedence.
     pub fn merge_with(&mut self, other: &Self) {
         if let Some(env) = &other.env {
-            self.env = match self.env.take() {
-                Some(mut self_env) => {
-                    for env in env.iter() {
-                        if self_env.variable(env.key()).is_some() {
-                            continue;
-                        }
-                        self_env.push(env.clone());
-                    }
-                    Some(self_env)
-                }
-                None => Some(env.clone()),
-            }
+            self.inherit_env(env);
         }
 
         let mut files = other.files.as_ref().cloned().unwrap_or_default();
@@ -175,6 +543,494 @@ impl ServiceConfig {
         labels.extend(self.labels.as_ref().cloned().unwrap_or_default());
         self.labels = Some(labels);
     }
+
+    /// Applies a partial update to `base`: the image is taken from `patch`, environment
+    /// variables and files of `patch` override those of `base`, and all other settings of
+    /// `base` are only overridden if `patch` specifies them.
+    pub fn merge(base: &ServiceConfig, patch: &ServiceConfig) -> ServiceConfig {
+        fn or_base<T: Clone>(patch: &[T], base: &[T]) -> Vec<T> {
+            if patch.is_empty() {
+                base.to_vec()
+            } else {
+                patch.to_vec()
+            }
+        }
+
+        let mut merged = base.clone();
+        merged.image = patch.image.clone();
+
+        merged.env = patch.env.clone();
+        if let Some(base_env) = &base.env {
+            merged.inherit_env(base_env);
+        }
+
+        if let Some(patch_files) = &patch.files {
+            let mut files = base.files.clone().unwrap_or_default();
+            files.extend(patch_files.clone());
+            merged.files = Some(files);
+        }
+
+        merged.memory_limit = patch.memory_limit.or(base.memory_limit);
+        merged.pull_timeout_secs = patch.pull_timeout_secs.or(base.pull_timeout_secs);
+        merged.startup_probe = patch
+            .startup_probe
+            .clone()
+            .or_else(|| base.startup_probe.clone());
+        merged.pull_policy = patch.pull_policy.or(base.pull_policy);
+        merged.host_port_bindings = or_base(&patch.host_port_bindings, &base.host_port_bindings);
+        merged.devices = or_base(&patch.devices, &base.devices);
+        merged.named_volumes = or_base(&patch.named_volumes, &base.named_volumes);
+        merged.shm_size_bytes = patch.shm_size_bytes.or(base.shm_size_bytes);
+        merged.memory_swap_bytes = patch.memory_swap_bytes.or(base.memory_swap_bytes);
+        merged.memory_swappiness = patch.memory_swappiness.or(base.memory_swappiness);
+        merged.pid_limit = patch.pid_limit.or(base.pid_limit);
+        merged.working_dir = patch
+            .working_dir
+            .clone()
+            .or_else(|| base.working_dir.clone());
+        merged.readiness_url = patch
+            .readiness_url
+            .clone()
+            .or_else(|| base.readiness_url.clone());
+        merged.stop_signal = patch
+            .stop_signal
+            .clone()
+            .or_else(|| base.stop_signal.clone());
+        merged.dns_override = patch
+            .dns_override
+            .clone()
+            .or_else(|| base.dns_override.clone());
+        merged.timezone = patch.timezone.clone().or_else(|| base.timezone.clone());
+        merged.ipc_mode = patch.ipc_mode.clone().or_else(|| base.ipc_mode.clone());
+        if !patch.env_inherit.is_empty() {
+            merged.env_inherit = patch.env_inherit.clone();
+            merged.env_inherit_strict = patch.env_inherit_strict;
+        }
+        merged.container_name = patch
+            .container_name
+            .clone()
+            .or_else(|| base.container_name.clone());
+        merged.ulimits = or_base(&patch.ulimits, &base.ulimits);
+        merged.env_from_secrets = or_base(&patch.env_from_secrets, &base.env_from_secrets);
+        merged.init_containers = or_base(&patch.init_containers, &base.init_containers);
+        merged.config_map_mounts = or_base(&patch.config_map_mounts, &base.config_map_mounts);
+        merged.secret_mounts = or_base(&patch.secret_mounts, &base.secret_mounts);
+        merged.sticky_sessions = patch
+            .sticky_sessions
+            .clone()
+            .or_else(|| base.sticky_sessions.clone());
+        merged.replicas = patch.replicas.or(base.replicas);
+        merged.depends_on = or_base(&patch.depends_on, &base.depends_on);
+
+        merged
+    }
+}
+
+/// Creates a [`ServiceConfig`], e.g. from a compose file or from the labels of a container, and
+/// checks that the service name and the image are provided.
+pub struct ServiceConfigBuilder {
+    service_name: Option<String>,
+    image: Option<Image>,
+    env: Option<Environment>,
+    container_type: Option<ContainerType>,
+}
+
+impl ServiceConfigBuilder {
+    pub fn new() -> Self {
+        ServiceConfigBuilder {
+            service_name: None,
+            image: None,
+            env: None,
+            container_type: None,
+        }
+    }
+
+    pub fn build(self) -> Result<ServiceConfig, ServiceConfigBuilderError> {
+        let service_name = self
+            .service_name
+            .ok_or(ServiceConfigBuilderError::MissingServiceName)?;
+        let image = self.image.ok_or(ServiceConfigBuilderError::MissingImage)?;
+
+        let mut config = ServiceConfig::new(service_name, image);
+        config.env = self.env;
+        if let Some(container_type) = self.container_type {
+            config.container_type = container_type;
+        }
+        Ok(config)
+    }
+
+    pub fn service_name(mut self, service_name: String) -> Self {
+        self.service_name = Some(service_name);
+        self
+    }
+
+    pub fn image(mut self, image: Image) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    pub fn env(mut self, env: Environment) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    pub fn container_type(mut self, container_type: ContainerType) -> Self {
+        self.container_type = Some(container_type);
+        self
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ServiceConfigBuilderError {
+    #[error("A service name must be provided.")]
+    MissingServiceName,
+    #[error("An image must be provided.")]
+    MissingImage,
+}
+
+/// Describes how to determine that the process of a service has been started: the service is
+/// considered as started when an HTTP GET request to `url_path` responds with a 2xx status code.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupProbe {
+    url_path: String,
+    #[serde(default = "StartupProbe::default_timeout_secs")]
+    timeout_secs: u64,
+    #[serde(default = "StartupProbe::default_interval_secs")]
+    interval_secs: u64,
+    #[serde(default = "StartupProbe::default_failure_threshold")]
+    failure_threshold: u8,
+}
+
+impl StartupProbe {
+    pub fn new(
+        url_path: String,
+        timeout_secs: u64,
+        interval_secs: u64,
+        failure_threshold: u8,
+    ) -> Self {
+        Self {
+            url_path,
+            timeout_secs,
+            interval_secs,
+            failure_threshold,
+        }
+    }
+
+    fn default_timeout_secs() -> u64 {
+        1
+    }
+
+    fn default_interval_secs() -> u64 {
+        10
+    }
+
+    fn default_failure_threshold() -> u8 {
+        3
+    }
+
+    pub fn url_path(&self) -> &str {
+        &self.url_path
+    }
+
+    /// Number of seconds after which a single request is considered as failed.
+    pub fn timeout_secs(&self) -> u64 {
+        self.timeout_secs
+    }
+
+    /// Number of seconds to wait between two requests.
+    pub fn interval_secs(&self) -> u64 {
+        self.interval_secs
+    }
+
+    /// Number of failed requests after which the service is considered as not started.
+    pub fn failure_threshold(&self) -> u8 {
+        self.failure_threshold
+    }
+}
+
+/// Routes the requests of a client to the same container of the service, based on a cookie that
+/// the reverse proxy sets on the first response. The affinity is best-effort: when the container
+/// restarts, the client is routed to another container.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StickySessions {
+    cookie_name: String,
+}
+
+impl StickySessions {
+    pub fn cookie_name(&self) -> &str {
+        &self.cookie_name
+    }
+}
+
+/// Publishes a port of the service's container on the host. If `host_port` is not provided,
+/// the container runtime assigns an ephemeral port.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PortBinding {
+    container_port: u16,
+    #[serde(default)]
+    host_port: Option<u16>,
+    #[serde(default)]
+    protocol: PortProtocol,
+}
+
+impl PortBinding {
+    pub fn new(container_port: u16, host_port: Option<u16>, protocol: PortProtocol) -> Self {
+        Self {
+            container_port,
+            host_port,
+            protocol,
+        }
+    }
+
+    pub fn container_port(&self) -> u16 {
+        self.container_port
+    }
+
+    pub fn host_port(&self) -> Option<u16> {
+        self.host_port
+    }
+
+    pub fn protocol(&self) -> &PortProtocol {
+        &self.protocol
+    }
+}
+
+/// Maps a device of the host into the service's container, similar to `docker run --device`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceMapping {
+    host_path: String,
+    container_path: String,
+    #[serde(default = "DeviceMapping::default_permissions")]
+    permissions: String,
+}
+
+impl DeviceMapping {
+    pub fn new(host_path: String, container_path: String, permissions: String) -> Self {
+        Self {
+            host_path,
+            container_path,
+            permissions,
+        }
+    }
+
+    fn default_permissions() -> String {
+        String::from("rwm")
+    }
+
+    pub fn host_path(&self) -> &str {
+        &self.host_path
+    }
+
+    pub fn container_path(&self) -> &str {
+        &self.container_path
+    }
+
+    /// The cgroup permissions of the device: a combination of `r` (read), `w` (write), and `m`
+    /// (mknod).
+    pub fn permissions(&self) -> &str {
+        &self.permissions
+    }
+}
+
+/// Mounts a volume with a fixed name into the service's container. Other than the volumes declared
+/// by the image, the volume can outlive the app, e.g. to keep the data of a database when the app
+/// is deleted and deployed again.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedVolumeMount {
+    volume_name: String,
+    container_path: String,
+}
+
+impl NamedVolumeMount {
+    pub fn new(volume_name: String, container_path: String) -> Self {
+        Self {
+            volume_name,
+            container_path,
+        }
+    }
+
+    pub fn volume_name(&self) -> &str {
+        &self.volume_name
+    }
+
+    pub fn container_path(&self) -> &str {
+        &self.container_path
+    }
+}
+
+/// Mounts an existing object, e.g. a Kubernetes `ConfigMap` or `Secret`, into the service's
+/// container. Without `sub_path`, all keys of the object will be mounted as files into the
+/// directory `mount_path`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectMount {
+    name: String,
+    mount_path: String,
+    #[serde(default)]
+    sub_path: Option<String>,
+}
+
+impl ObjectMount {
+    #[cfg(test)]
+    pub fn new(name: String, mount_path: String, sub_path: Option<String>) -> Self {
+        Self {
+            name,
+            mount_path,
+            sub_path,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mount_path(&self) -> &str {
+        &self.mount_path
+    }
+
+    pub fn sub_path(&self) -> Option<&str> {
+        self.sub_path.as_deref()
+    }
+}
+
+/// Overrides a resource limit of the service's container, similar to `docker run --ulimit`, e.g.
+/// `nofile` (open file descriptors) or `nproc` (processes).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct UlimitConfig {
+    name: String,
+    soft: u64,
+    hard: u64,
+}
+
+impl UlimitConfig {
+    pub fn new(name: String, soft: u64, hard: u64) -> Self {
+        Self { name, soft, hard }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn soft(&self) -> u64 {
+        self.soft
+    }
+
+    pub fn hard(&self) -> u64 {
+        self.hard
+    }
+}
+
+/// References a secret of the secret provider whose value is passed to the service's container as
+/// the environment variable `env_key`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretEnvRef {
+    secret_name: String,
+    env_key: String,
+}
+
+impl SecretEnvRef {
+    pub fn secret_name(&self) -> &str {
+        &self.secret_name
+    }
+
+    pub fn env_key(&self) -> &str {
+        &self.env_key
+    }
+}
+
+/// Describes a container that has to exit successfully before the service's container is started,
+/// similar to [init containers](https://kubernetes.io/docs/concepts/workloads/pods/init-containers/)
+/// of Kubernetes.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InitContainerConfig {
+    image: Image,
+    #[serde(default)]
+    command: Vec<String>,
+    /// Environment variables in the format `KEY=value`
+    #[serde(default)]
+    env: Vec<String>,
+}
+
+impl InitContainerConfig {
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    pub fn command(&self) -> &[String] {
+        &self.command
+    }
+
+    pub fn env(&self) -> &[String] {
+        &self.env
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PortProtocol {
+    Tcp,
+    Udp,
+}
+
+impl Default for PortProtocol {
+    fn default() -> Self {
+        Self::Tcp
+    }
+}
+
+impl std::fmt::Display for PortProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PortProtocol::Tcp => write!(f, "tcp"),
+            PortProtocol::Udp => write!(f, "udp"),
+        }
+    }
+}
+
+/// The IPC namespace of a container, written as `private`, `host`, or `container:<service name>`
+/// to join the IPC namespace of another service of the same app.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "String")]
+pub enum IpcMode {
+    Private,
+    Host,
+    Container { service_name: String },
+}
+
+impl TryFrom<String> for IpcMode {
+    type Error = String;
+
+    fn try_from(ipc_mode: String) -> Result<Self, Self::Error> {
+        match ipc_mode.as_str() {
+            "private" => Ok(Self::Private),
+            "host" => Ok(Self::Host),
+            _ => match ipc_mode.strip_prefix("container:") {
+                Some(service_name) if !service_name.is_empty() => Ok(Self::Container {
+                    service_name: service_name.to_string(),
+                }),
+                _ => Err(format!(
+                    "Invalid IPC mode {ipc_mode}, expected private, host, or container:<service name>"
+                )),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for IpcMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            IpcMode::Private => write!(f, "private"),
+            IpcMode::Host => write!(f, "host"),
+            IpcMode::Container { service_name } => write!(f, "container:{service_name}"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -191,8 +1047,11 @@ macro_rules! sc {
 
     ( $name:expr, $img:expr ) => {{
         use std::str::FromStr;
-        use crate::models::ServiceConfig;
-        ServiceConfig::new(String::from($name), crate::models::Image::from_str($img).unwrap())
+        crate::models::ServiceConfigBuilder::new()
+            .service_name(String::from($name))
+            .image(crate::models::Image::from_str($img).unwrap())
+            .build()
+            .unwrap()
     }};
 
     ( $name:expr, labels = ($($l_key:expr => $l_value:expr),*),
@@ -205,8 +1064,11 @@ macro_rules! sc {
         hasher.update($name);
         let img_hash = &format!("sha256:{:x}", hasher.finalize());
 
-        let mut config =
-            ServiceConfig::new(String::from($name), crate::models::Image::from_str(img_hash).unwrap());
+        let mut config = crate::models::ServiceConfigBuilder::new()
+            .service_name(String::from($name))
+            .image(crate::models::Image::from_str(img_hash).unwrap())
+            .build()
+            .unwrap();
 
         let mut _labels = std::collections::BTreeMap::new();
         $( _labels.insert(String::from($l_key), String::from($l_value)); )*
@@ -227,8 +1089,11 @@ macro_rules! sc {
         env = ($($env_key:expr => $env_value:expr),*)
         ) => {{
         use std::str::FromStr;
-        let mut config =
-            ServiceConfig::new(String::from($name), crate::models::Image::from_str($img).unwrap());
+        let mut config = crate::models::ServiceConfigBuilder::new()
+            .service_name(String::from($name))
+            .image(crate::models::Image::from_str($img).unwrap())
+            .build()
+            .unwrap();
 
         let mut _env = Vec::new();
         $( _env.push(crate::models::EnvironmentVariable::new(String::from($env_key), secstr::SecUtf8::from($env_value))); )*
@@ -242,8 +1107,11 @@ macro_rules! sc {
         env = ($($env_key:expr => $env_value:expr),*),
         files = ($($v_key:expr => $v_value:expr),*) ) => {{
         use std::str::FromStr;
-        let mut config =
-            ServiceConfig::new(String::from($name), crate::models::Image::from_str($img).unwrap());
+        let mut config = crate::models::ServiceConfigBuilder::new()
+            .service_name(String::from($name))
+            .image(crate::models::Image::from_str($img).unwrap())
+            .build()
+            .unwrap();
 
         let mut _labels = std::collections::BTreeMap::new();
         $( _labels.insert(String::from($l_key), String::from($l_value)); )*
@@ -265,6 +1133,7 @@ macro_rules! sc {
 mod tests {
     use super::*;
     use serde_json::from_value;
+    use std::str::FromStr;
 
     #[test]
     fn should_parse_service_config_json() {
@@ -289,6 +1158,352 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_parse_service_config_json_with_startup_probe() {
+        let config = from_value::<ServiceConfig>(serde_json::json!({
+            "serviceName": "wordpress",
+            "image": "wordpress",
+            "startupProbe": {
+                "urlPath": "/wp-admin/install.php",
+                "failureThreshold": 30
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            config.startup_probe(),
+            Some(&StartupProbe::new(
+                String::from("/wp-admin/install.php"),
+                1,
+                10,
+                30
+            ))
+        );
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_ipc_mode() {
+        let config = from_value::<ServiceConfig>(serde_json::json!({
+            "serviceName": "app",
+            "image": "app",
+            "ipcMode": "container:cache"
+        }))
+        .unwrap();
+
+        assert_eq!(
+            config.ipc_mode(),
+            Some(&IpcMode::Container {
+                service_name: String::from("cache")
+            })
+        );
+        assert!(from_value::<ServiceConfig>(serde_json::json!({
+            "serviceName": "app",
+            "image": "app",
+            "ipcMode": "shareable"
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_object_mounts() {
+        let config = serde_json::from_str::<ServiceConfig>(
+            r#"{
+                "serviceName": "nginx",
+                "image": "nginx",
+                "configMapMounts": [
+                    { "name": "nginx-config", "mountPath": "/etc/nginx/nginx.conf", "subPath": "nginx.conf" }
+                ],
+                "secretMounts": [
+                    { "name": "tls", "mountPath": "/etc/tls" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.config_map_mounts(),
+            &[ObjectMount::new(
+                String::from("nginx-config"),
+                String::from("/etc/nginx/nginx.conf"),
+                Some(String::from("nginx.conf"))
+            )]
+        );
+        assert_eq!(
+            config.secret_mounts(),
+            &[ObjectMount::new(
+                String::from("tls"),
+                String::from("/etc/tls"),
+                None
+            )]
+        );
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_ulimits() {
+        let config = serde_json::from_str::<ServiceConfig>(
+            r#"{
+                "serviceName": "db",
+                "image": "mariadb",
+                "ulimits": [
+                    { "name": "nofile", "soft": 65536, "hard": 65536 }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.ulimits(),
+            &[UlimitConfig::new(String::from("nofile"), 65536, 65536)]
+        );
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_init_containers() {
+        let config = serde_json::from_str::<ServiceConfig>(
+            r#"{
+                "serviceName": "api",
+                "image": "example/api",
+                "initContainers": [
+                    {
+                        "image": "flyway/flyway:10",
+                        "command": [ "migrate" ],
+                        "env": [ "FLYWAY_URL=jdbc:mariadb://db/api" ]
+                    },
+                    { "image": "busybox" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let init_containers = config.init_containers();
+        assert_eq!(init_containers.len(), 2);
+        assert_eq!(
+            init_containers[0].image(),
+            &Image::from_str("flyway/flyway:10").unwrap()
+        );
+        assert_eq!(init_containers[0].command(), &[String::from("migrate")]);
+        assert_eq!(
+            init_containers[0].env(),
+            &[String::from("FLYWAY_URL=jdbc:mariadb://db/api")]
+        );
+        assert!(init_containers[1].command().is_empty());
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_container_name() {
+        let config = serde_json::from_str::<ServiceConfig>(
+            r#"{
+                "serviceName": "db",
+                "image": "mariadb",
+                "containerName": "master-database"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.container_name(), Some("master-database"));
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_sticky_sessions() {
+        let config = serde_json::from_str::<ServiceConfig>(
+            r#"{
+                "serviceName": "frontend",
+                "image": "nginx",
+                "stickySessions": { "cookieName": "frontend-affinity" }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config
+                .sticky_sessions()
+                .map(|sticky_sessions| sticky_sessions.cookie_name()),
+            Some("frontend-affinity")
+        );
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_depends_on() {
+        let config = serde_json::from_str::<ServiceConfig>(
+            r#"{
+                "serviceName": "frontend",
+                "image": "nginx",
+                "dependsOn": [ "backend" ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.depends_on(), &[String::from("backend")]);
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_replicas() {
+        let config = serde_json::from_str::<ServiceConfig>(
+            r#"{
+                "serviceName": "frontend",
+                "image": "nginx",
+                "replicas": 3
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.replicas(), Some(3));
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_env_from_secrets() {
+        let config = serde_json::from_str::<ServiceConfig>(
+            r#"{
+                "serviceName": "db",
+                "image": "mariadb",
+                "envFromSecrets": [
+                    { "secretName": "db-password", "envKey": "MYSQL_PASSWORD" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let secret_env_ref = &config.env_from_secrets()[0];
+        assert_eq!(secret_env_ref.secret_name(), "db-password");
+        assert_eq!(secret_env_ref.env_key(), "MYSQL_PASSWORD");
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_named_volumes() {
+        let config = serde_json::from_str::<ServiceConfig>(
+            r#"{
+                "serviceName": "db",
+                "image": "mariadb",
+                "namedVolumes": [
+                    { "volumeName": "db-data", "containerPath": "/var/lib/mysql" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.named_volumes(),
+            &[NamedVolumeMount::new(
+                String::from("db-data"),
+                String::from("/var/lib/mysql")
+            )]
+        );
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_devices() {
+        let config = serde_json::from_str::<ServiceConfig>(
+            r#"{
+                "serviceName": "firmware-test",
+                "image": "firmware-test",
+                "devices": [
+                    { "hostPath": "/dev/ttyUSB0", "containerPath": "/dev/ttyUSB0" },
+                    { "hostPath": "/dev/video0", "containerPath": "/dev/camera", "permissions": "r" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.devices(),
+            &[
+                DeviceMapping::new(
+                    String::from("/dev/ttyUSB0"),
+                    String::from("/dev/ttyUSB0"),
+                    String::from("rwm")
+                ),
+                DeviceMapping::new(
+                    String::from("/dev/video0"),
+                    String::from("/dev/camera"),
+                    String::from("r")
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_service_config_json_with_host_port_bindings() {
+        let config = from_value::<ServiceConfig>(serde_json::json!({
+            "serviceName": "kafka",
+            "image": "bitnami/kafka",
+            "hostPortBindings": [{
+                "containerPort": 9092,
+                "hostPort": 19092
+            }, {
+                "containerPort": 9093,
+                "protocol": "udp"
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            config.host_port_bindings(),
+            &[
+                PortBinding::new(9092, Some(19092), PortProtocol::Tcp),
+                PortBinding::new(9093, None, PortProtocol::Udp)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_build_service_config() {
+        let config = ServiceConfigBuilder::new()
+            .service_name(String::from("db"))
+            .image(Image::from_str("mariadb:10.3").unwrap())
+            .container_type(ContainerType::Replica)
+            .build()
+            .unwrap();
+
+        assert_eq!(config.service_name(), "db");
+        assert_eq!(config.image().to_string(), "docker.io/library/mariadb:10.3");
+        assert_eq!(config.container_type(), &ContainerType::Replica);
+    }
+
+    #[test]
+    fn should_not_build_service_config_without_image() {
+        let result = ServiceConfigBuilder::new()
+            .service_name(String::from("db"))
+            .build();
+
+        assert_eq!(result, Err(ServiceConfigBuilderError::MissingImage));
+    }
+
+    #[test]
+    fn should_merge_patch_into_service_config() {
+        let mut base = sc!(
+            "db",
+            "mariadb:10.3",
+            env = ("MYSQL_USER" => "admin", "MYSQL_DATABASE" => "db")
+        );
+        base.set_memory_limit(Some(ByteSize::mb(512)));
+        let patch = sc!("db", "mariadb:10.4", env = ("MYSQL_USER" => "root"));
+
+        let merged = ServiceConfig::merge(&base, &patch);
+
+        assert_eq!(merged.image(), &"mariadb:10.4".parse::<Image>().unwrap());
+        assert_eq!(merged.memory_limit(), Some(ByteSize::mb(512)));
+        let env = merged.env().unwrap();
+        assert_eq!(
+            env.variable("MYSQL_USER").map(|v| v.value().unsecure()),
+            Some("root")
+        );
+        assert_eq!(
+            env.variable("MYSQL_DATABASE").map(|v| v.value().unsecure()),
+            Some("db")
+        );
+    }
+
+    #[test]
+    fn should_keep_readiness_url_of_base_when_merging() {
+        let mut base = sc!("api", "nginx:latest");
+        base.set_readiness_url(Some(String::from("/ready")));
+        let patch = sc!("api", "nginx:1.27");
+
+        let merged = ServiceConfig::merge(&base, &patch);
+
+        assert_eq!(merged.readiness_url(), Some("/ready"));
+    }
+
     #[test]
     fn should_merge_service_configs_labels() {
         let mut config = sc!(
@@ -344,16 +344,17 @@ mod tests {
 
     use super::*;
     use crate::config::Routing;
-    use crate::models::Image;
+    use crate::models::{Image, ServiceConfigBuilder};
     use crate::sc;
     use std::path::PathBuf;
 
     #[test]
     fn should_apply_app_companion_templating_with_service_name() {
-        let mut config = ServiceConfig::new(
-            String::from("postgres-{{application.name}}"),
-            Image::from_str("postgres").unwrap(),
-        );
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("postgres-{{application.name}}"))
+            .image(Image::from_str("postgres").unwrap())
+            .build()
+            .unwrap();
         config.set_env(Some(Environment::new(Vec::new())));
 
         let templated_config = config
@@ -365,10 +366,11 @@ mod tests {
 
     #[test]
     fn should_apply_app_companion_templating_with_envs() {
-        let mut config = ServiceConfig::new(
-            String::from("postgres-db"),
-            Image::from_str("postgres").unwrap(),
-        );
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("postgres-db"))
+            .image(Image::from_str("postgres").unwrap())
+            .build()
+            .unwrap();
         config.set_env(Some(Environment::new(vec![
             EnvironmentVariable::with_templating(
                 "DATABASE_SCHEMAS".to_string(),
@@ -381,14 +383,16 @@ mod tests {
         ])));
 
         let service_configs = vec![
-            ServiceConfig::new(
-                String::from("service-a"),
-                Image::from_str("service").unwrap(),
-            ),
-            ServiceConfig::new(
-                String::from("service-b"),
-                Image::from_str("service").unwrap(),
-            ),
+            ServiceConfigBuilder::new()
+                .service_name(String::from("service-a"))
+                .image(Image::from_str("service").unwrap())
+                .build()
+                .unwrap(),
+            ServiceConfigBuilder::new()
+                .service_name(String::from("service-b"))
+                .image(Image::from_str("service").unwrap())
+                .build()
+                .unwrap(),
         ];
         let templated_config = config
             .apply_templating_for_application_companion(&AppName::master(), &None, &service_configs)
@@ -401,10 +405,11 @@ mod tests {
 
     #[test]
     fn should_apply_app_companion_templating_with_labels() {
-        let mut config = ServiceConfig::new(
-            String::from("postgres-db"),
-            Image::from_str("postgres").unwrap(),
-        );
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("postgres-db"))
+            .image(Image::from_str("postgres").unwrap())
+            .build()
+            .unwrap();
 
         let mut labels = BTreeMap::new();
         labels.insert(
@@ -414,14 +419,16 @@ mod tests {
         config.set_labels(Some(labels));
 
         let service_configs = vec![
-            ServiceConfig::new(
-                String::from("service-a"),
-                Image::from_str("service").unwrap(),
-            ),
-            ServiceConfig::new(
-                String::from("service-b"),
-                Image::from_str("service").unwrap(),
-            ),
+            ServiceConfigBuilder::new()
+                .service_name(String::from("service-a"))
+                .image(Image::from_str("service").unwrap())
+                .build()
+                .unwrap(),
+            ServiceConfigBuilder::new()
+                .service_name(String::from("service-b"))
+                .image(Image::from_str("service").unwrap())
+                .build()
+                .unwrap(),
         ];
         let templated_config = config
             .apply_templating_for_application_companion(&AppName::master(), &None, &service_configs)
@@ -435,10 +442,11 @@ mod tests {
 
     #[test]
     fn should_not_apply_app_companion_templating_with_invalid_envs() {
-        let mut config = ServiceConfig::new(
-            String::from("postgres-db"),
-            Image::from_str("postgres").unwrap(),
-        );
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("postgres-db"))
+            .image(Image::from_str("postgres").unwrap())
+            .build()
+            .unwrap();
         config.set_env(Some(Environment::new(vec![
             EnvironmentVariable::with_templating(
                 "DATABASE_SCHEMAS".to_string(),
@@ -458,10 +466,11 @@ mod tests {
 
     #[test]
     fn should_apply_app_companion_templating_with_volumes() {
-        let mut config = ServiceConfig::new(
-            String::from("nginx-proxy"),
-            Image::from_str("nginx").unwrap(),
-        );
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("nginx-proxy"))
+            .image(Image::from_str("nginx").unwrap())
+            .build()
+            .unwrap();
 
         let mount_path = PathBuf::from("/etc/ningx/conf.d/default.conf");
         let mut files = BTreeMap::new();
@@ -478,14 +487,16 @@ location /{{name}} {
         config.set_files(Some(files));
 
         let service_configs = vec![
-            ServiceConfig::new(
-                String::from("service-a"),
-                Image::from_str("service").unwrap(),
-            ),
-            ServiceConfig::new(
-                String::from("service-b"),
-                Image::from_str("service").unwrap(),
-            ),
+            ServiceConfigBuilder::new()
+                .service_name(String::from("service-a"))
+                .image(Image::from_str("service").unwrap())
+                .build()
+                .unwrap(),
+            ServiceConfigBuilder::new()
+                .service_name(String::from("service-b"))
+                .image(Image::from_str("service").unwrap())
+                .build()
+                .unwrap(),
         ];
         let templated_config = config
             .apply_templating_for_application_companion(&AppName::master(), &None, &service_configs)
@@ -507,33 +518,38 @@ location /service-b {
 
     #[test]
     fn should_apply_templating_with_is_not_companion_helper() {
-        let mut service_a = ServiceConfig::new(
-            String::from("service-a"),
-            Image::from_str("service").unwrap(),
-        );
+        let mut service_a = ServiceConfigBuilder::new()
+            .service_name(String::from("service-a"))
+            .image(Image::from_str("service").unwrap())
+            .build()
+            .unwrap();
         service_a.set_container_type(ContainerType::Instance);
-        let mut service_b = ServiceConfig::new(
-            String::from("service-b"),
-            Image::from_str("service").unwrap(),
-        );
+        let mut service_b = ServiceConfigBuilder::new()
+            .service_name(String::from("service-b"))
+            .image(Image::from_str("service").unwrap())
+            .build()
+            .unwrap();
         service_b.set_container_type(ContainerType::Replica);
-        let mut service_c = ServiceConfig::new(
-            String::from("service-c"),
-            Image::from_str("service").unwrap(),
-        );
+        let mut service_c = ServiceConfigBuilder::new()
+            .service_name(String::from("service-c"))
+            .image(Image::from_str("service").unwrap())
+            .build()
+            .unwrap();
         service_c.set_container_type(ContainerType::ApplicationCompanion);
-        let mut service_d = ServiceConfig::new(
-            String::from("service-d"),
-            Image::from_str("service").unwrap(),
-        );
+        let mut service_d = ServiceConfigBuilder::new()
+            .service_name(String::from("service-d"))
+            .image(Image::from_str("service").unwrap())
+            .build()
+            .unwrap();
         service_d.set_container_type(ContainerType::ServiceCompanion);
 
         let service_configs = vec![service_a, service_b, service_c, service_d];
 
-        let mut config = ServiceConfig::new(
-            String::from("nginx-proxy"),
-            Image::from_str("nginx").unwrap(),
-        );
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("nginx-proxy"))
+            .image(Image::from_str("nginx").unwrap())
+            .build()
+            .unwrap();
         let mount_path = PathBuf::from("/etc/ningx/conf.d/default.conf");
         let mut files = BTreeMap::new();
         files.insert(
@@ -570,33 +586,38 @@ location /service-b {
 
     #[test]
     fn should_apply_templating_with_is_companion_helper() {
-        let mut service_a = ServiceConfig::new(
-            String::from("service-a"),
-            Image::from_str("service").unwrap(),
-        );
+        let mut service_a = ServiceConfigBuilder::new()
+            .service_name(String::from("service-a"))
+            .image(Image::from_str("service").unwrap())
+            .build()
+            .unwrap();
         service_a.set_container_type(ContainerType::Instance);
-        let mut service_b = ServiceConfig::new(
-            String::from("service-b"),
-            Image::from_str("service").unwrap(),
-        );
+        let mut service_b = ServiceConfigBuilder::new()
+            .service_name(String::from("service-b"))
+            .image(Image::from_str("service").unwrap())
+            .build()
+            .unwrap();
         service_b.set_container_type(ContainerType::Replica);
-        let mut service_c = ServiceConfig::new(
-            String::from("service-c"),
-            Image::from_str("service").unwrap(),
-        );
+        let mut service_c = ServiceConfigBuilder::new()
+            .service_name(String::from("service-c"))
+            .image(Image::from_str("service").unwrap())
+            .build()
+            .unwrap();
         service_c.set_container_type(ContainerType::ApplicationCompanion);
-        let mut service_d = ServiceConfig::new(
-            String::from("service-d"),
-            Image::from_str("service").unwrap(),
-        );
+        let mut service_d = ServiceConfigBuilder::new()
+            .service_name(String::from("service-d"))
+            .image(Image::from_str("service").unwrap())
+            .build()
+            .unwrap();
         service_d.set_container_type(ContainerType::ServiceCompanion);
 
         let service_configs = vec![service_a, service_b, service_c, service_d];
 
-        let mut config = ServiceConfig::new(
-            String::from("nginx-proxy"),
-            Image::from_str("nginx").unwrap(),
-        );
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("nginx-proxy"))
+            .image(Image::from_str("nginx").unwrap())
+            .build()
+            .unwrap();
         let mount_path = PathBuf::from("/etc/ningx/conf.d/default.conf");
         let mut files = BTreeMap::new();
         files.insert(
@@ -643,10 +664,11 @@ location /service-d {
 
     #[test]
     fn should_apply_templating_for_router() {
-        let mut config = ServiceConfig::new(
-            String::from("api-gateway"),
-            Image::from_str("api-gateway").unwrap(),
-        );
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("api-gateway"))
+            .image(Image::from_str("api-gateway").unwrap())
+            .build()
+            .unwrap();
         config.set_routing(Routing {
             rule: Some("PathPrefix(`/{{application.name}}/`)".to_string()),
             additional_middlewares: BTreeMap::new(),
@@ -662,10 +684,11 @@ location /service-d {
 
     #[test]
     fn should_apply_templating_for_middlewares_with_array_structure() {
-        let mut config = ServiceConfig::new(
-            String::from("api-gateway"),
-            Image::from_str("api-gateway").unwrap(),
-        );
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("api-gateway"))
+            .image(Image::from_str("api-gateway").unwrap())
+            .build()
+            .unwrap();
 
         let headers = serde_value::to_value(serde_json::json!({
             "prefixes": [ "/{{application.name}}" ]
@@ -695,10 +718,11 @@ location /service-d {
 
     #[test]
     fn should_apply_templating_for_middlewares_with_map_structure() {
-        let mut config = ServiceConfig::new(
-            String::from("api-gateway"),
-            Image::from_str("api-gateway").unwrap(),
-        );
+        let mut config = ServiceConfigBuilder::new()
+            .service_name(String::from("api-gateway"))
+            .image(Image::from_str("api-gateway").unwrap())
+            .build()
+            .unwrap();
 
         let headers = serde_value::to_value(serde_json::json!({
             "customRequestHeaders": {
@@ -0,0 +1,192 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2024 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::models::service::{ContainerType, Service, ServiceStatus};
+use crate::models::AppName;
+
+/// Filters the services of all apps, e.g. to find the services that run an image that needs to
+/// be patched. All criteria must be met and criteria without value match all services.
+#[derive(Clone, Debug, Default)]
+pub struct ServiceQuery {
+    pub image_prefix: Option<String>,
+    pub app_name_prefix: Option<String>,
+    pub service_name: Option<String>,
+    pub container_type: Option<ContainerType>,
+    pub tag: Option<String>,
+}
+
+impl ServiceQuery {
+    pub fn matches(&self, result: &ServiceSearchResult) -> bool {
+        self.app_name_prefix
+            .as_ref()
+            .map_or(true, |prefix| result.app_name.starts_with(prefix.as_str()))
+            && self
+                .image_prefix
+                .as_ref()
+                .map_or(true, |prefix| Self::image_matches(&result.image, prefix))
+            && self
+                .service_name
+                .as_ref()
+                .map_or(true, |service_name| &result.service_name == service_name)
+            && self.container_type.as_ref().map_or(true, |container_type| {
+                &result.container_type == container_type
+            })
+            && self
+                .tag
+                .as_ref()
+                .map_or(true, |tag| result.tags.contains(tag))
+    }
+
+    /// Images of Docker Hub can be searched without registry and without the `library` user, e.g.
+    /// `nginx` matches `docker.io/library/nginx:1.27`.
+    fn image_matches(image: &str, prefix: &str) -> bool {
+        let docker_hub_image = image.strip_prefix("docker.io/");
+        let official_image = docker_hub_image.and_then(|image| image.strip_prefix("library/"));
+
+        [Some(image), docker_hub_image, official_image]
+            .iter()
+            .flatten()
+            .any(|image| image.starts_with(prefix))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceSearchResult {
+    app_name: AppName,
+    service_name: String,
+    /// The image as written in the service configuration, e.g. `docker.io/library/nginx:1.27`.
+    image: String,
+    status: ServiceStatus,
+    #[serde(skip)]
+    container_type: ContainerType,
+    #[serde(skip)]
+    tags: Vec<String>,
+}
+
+impl ServiceSearchResult {
+    pub fn new(
+        app_name: AppName,
+        service_name: String,
+        image: String,
+        status: ServiceStatus,
+        container_type: ContainerType,
+        tags: Vec<String>,
+    ) -> Self {
+        Self {
+            app_name,
+            service_name,
+            image,
+            status,
+            container_type,
+            tags,
+        }
+    }
+
+    pub fn from_service(app_name: AppName, service: &Service, tags: Vec<String>) -> Self {
+        Self::new(
+            app_name,
+            service.service_name().clone(),
+            service.image().to_string(),
+            service.status().clone(),
+            service.container_type().clone(),
+            tags,
+        )
+    }
+
+    pub fn app_name(&self) -> &AppName {
+        &self.app_name
+    }
+
+    pub fn service_name(&self) -> &str {
+        &self.service_name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn result(app_name: &str, image: &str, tags: &[&str]) -> ServiceSearchResult {
+        ServiceSearchResult::new(
+            AppName::from_str(app_name).unwrap(),
+            String::from("web"),
+            String::from(image),
+            ServiceStatus::Running,
+            ContainerType::Instance,
+            tags.iter().map(|tag| tag.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn should_match_all_services_without_criteria() {
+        assert!(ServiceQuery::default().matches(&result("master", "docker.io/library/nginx", &[])));
+    }
+
+    #[test]
+    fn should_match_docker_hub_images_without_registry() {
+        let query = ServiceQuery {
+            image_prefix: Some(String::from("nginx")),
+            ..Default::default()
+        };
+
+        assert!(query.matches(&result("master", "docker.io/library/nginx:1.27", &[])));
+        assert!(!query.matches(&result("master", "ghcr.io/myorg/nginx:1.27", &[])));
+    }
+
+    #[test]
+    fn should_match_services_by_all_criteria() {
+        let query = ServiceQuery {
+            image_prefix: Some(String::from("docker.io/library/nginx")),
+            app_name_prefix: Some(String::from("feature-")),
+            service_name: Some(String::from("web")),
+            container_type: Some(ContainerType::Instance),
+            tag: Some(String::from("team-a")),
+        };
+
+        assert!(query.matches(&result(
+            "feature-login",
+            "docker.io/library/nginx:1.27",
+            &["team-a"]
+        )));
+        assert!(!query.matches(&result(
+            "master",
+            "docker.io/library/nginx:1.27",
+            &["team-a"]
+        )));
+        assert!(!query.matches(&result(
+            "feature-login",
+            "docker.io/library/httpd:2.4",
+            &["team-a"]
+        )));
+        assert!(!query.matches(&result(
+            "feature-login",
+            "docker.io/library/nginx:1.27",
+            &["team-b"]
+        )));
+    }
+}
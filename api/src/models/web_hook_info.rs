@@ -24,13 +24,14 @@
  * =========================LICENSE_END==================================
  */
 
-use regex::Regex;
+use crate::models::{AppName, AppNameError};
 use rocket::data::{self, Data, FromData, ToByteUnit};
 use rocket::http::Status;
 use rocket::request::Request;
 use serde::de::Error as DeserializeError;
 use serde::{Deserialize, Deserializer};
 use serde_json::from_str;
+use std::str::FromStr;
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -83,12 +84,15 @@ impl WebHookInfo {
         &self.pull_request.title
     }
 
-    pub fn get_app_name(&self) -> String {
-        let re: Regex = Regex::new(r"[A-Z]{3,}-\d+").unwrap();
-
-        match re.captures(&self.pull_request.from_ref.display_id) {
-            Some(c) => String::from(c.get(0).unwrap().as_str()),
-            None => self.pull_request.from_ref.display_id.clone(),
+    /// Returns the name of the app of the pull request's source branch, i.e. the ticket id of the
+    /// branch name or the branch name itself. Branch names that are no valid app names are
+    /// rejected instead of being sanitized because the sanitized name might belong to another app
+    /// that must not be deleted.
+    pub fn get_app_name(&self) -> Result<AppName, AppNameError> {
+        let branch = &self.pull_request.from_ref.display_id;
+        match AppName::from_ticket_id_of_branch(branch) {
+            Some(app_name) => Ok(app_name),
+            None => AppName::from_str(branch),
         }
     }
 
@@ -129,3 +133,41 @@ impl<'r> FromData<'r> for WebHookInfo {
         data::Outcome::Success(data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn web_hook_info(branch: &str) -> WebHookInfo {
+        from_str(&format!(
+            r#"{{
+                "eventKey": "pr:merged",
+                "pullRequest": {{ "title": "Some PR", "fromRef": {{ "displayId": "{branch}" }} }}
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_return_ticket_id_as_app_name() {
+        assert_eq!(
+            web_hook_info("feature/TICKET-123_do-thing")
+                .get_app_name()
+                .unwrap(),
+            AppName::from_str("TICKET-123").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_return_branch_name_as_app_name() {
+        assert_eq!(
+            web_hook_info("some-feature").get_app_name().unwrap(),
+            AppName::from_str("some-feature").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_not_sanitize_branch_name() {
+        assert!(web_hook_info("feature/some-thing").get_app_name().is_err());
+    }
+}
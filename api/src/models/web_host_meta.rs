@@ -29,9 +29,11 @@ use url::Url;
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Serialize, PartialEq)]
 pub struct WebHostMeta {
+    #[serde(skip_serializing_if = "Option::is_none")]
     properties: Option<Properties>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     links: Option<Vec<Link>>,
-    #[serde(default = "valid_web_host")]
+    #[serde(default = "valid_web_host", skip_serializing)]
     valid: bool,
 }
 
@@ -76,6 +78,27 @@ impl WebHostMeta {
         }
     }
 
+    /// Combines the links of all given web host meta data into a single document. Links that point
+    /// to the same target are only contained once. The properties are dropped because they
+    /// describe the individual services.
+    pub fn merge(metas: Vec<WebHostMeta>) -> WebHostMeta {
+        let mut links: Vec<Link> = Vec::new();
+        for link in metas
+            .into_iter()
+            .flat_map(|meta| meta.links.unwrap_or_default())
+        {
+            if !links.iter().any(|l| l.href == link.href) {
+                links.push(link);
+            }
+        }
+
+        WebHostMeta {
+            properties: None,
+            links: if links.is_empty() { None } else { Some(links) },
+            valid: true,
+        }
+    }
+
     pub fn is_valid(&self) -> bool {
         self.valid
     }
@@ -101,6 +124,18 @@ impl WebHostMeta {
         }
     }
 
+    /// Returns the targets of all links with the given relation type, e.g. `describedby`.
+    pub fn links_with_relation(&self, rel: &str) -> Vec<Url> {
+        match &self.links {
+            None => Vec::new(),
+            Some(links) => links
+                .iter()
+                .filter(|link| link.rel == rel)
+                .map(|link| link.href.clone())
+                .collect(),
+        }
+    }
+
     pub fn commit(&self) -> Option<String> {
         match &self.properties {
             None => None,
@@ -122,12 +157,18 @@ impl WebHostMeta {
         Ok(DateTime::<Utc>::deserialize(deserializer).ok())
     }
 
-    pub fn with_base_url(&self, url: &Url) -> WebHostMeta {
+    /// Points the links to `url`. If `path_prefix` is given, e.g. because PREvant is served by a
+    /// reverse proxy under a path, the paths of the links will be prefixed accordingly.
+    pub fn with_base_url(&self, url: &Url, path_prefix: Option<&str>) -> WebHostMeta {
         let mut web_host_meta = self.clone();
         if let Some(ref mut links) = web_host_meta.links {
             for link in links {
                 link.href = url
-                    .join(link.href.path())
+                    .join(&format!(
+                        "{}{}",
+                        path_prefix.unwrap_or_default(),
+                        link.href.path()
+                    ))
                     .expect("invalid urls in web host meta data");
             }
         }
@@ -141,6 +182,42 @@ mod tests {
     use super::*;
     use chrono::TimeZone;
 
+    #[test]
+    fn should_merge_links_deduplicated_by_href() {
+        let first = serde_json::from_str::<WebHostMeta>(
+            r#"{
+              "properties": { "https://schema.org/softwareVersion": "1.3" },
+              "links": [
+                { "rel": "describedby", "href": "https://example.com/master/a/docs" },
+                { "rel": "item", "href": "https://example.com/master/shared" }
+              ]
+            }"#,
+        )
+        .unwrap();
+        let second = serde_json::from_str::<WebHostMeta>(
+            r#"{
+              "links": [
+                { "rel": "related", "href": "https://example.com/master/shared" },
+                { "rel": "describedby", "href": "https://example.com/master/b/docs" }
+              ]
+            }"#,
+        )
+        .unwrap();
+
+        let merged = WebHostMeta::merge(vec![first, second]);
+
+        assert_eq!(
+            serde_json::to_value(&merged).unwrap(),
+            serde_json::json!({
+                "links": [
+                    { "rel": "describedby", "href": "https://example.com/master/a/docs" },
+                    { "rel": "item", "href": "https://example.com/master/shared" },
+                    { "rel": "describedby", "href": "https://example.com/master/b/docs" }
+                ]
+            })
+        );
+    }
+
     #[test]
     fn should_parse_meta_without_version_property() {
         let json = r#"{
@@ -282,11 +359,30 @@ mod tests {
 
         let meta = serde_json::from_str::<WebHostMeta>(json)
             .unwrap()
-            .with_base_url(&Url::parse("http://example.com").unwrap());
+            .with_base_url(&Url::parse("http://example.com").unwrap(), None);
 
         assert_eq!(
             meta.openapi(),
             Some(Url::parse("http://example.com/speca/petstore-api").unwrap())
         );
     }
+
+    #[test]
+    fn should_replace_base_url_in_links_with_path_prefix() {
+        let json = r#"{
+          "links":[{
+            "rel": "https://github.com/OAI/OpenAPI-Specification",
+            "href":"https://speca.io/master/petstore/swagger.json"
+          }]
+        }"#;
+
+        let meta = serde_json::from_str::<WebHostMeta>(json)
+            .unwrap()
+            .with_base_url(&Url::parse("http://example.com").unwrap(), Some("/prevant"));
+
+        assert_eq!(
+            meta.openapi(),
+            Some(Url::parse("http://example.com/prevant/master/petstore/swagger.json").unwrap())
+        );
+    }
 }
@@ -30,13 +30,26 @@ use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 use oci_client::client::ClientConfig;
 use oci_client::errors::OciDistributionError;
+use oci_client::manifest::{
+    IMAGE_DOCKER_LAYER_GZIP_MEDIA_TYPE, IMAGE_DOCKER_LAYER_TAR_MEDIA_TYPE,
+    IMAGE_LAYER_GZIP_MEDIA_TYPE, IMAGE_LAYER_MEDIA_TYPE,
+};
 use oci_client::secrets::RegistryAuth;
 use oci_client::{Client, Reference};
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use std::convert::From;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+/// The layer formats that the Docker daemon is able to load.
+static ACCEPTED_LAYER_MEDIA_TYPES: [&str; 4] = [
+    IMAGE_LAYER_MEDIA_TYPE,
+    IMAGE_LAYER_GZIP_MEDIA_TYPE,
+    IMAGE_DOCKER_LAYER_TAR_MEDIA_TYPE,
+    IMAGE_DOCKER_LAYER_GZIP_MEDIA_TYPE,
+];
+
 pub struct Registry<'a> {
     config: &'a Config,
 }
@@ -92,13 +105,70 @@ impl<'a> Registry<'a> {
         Ok(image_infos)
     }
 
+    /// Pulls the manifest, the config, and all layers of the image through the OCI Distribution
+    /// API. This works for registries that only serve OCI image manifests. The layers are streamed
+    /// into files of `dir` because they might not fit into memory.
+    pub async fn pull_image(
+        &self,
+        image: &Image,
+        dir: &Path,
+    ) -> Result<PulledImage, OciDistributionError> {
+        debug!("Pull image {image:?} through the OCI Distribution API");
+
+        let client = Self::client();
+        let reference = Self::reference(self.config, image);
+        let (manifest, _digest, config) = client
+            .pull_manifest_and_config(&reference, &Self::registry_auth(self.config, &reference))
+            .await?;
+
+        let mut layers = Vec::with_capacity(manifest.layers.len());
+        for (index, layer) in manifest.layers.iter().enumerate() {
+            if !ACCEPTED_LAYER_MEDIA_TYPES.contains(&layer.media_type.as_str()) {
+                return Err(OciDistributionError::IncompatibleLayerMediaTypeError(
+                    layer.media_type.clone(),
+                ));
+            }
+
+            let path = dir.join(format!("layer-{index}.tar"));
+            let mut file = tokio::fs::File::create(&path).await?;
+            client.pull_blob(&reference, layer, &mut file).await?;
+            file.sync_all().await?;
+            layers.push(path);
+        }
+
+        Ok(PulledImage { config, layers })
+    }
+
     async fn resolve_image_info<'i>(
         config: &Config,
         image: &'i Image,
     ) -> Result<(&'i Image, ImageInfo), (&'i Image, OciDistributionError)> {
         debug!("Resolve image manifest for {:?}", image);
 
-        let client = Client::new(ClientConfig {
+        let client = Self::client();
+        let reference = Self::reference(config, image);
+
+        let (_manifest, digest, config) = client
+            .pull_manifest_and_config(&reference, &Self::registry_auth(config, &reference))
+            .await
+            .map_err(|err| (image, err))?;
+
+        let blob = match serde_json::from_str::<ImageBlob>(&config) {
+            Ok(blob) => ImageInfo {
+                blob: Some(blob),
+                digest,
+            },
+            Err(err) => {
+                warn!("Cannot parse manifest blob for {image}: {err}");
+                ImageInfo { blob: None, digest }
+            }
+        };
+
+        Ok((image, blob))
+    }
+
+    fn client() -> Client {
+        Client::new(ClientConfig {
             platform_resolver: Some(Box::new(|entries| {
                 oci_client::client::current_platform_resolver(entries).or(
                     // There are cases where current_platform_resolver fails, e.g. in tests on
@@ -116,8 +186,10 @@ impl<'a> Registry<'a> {
                 )
             })),
             ..Default::default()
-        });
+        })
+    }
 
+    fn reference(config: &Config, image: &Image) -> Reference {
         let mut reference = Reference::from_str(&image.to_string())
             .expect("Image should be convertable if it is the Named variant");
 
@@ -125,23 +197,7 @@ impl<'a> Registry<'a> {
             reference.set_mirror_registry(mirror.to_string());
         }
 
-        let (_manifest, digest, config) = client
-            .pull_manifest_and_config(&reference, &Self::registry_auth(config, &reference))
-            .await
-            .map_err(|err| (image, err))?;
-
-        let blob = match serde_json::from_str::<ImageBlob>(&config) {
-            Ok(blob) => ImageInfo {
-                blob: Some(blob),
-                digest,
-            },
-            Err(err) => {
-                warn!("Cannot parse manifest blob for {image}: {err}");
-                ImageInfo { blob: None, digest }
-            }
-        };
-
-        Ok((image, blob))
+        reference
     }
 
     fn registry_auth(config: &Config, reference: &Reference) -> RegistryAuth {
@@ -154,6 +210,13 @@ impl<'a> Registry<'a> {
     }
 }
 
+/// An image that has been pulled by [`Registry::pull_image`].
+pub struct PulledImage {
+    pub config: String,
+    /// The files that contain the layers, in the order of the manifest.
+    pub layers: Vec<PathBuf>,
+}
+
 #[derive(Debug)]
 pub struct ImageInfo {
     blob: Option<ImageBlob>,
@@ -0,0 +1,178 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::{Data, Response};
+use std::fmt::Display;
+use std::future::Future;
+use uuid::Uuid;
+
+static REQUEST_ID_HEADER: &str = "X-Request-ID";
+static CORRELATION_ID_HEADER: &str = "X-Correlation-ID";
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: RequestId;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    fn from_headers(request: &Request<'_>) -> Self {
+        let headers = request.headers();
+        match headers
+            .get_one(REQUEST_ID_HEADER)
+            .or_else(|| headers.get_one(CORRELATION_ID_HEADER))
+            .filter(|id| !id.trim().is_empty())
+        {
+            Some(id) => RequestId(id.trim().to_string()),
+            None => RequestId(Uuid::new_v4().to_string()),
+        }
+    }
+
+    /// Returns the request ID of the request that is currently processed by this task.
+    pub fn current() -> Option<RequestId> {
+        CURRENT_REQUEST_ID.try_with(RequestId::clone).ok()
+    }
+
+    /// Runs the future with this request ID as the current one. Log statements emitted while the
+    /// future is polled will contain the request ID.
+    pub fn scope<F>(self, future: F) -> impl Future<Output = F::Output>
+    where
+        F: Future,
+    {
+        CURRENT_REQUEST_ID.scope(self, future)
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestId {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(
+            request
+                .local_cache(|| RequestId::from_headers(request))
+                .clone(),
+        )
+    }
+}
+
+/// Reads the request ID from the header `X-Request-ID` (or `X-Correlation-ID`), generates one if
+/// absent, and returns it to the client via the response header `X-Request-ID`. The request ID
+/// allows to correlate the log statements of the REST API with the ones of the infrastructure.
+pub struct RequestIdFairing;
+
+#[rocket::async_trait]
+impl Fairing for RequestIdFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ID",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _data: &mut Data<'_>) {
+        let request_id = RequestId::from_headers(request);
+        request.local_cache(|| request_id);
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let request_id = request.local_cache(|| RequestId::from_headers(request));
+        response.set_header(Header::new(REQUEST_ID_HEADER, request_id.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::local::asynchronous::Client;
+
+    #[get("/")]
+    async fn current_request_id(request_id: RequestId) -> Option<String> {
+        request_id
+            .scope(async { RequestId::current() })
+            .await
+            .map(|id| id.to_string())
+    }
+
+    async fn client() -> Client {
+        let rocket = rocket::build()
+            .attach(RequestIdFairing)
+            .mount("/", routes![current_request_id]);
+        Client::tracked(rocket).await.expect("valid rocket")
+    }
+
+    #[tokio::test]
+    async fn should_return_provided_request_id() {
+        let client = client().await;
+
+        let response = client
+            .get("/")
+            .header(Header::new("X-Request-ID", "abc-123"))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.headers().get_one("X-Request-ID"), Some("abc-123"));
+        assert_eq!(response.into_string().await, Some(String::from("abc-123")));
+    }
+
+    #[tokio::test]
+    async fn should_use_correlation_id_as_request_id() {
+        let client = client().await;
+
+        let response = client
+            .get("/")
+            .header(Header::new("X-Correlation-ID", "xyz-789"))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.headers().get_one("X-Request-ID"), Some("xyz-789"));
+    }
+
+    #[tokio::test]
+    async fn should_generate_request_id() {
+        let client = client().await;
+
+        let response = client.get("/").dispatch().await;
+
+        let request_id = response.headers().get_one("X-Request-ID").unwrap();
+        assert!(Uuid::parse_str(request_id).is_ok());
+    }
+
+    #[test]
+    fn should_not_have_current_request_id_outside_of_scope() {
+        assert_eq!(RequestId::current(), None);
+    }
+}
@@ -0,0 +1,103 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+use secstr::SecUtf8;
+use std::path::PathBuf;
+
+/// Provides the values of secrets that services reference by name, e.g. via `envFromSecrets`.
+pub trait SecretProvider: Send + Sync {
+    fn get_secret(&self, name: &str) -> Result<SecUtf8, SecretError>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("Cannot find secret {name}.")]
+    NotFound { name: String },
+    #[error("Cannot resolve secret {name} because no secret provider has been configured.")]
+    NoProvider { name: String },
+    #[error("The app {app_name} is not permitted to reference secret {name}.")]
+    NotPermitted { name: String, app_name: String },
+    #[error("Cannot read secrets from {path:?}: {err}")]
+    Unreadable { path: PathBuf, err: std::io::Error },
+}
+
+/// Reads the secrets from a file that contains a line `name=value` per secret. Empty lines and
+/// lines starting with `#` are ignored. The file is read for every secret so that changes apply
+/// without restarting PREvant.
+pub struct EnvFileSecretProvider {
+    path: PathBuf,
+}
+
+impl EnvFileSecretProvider {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl SecretProvider for EnvFileSecretProvider {
+    fn get_secret(&self, name: &str) -> Result<SecUtf8, SecretError> {
+        let content =
+            std::fs::read_to_string(&self.path).map_err(|err| SecretError::Unreadable {
+                path: self.path.clone(),
+                err,
+            })?;
+
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .find(|(key, _)| key.trim() == name)
+            .map(|(_, value)| SecUtf8::from(value.trim()))
+            .ok_or_else(|| SecretError::NotFound {
+                name: name.to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn should_read_secret_from_env_file() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        writeln!(file, "# credentials of the database")?;
+        writeln!(file, "db-user=admin")?;
+        writeln!(file, "db-password=s3cr3t=")?;
+
+        let provider = EnvFileSecretProvider::new(file.path().to_path_buf());
+
+        assert_eq!(provider.get_secret("db-password")?.unsecure(), "s3cr3t=");
+        assert!(matches!(
+            provider.get_secret("unknown"),
+            Err(SecretError::NotFound { .. })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+/*-
+ * ========================LICENSE_START=================================
+ * PREvant REST API
+ * %%
+ * Copyright (C) 2018 - 2021 aixigo AG
+ * %%
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * =========================LICENSE_END==================================
+ */
+
+use crate::config::Runtime;
+use rocket::serde::json::Json;
+use rocket::State;
+
+/// Describes the running PREvant instance and the backend it is connected to.
+#[derive(Clone, Debug, Serialize)]
+pub struct VersionInfo {
+    version: &'static str,
+    git_sha: &'static str,
+    build_date: &'static str,
+    infrastructure: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    docker_api_version: Option<String>,
+}
+
+impl VersionInfo {
+    pub fn new(runtime: &Runtime, docker_api_version: Option<String>) -> Self {
+        Self {
+            version: clap::crate_version!(),
+            git_sha: env!("PREVANT_GIT_SHA"),
+            build_date: env!("PREVANT_BUILD_DATE"),
+            infrastructure: match runtime {
+                Runtime::Docker => "docker",
+                Runtime::Kubernetes(_) => "kubernetes",
+            },
+            docker_api_version,
+        }
+    }
+}
+
+#[get("/version", format = "application/json")]
+pub fn version(version_info: &State<VersionInfo>) -> Json<VersionInfo> {
+    Json(VersionInfo::clone(version_info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::Status;
+    use rocket::local::asynchronous::Client;
+
+    #[tokio::test]
+    async fn should_return_version_info() {
+        let rocket = rocket::build()
+            .manage(VersionInfo::new(
+                &Runtime::Docker,
+                Some(String::from("1.41")),
+            ))
+            .mount("/api", routes![version]);
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client.get("/api/version").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+        assert_eq!(body["version"], clap::crate_version!());
+        assert_eq!(body["infrastructure"], "docker");
+        assert_eq!(body["docker_api_version"], "1.41");
+    }
+}
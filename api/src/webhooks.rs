@@ -29,16 +29,16 @@ use crate::apps::Apps;
 use crate::http_result::HttpResult;
 use crate::models::service::Service;
 use crate::models::web_hook_info::WebHookInfo;
-use crate::models::AppName;
+use crate::request_id::RequestId;
 use rocket::serde::json::Json;
 use rocket::State;
-use std::str::FromStr;
 use std::sync::Arc;
 
 #[post("/webhooks", format = "application/json", data = "<web_hook_info>")]
 pub async fn webhooks(
     apps: &State<Arc<Apps>>,
     web_hook_info: WebHookInfo,
+    request_id: RequestId,
 ) -> HttpResult<Json<Vec<Service>>> {
     info!(
         "Deleting app {:?} through web hook {:?} with event {:?}",
@@ -47,6 +47,5 @@ pub async fn webhooks(
         web_hook_info.get_event_key()
     );
 
-    let app_name = AppName::from_str(&web_hook_info.get_app_name());
-    delete_app_sync(app_name, apps).await
+    delete_app_sync(web_hook_info.get_app_name(), apps, request_id).await
 }
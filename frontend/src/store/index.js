@@ -128,7 +128,9 @@ export default new Store( {
             state.appsError = appsResponse;
          }
          else {
-            state.apps = appsResponse;
+            state.apps = Object.fromEntries(
+               appsResponse.map( appSummary => [ appSummary.appName, appSummary.services ] )
+            );
             state.appsError = null;
          }
       },